@@ -0,0 +1,283 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Local};
+
+use crate::data::model::{Process, ProcessKey};
+
+/// Weight of the newest sample in the smoothed rate alerts are checked
+/// against, so a single spike doesn't trip a rule.
+const SMOOTHING: f64 = 0.5;
+/// An active alert clears only once the smoothed rate drops below this
+/// fraction of its threshold, so rates hovering at the limit don't flap.
+const CLEAR_RATIO: f64 = 0.8;
+/// Fired/cleared events kept for the alert log popup.
+const LOG_CAPACITY: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertDirection {
+    Up,
+    Down,
+}
+
+impl AlertDirection {
+    pub fn label(self) -> &'static str {
+        match self {
+            AlertDirection::Up => "upload",
+            AlertDirection::Down => "download",
+        }
+    }
+}
+
+/// A rate threshold, optionally limited to processes with a given name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    pub process: Option<String>,
+    pub direction: AlertDirection,
+    /// Bytes/sec
+    pub threshold: f64,
+}
+
+impl AlertRule {
+    fn matches(&self, p: &Process) -> bool {
+        self.process
+            .as_ref()
+            .is_none_or(|name| p.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Parse a byte size such as "5MB", "1.5G", "500k", or "2048" (bytes).
+/// Units are binary, matching how rates are displayed.
+pub fn parse_size(s: &str) -> Result<f64, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size {:?}", s))?;
+    let multiplier = match unit.trim().to_ascii_uppercase().trim_end_matches("/S") {
+        "" | "B" => 1.0,
+        "K" | "KB" | "KIB" => 1024.0,
+        "M" | "MB" | "MIB" => 1_048_576.0,
+        "G" | "GB" | "GIB" => 1_073_741_824.0,
+        _ => return Err(format!("unknown unit in {:?} (use B, KB, MB, or GB)", s)),
+    };
+    Ok(value * multiplier)
+}
+
+/// Parse an `--alert-up`/`--alert-down` value: "5MB" or "Dropbox=5MB".
+pub fn parse_threshold(s: &str) -> Result<(Option<String>, f64), String> {
+    match s.rsplit_once('=') {
+        Some((name, size)) if !name.is_empty() => Ok((Some(name.to_string()), parse_size(size)?)),
+        _ => Ok((None, parse_size(s.trim_start_matches('='))?)),
+    }
+}
+
+/// A rule tripping or clearing for one process.
+#[derive(Debug, Clone)]
+pub struct AlertEvent {
+    pub at: DateTime<Local>,
+    /// Index of the rule in `Alerts::rules`
+    pub rule: usize,
+    pub process: String,
+    pub pid: u32,
+    pub direction: AlertDirection,
+    /// Smoothed rate when the event happened, bytes/sec
+    pub rate: f64,
+    pub threshold: f64,
+    pub fired: bool,
+}
+
+/// Alert rules and their state across refreshes.
+#[derive(Debug, Default)]
+pub struct Alerts {
+    pub rules: Vec<AlertRule>,
+    smoothed: HashMap<ProcessKey, (f64, f64)>,
+    /// Rule index and process currently over threshold
+    active: HashMap<(usize, ProcessKey), AlertEvent>,
+    pub log: VecDeque<AlertEvent>,
+}
+
+impl Alerts {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        Alerts {
+            rules,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Update smoothed rates and check every rule. Returns the alerts that
+    /// fired this refresh.
+    pub fn evaluate(&mut self, processes: &[Process]) -> Vec<AlertEvent> {
+        if self.rules.is_empty() {
+            return Vec::new();
+        }
+        let now = Local::now();
+        let previous = std::mem::take(&mut self.smoothed);
+        for p in processes {
+            let smoothed = match previous.get(&p.key()) {
+                Some(&(rate_in, rate_out)) => (
+                    SMOOTHING * p.rate_in + (1.0 - SMOOTHING) * rate_in,
+                    SMOOTHING * p.rate_out + (1.0 - SMOOTHING) * rate_out,
+                ),
+                None => (p.rate_in, p.rate_out),
+            };
+            self.smoothed.insert(p.key(), smoothed);
+        }
+
+        let mut fired = Vec::new();
+        for (i, rule) in self.rules.iter().enumerate() {
+            for p in processes.iter().filter(|p| rule.matches(p)) {
+                let (rate_in, rate_out) = self.smoothed[&p.key()];
+                let rate = match rule.direction {
+                    AlertDirection::Up => rate_out,
+                    AlertDirection::Down => rate_in,
+                };
+                let key = (i, p.key());
+                let active = self.active.contains_key(&key);
+                let event = |fired| AlertEvent {
+                    at: now,
+                    rule: i,
+                    process: p.name.clone(),
+                    pid: p.pid,
+                    direction: rule.direction,
+                    rate,
+                    threshold: rule.threshold,
+                    fired,
+                };
+                if !active && rate > rule.threshold {
+                    self.active.insert(key, event(true));
+                    fired.push(event(true));
+                } else if active && rate < rule.threshold * CLEAR_RATIO {
+                    self.active.remove(&key);
+                    push_log(&mut self.log, event(false));
+                }
+            }
+        }
+
+        // Processes that exited clear their alerts
+        let gone: Vec<_> = self
+            .active
+            .keys()
+            .filter(|(_, key)| !self.smoothed.contains_key(key))
+            .cloned()
+            .collect();
+        for key in gone {
+            if let Some(mut event) = self.active.remove(&key) {
+                event.at = now;
+                event.rate = 0.0;
+                event.fired = false;
+                push_log(&mut self.log, event);
+            }
+        }
+
+        for event in &fired {
+            push_log(&mut self.log, event.clone());
+        }
+        fired
+    }
+
+    /// Whether any rule is currently tripped for the process.
+    pub fn is_alerting(&self, key: &ProcessKey) -> bool {
+        self.active.keys().any(|(_, k)| k == key)
+    }
+
+    /// Currently tripped alerts, oldest first.
+    pub fn active(&self) -> Vec<&AlertEvent> {
+        let mut active: Vec<_> = self.active.values().collect();
+        active.sort_by_key(|e| e.at);
+        active
+    }
+}
+
+fn push_log(log: &mut VecDeque<AlertEvent>, event: AlertEvent) {
+    if log.len() == LOG_CAPACITY {
+        log.pop_front();
+    }
+    log.push_back(event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(name: &str, rate_out: f64) -> Process {
+        Process {
+            rate_out,
+            ..Process::for_test(name, 42)
+        }
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("500KB"), Ok(512_000.0));
+        assert_eq!(parse_size("10MB"), Ok(10.0 * 1_048_576.0));
+        assert_eq!(parse_size("1.5 GiB"), Ok(1.5 * 1_073_741_824.0));
+        assert_eq!(parse_size("100kb/s"), Ok(102_400.0));
+        assert_eq!(parse_size("750"), Ok(750.0));
+        assert_eq!(parse_size(" 64 B "), Ok(64.0));
+        assert_eq!(parse_size("10XB"), Err("unknown unit in \"10XB\" (use B, KB, MB, or GB)".to_string()));
+        assert_eq!(parse_size("MB"), Err("invalid size \"MB\"".to_string()));
+        assert!(parse_size("").is_err());
+        assert!(parse_size("1e6").is_err());
+    }
+
+    #[test]
+    fn test_parse_threshold() {
+        assert_eq!(parse_threshold("5MB"), Ok((None, 5.0 * 1_048_576.0)));
+        assert_eq!(parse_threshold("500k"), Ok((None, 512_000.0)));
+        assert_eq!(parse_threshold("2048"), Ok((None, 2048.0)));
+        assert_eq!(
+            parse_threshold("Google Chrome=1.5MB/s"),
+            Ok((Some("Google Chrome".to_string()), 1.5 * 1_048_576.0))
+        );
+        assert!(parse_threshold("5XB").is_err());
+        assert!(parse_threshold("fast").is_err());
+    }
+
+    #[test]
+    fn test_alert_fires_once_and_clears_with_hysteresis() {
+        let mut alerts = Alerts::new(vec![AlertRule {
+            process: None,
+            direction: AlertDirection::Up,
+            threshold: 1000.0,
+        }]);
+
+        // 0 → 1500 smooths to 750, then 1125: fires on the second tick
+        assert!(alerts.evaluate(&[process("curl", 0.0)]).is_empty());
+        assert!(alerts.evaluate(&[process("curl", 1500.0)]).is_empty());
+        let fired = alerts.evaluate(&[process("curl", 1500.0)]);
+        assert_eq!(fired.len(), 1);
+        assert!(alerts.is_alerting(&("curl".to_string(), 42)));
+
+        // Dipping just under the threshold doesn't clear or re-fire
+        assert!(alerts.evaluate(&[process("curl", 900.0)]).is_empty());
+        assert!(alerts.evaluate(&[process("curl", 1200.0)]).is_empty());
+        assert!(alerts.is_alerting(&("curl".to_string(), 42)));
+
+        // Well below clears it
+        alerts.evaluate(&[process("curl", 0.0)]);
+        alerts.evaluate(&[process("curl", 0.0)]);
+        assert!(!alerts.is_alerting(&("curl".to_string(), 42)));
+        assert_eq!(alerts.log.len(), 2);
+        assert!(alerts.log[0].fired);
+        assert!(!alerts.log[1].fired);
+    }
+
+    #[test]
+    fn test_rule_limited_to_process_name() {
+        let mut alerts = Alerts::new(vec![AlertRule {
+            process: Some("dropbox".to_string()),
+            direction: AlertDirection::Up,
+            threshold: 10.0,
+        }]);
+        let fired = alerts.evaluate(&[process("curl", 100.0), process("Dropbox", 100.0)]);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].process, "Dropbox");
+    }
+}
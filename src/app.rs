@@ -1,254 +1,3169 @@
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 use tokio::sync::mpsc;
 
-use crate::data::dns;
-use crate::data::model::{DnsCache, NetworkSnapshot, Process, SortField};
+use crate::data::addr;
+use crate::data::asn::{self, AsnLookup};
+use crate::data::direction;
+use crate::clipboard;
+use crate::export;
+use crate::keymap::Keymap;
+use crate::ui::columns::{Column, Columns};
+use crate::ui::connections::ConnectionColumn;
+use crate::ui::hitbox::{Click, Hitboxes};
+use crate::ui::input::InputLine;
+use crate::ui::layout;
+use crate::ui::processes::ProcessColumn;
+use crate::logfile::LogWriter;
+use crate::alerts::{AlertDirection, AlertRule, Alerts};
+use crate::config::Config;
+use crate::metrics::SharedSnapshot;
+use crate::notify::{DesktopNotifier, Notifier};
+use crate::output::Sink;
+use crate::recorder::Recorder;
+use crate::settings::State;
+use crate::units::Units;
+use crate::data::aliases::{self, AliasFile, Reload};
+use crate::data::dns::{self, DnsCache};
+use crate::data::filter::{self, Field, Filter, FilterSyntax};
+use crate::data::history::ProcessHistory;
+use crate::data::interfaces::{self, InterfaceStat};
+use crate::data::listeners::{self, Listener};
+use crate::data::model::{
+    self, Activity, Churn, Connection, ConnectionKey, Counters, Direction, NetworkSnapshot, OrgStat, PortStat, Process,
+    ProcessKey, RemoteHost, SortDirection, SortField, Watch,
+};
+use crate::data::netstat;
 use crate::data::nettop;
-use crate::data::procinfo;
+use crate::data::procinfo::{self, ProcessDetails};
 
-const BANDWIDTH_HISTORY_LEN: usize = 300;
+/// Time span covered by the churn history; the number of samples kept
+/// follows from the refresh interval, as for the `--history` span.
+const CHURN_HISTORY: Duration = Duration::from_secs(120);
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// Most samples a bandwidth history keeps (a day at the default interval),
+/// however long `--history` is and however short the interval.
+const MAX_HISTORY_SAMPLES: usize = 43_200;
+
+/// Time spans the Graph tab can show, zooming in and out, among those
+/// shorter than the bandwidth history; all of the history comes last.
+const GRAPH_WINDOWS: [Duration; 6] = [
+    Duration::from_secs(60),
+    Duration::from_secs(120),
+    Duration::from_secs(300),
+    Duration::from_secs(600),
+    Duration::from_secs(1800),
+    Duration::from_secs(3600),
+];
+
+/// Samples needed to cover `span` at one sample per `interval`.
+fn history_len(span: Duration, interval: Duration) -> usize {
+    (span.as_secs_f64() / interval.as_secs_f64()).ceil().max(1.0) as usize
+}
+
+/// Intervals without fresh data before the header marks it stale, and
+/// very stale.
+const STALE_INTERVALS: u32 = 3;
+const VERY_STALE_INTERVALS: u32 = 6;
+
+/// How current the data on screen is, by the time since the last
+/// successful fetch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    Fresh,
+    Stale,
+    VeryStale,
+}
+
+/// Processes listed under Top Processes on the Overview tab.
+pub const OVERVIEW_TOP: usize = 10;
+
+/// How long a status message stays in the footer, and the least time one
+/// is shown before a newer message takes its place.
+const STATUS_DURATION: Duration = Duration::from_secs(3);
+const STATUS_MIN_SHOWN: Duration = Duration::from_secs(1);
+
+/// How a footer status message is colored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A footer message and the time it is on screen. A message arriving while
+/// another is up waits until that one has had `STATUS_MIN_SHOWN`.
+#[derive(Debug, Clone)]
+pub struct StatusMessage {
+    pub text: String,
+    pub severity: Severity,
+    shown_from: Instant,
+    expires_at: Instant,
+}
+
+/// Largest count a motion key can be given; more digits stop adding up.
+const MAX_COUNT: usize = 99_999;
+
+/// Rows one notch of the mouse wheel moves the selection.
+const WHEEL_ROWS: usize = 3;
+
+/// How often the DNS cache is written to disk while running.
+const DNS_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Reverse lookups queued per refresh for rows that are not on screen.
+const DNS_BACKGROUND_PER_TICK: usize = 4;
+
+/// How long a vanished connection's first-seen time is remembered, so a
+/// connection that briefly drops out of nettop output keeps its age.
+const CONNECTION_GRACE: Duration = Duration::from_secs(60);
+
+/// How long a vanished process's peak rates are remembered, so a process
+/// that misses a few nettop samples keeps its peaks.
+const PEAK_GRACE: Duration = Duration::from_secs(60);
+
+/// A send queue at least this large (bytes) for `STALLED_TICKS` consecutive
+/// refreshes marks the connection as stalled.
+const STALLED_SEND_QUEUE: u32 = 16 * 1024;
+const STALLED_TICKS: usize = 3;
+
+/// Share of the data budget at which the header indicator turns to a
+/// warning.
+pub const BUDGET_WARN_RATIO: f64 = 0.8;
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
 pub enum ActiveTab {
     Processes,
     Connections,
+    Remotes,
+    Listeners,
+    Interfaces,
     Overview,
+    Graph,
 }
 
 impl ActiveTab {
     pub fn next(self) -> Self {
         match self {
             ActiveTab::Processes => ActiveTab::Connections,
-            ActiveTab::Connections => ActiveTab::Overview,
-            ActiveTab::Overview => ActiveTab::Processes,
+            ActiveTab::Connections => ActiveTab::Remotes,
+            ActiveTab::Remotes => ActiveTab::Listeners,
+            ActiveTab::Listeners => ActiveTab::Interfaces,
+            ActiveTab::Interfaces => ActiveTab::Overview,
+            ActiveTab::Overview => ActiveTab::Graph,
+            ActiveTab::Graph => ActiveTab::Processes,
         }
     }
 
     pub fn prev(self) -> Self {
         match self {
-            ActiveTab::Processes => ActiveTab::Overview,
+            ActiveTab::Processes => ActiveTab::Graph,
             ActiveTab::Connections => ActiveTab::Processes,
-            ActiveTab::Overview => ActiveTab::Connections,
+            ActiveTab::Remotes => ActiveTab::Connections,
+            ActiveTab::Listeners => ActiveTab::Remotes,
+            ActiveTab::Interfaces => ActiveTab::Listeners,
+            ActiveTab::Overview => ActiveTab::Interfaces,
+            ActiveTab::Graph => ActiveTab::Overview,
+        }
+    }
+}
+
+/// One tab's filter: what is applied, what is being typed, and whether the
+/// prompt is open.
+#[derive(Debug, Clone, Default)]
+pub struct TabFilter {
+    /// Applied filter lines; a row is shown if it matches any of them
+    pub text: Vec<String>,
+    /// `text` parsed
+    filter: Filter,
+    pub input: InputLine,
+    pub filtering: bool,
+}
+
+impl TabFilter {
+    /// A filter from lines already known to parse.
+    fn new(syntax: &FilterSyntax, text: Vec<String>) -> Self {
+        TabFilter {
+            filter: Filter::parse(syntax, &text).unwrap_or_default(),
+            text,
+            ..TabFilter::default()
         }
     }
 }
 
+/// Search within the active tab: rows stay visible and the selection jumps
+/// between those that match.
+#[derive(Debug, Clone, Default)]
+pub struct Search {
+    pub input: InputLine,
+    /// Whether the search prompt is open
+    pub typing: bool,
+    /// The last valid `input`, parsed like a filter
+    query: Filter,
+    /// Row selected when the prompt opened, which typing searches from
+    origin: usize,
+}
+
+/// A connection that disappeared from nettop output, kept around for a few
+/// ticks so short-lived flows remain visible.
+#[derive(Debug, Clone)]
+pub struct ClosedConnection {
+    /// The owning process as last seen, without its connections, so the
+    /// row can still be shown after the process has exited
+    pub process: Process,
+    pub connection: Connection,
+    pub ticks_left: usize,
+}
+
+/// A Connections row by position: a process in the snapshot, and one of its
+/// connections or an entry in the closed buffer; or a closed entry whose
+/// process has exited.
+#[derive(Debug, Clone, Copy)]
+enum RowRef {
+    Open(usize, usize),
+    Closed(usize, usize),
+    Exited(usize),
+}
+
+/// One row of the Connections tab: an open or recently closed connection
+/// and the process that owns it.
+pub struct ConnectionRow<'a> {
+    pub process: &'a Process,
+    pub conn: &'a Connection,
+    pub closed: bool,
+}
+
 pub struct App {
     pub active_tab: ActiveTab,
     pub snapshot: NetworkSnapshot,
+    pub remote_hosts: Vec<RemoteHost>,
+    pub port_stats: Vec<PortStat>,
+    pub org_stats: Vec<OrgStat>,
     pub process_index: usize,
     pub connection_index: usize,
+    /// Connections tab rows, worked out when they can change
+    connection_rows: Vec<RowRef>,
+    /// The highlighted process and connection, so the highlight follows
+    /// them when a refresh reorders the rows
+    selected_process: Option<ProcessKey>,
+    selected_connection: Option<ConnectionKey>,
+    /// Highlighted entry of the Overview's Top Processes, followed across
+    /// refreshes like the Processes tab's
+    pub overview_index: usize,
+    overview_process: Option<ProcessKey>,
+    pub remote_index: usize,
+    /// First row shown in each table, kept across frames so the view only
+    /// scrolls when the selection reaches an edge
+    pub process_offset: usize,
+    pub connection_offset: usize,
+    pub remote_offset: usize,
+    pub listener_offset: usize,
+    pub interface_offset: usize,
+    pub listeners: Vec<Listener>,
+    pub listener_index: usize,
+    pub interfaces: Vec<InterfaceStat>,
+    pub interface_index: usize,
+    pub interface_history: HashMap<String, VecDeque<f64>>,
+    /// Rate history of each process, `history_span` worth
+    pub process_history: ProcessHistory,
     pub sort_field: SortField,
-    pub filter_text: Option<String>,
-    pub filter_input: String,
-    pub filtering: bool,
+    pub sort_direction: SortDirection,
+    /// Each tab's filter, indexed by `ActiveTab`
+    filters: [TabFilter; 7],
+    /// The process the Connections tab is drilled into, if any
+    pub drilled: Option<ProcessKey>,
+    /// Connections selection from before the drill-down, put back on the
+    /// way out
+    drill_return: Option<ConnectionKey>,
+    pub filter_syntax: FilterSyntax,
+    pub search: Search,
     pub show_help: bool,
+    pub keymap: Keymap,
+    /// Table rows that fit in the main area, updated on every draw
+    pub viewport_rows: usize,
+    /// Count typed before a motion key, applied and cleared by the next key
+    pub pending_count: Option<usize>,
+    /// Where the last frame drew tabs and the table, for mouse clicks
+    pub hitboxes: Hitboxes,
+    pub show_process_detail: bool,
+    /// The process the detail popup was opened on; it stays on it however
+    /// the rows move
+    detail_process: Option<ProcessKey>,
+    pub show_connection_detail: bool,
+    /// Show details of the selected row in a pane under the table
+    pub show_detail_pane: bool,
+    /// Index into `graph_windows()` of the span the Graph tab shows
+    pub graph_window: usize,
+    /// Draw the selected process's rates over the totals on the Graph tab
+    pub graph_process: bool,
+    /// Draw the bandwidth sparkline on a logarithmic scale
+    pub log_scale: bool,
+    /// Command line and cwd of the process shown in the detail popup
+    pub process_details: Option<ProcessDetails>,
+    pub show_services: bool,
+    pub show_hostnames: bool,
+    pub inbound_only: bool,
+    pub hide_idle: bool,
     pub paused: bool,
     pub should_quit: bool,
-    pub bandwidth_history: VecDeque<f64>,
+    /// Total (in, out) rates, one sample per tick
+    pub bandwidth_history: VecDeque<(f64, f64)>,
+
+    // Connections opened/closed on the last tick, and (opened, closed) per tick
+    pub churn: Churn,
+    pub churn_history: VecDeque<(usize, usize)>,
+
+    // Recently closed connections; never counted in totals
+    pub closed_connections: Vec<ClosedConnection>,
+
+    // Session-wide peak of total (in + out) bandwidth
+    pub peak_total_rate: f64,
+    pub peak_total_at: Option<Instant>,
+
+    // Latest processes before display filtering (e.g. local exclusion)
+    all_processes: Vec<Process>,
 
     // Internal state for rate computation
-    prev_bytes: HashMap<(String, u32), (u64, u64)>,
+    prev_bytes: HashMap<ProcessKey, Counters>,
+    prev_conn_bytes: HashMap<ConnectionKey, (u64, u64)>,
+    prev_iface_bytes: HashMap<String, (u64, u64)>,
+    send_queue_ticks: HashMap<ConnectionKey, usize>,
+    conn_activity: HashMap<ConnectionKey, Activity>,
+
+    // Per-process peak rates (in, out) and when the process was last seen,
+    // kept for `PEAK_GRACE` when a process drops out
+    peak_rates: HashMap<ProcessKey, (f64, f64, Instant)>,
+
+    // Connection first-seen and last-seen times
+    conn_seen: HashMap<ConnectionKey, (Instant, Instant)>,
+
+    // Connection identities from the previous snapshot, None before the first
+    prev_conn_keys: Option<HashSet<ConnectionKey>>,
+    opened_totals: HashMap<ProcessKey, u64>,
 
     // DNS
-    dns_cache: DnsCache,
-    dns_pending: HashSet<String>,
+    pub dns_cache: DnsCache,
     dns_req_tx: mpsc::Sender<String>,
-    dns_res_rx: mpsc::Receiver<(String, Option<String>)>,
+    dns_res_rx: mpsc::Receiver<(String, dns::Lookup)>,
+
+    // Optional AS organization lookups
+    pub asn_lookup: Option<AsnLookup>,
 
     // Config
-    pub interval_secs: u64,
+    pub interval: Duration,
+    /// Time span of the bandwidth histories
+    pub history_span: Duration,
+    /// Samples kept in the bandwidth histories (`history_span` worth)
+    bandwidth_history_len: usize,
+    churn_history_len: usize,
+    /// Column order and visibility of the Processes and Connections tables
+    pub process_columns: Columns<ProcessColumn>,
+    pub connection_columns: Columns<ConnectionColumn>,
+    pub show_columns: bool,
+    /// Highlighted entry in the column chooser
+    pub column_index: usize,
+    pub show_sort_menu: bool,
+    /// Highlighted entry in the sort picker
+    pub sort_menu_index: usize,
+    /// Where widths adjusted with `<`/`>` are saved, and whether any were
+    state_file: Option<PathBuf>,
+    widths_changed: bool,
+    /// Show raw process names instead of app bundle display names
+    pub raw_names: bool,
+    /// How byte counts and rates are formatted
+    pub units: Units,
+    /// Where `e` writes snapshot exports; None for the current directory
+    pub export_dir: Option<PathBuf>,
+    /// Background writer for `--log`; keeps collecting while paused
+    pub log_writer: Option<LogWriter>,
+    /// Why the last nettop fetch failed, if it did
+    pub fetch_error: Option<String>,
+    /// When the last successful fetch finished, and how long it took
+    pub last_fetch: Option<(Instant, Duration)>,
+    /// A fetch is under way, shown by a spinner in the header
+    pub fetching: bool,
+    /// Fetches started, turning the spinner
+    pub fetch_count: usize,
+    /// Copy of the latest snapshot for the metrics endpoint
+    pub metrics_snapshot: Option<SharedSnapshot>,
+    /// Rate alert rules and their state
+    pub alerts: Alerts,
+    /// Ring the terminal bell when an alert fires
+    pub alert_bell: bool,
+    /// Set when an alert fired and the bell is due; taken by the UI loop
+    pub bell_pending: bool,
+    pub show_alert_log: bool,
+    /// Webhook/ntfy delivery for fired alerts
+    pub notifier: Option<Notifier>,
+    /// Notification Center alerts (--notify)
+    pub desktop_notifier: Option<DesktopNotifier>,
+    /// Public remote addresses seen this session, for new-host notifications
+    seen_remotes: Option<HashSet<String>>,
+    /// Scope collection and display to one process (--pid / --process)
+    pub watch: Option<Watch>,
+    /// The watched process is not in the latest sample
+    pub watch_exited: bool,
+    /// Data budget for the session in bytes (--budget)
+    pub budget: Option<u64>,
+    /// Bytes moved since launch (or the last budget reset), from per-tick deltas
+    pub budget_used: u64,
+    /// Highest budget threshold already announced: 0, 80, or 100 (%)
+    budget_announced: u8,
+    /// SQLite history writer (--db)
+    pub recorder: Option<Recorder>,
+    /// Influx/statsd sinks fed once per refresh
+    pub sinks: Vec<Box<dyn Sink>>,
+    /// Last error from an output sink
+    pub sink_warning: Option<String>,
+    pub closed_retention: usize,
+    pub ports_exclude_loopback: bool,
+    pub exclude_local: bool,
+    pub vpn_patterns: Vec<String>,
+    pub netstat_enrich: bool,
+    pub idle_after: usize,
+    pub resolve_local: bool,
+    pub dns_enabled: bool,
+    /// Where the DNS cache is persisted; None disables persistence
+    pub dns_cache_file: Option<PathBuf>,
+    dns_saved_at: Instant,
+    /// App bundle path → display name, so each Info.plist is read once
+    bundle_names: HashMap<String, Option<String>>,
+    /// Hosts-style file of address/CIDR labels shown instead of hostnames
+    pub alias_path: Option<PathBuf>,
+    alias_file: AliasFile,
+    /// Problem found the last time the alias file was read
+    pub alias_warning: Option<String>,
+    /// Transient footer messages, oldest first
+    status: VecDeque<StatusMessage>,
 }
 
 impl App {
-    pub fn new(sort_field: SortField, interval_secs: u64) -> Self {
-        let (dns_req_tx, dns_res_rx) = dns::spawn_dns_resolver();
+    /// App state for the merged command-line and config-file settings.
+    /// Resources (writers, sinks, lookups) are attached by the caller.
+    pub fn new(config: &Config) -> Self {
+        let interval = config.interval;
+        let bandwidth_history_len = history_len(config.history, interval).min(MAX_HISTORY_SAMPLES);
+        let churn_history_len = history_len(CHURN_HISTORY, interval);
+        let watch = match (config.pid, &config.process) {
+            (Some(pid), _) => Some(Watch::Pid(pid)),
+            (None, Some(name)) => Some(Watch::Name(name.clone())),
+            (None, None) => None,
+        };
+        let active_tab = match config.tab {
+            Some(tab) => tab,
+            None if watch.is_some() => ActiveTab::Connections,
+            None => ActiveTab::Processes,
+        };
+        let rules = config
+            .alert_up
+            .iter()
+            .map(|rule| (rule, AlertDirection::Up))
+            .chain(config.alert_down.iter().map(|rule| (rule, AlertDirection::Down)))
+            .map(|((process, threshold), direction)| AlertRule {
+                process: process.clone(),
+                direction,
+                threshold: *threshold,
+            })
+            .collect();
+        let mut process_columns = config.process_columns.clone();
+        let shown = [
+            (config.show_peak, ProcessColumn::Peak),
+            (config.show_user, ProcessColumn::User),
+            (config.show_uptime, ProcessColumn::Uptime),
+            (config.show_pps, ProcessColumn::PpsIn),
+            (config.show_pps, ProcessColumn::PpsOut),
+        ];
+        for (_, column) in shown.into_iter().filter(|(on, _)| *on) {
+            process_columns.show(column);
+        }
+        let (dns_req_tx, dns_res_rx) = dns::spawn_dns_resolver(false);
         App {
-            active_tab: ActiveTab::Processes,
+            active_tab,
             snapshot: NetworkSnapshot::default(),
+            remote_hosts: Vec::new(),
+            port_stats: Vec::new(),
+            org_stats: Vec::new(),
             process_index: 0,
             connection_index: 0,
-            sort_field,
-            filter_text: None,
-            filter_input: String::new(),
-            filtering: false,
+            connection_rows: Vec::new(),
+            selected_process: None,
+            overview_index: 0,
+            overview_process: None,
+            selected_connection: None,
+            remote_index: 0,
+            process_offset: 0,
+            connection_offset: 0,
+            remote_offset: 0,
+            listener_offset: 0,
+            interface_offset: 0,
+            listeners: Vec::new(),
+            listener_index: 0,
+            interfaces: Vec::new(),
+            interface_index: 0,
+            interface_history: HashMap::new(),
+            process_history: ProcessHistory::new(bandwidth_history_len),
+            sort_field: config.sort_by,
+            sort_direction: config.sort_by.default_direction(),
+            // Checked when the config was loaded; every tab starts with it
+            filters: std::array::from_fn(|_| TabFilter::new(&config.filter_syntax(), config.filter.clone())),
+            filter_syntax: config.filter_syntax(),
+            drilled: None,
+            drill_return: None,
+            search: Search::default(),
             show_help: false,
+            keymap: config.keymap.clone(),
+            viewport_rows: 0,
+            pending_count: None,
+            hitboxes: Hitboxes::default(),
+            show_process_detail: false,
+            detail_process: None,
+            show_connection_detail: false,
+            show_detail_pane: false,
+            graph_window: 2,
+            graph_process: false,
+            log_scale: config.log_scale,
+            process_details: None,
+            show_services: true,
+            show_hostnames: true,
+            inbound_only: false,
+            hide_idle: false,
             paused: false,
             should_quit: false,
-            bandwidth_history: VecDeque::with_capacity(BANDWIDTH_HISTORY_LEN),
+            bandwidth_history: VecDeque::with_capacity(bandwidth_history_len),
+            churn: Churn::default(),
+            churn_history: VecDeque::with_capacity(churn_history_len),
+            closed_connections: Vec::new(),
+            peak_total_rate: 0.0,
+            peak_total_at: None,
+            all_processes: Vec::new(),
             prev_bytes: HashMap::new(),
-            dns_cache: HashMap::new(),
-            dns_pending: HashSet::new(),
+            prev_conn_bytes: HashMap::new(),
+            prev_iface_bytes: HashMap::new(),
+            send_queue_ticks: HashMap::new(),
+            conn_activity: HashMap::new(),
+            peak_rates: HashMap::new(),
+            conn_seen: HashMap::new(),
+            prev_conn_keys: None,
+            opened_totals: HashMap::new(),
+            dns_cache: DnsCache::new(config.dns_cache_size),
             dns_req_tx,
             dns_res_rx,
-            interval_secs,
+            asn_lookup: None,
+            interval,
+            history_span: config.history,
+            bandwidth_history_len,
+            churn_history_len,
+            process_columns,
+            connection_columns: config.connection_columns.clone(),
+            show_columns: false,
+            column_index: 0,
+            show_sort_menu: false,
+            sort_menu_index: 0,
+            state_file: None,
+            widths_changed: false,
+            raw_names: config.raw_names,
+            units: config.units,
+            export_dir: config.export_dir.clone(),
+            log_writer: None,
+            fetch_error: None,
+            last_fetch: None,
+            fetching: false,
+            fetch_count: 0,
+            metrics_snapshot: None,
+            alerts: Alerts::new(rules),
+            alert_bell: config.alert_bell,
+            bell_pending: false,
+            show_alert_log: false,
+            notifier: None,
+            desktop_notifier: None,
+            seen_remotes: None,
+            watch,
+            watch_exited: false,
+            budget: config.budget.map(|bytes| bytes as u64),
+            budget_used: 0,
+            budget_announced: 0,
+            recorder: None,
+            sinks: Vec::new(),
+            sink_warning: None,
+            closed_retention: config.closed_retention,
+            ports_exclude_loopback: config.ports_skip_loopback,
+            exclude_local: config.no_local,
+            vpn_patterns: config.vpn_ifaces.clone(),
+            netstat_enrich: config.netstat,
+            idle_after: config.idle_after,
+            resolve_local: config.resolve_local,
+            dns_enabled: !config.no_dns,
+            dns_cache_file: None,
+            dns_saved_at: Instant::now(),
+            bundle_names: HashMap::new(),
+            alias_path: config.aliases.clone(),
+            alias_file: AliasFile::default(),
+            alias_warning: None,
+            status: VecDeque::new(),
         }
     }
 
     pub async fn update_data(&mut self) {
-        if self.paused {
+        // Pausing freezes the display, but a log still gets every refresh
+        if self.paused && self.log_writer.is_none() {
             return;
         }
 
         // Drain any DNS results
-        dns::drain_dns_results(&mut self.dns_res_rx, &mut self.dns_cache, &mut self.dns_pending);
+        dns::drain_dns_results(&mut self.dns_res_rx, &mut self.dns_cache);
 
         // Fetch nettop data
+        self.fetch_count += 1;
+        let started = Instant::now();
         let mut processes = match nettop::fetch_nettop_snapshot().await {
             Ok(p) => p,
-            Err(_) => return,
+            Err(e) => {
+                // Said once per distinct error, not on every refresh
+                if self.fetch_error.as_ref() != Some(&e) {
+                    self.push_status(Severity::Error, format!("refresh failed: {}", e));
+                }
+                self.fetch_error = Some(e);
+                return;
+            }
         };
+        self.fetch_error = None;
+        self.last_fetch = Some((Instant::now(), started.elapsed()));
+        self.scope_to_watch(&mut processes);
 
         // Compute rates
-        let interval = self.interval_secs as f64;
+        let interval = self.interval.as_secs_f64();
         nettop::compute_rates(&mut processes, &self.prev_bytes, interval);
+        nettop::compute_connection_rates(&mut processes, &self.prev_conn_bytes, interval);
+        self.budget_used += nettop::transferred(&processes, &self.prev_bytes);
+        self.check_budget();
 
         // Save current bytes for next rate computation
         self.prev_bytes = processes
             .iter()
-            .map(|p| ((p.name.clone(), p.pid), (p.bytes_in, p.bytes_out)))
+            .map(|p| (p.key(), p.counters()))
+            .collect();
+        self.prev_conn_bytes = processes
+            .iter()
+            .flat_map(|p| {
+                p.connections
+                    .iter()
+                    .map(move |c| (c.key(p.pid), (c.bytes_in, c.bytes_out)))
+            })
             .collect();
 
-        // Enrich with process paths
+        if let Some(writer) = &self.log_writer {
+            writer.log(&processes);
+        }
+        if self.paused {
+            return;
+        }
+
+        self.track_peaks(&mut processes, Instant::now());
+        self.process_history.record(&processes);
+
+        // Listening sockets; keep the previous list if lsof fails
+        if let Ok(mut listeners) = listeners::fetch_listeners().await {
+            if self.watch.is_some() {
+                listeners.retain(|l| processes.iter().any(|p| p.pid == l.pid));
+            }
+            self.listeners = listeners;
+        }
+
+        interfaces::tag_vpn(&mut processes, &self.vpn_patterns);
+        if self.netstat_enrich {
+            if let Ok(sockets) = netstat::fetch_sockets().await {
+                netstat::enrich(&mut processes, &sockets);
+            }
+            self.track_send_queues(&mut processes);
+        }
+        direction::tag_directions(&mut processes, &self.listeners);
+        self.track_connection_ages(&mut processes);
+        self.conn_activity = model::track_idle(&self.conn_activity, &mut processes, self.idle_after);
+        self.track_churn(&mut processes);
+        self.track_closed(&processes);
+
+        // Enrich with process paths and owners
         procinfo::enrich_process_paths(&mut processes);
+        procinfo::enrich_process_info(&mut processes);
+        procinfo::enrich_display_names(&mut processes, &mut self.bundle_names);
 
-        // Update DNS
-        dns::update_dns(
-            &mut processes,
-            &self.dns_cache,
-            &mut self.dns_pending,
-            &self.dns_req_tx,
-        );
+        dns::apply_hostnames(&mut processes, &mut self.dns_cache);
+        self.reload_aliases(false);
+        aliases::apply_aliases(&mut processes, &self.alias_file.aliases);
 
-        // Sort
-        self.sort_processes(&mut processes);
+        if let Some(lookup) = self.asn_lookup.as_mut() {
+            asn::annotate(&mut processes, lookup);
+        }
+
+        if let Ok(interfaces) = interfaces::fetch_interface_stats().await {
+            self.update_interfaces(interfaces);
+        }
 
         // Build snapshot
-        self.snapshot = NetworkSnapshot::from_processes(processes);
+        self.all_processes = processes;
+        self.rebuild_snapshot();
+        self.request_visible_dns();
+        self.request_background_dns();
+        if self.dns_saved_at.elapsed() >= DNS_SAVE_INTERVAL {
+            self.save_dns_cache();
+        }
+
+        if let Some(shared) = &self.metrics_snapshot {
+            if let Ok(mut published) = shared.write() {
+                *published = self.snapshot.clone();
+            }
+        }
+        let fired = self.alerts.evaluate(&self.all_processes);
+        if !fired.is_empty() && self.alert_bell {
+            self.bell_pending = true;
+        }
+        if let Some(notifier) = &self.notifier {
+            for event in &fired {
+                notifier.notify(event);
+            }
+        }
+        if let Some(desktop) = self.desktop_notifier.as_mut() {
+            for event in &fired {
+                desktop.alert(event);
+            }
+        }
+        self.notify_new_remotes();
+        if let Some(recorder) = &self.recorder {
+            recorder.record(&self.snapshot);
+        }
+        if !self.sinks.is_empty() {
+            self.sink_warning = None;
+            for sink in self.sinks.iter_mut() {
+                if let Err(e) = sink.emit(&self.snapshot) {
+                    self.sink_warning = Some(format!("output: {}", e));
+                }
+            }
+        }
 
         // Update bandwidth history
         let total_rate = self.snapshot.total_rate_in + self.snapshot.total_rate_out;
-        if self.bandwidth_history.len() >= BANDWIDTH_HISTORY_LEN {
+        if self.bandwidth_history.len() >= self.bandwidth_history_len {
             self.bandwidth_history.pop_front();
         }
-        self.bandwidth_history.push_back(total_rate);
+        self.bandwidth_history
+            .push_back((self.snapshot.total_rate_in, self.snapshot.total_rate_out));
+        if total_rate > self.peak_total_rate {
+            self.peak_total_rate = total_rate;
+            self.peak_total_at = Some(Instant::now());
+        }
+    }
+
+    /// Rebuild the displayed snapshot and its aggregates from the latest
+    /// processes, applying the local-traffic exclusion and current sort.
+    pub fn rebuild_snapshot(&mut self) {
+        let mut processes = self.all_processes.clone();
+        if self.exclude_local {
+            addr::exclude_local(&mut processes);
+        }
+        self.sort_processes(&mut processes);
+
+        self.snapshot = NetworkSnapshot::from_processes(processes);
+        self.refresh_connection_rows();
+        self.remote_hosts = self.snapshot.remote_hosts();
+        self.sort_remote_hosts();
+        self.sort_listeners();
+        self.sort_interfaces();
+        self.port_stats = self.snapshot.port_stats(self.ports_exclude_loopback);
+        if self.asn_lookup.is_some() {
+            self.org_stats = self.snapshot.org_stats();
+        }
 
-        // Clamp indices
-        let max_proc = self.snapshot.processes.len().saturating_sub(1);
-        if self.process_index > max_proc {
-            self.process_index = max_proc;
+        // Clamp indices; the selected process and connection are followed
+        let max_remote = self.filtered_remote_hosts().len().saturating_sub(1);
+        if self.remote_index > max_remote {
+            self.remote_index = max_remote;
+        }
+        let max_listener = self.filtered_listeners().len().saturating_sub(1);
+        if self.listener_index > max_listener {
+            self.listener_index = max_listener;
         }
+        let max_interface = self.filtered_interfaces().len().saturating_sub(1);
+        if self.interface_index > max_interface {
+            self.interface_index = max_interface;
+        }
+        self.restore_selection();
     }
 
-    fn sort_processes(&self, processes: &mut Vec<Process>) {
-        match self.sort_field {
-            SortField::Name => processes.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase())),
-            SortField::Pid => processes.sort_by_key(|p| p.pid),
-            SortField::Connections => {
-                processes.sort_by(|a, b| b.connection_count().cmp(&a.connection_count()))
+    /// Persist the DNS cache if a cache file is configured. Failures are
+    /// ignored; the cache is only an optimization.
+    pub fn save_dns_cache(&mut self) {
+        self.dns_saved_at = Instant::now();
+        if let Some(path) = &self.dns_cache_file {
+            let _ = self.dns_cache.save(path);
+        }
+    }
+
+    /// Re-read the alias file if it changed, or unconditionally when `force`
+    /// (the manual reload key).
+    fn reload_aliases(&mut self, force: bool) {
+        let Some(path) = &self.alias_path else {
+            return;
+        };
+        if let Reload::Reloaded(warning) = self.alias_file.reload(path, force) {
+            self.alias_warning = warning;
+        }
+    }
+
+    /// Keep only the watched process. A watched PID that exits is then
+    /// watched by its last name, so a restarted process is picked up again.
+    fn scope_to_watch(&mut self, processes: &mut Vec<Process>) {
+        let Some(watch) = &self.watch else {
+            return;
+        };
+        processes.retain(|p| watch.matches(p));
+        match (processes.first(), watch) {
+            (Some(_), _) => self.watch_exited = false,
+            (None, Watch::Pid(_)) => {
+                if let Some(p) = self.all_processes.first() {
+                    self.watch = Some(Watch::Name(p.name.clone()));
+                }
+                self.watch_exited = true;
+            }
+            (None, Watch::Name(_)) => self.watch_exited = true,
+        }
+    }
+
+    /// Announce crossing 80% and 100% of the data budget once each.
+    fn check_budget(&mut self) {
+        let Some(budget) = self.budget else {
+            return;
+        };
+        let used = self.budget_used as f64 / budget.max(1) as f64;
+        let level = if used >= 1.0 {
+            100
+        } else if used >= BUDGET_WARN_RATIO {
+            80
+        } else {
+            0
+        };
+        if level <= self.budget_announced {
+            return;
+        }
+        self.budget_announced = level;
+        let message = format!("Data budget {}% used", level);
+        if self.alert_bell {
+            self.bell_pending = true;
+        }
+        if let Some(desktop) = self.desktop_notifier.as_mut() {
+            desktop.budget(&message);
+        }
+        self.push_status(Severity::Warning, message);
+    }
+
+    /// Restart the data budget count from zero.
+    pub fn reset_budget(&mut self) {
+        self.budget_used = 0;
+        self.budget_announced = 0;
+        self.set_status("Data budget counter reset");
+    }
+
+    /// Notify about public remote addresses not seen before this session.
+    /// The first refresh only records the baseline.
+    fn notify_new_remotes(&mut self) {
+        let Some(desktop) = self.desktop_notifier.as_mut() else {
+            return;
+        };
+        let baseline = self.seen_remotes.is_none();
+        let seen = self.seen_remotes.get_or_insert_with(HashSet::new);
+        for p in &self.all_processes {
+            for conn in &p.connections {
+                if addr::classify_addr(&conn.remote_addr) != Some(addr::AddrClass::Public) {
+                    continue;
+                }
+                if seen.insert(conn.remote_addr.clone()) && !baseline {
+                    let host = conn.hostname.as_deref().unwrap_or(&conn.remote_addr);
+                    desktop.new_host(&p.name, host);
+                }
+            }
+        }
+    }
+
+    /// Reload the alias file now and relabel the current rows.
+    pub fn refresh_aliases(&mut self) {
+        self.reload_aliases(true);
+        self.relabel_hosts();
+    }
+
+    /// Re-apply cached hostnames, lookup states, and aliases to the current
+    /// rows.
+    fn relabel_hosts(&mut self) {
+        dns::apply_hostnames(&mut self.all_processes, &mut self.dns_cache);
+        aliases::apply_aliases(&mut self.all_processes, &self.alias_file.aliases);
+        self.rebuild_snapshot();
+    }
+
+    /// Restart the resolver so it consults the system DNS cache first.
+    pub fn enable_system_dns_cache(&mut self) {
+        let (req_tx, res_rx) = dns::spawn_dns_resolver(true);
+        self.dns_req_tx = req_tx;
+        self.dns_res_rx = res_rx;
+        self.dns_cache.clear_pending();
+    }
+
+    /// Apply any finished reverse lookups to the displayed rows. Called
+    /// between refreshes so hostnames appear without waiting a full tick.
+    pub fn poll_dns(&mut self) {
+        let received = dns::drain_dns_results(&mut self.dns_res_rx, &mut self.dns_cache);
+        if received > 0 {
+            self.relabel_hosts();
+        }
+    }
+
+    /// Queue lookups for remote addresses on screen (plus a screenful either
+    /// side) in the Connections or Remotes tab. Cheap enough to call after
+    /// every key press so scrolling, filtering, and tab switches take effect
+    /// immediately.
+    pub fn request_visible_dns(&mut self) {
+        if !self.dns_enabled {
+            return;
+        }
+        let page = self.viewport_rows.max(1);
+        let ips: Vec<String> = match self.active_tab {
+            ActiveTab::Connections => {
+                let rows = self.filtered_connections();
+                let start = self.connection_index.saturating_sub(page * 2);
+                rows.iter()
+                    .skip(start)
+                    .take(page * 4)
+                    .map(|r| r.conn.remote_addr.clone())
+                    .collect()
             }
-            SortField::BytesIn => {
-                processes.sort_by(|a, b| b.bytes_in.cmp(&a.bytes_in))
+            ActiveTab::Remotes => {
+                let hosts = self.filtered_remote_hosts();
+                let start = self.remote_index.saturating_sub(page * 2);
+                hosts.iter().skip(start).take(page * 4).map(|h| h.host.clone()).collect()
             }
-            SortField::BytesOut => {
-                processes.sort_by(|a, b| b.bytes_out.cmp(&a.bytes_out))
+            _ => return,
+        };
+        let aliases = &self.alias_file.aliases;
+        let queued = dns::request_lookups(
+            ips.iter().map(String::as_str).filter(|ip| aliases.lookup(ip).is_none()),
+            usize::MAX,
+            &mut self.dns_cache,
+            &self.dns_req_tx,
+            self.resolve_local,
+        );
+        // Show the newly queued rows as resolving right away
+        if queued > 0 {
+            self.relabel_hosts();
+        }
+    }
+
+    /// Trickle lookups for off-screen addresses so they are mostly resolved
+    /// by the time they scroll into view.
+    fn request_background_dns(&mut self) {
+        if !self.dns_enabled {
+            return;
+        }
+        let ips: Vec<String> = self
+            .all_processes
+            .iter()
+            .flat_map(|p| p.connections.iter().map(|c| c.remote_addr.clone()))
+            .collect();
+        let aliases = &self.alias_file.aliases;
+        dns::request_lookups(
+            ips.iter().map(String::as_str).filter(|ip| aliases.lookup(ip).is_none()),
+            DNS_BACKGROUND_PER_TICK,
+            &mut self.dns_cache,
+            &self.dns_req_tx,
+            self.resolve_local,
+        );
+    }
+
+    /// Compute interface rates and extend each interface's rate history.
+    /// Interfaces that vanished (e.g. a VPN going down) lose their history.
+    fn update_interfaces(&mut self, mut interfaces: Vec<InterfaceStat>) {
+        let interval = self.interval.as_secs_f64();
+        interfaces::compute_interface_rates(&mut interfaces, &self.prev_iface_bytes, interval);
+        self.prev_iface_bytes = interfaces
+            .iter()
+            .map(|i| (i.name.clone(), (i.bytes_in, i.bytes_out)))
+            .collect();
+
+        self.interface_history
+            .retain(|name, _| interfaces.iter().any(|i| &i.name == name));
+        for iface in &interfaces {
+            let history = self
+                .interface_history
+                .entry(iface.name.clone())
+                .or_insert_with(|| VecDeque::with_capacity(self.bandwidth_history_len));
+            if history.len() >= self.bandwidth_history_len {
+                history.pop_front();
             }
-            SortField::RateIn => {
-                processes.sort_by(|a, b| b.rate_in.partial_cmp(&a.rate_in).unwrap_or(std::cmp::Ordering::Equal))
+            history.push_back(iface.rate_in + iface.rate_out);
+        }
+        self.interfaces = interfaces;
+    }
+
+    /// Stamp each connection with the time it was first observed and forget
+    /// connections that have been gone longer than the grace period.
+    fn track_connection_ages(&mut self, processes: &mut [Process]) {
+        let now = Instant::now();
+        for p in processes.iter_mut() {
+            for conn in p.connections.iter_mut() {
+                let seen = self.conn_seen.entry(conn.key(p.pid)).or_insert((now, now));
+                seen.1 = now;
+                conn.first_seen = Some(seen.0);
             }
-            SortField::RateOut => {
-                processes.sort_by(|a, b| b.rate_out.partial_cmp(&a.rate_out).unwrap_or(std::cmp::Ordering::Equal))
+        }
+        self.conn_seen
+            .retain(|_, (_, last_seen)| now.duration_since(*last_seen) < CONNECTION_GRACE);
+    }
+
+    /// Flag connections whose send queue has stayed large for several ticks.
+    fn track_send_queues(&mut self, processes: &mut [Process]) {
+        let mut ticks = HashMap::new();
+        for p in processes.iter_mut() {
+            for conn in p.connections.iter_mut() {
+                if conn.send_q.is_some_and(|q| q >= STALLED_SEND_QUEUE) {
+                    let key = conn.key(p.pid);
+                    let count = self.send_queue_ticks.get(&key).copied().unwrap_or(0) + 1;
+                    conn.send_stalled = count >= STALLED_TICKS;
+                    ticks.insert(key, count);
+                }
             }
         }
+        self.send_queue_ticks = ticks;
     }
 
-    pub fn filtered_processes(&self) -> Vec<&Process> {
-        let filter = match &self.filter_text {
-            Some(f) if !f.is_empty() => Some(f.to_lowercase()),
-            _ => None,
+    /// Count connections opened and closed since the previous snapshot.
+    fn track_churn(&mut self, processes: &mut [Process]) {
+        let keys = model::connection_keys(processes);
+        let churn = match &self.prev_conn_keys {
+            Some(prev) => model::connection_churn(prev, processes),
+            None => Churn::default(),
         };
+        self.prev_conn_keys = Some(keys);
 
-        self.snapshot
-            .processes
+        for p in processes.iter_mut() {
+            let key = p.key();
+            let opened = churn.opened_by_process.get(&key).copied().unwrap_or(0);
+            let total = self.opened_totals.entry(key).or_insert(0);
+            *total += opened as u64;
+            p.new_connections = opened;
+            p.total_new_connections = *total;
+        }
+
+        if self.churn_history.len() >= self.churn_history_len {
+            self.churn_history.pop_front();
+        }
+        self.churn_history.push_back((churn.opened, churn.closed));
+        self.churn = churn;
+    }
+
+    /// Move connections that vanished since the last snapshot into the
+    /// closed buffer and age out entries whose retention has expired.
+    fn track_closed(&mut self, processes: &[Process]) {
+        let current = model::connection_keys(processes);
+
+        self.closed_connections.retain_mut(|c| {
+            c.ticks_left = c.ticks_left.saturating_sub(1);
+            c.ticks_left > 0 && !current.contains(&c.connection.key(c.process.pid))
+        });
+
+        if self.closed_retention == 0 {
+            return;
+        }
+        for p in &self.all_processes {
+            for conn in &p.connections {
+                if !current.contains(&conn.key(p.pid)) {
+                    self.closed_connections.push(ClosedConnection {
+                        process: Process {
+                            connections: Vec::new(),
+                            ..p.clone()
+                        },
+                        connection: conn.clone(),
+                        ticks_left: self.closed_retention,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Connections tab rows in display order: each process's open
+    /// connections followed by its recently closed ones, then the closed
+    /// connections of processes that have exited, after the idle,
+    /// inbound, and text filters. Cheap: the rows are worked out by
+    /// `refresh_connection_rows`.
+    pub fn filtered_connections(&self) -> Vec<ConnectionRow<'_>> {
+        self.connection_rows
             .iter()
-            .filter(|p| {
-                if let Some(ref f) = filter {
-                    p.name.to_lowercase().contains(f)
-                        || p.path.as_deref().unwrap_or("").to_lowercase().contains(f)
-                        || p.pid.to_string().contains(f)
-                } else {
-                    true
+            .filter_map(|row| match *row {
+                RowRef::Open(p, c) => {
+                    let process = self.snapshot.processes.get(p)?;
+                    let conn = process.connections.get(c)?;
+                    Some(ConnectionRow { process, conn, closed: false })
+                }
+                RowRef::Closed(p, c) => {
+                    let process = self.snapshot.processes.get(p)?;
+                    let conn = &self.closed_connections.get(c)?.connection;
+                    Some(ConnectionRow { process, conn, closed: true })
+                }
+                RowRef::Exited(c) => {
+                    let closed = self.closed_connections.get(c)?;
+                    Some(ConnectionRow { process: &closed.process, conn: &closed.connection, closed: true })
                 }
             })
             .collect()
     }
 
-    pub fn nav_up(&mut self) {
-        match self.active_tab {
-            ActiveTab::Processes => {
-                self.process_index = self.process_index.saturating_sub(1);
+    /// Work out the Connections rows again. Called whenever the snapshot,
+    /// the closed connections, or anything the filters look at changes,
+    /// rather than on every frame and key press.
+    fn refresh_connection_rows(&mut self) {
+        let mut closed_by_process: HashMap<ProcessKey, Vec<usize>> = HashMap::new();
+        for (i, closed) in self.closed_connections.iter().enumerate() {
+            closed_by_process.entry(closed.process.key()).or_default().push(i);
+        }
+        let closed_rows = |process: &Process, mut closed: Vec<usize>| {
+            closed.retain(|c| self.connection_matches(process, &self.closed_connections[*c].connection));
+            closed.sort_by(|a, b| {
+                self.closed_connections[*a]
+                    .connection
+                    .display_order(&self.closed_connections[*b].connection)
+            });
+            closed
+        };
+
+        let mut rows = Vec::new();
+        for (p, process) in self.snapshot.processes.iter().enumerate() {
+            if self.outside_drill(process) {
+                continue;
             }
-            ActiveTab::Connections => {
-                self.connection_index = self.connection_index.saturating_sub(1);
+            for (c, conn) in process.connections.iter().enumerate() {
+                if !(self.hide_idle && conn.idle) && self.connection_matches(process, conn) {
+                    rows.push(RowRef::Open(p, c));
+                }
             }
-            _ => {}
+            let closed = closed_by_process.remove(&process.key()).unwrap_or_default();
+            rows.extend(closed_rows(process, closed).into_iter().map(|c| RowRef::Closed(p, c)));
         }
+
+        // Short-lived processes (a quick `curl`) are gone by the time their
+        // connections close; theirs follow, in the order they closed
+        let running: HashSet<ProcessKey> = self.all_processes.iter().map(Process::key).collect();
+        let mut exited: Vec<Vec<usize>> = closed_by_process
+            .into_iter()
+            .filter(|(key, _)| !running.contains(key))
+            .map(|(_, closed)| closed)
+            .collect();
+        exited.sort_by_key(|closed| closed[0]);
+        for closed in exited {
+            let process = &self.closed_connections[closed[0]].process;
+            if !self.outside_drill(process) {
+                rows.extend(closed_rows(process, closed).into_iter().map(RowRef::Exited));
+            }
+        }
+        self.connection_rows = rows;
     }
 
-    pub fn nav_down(&mut self) {
-        match self.active_tab {
-            ActiveTab::Processes => {
-                let max = self.filtered_processes().len().saturating_sub(1);
-                if self.process_index < max {
-                    self.process_index += 1;
+    /// Whether a drill-down into another process hides this one's
+    /// connections.
+    fn outside_drill(&self, process: &Process) -> bool {
+        self.drilled.as_ref().is_some_and(|(name, pid)| *pid != process.pid || *name != process.name)
+    }
+
+    /// Whether a connection passes the inbound toggle and the filter, whose
+    /// terms cover the process as well as addresses.
+    fn connection_matches(&self, process: &Process, conn: &Connection) -> bool {
+        if self.inbound_only && conn.direction != Direction::Inbound {
+            return false;
+        }
+        self.filter_for(ActiveTab::Connections).eval(&filter::ConnectionRow {
+            process,
+            conn,
+            services: self.show_services,
+            hostnames: self.show_hostnames,
+        })
+    }
+
+    fn sort_processes(&self, processes: &mut [Process]) {
+        model::sort_processes(processes, self.sort_field, self.sort_direction);
+    }
+
+    /// Whether the direction was flipped away from the field's default.
+    /// Views that fall back to their own key (a name or port) for a field
+    /// keep that key's natural order unless flipped.
+    pub fn sort_flipped(&self) -> bool {
+        self.sort_direction != self.sort_field.default_direction()
+    }
+
+    /// Sort remote hosts by the current sort field; PID has no meaning for
+    /// hosts so it sorts by name like Name does, and packet rates (tracked
+    /// per process only) fall back to byte rates.
+    fn sort_remote_hosts(&mut self) {
+        let direction = self.sort_direction;
+        let hosts = &mut self.remote_hosts;
+        // Ties keep host order: the sorts below are stable
+        hosts.sort_by(|a, b| a.host.cmp(&b.host));
+        match self.sort_field {
+            SortField::Name | SortField::Pid => {
+                hosts.sort_by(|a, b| direction.apply(a.host.to_lowercase().cmp(&b.host.to_lowercase())))
+            }
+            SortField::Connections => hosts.sort_by(|a, b| direction.apply(a.connections.cmp(&b.connections))),
+            SortField::BytesIn => hosts.sort_by(|a, b| direction.apply(a.bytes_in.cmp(&b.bytes_in))),
+            SortField::BytesOut => hosts.sort_by(|a, b| direction.apply(a.bytes_out.cmp(&b.bytes_out))),
+            SortField::RateIn | SortField::PpsIn => {
+                hosts.sort_by(|a, b| direction.apply(a.rate_in.total_cmp(&b.rate_in)))
+            }
+            SortField::RateOut | SortField::PpsOut => {
+                hosts.sort_by(|a, b| direction.apply(a.rate_out.total_cmp(&b.rate_out)))
+            }
+        }
+    }
+
+    pub fn filtered_remote_hosts(&self) -> Vec<&RemoteHost> {
+        self.remote_hosts
+            .iter()
+            .filter(|h| self.filter_for(ActiveTab::Remotes).eval(*h))
+            .collect()
+    }
+
+    /// Listeners sort by process name or PID when those fields are selected,
+    /// and by port otherwise since they carry no traffic.
+    fn sort_listeners(&mut self) {
+        let direction = self.sort_direction;
+        match self.sort_field {
+            SortField::Name => self.listeners.sort_by(|a, b| {
+                direction.apply(
+                    a.process
+                        .to_lowercase()
+                        .cmp(&b.process.to_lowercase())
+                        .then(a.port.cmp(&b.port)),
+                )
+            }),
+            SortField::Pid => self.listeners.sort_by(|a, b| direction.apply((a.pid, a.port).cmp(&(b.pid, b.port)))),
+            _ => {
+                self.listeners.sort_by_key(|l| (l.port, l.pid));
+                if self.sort_flipped() {
+                    self.listeners.reverse();
                 }
             }
-            ActiveTab::Connections => {
-                self.connection_index += 1;
+        }
+    }
+
+    fn sort_interfaces(&mut self) {
+        let direction = self.sort_direction;
+        let flipped = self.sort_flipped();
+        let ifaces = &mut self.interfaces;
+        // Ties keep name order: the sorts below are stable
+        ifaces.sort_by(|a, b| a.name.cmp(&b.name));
+        match self.sort_field {
+            SortField::BytesIn => ifaces.sort_by(|a, b| direction.apply(a.bytes_in.cmp(&b.bytes_in))),
+            SortField::BytesOut => ifaces.sort_by(|a, b| direction.apply(a.bytes_out.cmp(&b.bytes_out))),
+            SortField::RateIn | SortField::PpsIn => {
+                ifaces.sort_by(|a, b| direction.apply(a.rate_in.total_cmp(&b.rate_in)))
+            }
+            SortField::RateOut | SortField::PpsOut => {
+                ifaces.sort_by(|a, b| direction.apply(a.rate_out.total_cmp(&b.rate_out)))
+            }
+            SortField::Name | SortField::Pid | SortField::Connections => {
+                if flipped {
+                    ifaces.reverse();
+                }
             }
-            _ => {}
         }
     }
 
-    pub fn cycle_sort(&mut self) {
-        self.sort_field = self.sort_field.next();
+    pub fn filtered_interfaces(&self) -> Vec<&InterfaceStat> {
+        self.interfaces
+            .iter()
+            .filter(|i| {
+                self.filter_for(ActiveTab::Interfaces).eval(&filter::InterfaceRow {
+                    name: &i.name,
+                    vpn: interfaces::is_vpn_interface(&i.name, &self.vpn_patterns),
+                })
+            })
+            .collect()
     }
 
-    pub fn enter_filter(&mut self) {
-        self.filtering = true;
-        self.filter_input.clear();
+    pub fn filtered_listeners(&self) -> Vec<&Listener> {
+        self.listeners
+            .iter()
+            .filter(|l| self.filter_for(ActiveTab::Listeners).eval(*l))
+            .collect()
     }
 
-    pub fn apply_filter(&mut self) {
-        self.filtering = false;
-        if self.filter_input.is_empty() {
-            self.filter_text = None;
+    pub fn filtered_processes(&self) -> Vec<&Process> {
+        self.snapshot
+            .processes
+            .iter()
+            .filter(|p| self.filter_for(ActiveTab::Processes).eval(*p))
+            .collect()
+    }
+
+    pub fn selected_process(&self) -> Option<&Process> {
+        self.filtered_processes().get(self.process_index).copied()
+    }
+
+    /// How old the data is against the refresh interval. Paused data is
+    /// old on purpose, so it counts as fresh.
+    pub fn freshness(&self) -> Freshness {
+        let Some((at, _)) = self.last_fetch else {
+            return Freshness::Fresh;
+        };
+        let age = at.elapsed();
+        if self.paused || age <= self.interval * STALE_INTERVALS {
+            Freshness::Fresh
+        } else if age <= self.interval * VERY_STALE_INTERVALS {
+            Freshness::Stale
         } else {
-            self.filter_text = Some(self.filter_input.clone());
+            Freshness::VeryStale
         }
     }
 
-    pub fn cancel_filter(&mut self) {
-        self.filtering = false;
-        self.filter_text = None;
-        self.filter_input.clear();
+    /// The Overview's Top Processes, in the current sort order.
+    pub fn top_processes(&self) -> Vec<&Process> {
+        self.snapshot.processes.iter().take(OVERVIEW_TOP).collect()
     }
 
-    pub fn drill_down(&mut self) {
+    /// Show a message in the footer for a few seconds.
+    pub fn set_status(&mut self, message: impl Into<String>) {
+        self.push_status(Severity::Info, message);
+    }
+
+    /// Queue a footer message, colored by `severity`.
+    pub fn push_status(&mut self, severity: Severity, message: impl Into<String>) {
+        self.push_status_at(Instant::now(), severity, message.into());
+    }
+
+    fn push_status_at(&mut self, now: Instant, severity: Severity, text: String) {
+        self.status.retain(|m| m.expires_at > now);
+        let shown_from = match self.status.back() {
+            Some(last) => now.max(last.shown_from + STATUS_MIN_SHOWN),
+            None => now,
+        };
+        self.status.push_back(StatusMessage {
+            text,
+            severity,
+            shown_from,
+            expires_at: shown_from + STATUS_DURATION,
+        });
+    }
+
+    /// The footer message on screen now, if any.
+    pub fn status_message(&self) -> Option<&StatusMessage> {
+        self.status_at(Instant::now())
+    }
+
+    fn status_at(&self, now: Instant) -> Option<&StatusMessage> {
+        self.status
+            .iter()
+            .rev()
+            .find(|m| m.shown_from <= now && now < m.expires_at)
+    }
+
+    /// Clear the message on screen, as any key press does; queued ones
+    /// still get their turn.
+    pub fn dismiss_status(&mut self) {
+        let now = Instant::now();
+        self.status.retain(|m| m.shown_from > now);
+    }
+
+    /// Copy the selected row's most useful field: the remote address
+    /// (hostname with `alternate`) on Connections and Remotes, the PID (full
+    /// path with `alternate`) on Processes.
+    pub fn copy_selection(&mut self, alternate: bool) {
+        let text = match self.active_tab {
+            ActiveTab::Processes => self.selected_process().and_then(|p| {
+                if alternate {
+                    p.path.clone()
+                } else {
+                    Some(p.pid.to_string())
+                }
+            }),
+            ActiveTab::Connections => {
+                self.filtered_connections().get(self.connection_index).map(|r| {
+                    if alternate {
+                        r.conn.remote_host().to_string()
+                    } else {
+                        r.conn.remote_addr.clone()
+                    }
+                })
+            }
+            ActiveTab::Remotes => self
+                .filtered_remote_hosts()
+                .get(self.remote_index)
+                .map(|h| h.host.clone()),
+            ActiveTab::Listeners => self
+                .filtered_listeners()
+                .get(self.listener_index)
+                .map(|l| format!("{}:{}", l.addr, l.port)),
+            ActiveTab::Interfaces => self
+                .filtered_interfaces()
+                .get(self.interface_index)
+                .map(|i| i.name.clone()),
+            ActiveTab::Overview | ActiveTab::Graph => None,
+        };
+        let Some(text) = text else {
+            self.push_status(Severity::Warning, "nothing to copy");
+            return;
+        };
+        match clipboard::copy(&text) {
+            Ok(()) => self.set_status(format!("copied {}", text)),
+            Err(e) => self.push_status(Severity::Error, format!("copy failed: {}", e)),
+        }
+    }
+
+    /// Processes as currently shown: on the Connections tab, the drilled
+    /// process (or every process) keeping only the connections matching
+    /// the filter; elsewhere the process filter applies.
+    fn visible_processes(&self) -> Vec<Process> {
+        if self.active_tab != ActiveTab::Connections {
+            return self.filtered_processes().into_iter().cloned().collect();
+        }
+        self.snapshot
+            .processes
+            .iter()
+            .filter(|process| !self.outside_drill(process))
+            .filter_map(|process| {
+                let mut p = process.clone();
+                p.connections.retain(|c| {
+                    !(self.hide_idle && c.idle)
+                        && self.connection_matches(process, c)
+                });
+                (!p.connections.is_empty()).then_some(p)
+            })
+            .collect()
+    }
+
+    /// Write what is on screen to a timestamped JSON file and report the
+    /// path (or the error) in the footer.
+    pub fn export_snapshot(&mut self) {
+        let snapshot = NetworkSnapshot::from_processes(self.visible_processes());
+        let dir = self.export_dir.clone().unwrap_or_else(|| PathBuf::from("."));
+        let filter = self.filter_label();
+        match export::write_snapshot_json(&snapshot, filter.as_deref(), &dir) {
+            Ok(path) => self.set_status(format!("exported {}", path.display())),
+            Err(e) => self.push_status(Severity::Error, format!("export failed: {}", e)),
+        }
+    }
+
+    /// Write the Processes or Connections table, as filtered and sorted on
+    /// screen, to a timestamped CSV file.
+    pub fn export_csv(&mut self) {
+        let (csv, table) = match self.active_tab {
+            ActiveTab::Processes => (export::processes_csv(&self.filtered_processes()), "processes"),
+            ActiveTab::Connections => {
+                let rows: Vec<_> = self
+                    .filtered_connections()
+                    .iter()
+                    .map(|r| (r.process.name.as_str(), r.process.pid, r.conn, r.closed))
+                    .collect();
+                (export::connections_csv(&rows), "connections")
+            }
+            _ => {
+                self.push_status(Severity::Warning, "CSV export is available on the Processes and Connections tabs");
+                return;
+            }
+        };
+        let dir = self.export_dir.clone().unwrap_or_else(|| PathBuf::from("."));
+        match export::write_csv(&csv, table, &dir) {
+            Ok(path) => self.set_status(format!("exported {}", path.display())),
+            Err(e) => self.push_status(Severity::Error, format!("export failed: {}", e)),
+        }
+    }
+
+    /// Raise each process's peak rates to its current ones, and forget the
+    /// peaks of processes that have been gone longer than the grace period.
+    fn track_peaks(&mut self, processes: &mut [Process], now: Instant) {
+        for p in processes.iter_mut() {
+            let peak = self.peak_rates.entry(p.key()).or_insert((0.0, 0.0, now));
+            peak.0 = peak.0.max(p.rate_in);
+            peak.1 = peak.1.max(p.rate_out);
+            peak.2 = now;
+            p.peak_rate_in = peak.0;
+            p.peak_rate_out = peak.1;
+        }
+        self.peak_rates
+            .retain(|_, (_, _, last_seen)| now.duration_since(*last_seen) < PEAK_GRACE);
+    }
+
+    /// Forget previous byte counters and peaks so rates and peaks start fresh.
+    pub fn reset_baselines(&mut self) {
+        self.prev_bytes.clear();
+        self.peak_rates.clear();
+        self.peak_total_rate = 0.0;
+        self.peak_total_at = None;
+        self.closed_connections.clear();
+        self.refresh_connection_rows();
+        for p in self.snapshot.processes.iter_mut() {
+            p.peak_rate_in = 0.0;
+            p.peak_rate_out = 0.0;
+        }
+    }
+
+    pub fn toggle_process_detail(&mut self) {
         if self.active_tab == ActiveTab::Processes {
-            // Get the selected process name before mutating
-            let name = self
-                .filtered_processes()
-                .get(self.process_index)
-                .map(|p| p.name.clone());
+            self.show_process_detail = !self.show_process_detail;
+            self.detail_process = self
+                .selected_process()
+                .filter(|_| self.show_process_detail)
+                .map(|p| p.key());
+            self.process_details = self
+                .selected_process()
+                .filter(|_| self.show_process_detail)
+                .map(|p| procinfo::fetch_process_details(p.pid));
+        }
+    }
 
-            self.active_tab = ActiveTab::Connections;
-            if let Some(name) = name {
-                self.filter_text = Some(name.clone());
-                self.filter_input = name;
+    /// Show a shorter (`zoom_in`) or longer span of history on the Graph tab.
+    pub fn zoom_graph(&mut self, zoom_in: bool) {
+        self.graph_window = if zoom_in {
+            self.graph_window.min(self.graph_windows().len() - 1).saturating_sub(1)
+        } else {
+            (self.graph_window + 1).min(self.graph_windows().len() - 1)
+        };
+    }
+
+    /// Spans the Graph tab zooms between, ending with the whole history.
+    pub fn graph_windows(&self) -> Vec<Duration> {
+        let mut windows: Vec<Duration> = GRAPH_WINDOWS
+            .into_iter()
+            .filter(|&window| window < self.history_span)
+            .collect();
+        windows.push(self.history_span);
+        windows
+    }
+
+    /// The span the Graph tab shows.
+    pub fn graph_span(&self) -> Duration {
+        let windows = self.graph_windows();
+        windows[self.graph_window.min(windows.len() - 1)]
+    }
+
+    /// Name and PID of the process the detail popup was opened on.
+    pub fn detail_key(&self) -> Option<&ProcessKey> {
+        self.detail_process.as_ref()
+    }
+
+    /// The detail popup's process as of the latest refresh; None once it has
+    /// exited.
+    pub fn detail_process(&self) -> Option<&Process> {
+        let (name, pid) = self.detail_process.as_ref()?;
+        // Excluded local traffic can drop a live process from the snapshot
+        self.snapshot
+            .processes
+            .iter()
+            .chain(&self.all_processes)
+            .find(|p| p.pid == *pid && p.name == *name)
+    }
+
+    pub fn toggle_connection_detail(&mut self) {
+        if self.active_tab == ActiveTab::Connections {
+            self.show_connection_detail = !self.show_connection_detail;
+        }
+    }
+
+    /// The Connections row the selection is on.
+    pub fn selected_connection_row(&self) -> Option<ConnectionRow<'_>> {
+        self.filtered_connections().into_iter().nth(self.connection_index)
+    }
+
+    /// How long a connection's byte counters have stood still, going by the
+    /// snapshots it has been in; None once it has closed.
+    pub fn idle_time(&self, pid: u32, conn: &Connection) -> Option<Duration> {
+        self.conn_activity
+            .get(&conn.key(pid))
+            .map(|activity| self.interval * activity.idle_ticks as u32)
+    }
+
+    /// Record which process and connection the indices point at.
+    fn remember_selection(&mut self) {
+        self.selected_process = self.filtered_processes().get(self.process_index).map(|p| p.key());
+        self.selected_connection = self
+            .filtered_connections()
+            .get(self.connection_index)
+            .map(|r| r.conn.key(r.process.pid));
+        self.overview_process = self.top_processes().get(self.overview_index).map(|p| p.key());
+    }
+
+    /// Move the indices back onto the selected process and connection after
+    /// the rows changed, clamping when they are gone (and then selecting
+    /// whatever row the index lands on).
+    fn restore_selection(&mut self) {
+        let processes = self.filtered_processes();
+        let found = self
+            .selected_process
+            .as_ref()
+            .and_then(|key| processes.iter().position(|p| p.key() == *key));
+        self.process_index = found.unwrap_or(self.process_index.min(processes.len().saturating_sub(1)));
+
+        let connections = self.filtered_connections();
+        let found = self
+            .selected_connection
+            .as_ref()
+            .and_then(|key| connections.iter().position(|r| r.conn.key(r.process.pid) == *key));
+        self.connection_index = found.unwrap_or(self.connection_index.min(connections.len().saturating_sub(1)));
+
+        let top = self.top_processes();
+        let found = self
+            .overview_process
+            .as_ref()
+            .and_then(|key| top.iter().position(|p| p.key() == *key));
+        self.overview_index = found.unwrap_or(self.overview_index.min(top.len().saturating_sub(1)));
+
+        self.remember_selection();
+        self.follow_selection();
+    }
+
+    pub fn nav_up(&mut self, rows: usize) {
+        self.move_selection(|index, _| index.saturating_sub(rows));
+    }
+
+    pub fn nav_down(&mut self, rows: usize) {
+        self.move_selection(|index, _| index.saturating_add(rows));
+    }
+
+    /// Select a row by its 1-based number, as `15G` does.
+    pub fn nav_to_row(&mut self, row: usize) {
+        self.move_selection(|_, _| row.saturating_sub(1));
+    }
+
+    pub fn page_down(&mut self, pages: usize) {
+        self.nav_down(self.viewport_rows.max(1).saturating_mul(pages));
+    }
+
+    pub fn page_up(&mut self, pages: usize) {
+        self.nav_up(self.viewport_rows.max(1).saturating_mul(pages));
+    }
+
+    /// Act on a left click by what the last frame drew there: switch tabs,
+    /// select a row, or sort by a column (reversing it if already sorted).
+    pub fn click(&mut self, column: u16, row: u16) {
+        match self.hitboxes.click(column, row) {
+            Some(Click::Tab(tab)) => self.active_tab = tab,
+            // Below the last row nothing changes
+            Some(Click::Row(index)) => {
+                self.move_selection(|current, len| if index < len { index } else { current })
+            }
+            Some(Click::Header(field)) => self.choose_sort(field),
+            None => {}
+        }
+    }
+
+    /// Scroll the table under the mouse wheel.
+    pub fn scroll(&mut self, column: u16, row: u16, down: bool) {
+        if !self.hitboxes.over_table(column, row) {
+            return;
+        }
+        if down {
+            self.nav_down(WHEEL_ROWS);
+        } else {
+            self.nav_up(WHEEL_ROWS);
+        }
+    }
+
+    /// Add a digit to the count typed before a motion key, as in `15j`. A
+    /// leading zero is ignored.
+    pub fn push_count_digit(&mut self, digit: u32) {
+        if digit == 0 && self.pending_count.is_none() {
+            return;
+        }
+        let count = self.pending_count.unwrap_or(0);
+        self.pending_count = Some(count.saturating_mul(10).saturating_add(digit as usize).min(MAX_COUNT));
+    }
+
+    pub fn nav_first(&mut self) {
+        self.move_selection(|_, _| 0);
+    }
+
+    pub fn nav_last(&mut self) {
+        self.move_selection(|_, len| len.saturating_sub(1));
+    }
+
+    /// Move the active tab's selection to `to(index, rows)`, kept within the
+    /// rows. The table scrolls to keep the selection on screen.
+    fn move_selection(&mut self, to: impl FnOnce(usize, usize) -> usize) {
+        let len = match self.active_tab {
+            ActiveTab::Processes => self.filtered_processes().len(),
+            ActiveTab::Connections => self.filtered_connections().len(),
+            ActiveTab::Remotes => self.filtered_remote_hosts().len(),
+            ActiveTab::Listeners => self.filtered_listeners().len(),
+            ActiveTab::Interfaces => self.filtered_interfaces().len(),
+            ActiveTab::Overview => self.top_processes().len(),
+            ActiveTab::Graph => return,
+        };
+        let index = match self.active_tab {
+            ActiveTab::Processes => &mut self.process_index,
+            ActiveTab::Connections => &mut self.connection_index,
+            ActiveTab::Remotes => &mut self.remote_index,
+            ActiveTab::Listeners => &mut self.listener_index,
+            ActiveTab::Interfaces => &mut self.interface_index,
+            ActiveTab::Overview => &mut self.overview_index,
+            ActiveTab::Graph => return,
+        };
+        *index = to(*index, len).min(len.saturating_sub(1));
+        self.remember_selection();
+        self.follow_selection();
+    }
+
+    /// Scroll each table just enough to keep its selection on screen.
+    fn follow_selection(&mut self) {
+        let height = self.viewport_rows;
+        self.process_offset =
+            layout::scroll_offset(self.process_offset, self.process_index, self.filtered_processes().len(), height);
+        self.connection_offset = layout::scroll_offset(
+            self.connection_offset,
+            self.connection_index,
+            self.filtered_connections().len(),
+            height,
+        );
+        self.remote_offset =
+            layout::scroll_offset(self.remote_offset, self.remote_index, self.filtered_remote_hosts().len(), height);
+        self.listener_offset =
+            layout::scroll_offset(self.listener_offset, self.listener_index, self.filtered_listeners().len(), height);
+        let height = height.saturating_sub(crate::ui::interfaces::SPARKLINE_HEIGHT as usize);
+        self.interface_offset = layout::scroll_offset(
+            self.interface_offset,
+            self.interface_index,
+            self.filtered_interfaces().len(),
+            height,
+        );
+    }
+
+    pub fn toggle_columns(&mut self) {
+        self.show_columns = !self.show_columns;
+        self.column_index = 0;
+    }
+
+    /// Label and visibility of each column of the current tab's table, for
+    /// the column chooser; None on tabs without column settings.
+    pub fn column_choices(&self) -> Option<Vec<(&'static str, bool)>> {
+        fn choices<C: Column>(columns: &Columns<C>) -> Vec<(&'static str, bool)> {
+            columns.entries().iter().map(|(c, visible)| (c.label(), *visible)).collect()
+        }
+        match self.active_tab {
+            ActiveTab::Processes => Some(choices(&self.process_columns)),
+            ActiveTab::Connections => Some(choices(&self.connection_columns)),
+            _ => None,
+        }
+    }
+
+    pub fn column_nav(&mut self, down: bool) {
+        let len = self.column_choices().map_or(0, |c| c.len());
+        self.column_index = if down {
+            (self.column_index + 1).min(len.saturating_sub(1))
+        } else {
+            self.column_index.saturating_sub(1)
+        };
+    }
+
+    pub fn toggle_column(&mut self) {
+        match self.active_tab {
+            ActiveTab::Processes => self.process_columns.toggle(self.column_index),
+            ActiveTab::Connections => self.connection_columns.toggle(self.column_index),
+            _ => {}
+        }
+    }
+
+    /// Apply widths saved by an earlier session, and save here on exit.
+    pub fn load_column_widths(&mut self, path: PathBuf) {
+        let state = State::load(&path);
+        // A stale entry (a column since renamed) shouldn't cost the rest
+        for (id, width) in &state.process_widths {
+            let _ = self.process_columns.restore_widths([(id, width)]);
+        }
+        for (id, width) in &state.connection_widths {
+            let _ = self.connection_columns.restore_widths([(id, width)]);
+        }
+        self.state_file = Some(path);
+    }
+
+    /// Persist widths adjusted this session. Failures are ignored, like
+    /// the DNS cache's.
+    pub fn save_column_widths(&self) {
+        let Some(path) = self.state_file.as_ref().filter(|_| self.widths_changed) else {
+            return;
+        };
+        let state = State {
+            process_widths: self.process_columns.resized_widths().collect(),
+            connection_widths: self.connection_columns.resized_widths().collect(),
+        };
+        let _ = state.save(path);
+    }
+
+    /// Widen or narrow the column being sorted by (Processes) or the Remote
+    /// column (Connections, which has no sort column).
+    pub fn resize_column(&mut self, grow: bool) {
+        match self.active_tab {
+            ActiveTab::Processes => {
+                let sorted = ProcessColumn::ALL
+                    .iter()
+                    .copied()
+                    .find(|c| c.sort_field() == Some(self.sort_field));
+                match sorted.filter(|c| self.process_columns.visible().any(|v| v == *c)) {
+                    Some(column) => self.process_columns.resize(column, grow),
+                    None => {
+                        self.push_status(Severity::Warning, "The sorted column isn't shown");
+                        return;
+                    }
+                }
+            }
+            ActiveTab::Connections => self.connection_columns.resize(ConnectionColumn::Remote, grow),
+            _ => return,
+        }
+        self.widths_changed = true;
+    }
+
+    pub fn cycle_units(&mut self) {
+        self.units = self.units.next();
+        self.set_status(format!("Units: {}", self.units.label()));
+    }
+
+    pub fn cycle_sort(&mut self) {
+        self.set_sort(self.sort_field.next());
+    }
+
+    pub fn prev_sort(&mut self) {
+        self.set_sort(self.sort_field.prev());
+    }
+
+    fn set_sort(&mut self, field: SortField) {
+        self.sort_field = field;
+        self.sort_direction = field.default_direction();
+        self.rebuild_snapshot();
+    }
+
+    /// Pick a sort field from the sort picker. Picking the current field
+    /// again reverses it, like clicking a sorted column header.
+    pub fn choose_sort(&mut self, field: SortField) {
+        if field == self.sort_field {
+            self.flip_sort();
+        } else {
+            self.set_sort(field);
+        }
+        self.show_sort_menu = false;
+    }
+
+    /// Open the sort picker on the current field.
+    pub fn toggle_sort_menu(&mut self) {
+        self.show_sort_menu = !self.show_sort_menu;
+        self.sort_menu_index = SortField::ALL.iter().position(|f| *f == self.sort_field).unwrap_or(0);
+    }
+
+    pub fn sort_menu_nav(&mut self, down: bool) {
+        self.sort_menu_index = if down {
+            (self.sort_menu_index + 1).min(SortField::ALL.len() - 1)
+        } else {
+            self.sort_menu_index.saturating_sub(1)
+        };
+    }
+
+    pub fn flip_sort(&mut self) {
+        self.sort_direction = self.sort_direction.flip();
+        self.rebuild_snapshot();
+    }
+
+    pub fn toggle_inbound_only(&mut self) {
+        self.inbound_only = !self.inbound_only;
+        self.refresh_connection_rows();
+        self.restore_selection();
+    }
+
+    pub fn toggle_hide_idle(&mut self) {
+        self.hide_idle = !self.hide_idle;
+        self.refresh_connection_rows();
+        self.restore_selection();
+    }
+
+    /// Service names and hostnames are part of what the filter matches.
+    pub fn toggle_services(&mut self) {
+        self.show_services = !self.show_services;
+        self.refresh_connection_rows();
+        self.restore_selection();
+    }
+
+    pub fn toggle_hostnames(&mut self) {
+        self.show_hostnames = !self.show_hostnames;
+        self.refresh_connection_rows();
+        self.restore_selection();
+    }
+
+    pub fn toggle_exclude_local(&mut self) {
+        self.exclude_local = !self.exclude_local;
+        self.rebuild_snapshot();
+    }
+
+    /// A tab's applied filter.
+    pub fn filter_for(&self, tab: ActiveTab) -> &Filter {
+        &self.filters[tab as usize].filter
+    }
+
+    /// The active tab's filter.
+    pub fn tab_filter(&self) -> &TabFilter {
+        &self.filters[self.active_tab as usize]
+    }
+
+    pub fn tab_filter_mut(&mut self) -> &mut TabFilter {
+        &mut self.filters[self.active_tab as usize]
+    }
+
+    /// Whether the active tab's filter prompt is open.
+    pub fn filtering(&self) -> bool {
+        self.tab_filter().filtering
+    }
+
+    /// Why the filter being typed can't be applied, for the prompt.
+    pub fn filter_error(&self) -> Option<String> {
+        self.filter_syntax.parse(self.tab_filter().input.text()).err()
+    }
+
+    /// The active tab's filter as shown in the footer and recorded in
+    /// exports.
+    pub fn filter_label(&self) -> Option<String> {
+        let text = &self.tab_filter().text;
+        (!text.is_empty()).then(|| text.join(" | "))
+    }
+
+    pub fn enter_filter(&mut self) {
+        let state = self.tab_filter_mut();
+        state.filtering = true;
+        state.input.clear();
+    }
+
+    /// Apply the typed filter to the active tab; a bad pattern keeps the
+    /// prompt open with its error shown instead.
+    pub fn apply_filter(&mut self) {
+        if self.filter_error().is_some() {
+            return;
+        }
+        let input = self.tab_filter().input.clone();
+        let text = if input.is_empty() { Vec::new() } else { vec![input.text().to_string()] };
+        *self.tab_filter_mut() = TabFilter { input, ..TabFilter::new(&self.filter_syntax, text) };
+        self.refresh_connection_rows();
+        self.restore_selection();
+    }
+
+    /// Clear the active tab's filter.
+    pub fn cancel_filter(&mut self) {
+        *self.tab_filter_mut() = TabFilter::default();
+        self.refresh_connection_rows();
+        self.restore_selection();
+    }
+
+    /// The byte ranges of a cell's text to highlight on `tab`, for both the
+    /// filter and the search.
+    pub fn highlights(&self, tab: ActiveTab, text: &str, field: Option<Field>) -> Vec<Range<usize>> {
+        let mut ranges = self.filter_for(tab).highlights(text, field);
+        ranges.extend(self.search.query.highlights(text, field));
+        filter::merge_ranges(ranges)
+    }
+
+    /// The active tab's selected row.
+    fn selected_row(&self) -> usize {
+        match self.active_tab {
+            ActiveTab::Processes => self.process_index,
+            ActiveTab::Connections => self.connection_index,
+            ActiveTab::Remotes => self.remote_index,
+            ActiveTab::Listeners => self.listener_index,
+            ActiveTab::Interfaces => self.interface_index,
+            ActiveTab::Overview | ActiveTab::Graph => 0,
+        }
+    }
+
+    /// Rows of the active tab, as filtered, that match the search.
+    fn search_matches(&self) -> Vec<usize> {
+        let query = &self.search.query;
+        if query.is_empty() {
+            return Vec::new();
+        }
+        fn positions<T>(rows: Vec<T>, hit: impl Fn(&T) -> bool) -> Vec<usize> {
+            rows.iter().enumerate().filter_map(|(i, row)| hit(row).then_some(i)).collect()
+        }
+        match self.active_tab {
+            ActiveTab::Processes => positions(self.filtered_processes(), |p| query.eval(*p)),
+            ActiveTab::Connections => positions(self.filtered_connections(), |r| {
+                query.eval(&filter::ConnectionRow {
+                    process: r.process,
+                    conn: r.conn,
+                    services: self.show_services,
+                    hostnames: self.show_hostnames,
+                })
+            }),
+            ActiveTab::Remotes => positions(self.filtered_remote_hosts(), |h| query.eval(*h)),
+            ActiveTab::Listeners => positions(self.filtered_listeners(), |l| query.eval(*l)),
+            ActiveTab::Interfaces => positions(self.filtered_interfaces(), |i| {
+                query.eval(&filter::InterfaceRow {
+                    name: &i.name,
+                    vpn: interfaces::is_vpn_interface(&i.name, &self.vpn_patterns),
+                })
+            }),
+            ActiveTab::Overview | ActiveTab::Graph => Vec::new(),
+        }
+    }
+
+    /// Whether a search is in effect, typed or applied.
+    pub fn searching(&self) -> bool {
+        self.search.typing || !self.search.query.is_empty()
+    }
+
+    /// The selected row's place among the search matches (1-based, if it is
+    /// one) and how many there are; `None` without a search.
+    pub fn search_status(&self) -> Option<(Option<usize>, usize)> {
+        if self.search.query.is_empty() {
+            return None;
+        }
+        let matches = self.search_matches();
+        let selected = self.selected_row();
+        let current = matches.iter().position(|&row| row == selected).map(|i| i + 1);
+        Some((current, matches.len()))
+    }
+
+    /// Whether the selected row is a search match, to draw it distinctly.
+    pub fn on_search_match(&self) -> bool {
+        matches!(self.search_status(), Some((Some(_), _)))
+    }
+
+    /// Why the search being typed can't be used, for the prompt.
+    pub fn search_error(&self) -> Option<String> {
+        self.filter_syntax.parse(self.search.input.text()).err()
+    }
+
+    pub fn enter_search(&mut self) {
+        self.search = Search {
+            typing: true,
+            origin: self.selected_row(),
+            ..Search::default()
+        };
+    }
+
+    /// Re-run the search after an edit, selecting the first match from
+    /// where the prompt was opened, wrapping past the end.
+    pub fn update_search(&mut self) {
+        let Ok(query) = Filter::parse(&self.filter_syntax, &[self.search.input.text().to_string()]) else {
+            return;
+        };
+        self.search.query = query;
+        let origin = self.search.origin;
+        let matches = self.search_matches();
+        let target = matches.iter().find(|&&row| row >= origin).or(matches.first()).copied();
+        self.move_selection(|index, _| target.unwrap_or(index.min(origin)));
+    }
+
+    /// Close the prompt, keeping the search for `n`/`N`; a bad pattern
+    /// keeps it open instead.
+    pub fn finish_search(&mut self) {
+        if self.search_error().is_none() {
+            self.search.typing = false;
+        }
+    }
+
+    /// Drop the search. Cancelling while typing returns to the row selected
+    /// before.
+    pub fn cancel_search(&mut self) {
+        let origin = self.search.typing.then_some(self.search.origin);
+        self.search = Search::default();
+        if let Some(origin) = origin {
+            self.move_selection(|_, _| origin);
+        }
+    }
+
+    /// Select the `count`th next (or previous) match, wrapping at the ends.
+    pub fn search_next(&mut self, forward: bool, count: usize) {
+        let matches = self.search_matches();
+        if matches.is_empty() {
+            if !self.search.query.is_empty() {
+                self.set_status("no matches");
             }
+            return;
+        }
+        let selected = self.selected_row();
+        let len = matches.len();
+        let count = count.max(1);
+        let next = if forward {
+            // Matches up to the selection; the one after is the first hit
+            let before = matches.partition_point(|&row| row <= selected);
+            (before + (count - 1) % len) % len
+        } else {
+            let before = matches.partition_point(|&row| row < selected);
+            (before + len - count % len) % len
+        };
+        let target = matches[next];
+        self.move_selection(|_, _| target);
+    }
+
+    /// Show the selected process's connections, or those to the selected
+    /// host or listening port. A process is tracked by name and PID, leaving
+    /// the Connections filter to narrow its rows further; hosts and ports
+    /// replace that filter instead. On the Overview, go to the highlighted
+    /// top process on the Processes tab.
+    pub fn drill_down(&mut self) {
+        if self.active_tab == ActiveTab::Overview {
+            let Some(key) = self.top_processes().get(self.overview_index).map(|p| p.key()) else {
+                return;
+            };
+            if !self.filtered_processes().iter().any(|p| p.key() == key) {
+                self.set_status(format!("{} is hidden by the Processes filter", key.0));
+                return;
+            }
+            self.selected_process = Some(key);
+            self.active_tab = ActiveTab::Processes;
+            self.restore_selection();
+            return;
+        }
+        if self.active_tab == ActiveTab::Processes {
+            let Some(key) = self.selected_process().map(|p| p.key()) else {
+                return;
+            };
+            if self.drilled.is_none() {
+                self.drill_return = self.selected_connection.take();
+            }
+            self.drilled = Some(key);
+            self.active_tab = ActiveTab::Connections;
+            self.selected_connection = None;
             self.connection_index = 0;
+            self.refresh_connection_rows();
+            self.restore_selection();
+            return;
         }
+
+        // Get the selected host name or local port before mutating
+        let target = match self.active_tab {
+            ActiveTab::Remotes => self
+                .filtered_remote_hosts()
+                .get(self.remote_index)
+                .map(|h| FilterSyntax::exact(Field::Host, &h.host)),
+            ActiveTab::Listeners => self
+                .filtered_listeners()
+                .get(self.listener_index)
+                .map(|l| FilterSyntax::exact(Field::Port, &l.port.to_string())),
+            _ => return,
+        };
+
+        self.active_tab = ActiveTab::Connections;
+        self.drilled = None;
+        self.drill_return = None;
+        if let Some(target) = target {
+            *self.tab_filter_mut() = TabFilter {
+                input: InputLine::new(&target),
+                ..TabFilter::new(&self.filter_syntax, vec![target])
+            };
+        }
+        self.selected_connection = None;
+        self.connection_index = 0;
+        self.refresh_connection_rows();
+        self.restore_selection();
+    }
+
+    /// Whether the Connections tab is up and drilled into a process.
+    pub fn in_drill_down(&self) -> bool {
+        self.active_tab == ActiveTab::Connections && self.drilled.is_some()
+    }
+
+    /// Leave a process drill-down for the Processes tab, where that process
+    /// is still selected, and put back the Connections selection.
+    pub fn drill_up(&mut self) {
+        if !self.in_drill_down() {
+            return;
+        }
+        self.drilled = None;
+        self.active_tab = ActiveTab::Processes;
+        self.selected_connection = self.drill_return.take();
+        self.refresh_connection_rows();
+        self.restore_selection();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn conn(remote_port: u16) -> Connection {
+        Connection {
+            direction: Direction::Outbound,
+            ..Connection::for_test(remote_port)
+        }
+    }
+
+    fn process(name: &str, pid: u32, rate_in: f64, connections: Vec<Connection>) -> Process {
+        Process {
+            rate_in,
+            connections,
+            ..Process::for_test(name, pid)
+        }
+    }
+
+    /// An app sorted by Rate In showing the given processes. Needs a runtime
+    /// for the DNS resolver task.
+    fn app(processes: Vec<Process>) -> App {
+        let mut app = App::new(&Config::try_parse_from(["nm", "--sort-by", "rate-in"]).unwrap());
+        app.all_processes = processes;
+        app.rebuild_snapshot();
+        app
+    }
+
+    fn refresh(app: &mut App, processes: Vec<Process>) {
+        app.all_processes = processes;
+        app.rebuild_snapshot();
+    }
+
+    fn selected(app: &App) -> Option<&str> {
+        app.selected_process().map(|p| p.name.as_str())
+    }
+
+    #[tokio::test]
+    async fn test_selection_follows_process_across_reorders() {
+        let mut app = app(vec![
+            process("Spotify", 1, 30.0, vec![]),
+            process("Dropbox", 2, 20.0, vec![]),
+            process("curl", 3, 10.0, vec![]),
+        ]);
+        app.nav_down(1);
+        assert_eq!(selected(&app), Some("Dropbox"));
+
+        // Dropbox gets busier and moves to the top
+        refresh(&mut app, vec![
+            process("Spotify", 1, 30.0, vec![]),
+            process("Dropbox", 2, 50.0, vec![]),
+            process("curl", 3, 10.0, vec![]),
+        ]);
+        assert_eq!(app.process_index, 0);
+        assert_eq!(selected(&app), Some("Dropbox"));
+
+        // Navigation moves by index and takes the new row with it
+        app.nav_down(1);
+        assert_eq!(selected(&app), Some("Spotify"));
+        refresh(&mut app, vec![
+            process("Spotify", 1, 5.0, vec![]),
+            process("Dropbox", 2, 50.0, vec![]),
+            process("curl", 3, 10.0, vec![]),
+        ]);
+        assert_eq!(selected(&app), Some("Spotify"));
+        assert_eq!(app.process_index, 2);
+    }
+
+    #[tokio::test]
+    async fn test_selection_clamps_when_process_disappears() {
+        let mut app = app(vec![
+            process("Spotify", 1, 30.0, vec![]),
+            process("Dropbox", 2, 20.0, vec![]),
+            process("curl", 3, 10.0, vec![]),
+        ]);
+        app.nav_down(1);
+        app.nav_down(1);
+        assert_eq!(selected(&app), Some("curl"));
+
+        // curl exits: the index clamps to the last row, which is now selected
+        refresh(&mut app, vec![process("Spotify", 1, 30.0, vec![]), process("Dropbox", 2, 20.0, vec![])]);
+        assert_eq!(selected(&app), Some("Dropbox"));
+        // A new curl (same name, new PID) is a different process
+        refresh(&mut app, vec![
+            process("curl", 4, 90.0, vec![]),
+            process("Spotify", 1, 30.0, vec![]),
+            process("Dropbox", 2, 20.0, vec![]),
+        ]);
+        assert_eq!(selected(&app), Some("Dropbox"));
+
+        refresh(&mut app, vec![]);
+        assert_eq!(app.process_index, 0);
+        assert_eq!(selected(&app), None);
+    }
+
+    #[tokio::test]
+    async fn test_selection_survives_filter_changes() {
+        let mut app = app(vec![
+            process("Spotify", 1, 30.0, vec![]),
+            process("Dropbox", 2, 20.0, vec![]),
+            process("dropboxd", 3, 10.0, vec![]),
+        ]);
+        app.nav_down(1);
+        app.nav_down(1);
+        assert_eq!(selected(&app), Some("dropboxd"));
+
+        // Still visible under the filter: it stays selected at its new index
+        app.tab_filter_mut().input = InputLine::new("drop");
+        app.apply_filter();
+        assert_eq!(app.process_index, 1);
+        assert_eq!(selected(&app), Some("dropboxd"));
+
+        app.cancel_filter();
+        assert_eq!(app.process_index, 2);
+        assert_eq!(selected(&app), Some("dropboxd"));
+
+        // Filtered out: the selection falls back to a visible row
+        app.tab_filter_mut().input = InputLine::new("spot");
+        app.apply_filter();
+        assert_eq!(selected(&app), Some("Spotify"));
+    }
+
+    #[tokio::test]
+    async fn test_selection_follows_connection() {
+        let mut app = app(vec![
+            process("Spotify", 1, 30.0, vec![conn(443), conn(4070)]),
+            process("Dropbox", 2, 20.0, vec![conn(443)]),
+        ]);
+        app.active_tab = ActiveTab::Connections;
+        app.nav_down(1);
+        let key = |app: &App| {
+            app.filtered_connections()
+                .get(app.connection_index)
+                .map(|r| (r.process.pid, r.conn.remote_port))
+        };
+        assert_eq!(key(&app), Some((1, 4070)));
+
+        // Dropbox's connection moves above Spotify's
+        refresh(&mut app, vec![
+            process("Spotify", 1, 30.0, vec![conn(443), conn(4070)]),
+            process("Dropbox", 2, 90.0, vec![conn(443)]),
+        ]);
+        assert_eq!(app.connection_index, 2);
+        assert_eq!(key(&app), Some((1, 4070)));
+
+        // Hiding outbound connections hides everything; showing them again
+        // lands back on the top row, the old one having been lost
+        app.toggle_inbound_only();
+        assert_eq!(key(&app), None);
+        app.toggle_inbound_only();
+        assert_eq!(key(&app), Some((2, 443)));
+    }
+
+    #[tokio::test]
+    async fn test_connection_navigation_stays_within_rows() {
+        let mut app = app(vec![
+            process("Spotify", 1, 30.0, vec![conn(443), conn(4070)]),
+            process("Dropbox", 2, 20.0, vec![conn(443)]),
+        ]);
+        app.active_tab = ActiveTab::Connections;
+        app.nav_up(1);
+        assert_eq!(app.connection_index, 0);
+        for _ in 0..10 {
+            app.nav_down(1);
+        }
+        assert_eq!(app.connection_index, 2);
+        app.nav_up(1);
+        assert_eq!(app.connection_index, 1);
+
+        // The filter narrows the rows navigation can reach
+        app.tab_filter_mut().input = InputLine::new("spotify");
+        app.apply_filter();
+        for _ in 0..10 {
+            app.nav_down(1);
+        }
+        assert_eq!(app.connection_index, 1);
+
+        // A refresh with fewer rows pulls the index back in
+        refresh(&mut app, vec![process("Spotify", 1, 30.0, vec![conn(443)])]);
+        assert_eq!(app.connection_index, 0);
+        app.nav_down(1);
+        assert_eq!(app.connection_index, 0);
+    }
+
+    #[tokio::test]
+    async fn test_page_and_jump_navigation() {
+        let processes = (1..=10).map(|pid| process("curl", pid, 100.0 - pid as f64, vec![])).collect();
+        let mut app = app(processes);
+        app.viewport_rows = 4;
+        let pid = |app: &App| app.selected_process().map(|p| p.pid);
+
+        app.page_down(1);
+        assert_eq!((app.process_index, pid(&app)), (4, Some(5)));
+        app.page_down(1);
+        app.page_down(1);
+        assert_eq!(app.process_index, 9);
+        app.page_up(1);
+        assert_eq!(app.process_index, 5);
+        app.nav_first();
+        assert_eq!(app.process_index, 0);
+        app.page_up(1);
+        assert_eq!(app.process_index, 0);
+        app.nav_last();
+        assert_eq!((app.process_index, pid(&app)), (9, Some(10)));
+
+        // A list shorter than a page
+        app.tab_filter_mut().input = InputLine::new("7");
+        app.apply_filter();
+        app.page_down(1);
+        assert_eq!((app.process_index, pid(&app)), (0, Some(7)));
+
+        app.active_tab = ActiveTab::Connections;
+        app.nav_last();
+        app.page_down(1);
+        assert_eq!(app.connection_index, 0);
+    }
+
+    #[tokio::test]
+    async fn test_count_prefix() {
+        let processes = (1..=20).map(|pid| process("curl", pid, 100.0 - pid as f64, vec![])).collect();
+        let mut app = app(processes);
+        app.push_count_digit(0);
+        assert_eq!(app.pending_count, None);
+        app.push_count_digit(1);
+        app.push_count_digit(5);
+        assert_eq!(app.pending_count, Some(15));
+
+        let count = app.pending_count.take().unwrap();
+        app.nav_down(count);
+        assert_eq!(app.process_index, 15);
+        // Counts stop at the list bounds
+        app.nav_down(50);
+        assert_eq!(app.process_index, 19);
+        app.nav_up(usize::MAX);
+        assert_eq!(app.process_index, 0);
+        app.nav_to_row(7);
+        assert_eq!(app.selected_process().map(|p| p.pid), Some(7));
+        app.nav_to_row(0);
+        assert_eq!(app.process_index, 0);
+
+        for _ in 0..10 {
+            app.push_count_digit(9);
+        }
+        assert_eq!(app.pending_count, Some(MAX_COUNT));
+    }
+
+    #[tokio::test]
+    async fn test_mouse_clicks() {
+        use crate::ui::hitbox::TableHitbox;
+        use ratatui::layout::{Constraint, Rect};
+
+        let processes = (1..=5).map(|pid| process("curl", pid, 100.0 - pid as f64, vec![])).collect();
+        let mut app = app(processes);
+        // Name and Rate In columns, header on y=1 and rows from y=2
+        app.hitboxes = Hitboxes {
+            tabs: vec![(Rect::new(1, 20, 11, 1), ActiveTab::Connections)],
+            table: Some(TableHitbox::new(
+                Rect::new(0, 0, 40, 10),
+                &[Constraint::Length(10), Constraint::Length(10)],
+                &[Some(SortField::Name), Some(SortField::RateIn)],
+                0,
+            )),
+        };
+
+        app.click(5, 4);
+        assert_eq!(app.selected_process().map(|p| p.pid), Some(3));
+        // Past the last row
+        app.click(5, 8);
+        assert_eq!(app.process_index, 2);
+
+        // Sorting by the clicked header, then reversing it
+        app.click(3, 1);
+        assert_eq!((app.sort_field, app.sort_direction), (SortField::Name, SortDirection::Asc));
+        app.click(3, 1);
+        assert_eq!((app.sort_field, app.sort_direction), (SortField::Name, SortDirection::Desc));
+        assert_eq!(app.selected_process().map(|p| p.pid), Some(3));
+
+        app.scroll(5, 5, true);
+        assert_eq!(app.process_index, 4);
+        app.scroll(5, 30, false);
+        assert_eq!(app.process_index, 4);
+
+        app.click(2, 20);
+        assert_eq!(app.active_tab, ActiveTab::Connections);
+    }
+
+    /// Render the active tab into a 160x30 buffer and return its lines.
+    fn render(app: &App) -> Vec<String> {
+        render_at(app, 160, 30)
+    }
+
+    fn render_at(app: &App, width: u16, height: u16) -> Vec<String> {
+        use ratatui::backend::TestBackend;
+        use ratatui::Terminal;
+
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|f| match app.active_tab {
+                ActiveTab::Processes => drop(crate::ui::processes::render(f, f.area(), app)),
+                ActiveTab::Remotes => drop(crate::ui::remotes::render(f, f.area(), app)),
+                _ => drop(crate::ui::connections::render(f, f.area(), app)),
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_render_only_visible_rows() {
+        let conns = (1..=200).map(|port| conn(10_000 + port)).collect();
+        let mut app = app(vec![process("Spotify", 1, 30.0, conns)]);
+        app.active_tab = ActiveTab::Connections;
+        app.viewport_rows = 27;
+        app.nav_to_row(150);
+
+        // 27 rows fit; the selection sits on the last of them
+        let lines = render(&app);
+        let selected = lines.iter().position(|l| l.contains("▸")).unwrap();
+        assert_eq!(selected, 28);
+        assert!(lines[28].contains(":10150"), "{}", lines[28]);
+        assert!(lines[2].contains(":10124"), "{}", lines[2]);
+        // Moving up within the view doesn't scroll
+        app.nav_up(20);
+        let lines = render(&app);
+        assert!(lines[2].contains(":10124"), "{}", lines[2]);
+        assert!(lines[8].contains("▸"));
+        app.nav_to_row(1);
+        assert!(render(&app)[2].contains(":10001"));
+    }
+
+    /// Frame build time for a large Connections table:
+    /// `cargo test --release bench_connections_frame -- --ignored --nocapture`
+    #[tokio::test]
+    #[ignore]
+    async fn bench_connections_frame() {
+        let processes = (1..=50)
+            .map(|pid| process("curl", pid, pid as f64, (1..=100).map(|port| conn(port * 100)).collect()))
+            .collect();
+        let mut app = app(processes);
+        app.active_tab = ActiveTab::Connections;
+        app.nav_to_row(2500);
+
+        let frames = 200;
+        let started = Instant::now();
+        for _ in 0..frames {
+            render(&app);
+        }
+        println!("{} connections: {:?} per frame", app.filtered_connections().len(), started.elapsed() / frames);
+    }
+
+    #[tokio::test]
+    async fn test_offset_follows_selection() {
+        let processes = (1..=100).map(|pid| process("curl", pid, 1000.0 - pid as f64, vec![])).collect();
+        let mut app = app(processes);
+        app.viewport_rows = 10;
+
+        app.nav_down(15);
+        assert_eq!(app.process_offset, 6);
+        app.nav_up(5);
+        assert_eq!(app.process_offset, 6);
+        app.nav_up(5);
+        assert_eq!(app.process_offset, 5);
+        app.nav_last();
+        assert_eq!(app.process_offset, 90);
+
+        // The filter leaves fewer rows than the offset skips
+        app.tab_filter_mut().input = InputLine::new("5");
+        app.apply_filter();
+        assert_eq!(app.filtered_processes().len(), 19);
+        assert_eq!(app.process_offset, 9);
+        assert!((app.process_offset..app.process_offset + 10).contains(&app.process_index));
+
+        // A refresh that shrinks the list below the offset
+        refresh(&mut app, (1..=4).map(|pid| process("curl5", pid, 0.0, vec![])).collect());
+        assert_eq!((app.process_offset, app.process_index), (0, 3));
+    }
+
+    #[tokio::test]
+    async fn test_regex_filter() {
+        let mut app = app(vec![
+            process("com.apple.WebKit", 1, 30.0, vec![conn(443), conn(8443)]),
+            process("Safari", 2, 20.0, vec![conn(4430)]),
+        ]);
+        app.show_services = false;
+        let names = |app: &App| app.filtered_processes().iter().map(|p| p.name.clone()).collect::<Vec<_>>();
+
+        app.enter_filter();
+        app.tab_filter_mut().input = InputLine::new(r"/^COM\.apple\.");
+        app.apply_filter();
+        assert_eq!(names(&app), ["com.apple.WebKit"]);
+
+        app.active_tab = ActiveTab::Connections;
+        app.enter_filter();
+        app.tab_filter_mut().input = InputLine::new("/:(443|8443)$");
+        app.apply_filter();
+        let ports = app.filtered_connections().iter().map(|r| r.conn.remote_port).collect::<Vec<_>>();
+        assert_eq!(ports, [443, 8443]);
+
+        // A bad pattern keeps the prompt open and the last filter in place
+        app.enter_filter();
+        app.tab_filter_mut().input = InputLine::new("/(443");
+        assert_eq!(app.filter_error().as_deref(), Some("invalid regex: unclosed group"));
+        app.apply_filter();
+        assert!(app.filtering());
+        assert_eq!(app.tab_filter().text, ["/:(443|8443)$"]);
+        assert_eq!(app.filtered_connections().len(), 2);
+
+        // Without the prefix it is a plain substring
+        app.tab_filter_mut().input = InputLine::new("(443");
+        assert_eq!(app.filter_error(), None);
+        app.apply_filter();
+        assert!(app.filtered_connections().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_negated_filter_terms() {
+        let mut app = app(vec![
+            process("Google Chrome", 1, 30.0, vec![conn(443), conn(4070)]),
+            process("Google Chrome Helper", 2, 20.0, vec![conn(443)]),
+            process("mDNSResponder", 3, 10.0, vec![conn(5353)]),
+        ]);
+        app.show_services = false;
+        let names = |app: &App| app.filtered_processes().iter().map(|p| p.name.clone()).collect::<Vec<_>>();
+
+        app.tab_filter_mut().input = InputLine::new("!mdns");
+        app.apply_filter();
+        assert_eq!(names(&app), ["Google Chrome", "Google Chrome Helper"]);
+
+        app.tab_filter_mut().input = InputLine::new("chrome !helper");
+        app.apply_filter();
+        assert_eq!(names(&app), ["Google Chrome"]);
+
+        // The same filter applies to connection rows
+        app.active_tab = ActiveTab::Connections;
+        app.tab_filter_mut().input = InputLine::new("chrome !:4070");
+        app.apply_filter();
+        let rows = app.filtered_connections().iter().map(|r| (r.process.pid, r.conn.remote_port)).collect::<Vec<_>>();
+        assert_eq!(rows, [(1, 443), (2, 443)]);
+
+        // Drilling into a process keeps the filter, applied within its rows
+        app.active_tab = ActiveTab::Processes;
+        app.cancel_filter();
+        app.drill_down();
+        assert_eq!(app.tab_filter().text, ["chrome !:4070"]);
+        let rows = app.filtered_connections().iter().map(|r| (r.process.pid, r.conn.remote_port)).collect::<Vec<_>>();
+        assert_eq!(rows, [(1, 443)]);
+    }
+
+    #[tokio::test]
+    async fn test_filters_are_per_tab() {
+        let mut app = app(vec![
+            process("Spotify", 1, 30.0, vec![conn(443)]),
+            process("Dropbox", 2, 20.0, vec![conn(443)]),
+        ]);
+        let names = |app: &App| app.filtered_processes().iter().map(|p| p.name.clone()).collect::<Vec<_>>();
+
+        // Drilling down from Remotes filters the Connections tab only
+        app.active_tab = ActiveTab::Remotes;
+        app.drill_down();
+        assert_eq!(app.active_tab, ActiveTab::Connections);
+        assert_eq!(app.filter_label().as_deref(), Some("host:=1.2.3.4"));
+        assert_eq!(app.filtered_connections().len(), 2);
+        app.active_tab = ActiveTab::Processes;
+        assert_eq!(app.filter_label(), None);
+        assert_eq!(names(&app), ["Spotify", "Dropbox"]);
+
+        app.enter_filter();
+        app.tab_filter_mut().input = InputLine::new("drop");
+        app.apply_filter();
+        assert_eq!(names(&app), ["Dropbox"]);
+
+        // Esc clears only the current tab's filter
+        app.active_tab = ActiveTab::Connections;
+        assert!(!app.filtering());
+        app.enter_filter();
+        app.tab_filter_mut().input = InputLine::new("drop");
+        app.apply_filter();
+        assert_eq!(app.filtered_connections().len(), 1);
+        app.cancel_filter();
+        assert_eq!(app.filtered_connections().len(), 2);
+        app.active_tab = ActiveTab::Processes;
+        assert_eq!(app.filter_label().as_deref(), Some("drop"));
+        assert_eq!(names(&app), ["Dropbox"]);
+    }
+
+    fn type_search(app: &mut App, text: &str) {
+        for c in text.chars() {
+            app.search.input.insert(c);
+            app.update_search();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_jumps_while_typing() {
+        let mut app = app(vec![
+            process("Spotify", 1, 50.0, vec![]),
+            process("chrome", 2, 40.0, vec![]),
+            process("Dropbox", 3, 30.0, vec![]),
+            process("chromium", 4, 20.0, vec![]),
+        ]);
+        app.process_index = 1;
+        app.enter_search();
+        type_search(&mut app, "d");
+        assert_eq!(selected(&app), Some("Dropbox"));
+        type_search(&mut app, "r");
+        assert_eq!(selected(&app), Some("Dropbox"));
+        app.search.input.clear();
+        type_search(&mut app, "chromi");
+        assert_eq!(selected(&app), Some("chromium"));
+        // Every row stays visible
+        assert_eq!(app.filtered_processes().len(), 4);
+        assert_eq!(app.search_status(), Some((Some(1), 1)));
+
+        // Cancelling while typing returns to where the search started
+        app.cancel_search();
+        assert_eq!(selected(&app), Some("chrome"));
+        assert!(!app.searching());
+    }
+
+    #[tokio::test]
+    async fn test_search_next_and_prev_wrap() {
+        let mut app = app(vec![
+            process("chrome", 1, 50.0, vec![]),
+            process("Spotify", 2, 40.0, vec![]),
+            process("chromium", 3, 30.0, vec![]),
+            process("chrome_crashpad", 4, 20.0, vec![]),
+        ]);
+        app.enter_search();
+        type_search(&mut app, "chrom");
+        app.finish_search();
+        assert!(!app.search.typing);
+        assert_eq!(selected(&app), Some("chrome"));
+        assert_eq!(app.search_status(), Some((Some(1), 3)));
+
+        app.search_next(true, 1);
+        assert_eq!(selected(&app), Some("chromium"));
+        app.search_next(true, 2);
+        assert_eq!(selected(&app), Some("chrome"));
+        app.search_next(false, 1);
+        assert_eq!(selected(&app), Some("chrome_crashpad"));
+
+        // From a row that isn't a match, n and N go to its neighbours
+        app.process_index = 1;
+        assert_eq!(app.search_status(), Some((None, 3)));
+        app.search_next(true, 1);
+        assert_eq!(selected(&app), Some("chromium"));
+        app.process_index = 1;
+        app.search_next(false, 1);
+        assert_eq!(selected(&app), Some("chrome"));
+
+        // Finishing the search leaves the selection where it is
+        app.cancel_search();
+        assert_eq!(selected(&app), Some("chrome"));
+        assert_eq!(app.search_status(), None);
+    }
+
+    #[tokio::test]
+    async fn test_search_within_filter() {
+        let mut app = app(vec![
+            process("chrome", 1, 50.0, vec![]),
+            process("Spotify", 2, 40.0, vec![]),
+            process("chromium", 3, 30.0, vec![]),
+        ]);
+        app.enter_filter();
+        app.tab_filter_mut().input = InputLine::new("!chrome");
+        app.apply_filter();
+        app.enter_search();
+        type_search(&mut app, "chrom");
+        assert_eq!(selected(&app), Some("chromium"));
+        assert_eq!(app.search_status(), Some((Some(1), 1)));
+
+        // Highlights cover both the filter and the search
+        let mut app = self::app(vec![process("chrome", 1, 50.0, vec![])]);
+        app.enter_filter();
+        app.tab_filter_mut().input = InputLine::new("chr");
+        app.apply_filter();
+        app.enter_search();
+        type_search(&mut app, "ome");
+        let ranges = app.highlights(ActiveTab::Processes, "chrome", None);
+        assert_eq!(ranges.iter().map(|r| (r.start, r.end)).collect::<Vec<_>>(), [(0, 6)]);
+    }
+
+    #[tokio::test]
+    async fn test_drill_down_and_back() {
+        let mut app = app(vec![
+            process("node", 1, 50.0, vec![conn(443), conn(80)]),
+            process("nodemon", 2, 40.0, vec![conn(443)]),
+            process("node", 3, 30.0, vec![conn(8080)]),
+        ]);
+        let rows = |app: &App| app.filtered_connections().iter().map(|r| (r.process.pid, r.conn.remote_port)).collect::<Vec<_>>();
+        app.active_tab = ActiveTab::Connections;
+        app.connection_index = 3;
+        app.remember_selection();
+        app.enter_filter();
+        app.tab_filter_mut().input = InputLine::new("!port:80");
+        app.apply_filter();
+        assert!(!app.in_drill_down());
+
+        // Only the exact process, with the filter still applying
+        app.active_tab = ActiveTab::Processes;
+        app.process_index = 2;
+        app.drill_down();
+        assert!(app.in_drill_down());
+        assert_eq!(app.drilled, Some(("node".to_string(), 3)));
+        assert_eq!(rows(&app), [(3, 8080)]);
+        app.active_tab = ActiveTab::Processes;
+        app.process_index = 0;
+        app.drill_down();
+        assert_eq!(rows(&app), [(1, 443)]);
+        assert_eq!(app.tab_filter().text, ["!port:80"]);
+
+        // Back returns to the process, and the earlier connection and filter
+        app.drill_up();
+        assert_eq!(app.active_tab, ActiveTab::Processes);
+        assert_eq!(app.drilled, None);
+        assert_eq!(app.process_index, 0);
+        app.active_tab = ActiveTab::Connections;
+        assert_eq!(app.tab_filter().text, ["!port:80"]);
+        assert_eq!(rows(&app)[app.connection_index], (3, 8080));
+    }
+
+    #[tokio::test]
+    async fn test_connection_detail() {
+        let mut resolved = conn(443);
+        resolved.hostname = Some(format!("{}.example.com", "edge-cache-".repeat(12)));
+        let mut app = app(vec![process("curl", 7, 10.0, vec![conn(80), resolved])]);
+
+        // Only the Connections tab has a connection to show
+        app.toggle_connection_detail();
+        assert!(!app.show_connection_detail);
+        app.active_tab = ActiveTab::Connections;
+        app.nav_down(1);
+        app.toggle_connection_detail();
+        assert!(app.show_connection_detail);
+        let row = app.selected_connection_row().unwrap();
+        assert_eq!((row.process.pid, row.conn.remote_port), (7, 443));
+
+        // The long hostname wraps onto a second line inside the popup
+        use ratatui::backend::TestBackend;
+        use ratatui::Terminal;
+        let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+        terminal.draw(|f| crate::ui::connection_detail::render(f, &app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let text = (0..40)
+            .map(|y| (0..100).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+            .collect::<Vec<_>>();
+        let host = text.iter().position(|l| l.contains("Hostname")).unwrap();
+        let protocol = text.iter().position(|l| l.contains("Protocol")).unwrap();
+        let wrapped = text[host..protocol]
+            .iter()
+            .map(|l| l.trim().trim_matches('│').trim())
+            .collect::<String>();
+        assert!(protocol > host + 1);
+        assert!(wrapped.contains(app.selected_connection_row().unwrap().conn.hostname.as_deref().unwrap()));
+        assert!(text.iter().any(|l| l.contains("TCP over IPv4")));
+        assert!(text.iter().any(|l| l.contains("curl (7)")));
+    }
+
+    #[tokio::test]
+    async fn test_process_detail_follows_process() {
+        let mut app = app(vec![
+            process("Spotify", 1, 30.0, vec![]),
+            process("Dropbox", 2, 20.0, vec![]),
+        ]);
+        app.nav_down(1);
+        app.toggle_process_detail();
+        assert_eq!(app.detail_process().map(|p| p.pid), Some(2));
+
+        // Still on Dropbox after it moves to the top
+        refresh(&mut app, vec![process("Dropbox", 2, 90.0, vec![]), process("Spotify", 1, 30.0, vec![])]);
+        assert_eq!(app.detail_process().map(|p| p.rate_in), Some(90.0));
+
+        // Once it exits the popup has nothing to show
+        refresh(&mut app, vec![process("Spotify", 1, 30.0, vec![])]);
+        assert!(app.detail_process().is_none());
+        assert_eq!(app.detail_key(), Some(&("Dropbox".to_string(), 2)));
+    }
+
+    #[tokio::test]
+    async fn test_detail_pane_follows_selection() {
+        use ratatui::backend::TestBackend;
+        use ratatui::Terminal;
+
+        let mut busy = conn(443);
+        busy.rate_in = 500.0;
+        let mut app = app(vec![process("curl", 7, 10.0, vec![conn(80), busy]), process("ssh", 9, 5.0, vec![])]);
+        app.all_processes[0].connections[1].remote_addr = "9.9.9.9".to_string();
+        app.rebuild_snapshot();
+        let draw = |app: &App, width: u16, height: u16| {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal
+                .draw(|f| crate::ui::detail_pane::render(f, f.area(), app))
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            (0..height)
+                .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let text = draw(&app, 80, 6);
+        assert!(text.contains("curl (7)"));
+        assert!(text.contains("Top hosts 9.9.9.9"));
+        app.nav_down(1);
+        assert!(draw(&app, 80, 6).contains("ssh (9)"));
+
+        app.active_tab = ActiveTab::Connections;
+        assert!(draw(&app, 80, 6).contains("192.168.0.2:50000 → 1.2.3.4:80"));
+
+        // Cramped terminals cut the text short rather than panic
+        for (width, height) in [(1, 1), (3, 2), (10, 6), (80, 0)] {
+            draw(&app, width, height);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_graph_zoom_and_overlay() {
+        use ratatui::backend::TestBackend;
+        use ratatui::Terminal;
+
+        let mut app = app(vec![process("curl", 7, 10.0, vec![])]);
+        assert_eq!(ActiveTab::Overview.next(), ActiveTab::Graph);
+        assert_eq!(ActiveTab::Processes.prev(), ActiveTab::Graph);
+        assert_eq!(app.graph_span(), Duration::from_secs(300));
+        for _ in 0..5 {
+            app.zoom_graph(true);
+        }
+        assert_eq!(app.graph_span(), Duration::from_secs(60));
+        for _ in 0..5 {
+            app.zoom_graph(false);
+        }
+        assert_eq!(app.graph_span(), Duration::from_secs(600));
+        app.history_span = Duration::from_secs(240);
+        assert_eq!(app.graph_windows(), [60, 120, 240].map(Duration::from_secs));
+        assert_eq!(app.graph_span(), Duration::from_secs(240));
+        app.zoom_graph(true);
+        assert_eq!(app.graph_span(), Duration::from_secs(120));
+        app.history_span = Duration::from_secs(600);
+        app.graph_window = 3;
+
+        let processes = app.all_processes.clone();
+        app.process_history.record(&processes);
+        app.bandwidth_history.extend([(1000.0, 10.0), (2000.0, 20.0)]);
+        app.graph_process = true;
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|f| crate::ui::graph::render(f, f.area(), &app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let text = (0..20)
+            .map(|y| (0..100).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(text.contains("last 10m"));
+        assert!(text.contains("10m ago"));
+        assert!(text.contains("now"));
+        assert!(text.contains("curl (7)"));
+    }
+
+    #[tokio::test]
+    async fn test_overview_top_processes_selectable() {
+        let mut app = app(vec![
+            process("Spotify", 1, 30.0, vec![]),
+            process("Dropbox", 2, 20.0, vec![]),
+            process("curl", 3, 10.0, vec![]),
+        ]);
+        app.active_tab = ActiveTab::Overview;
+        app.nav_down(1);
+        assert_eq!(app.overview_index, 1);
+        app.nav_down(5);
+        assert_eq!(app.overview_index, 2);
+        app.nav_up(1);
+        assert_eq!(app.top_processes()[app.overview_index].name, "Dropbox");
+        // The Processes selection is left alone
+        assert_eq!(selected(&app), Some("Spotify"));
+
+        // The highlight follows its process when the order changes
+        refresh(&mut app, vec![
+            process("Spotify", 1, 30.0, vec![]),
+            process("Dropbox", 2, 50.0, vec![]),
+            process("curl", 3, 10.0, vec![]),
+        ]);
+        assert_eq!(app.overview_index, 0);
+
+        app.drill_down();
+        assert_eq!(app.active_tab, ActiveTab::Processes);
+        assert_eq!(selected(&app), Some("Dropbox"));
+
+        // A process the Processes filter hides stays put on the Overview
+        app.tab_filter_mut().input = InputLine::new("curl");
+        app.apply_filter();
+        app.active_tab = ActiveTab::Overview;
+        app.drill_down();
+        assert_eq!(app.active_tab, ActiveTab::Overview);
+        assert!(app.status_message().is_some_and(|m| m.text.contains("hidden")));
+    }
+
+    #[tokio::test]
+    async fn test_freshness_against_interval() {
+        let mut app = app(vec![]);
+        assert_eq!(app.freshness(), Freshness::Fresh);
+        let ago = |secs| Instant::now().checked_sub(Duration::from_secs(secs)).unwrap();
+        app.last_fetch = Some((ago(5), Duration::from_millis(800)));
+        assert_eq!(app.freshness(), Freshness::Fresh);
+        app.last_fetch = Some((ago(7), Duration::from_millis(800)));
+        assert_eq!(app.freshness(), Freshness::Stale);
+        app.last_fetch = Some((ago(13), Duration::from_millis(800)));
+        assert_eq!(app.freshness(), Freshness::VeryStale);
+        app.paused = true;
+        assert_eq!(app.freshness(), Freshness::Fresh);
+    }
+
+    #[tokio::test]
+    async fn test_status_messages_queue_and_expire() {
+        let mut app = app(vec![]);
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        app.push_status_at(start, Severity::Info, "copied 1.2.3.4".to_string());
+        app.push_status_at(at(100), Severity::Error, "export failed".to_string());
+        app.push_status_at(at(200), Severity::Warning, "nothing to copy".to_string());
+        let shown = |app: &App, ms| app.status_at(at(ms)).map(|m| (m.text.clone(), m.severity));
+
+        // Each waits for the one before to have been up a second
+        assert_eq!(shown(&app, 500), Some(("copied 1.2.3.4".to_string(), Severity::Info)));
+        assert_eq!(shown(&app, 1500), Some(("export failed".to_string(), Severity::Error)));
+        assert_eq!(shown(&app, 2500), Some(("nothing to copy".to_string(), Severity::Warning)));
+        assert_eq!(shown(&app, 4900), Some(("nothing to copy".to_string(), Severity::Warning)));
+        assert_eq!(shown(&app, 5000), None);
+
+        // Expired messages don't hold up new ones
+        app.push_status_at(at(9000), Severity::Info, "Units: bits".to_string());
+        assert_eq!(app.status.len(), 1);
+        assert_eq!(shown(&app, 9000).map(|(text, _)| text), Some("Units: bits".to_string()));
+
+        app.set_status("exported nm.json");
+        app.dismiss_status();
+        assert!(app.status_message().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_columns_give_way_as_the_terminal_narrows() {
+        let mut app = app(vec![process("curl", 7, 10.0, vec![conn(443)])]);
+        // Header labels, split where columns are at least two cells apart
+        let header = |app: &App, width| {
+            render_at(app, width, 6)[1]
+                .trim_matches(|c| c == '│' || c == ' ')
+                .split("  ")
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join("|")
+        };
+
+        let expected = [
+            (ActiveTab::Processes, 160, "Process|PID|Conn|Down|Up|Rate In ▼|Rate Out"),
+            (ActiveTab::Processes, 99, "Process|PID|Conn|Rate In ▼|Rate Out"),
+            (ActiveTab::Processes, 59, "Process|Conn|Rate In ▼|Rate Out"),
+            (ActiveTab::Connections, 160, "Process|Proto Local|Remote|Iface|Dir|State|Age|Down|Up"),
+            (ActiveTab::Connections, 99, "Process|Proto Local|Remote|Iface|Dir|State|Age"),
+            (ActiveTab::Connections, 59, "Process|Proto Local|Remote|Iface|Dir|Age"),
+            (ActiveTab::Remotes, 160, "Host|Conn|Down|Up|Rate In ▼|Rate Out|Processes"),
+            (ActiveTab::Remotes, 99, "Host|Conn|Rate In ▼|Rate Out|Processes"),
+            (ActiveTab::Remotes, 59, "Host|Conn|Rate In ▼|Rate Out|Processes"),
+        ];
+        for (tab, width, labels) in expected {
+            app.active_tab = tab;
+            assert_eq!(header(&app, width), labels, "{:?} at {}", tab, width);
+        }
+
+        // Columns picked in the config stay while they fit, then give way
+        // one at a time, widest breakpoint first
+        app.active_tab = ActiveTab::Processes;
+        let ids = ["process", "pid", "down", "rate-in"].map(String::from);
+        app.process_columns = Columns::from_ids(&ids).unwrap();
+        assert_eq!(header(&app, 99), "Process|PID|Down|Rate In ▼");
+        assert_eq!(header(&app, 55), "Process|PID|Down|Rate In ▼");
+        assert_eq!(header(&app, 50), "Process|PID|Rate In ▼");
+    }
+
+    #[tokio::test]
+    async fn test_export_while_drilled() {
+        let mut app = app(vec![
+            process("node", 1, 50.0, vec![conn(443), conn(80)]),
+            process("curl", 2, 40.0, vec![conn(443)]),
+        ]);
+        app.process_index = 1;
+        app.drill_down();
+        assert_eq!(app.active_tab, ActiveTab::Connections);
+
+        let dir = std::env::temp_dir().join(format!("nm-drill-export-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        app.export_dir = Some(dir.clone());
+        app.export_snapshot();
+        let path = app.status_message().unwrap().text.strip_prefix("exported ").unwrap().to_string();
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // Only the drilled process, as the table and the CSV export show
+        let processes = json["processes"].as_array().unwrap();
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0]["name"], "curl");
+        assert_eq!(processes[0]["connections"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fixed_alias_file_clears_warning() {
+        let path = std::env::temp_dir().join(format!("nm-aliases-{}", std::process::id()));
+        std::fs::write(&path, "10.0.0.1 nas\nnot-an-address x\n").unwrap();
+        let mut app = app(vec![]);
+        app.alias_path = Some(path.clone());
+        app.reload_aliases(false);
+        assert_eq!(app.alias_warning.as_deref(), Some("aliases: line 2: invalid address \"not-an-address\""));
+
+        // Unchanged: the warning stays
+        app.reload_aliases(false);
+        assert!(app.alias_warning.is_some());
+
+        // Fixed and saved again: the next automatic reload clears it
+        std::fs::write(&path, "10.0.0.1 nas\n").unwrap();
+        let later = std::time::SystemTime::now() + Duration::from_secs(5);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        app.reload_aliases(false);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(app.alias_warning, None);
+        assert_eq!(app.alias_file.aliases.lookup("10.0.0.1"), Some("nas"));
+    }
+
+    #[tokio::test]
+    async fn test_drill_into_host_matches_it_exactly() {
+        let mut similar = conn(443);
+        similar.remote_addr = "11.2.3.45".to_string();
+        let mut longer = conn(443);
+        longer.remote_addr = "1.2.3.40".to_string();
+        let mut app = app(vec![
+            process("curl", 1, 30.0, vec![conn(443)]),
+            process("1.2.3.4-agent", 2, 20.0, vec![similar, longer]),
+        ]);
+        app.active_tab = ActiveTab::Remotes;
+        let index = app.filtered_remote_hosts().iter().position(|h| h.host == "1.2.3.4").unwrap();
+        app.remote_index = index;
+        app.drill_down();
+        let rows = app.filtered_connections().iter().map(|r| r.conn.remote_addr.clone()).collect::<Vec<_>>();
+        assert_eq!(rows, ["1.2.3.4"]);
+    }
+
+    #[tokio::test]
+    async fn test_peaks_survive_a_dropout_until_the_grace_period() {
+        let mut app = app(vec![]);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        app.track_peaks(&mut [process("curl", 1, 50.0, vec![]), process("ssh", 2, 10.0, vec![])], at(0));
+
+        // ssh misses a sample and comes back with its peak
+        app.track_peaks(&mut [process("curl", 1, 20.0, vec![])], at(1));
+        let mut processes = [process("ssh", 2, 5.0, vec![])];
+        app.track_peaks(&mut processes, at(2));
+        assert_eq!(processes[0].peak_rate_in, 10.0);
+
+        // curl has been gone for the whole grace period
+        app.track_peaks(&mut processes, at(1) + PEAK_GRACE);
+        assert_eq!(app.peak_rates.len(), 1);
+        let mut processes = [process("curl", 1, 5.0, vec![])];
+        app.track_peaks(&mut processes, at(2) + PEAK_GRACE);
+        assert_eq!(processes[0].peak_rate_in, 5.0);
+    }
+
+    #[tokio::test]
+    async fn test_closed_connections_outlive_their_process() {
+        let mut app = app(vec![
+            process("ssh", 1, 30.0, vec![conn(22)]),
+            process("curl", 2, 20.0, vec![conn(443), conn(80)]),
+        ]);
+        app.closed_retention = 3;
+        let processes = vec![process("ssh", 1, 30.0, vec![conn(22)])];
+        app.track_closed(&processes);
+        refresh(&mut app, processes);
+
+        let rows = app
+            .filtered_connections()
+            .iter()
+            .map(|r| (r.process.name.clone(), r.conn.remote_port, r.closed))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [("ssh".to_string(), 22, false), ("curl".to_string(), 80, true), ("curl".to_string(), 443, true)]
+        );
+
+        app.active_tab = ActiveTab::Connections;
+        app.enter_filter();
+        app.tab_filter_mut().input = InputLine::new("!curl");
+        app.apply_filter();
+        assert_eq!(app.filtered_connections().len(), 1);
     }
 }
@@ -0,0 +1,21 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Put text on the system clipboard by piping it to `pbcopy`.
+pub fn copy(text: &str) -> Result<(), String> {
+    let mut child = Command::new("pbcopy")
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("pbcopy: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("pbcopy: {}", e))?;
+    }
+    let status = child.wait().map_err(|e| format!("pbcopy: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("pbcopy exited with {}", status))
+    }
+}
@@ -1,28 +1,613 @@
-use clap::Parser;
+use std::collections::BTreeMap;
+
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+
+use crate::app::ActiveTab;
+use crate::data::model::SortField;
+use crate::data::filter::FilterSyntax;
+use crate::keymap::Keymap;
+use crate::ui::columns::Columns;
+use crate::ui::theme::{Theme, ThemeName};
+use crate::units::Units;
+use crate::settings::{self, expand_home, Interval, Settings, Width};
 
 #[derive(Parser, Debug)]
 #[command(name = "nm", about = "Network Monitor TUI — lightweight terminal network traffic viewer")]
 pub struct Config {
-    /// Refresh interval in seconds
-    #[arg(short, long, default_value_t = 2)]
-    pub interval: u64,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Config file (default: ~/.config/nm/config.toml)
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<std::path::PathBuf>,
+
+    /// Print a documented example config file and exit
+    #[arg(long)]
+    pub print_default_config: bool,
+
+    /// Refresh interval: seconds (2, 0.5) or a duration (500ms, 1m); at least 100ms
+    #[arg(short, long, default_value = "2", value_parser = parse_interval)]
+    pub interval: std::time::Duration,
+
+    /// How far back the bandwidth histories go (1m to 24h), e.g. 30m
+    #[arg(long, value_name = "DURATION", default_value = "10m", value_parser = parse_history)]
+    pub history: std::time::Duration,
+
+    /// Initial sort field
+    #[arg(short, long, value_enum, default_value_t = crate::data::model::SortField::RateIn)]
+    pub sort_by: crate::data::model::SortField,
+
+    /// Units for byte counts and rates
+    #[arg(long, value_enum, default_value_t = crate::units::Units::Bytes)]
+    pub units: crate::units::Units,
+
+    /// Tab to start on
+    #[arg(long, value_enum, value_name = "TAB")]
+    pub tab: Option<crate::app::ActiveTab>,
+
+    /// Print a table of the top processes after two samples and exit (no TUI)
+    #[arg(long)]
+    pub once: bool,
+
+    /// Watch only this process: its connections, totals, and rate history
+    #[arg(long, value_name = "PID", conflicts_with = "process")]
+    pub pid: Option<u32>,
+
+    /// Watch only processes with this name (case-insensitive), like --pid
+    #[arg(long, value_name = "NAME")]
+    pub process: Option<String>,
+
+    /// Start with this filter applied; repeat to show rows matching any of them
+    #[arg(long, value_name = "TEXT")]
+    pub filter: Vec<String>,
+
+    /// Treat all filter input as a case-insensitive regular expression
+    #[arg(long)]
+    pub regex_filter: bool,
+
+    /// Filter input starting with this is a regular expression ("" for none)
+    #[arg(long, value_name = "PREFIX", default_value = "/")]
+    pub regex_prefix: String,
+
+    /// Run for this long and exit, e.g. 90s, 5m (TUI, --stream, or headless)
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    pub duration: Option<std::time::Duration>,
+
+    /// Print one compact JSON snapshot per refresh to stdout until interrupted (no TUI)
+    #[arg(long)]
+    pub stream: bool,
+
+    /// Keys kept in --stream lines, e.g. total_rate_in,processes,rate_in
+    /// (timestamp and each process's name and pid are always included)
+    #[arg(long, value_name = "FIELDS", value_delimiter = ',')]
+    pub stream_fields: Vec<String>,
+
+    /// With --once, print the snapshot as JSON instead of a table
+    ///
+    /// Schema (version 1): an object with `schema_version`, `timestamp`
+    /// (RFC 3339), `interval_secs`, `total_bytes_in`, `total_bytes_out`,
+    /// `total_rate_in`, `total_rate_out`, `total_pps_in`, `total_pps_out`,
+    /// `total_connections`, and `processes`. Each process has `name`, `pid`,
+    /// `path`, `user`, `display_name`, `started` (Unix seconds), byte,
+    /// packet, and rate counters, `peak_rate_in`/`peak_rate_out`, and
+    /// `connections`, each with `local_addr`, `local_port`, `remote_addr`,
+    /// `remote_port`, `protocol`, `state`, `interface`, `via_vpn`,
+    /// `direction`, byte and rate counters, `hostname` (only if already
+    /// resolved), and `asn`. Rates are bytes/sec. On failure a single
+    /// `{"error": "..."}` object is printed and the exit status is 1.
+    #[arg(long, verbatim_doc_comment)]
+    pub json: bool,
+
+    /// Number of processes printed by --once (0 for all)
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    pub top: usize,
+
+    /// Show a Peak rate column in the Processes tab
+    #[arg(long)]
+    pub show_peak: bool,
+
+    /// Show PPS In/PPS Out packet-rate columns in the Processes tab
+    #[arg(long)]
+    pub show_pps: bool,
+
+    /// Show a User column (process owner) in the Processes tab
+    #[arg(long)]
+    pub show_user: bool,
+
+    /// Show raw process names instead of names from their app bundles
+    #[arg(long)]
+    pub raw_names: bool,
+
+    /// Show an Uptime column (time since process start) in the Processes tab
+    #[arg(long)]
+    pub show_uptime: bool,
+
+    /// Number of refreshes to keep closed connections visible (0 disables)
+    #[arg(long, default_value_t = 3)]
+    pub closed_retention: usize,
+
+    /// Leave loopback-only traffic out of the Overview port breakdown
+    #[arg(long)]
+    pub ports_skip_loopback: bool,
+
+    /// Start with loopback, link-local, and own-address traffic excluded
+    #[arg(long)]
+    pub no_local: bool,
+
+    /// Refreshes without traffic before a connection is shown as idle (0 disables)
+    #[arg(long, default_value_t = 30)]
+    pub idle_after: usize,
+
+    /// Extra interface names treated as VPN tunnels (prefix with trailing `*`,
+    /// or an exact name); may be repeated
+    #[arg(long = "vpn-iface", value_name = "PATTERN")]
+    pub vpn_ifaces: Vec<String>,
+
+    /// Fill connection state and socket queue sizes from `netstat -anv` each refresh
+    #[arg(long)]
+    pub netstat: bool,
+
+    /// Disable reverse DNS entirely and show raw IP addresses
+    #[arg(long)]
+    pub no_dns: bool,
+
+    /// Prefer names from the macOS DNS cache (dscacheutil) over PTR records
+    #[arg(long)]
+    pub dns_system_cache: bool,
+
+    /// Also reverse-resolve private, link-local, loopback, and multicast addresses
+    #[arg(long)]
+    pub resolve_local: bool,
+
+    /// Don't load or save the DNS cache under ~/.cache/nm between runs
+    #[arg(long)]
+    pub no_cache_file: bool,
+
+    /// Hosts-style file mapping addresses or CIDR ranges to labels
+    #[arg(long, value_name = "PATH")]
+    pub aliases: Option<std::path::PathBuf>,
+
+    /// Append one JSON line per refresh (per-process bytes and rates) to this file
+    #[arg(long, value_name = "PATH")]
+    pub log: Option<std::path::PathBuf>,
+
+    /// Serve Prometheus metrics at http://ADDR/metrics (e.g. 127.0.0.1:9184)
+    #[arg(long, value_name = "ADDR")]
+    pub metrics_listen: Option<std::net::SocketAddr>,
+
+    /// Aggregate metrics by process name instead of per PID
+    #[arg(long)]
+    pub metrics_by_name: bool,
+
+    /// Send InfluxDB line protocol to HOST:PORT over UDP every refresh
+    #[arg(long, value_name = "HOST:PORT")]
+    pub influx_udp: Option<String>,
+
+    /// Send statsd gauges to HOST:PORT over UDP every refresh
+    #[arg(long, value_name = "HOST:PORT")]
+    pub statsd_udp: Option<String>,
+
+    /// Alert when a process uploads faster than RATE (e.g. 5MB), optionally
+    /// only for processes named NAME; repeatable
+    #[arg(long, value_name = "[NAME=]RATE", value_parser = crate::alerts::parse_threshold)]
+    pub alert_up: Vec<(Option<String>, f64)>,
+
+    /// Alert when a process downloads faster than RATE (e.g. 20MB), optionally
+    /// only for processes named NAME; repeatable
+    #[arg(long, value_name = "[NAME=]RATE", value_parser = crate::alerts::parse_threshold)]
+    pub alert_down: Vec<(Option<String>, f64)>,
+
+    /// Ring the terminal bell when an alert fires
+    #[arg(long)]
+    pub alert_bell: bool,
+
+    /// POST a JSON payload (timestamp, process, pid, direction, rate,
+    /// threshold) to URL when an alert fires
+    #[arg(long, value_name = "URL")]
+    pub alert_webhook: Option<String>,
+
+    /// Publish fired alerts to an ntfy topic (name on ntfy.sh, or full URL)
+    #[arg(long, value_name = "TOPIC")]
+    pub alert_ntfy: Option<String>,
+
+    /// Minimum time between notifications for the same process and direction
+    #[arg(long, default_value = "5m", value_parser = humantime::parse_duration)]
+    pub alert_cooldown: std::time::Duration,
+
+    /// Show macOS notifications when an alert fires or a new public remote
+    /// host appears
+    #[arg(long)]
+    pub notify: bool,
+
+    /// Track data used this session against a budget, e.g. 2GB
+    #[arg(long, value_name = "SIZE", value_parser = crate::alerts::parse_size)]
+    pub budget: Option<f64>,
+
+    /// Record every refresh (snapshots, processes, connections) into a SQLite database
+    #[arg(long, value_name = "PATH")]
+    pub db: Option<std::path::PathBuf>,
+
+    /// Directory for snapshot exports (default: current directory)
+    #[arg(long, value_name = "DIR")]
+    pub export_dir: Option<std::path::PathBuf>,
+
+    /// Maximum reverse DNS entries kept before least recently used ones are evicted
+    #[arg(long, default_value_t = crate::data::dns::DEFAULT_CACHE_SIZE)]
+    pub dns_cache_size: usize,
+
+    /// GeoLite2-ASN database for annotating remote IPs with their AS organization
+    #[arg(long, value_name = "PATH")]
+    pub asn_db: Option<std::path::PathBuf>,
+
+    /// Color theme
+    #[arg(long, value_enum, value_name = "NAME", default_value_t = crate::ui::theme::ThemeName::Default)]
+    pub theme: crate::ui::theme::ThemeName,
+
+    /// Rates above this are colored as medium (yellow), e.g. 500KB
+    #[arg(long, value_name = "SIZE", value_parser = crate::alerts::parse_size)]
+    pub rate_medium: Option<f64>,
+
+    /// Rates above this are colored as high (red), e.g. 10MB
+    #[arg(long, value_name = "SIZE", value_parser = crate::alerts::parse_size)]
+    pub rate_high: Option<f64>,
+
+    /// Color rates by their share of the busiest process's current rate
+    #[arg(long)]
+    pub relative_colors: bool,
+
+    /// Draw without colors (also set by a non-empty NO_COLOR environment variable)
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Leave the mouse to the terminal, for its own text selection
+    #[arg(long)]
+    pub no_mouse: bool,
+
+    /// Draw the bandwidth sparkline on a logarithmic scale
+    #[arg(long)]
+    pub log_scale: bool,
 
-    /// Initial sort field: name, pid, conn, down, up, rate-in, rate-out
-    #[arg(short, long, default_value = "rate-in")]
-    pub sort_by: String,
+    /// Key bindings, from the `[keys]` config section
+    #[arg(skip)]
+    pub keymap: crate::keymap::Keymap,
+
+    /// Processes table columns, from the `[columns]` config section
+    #[arg(skip)]
+    pub process_columns: crate::ui::columns::Columns<crate::ui::processes::ProcessColumn>,
+
+    /// Connections table columns, from the `[columns]` config section
+    #[arg(skip)]
+    pub connection_columns: crate::ui::columns::Columns<crate::ui::connections::ConnectionColumn>,
+
+    /// `--theme` with the `[colors]` config section applied
+    #[arg(skip)]
+    pub palette: crate::ui::theme::Theme,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Print per-process traffic totals recorded with --db
+    Report {
+        /// Database written by --db
+        #[arg(long, value_name = "PATH")]
+        db: std::path::PathBuf,
+
+        /// How far back to total, e.g. 90m, 24h, 7d
+        #[arg(long, default_value = "24h", value_parser = humantime::parse_duration)]
+        since: std::time::Duration,
+    },
 }
 
 impl Config {
-    pub fn parse_sort_field(&self) -> crate::data::model::SortField {
-        match self.sort_by.as_str() {
-            "name" => crate::data::model::SortField::Name,
-            "pid" => crate::data::model::SortField::Pid,
-            "conn" => crate::data::model::SortField::Connections,
-            "down" => crate::data::model::SortField::BytesIn,
-            "up" => crate::data::model::SortField::BytesOut,
-            "rate-in" => crate::data::model::SortField::RateIn,
-            "rate-out" => crate::data::model::SortField::RateOut,
-            _ => crate::data::model::SortField::RateIn,
+    /// Parse the command line, then take anything it didn't set from the
+    /// config file.
+    pub fn load() -> Result<Self, String> {
+        let matches = Config::command().get_matches();
+        let mut config = Config::from_arg_matches(&matches).map_err(|e| e.to_string())?;
+        if config.print_default_config {
+            return Ok(config);
+        }
+        let path = config.config.clone().or_else(settings::config_file_path);
+        let settings = match &path {
+            Some(path) => Settings::load(path, config.config.is_some())?,
+            None => Settings::default(),
+        };
+        config.merge(settings, &matches).map_err(|e| match &path {
+            Some(path) => format!("{}: {}", path.display(), e),
+            None => e,
+        })?;
+        let syntax = config.filter_syntax();
+        for term in &config.filter {
+            syntax.parse(term).map_err(|e| format!("--filter {:?}: {}", term, e))?;
+        }
+        Ok(config)
+    }
+
+    /// How filter input is read, from `--regex-filter` and `--regex-prefix`.
+    pub fn filter_syntax(&self) -> FilterSyntax {
+        FilterSyntax {
+            prefix: self.regex_prefix.clone(),
+            always_regex: self.regex_filter,
+        }
+    }
+
+    /// Apply config-file settings to every option not given on the command
+    /// line.
+    fn merge(&mut self, settings: Settings, matches: &ArgMatches) -> Result<(), String> {
+        let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+        macro_rules! merge {
+            ($field:ident) => {
+                merge!($field, |value| Ok::<_, String>(value))
+            };
+            ($field:ident, $convert:expr) => {
+                if let Some(value) = settings.$field {
+                    if !from_cli(stringify!($field)) {
+                        self.$field = ($convert)(value)
+                            .map_err(|e| format!("{}: {}", stringify!($field).replace('_', "-"), e))?;
+                    }
+                }
+            };
+        }
+        let path = |value| Ok::<_, String>(Some(expand_home(value)));
+        let thresholds = |values: Vec<String>| {
+            values
+                .iter()
+                .map(|v| crate::alerts::parse_threshold(v))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        merge!(interval, |value| match value {
+            Interval::Secs(secs) => parse_interval(&secs.to_string()),
+            Interval::Text(text) => parse_interval(&text),
+        });
+        merge!(history, |value: String| parse_history(&value));
+        merge!(regex_filter);
+        merge!(regex_prefix);
+        merge!(sort_by, |value: String| SortField::from_str(&value, false));
+        merge!(tab, |value: String| ActiveTab::from_str(&value, false).map(Some));
+        merge!(units, |value: String| Units::from_str(&value, false));
+        merge!(show_peak);
+        merge!(show_pps);
+        merge!(show_user);
+        merge!(show_uptime);
+        merge!(raw_names);
+        merge!(closed_retention);
+        merge!(idle_after);
+        merge!(no_local);
+        merge!(ports_skip_loopback);
+        merge!(netstat);
+        merge!(vpn_ifaces);
+        merge!(no_dns);
+        merge!(resolve_local);
+        merge!(dns_system_cache);
+        merge!(no_cache_file);
+        merge!(dns_cache_size);
+        merge!(aliases, path);
+        merge!(asn_db, path);
+        merge!(alert_up, thresholds);
+        merge!(alert_down, thresholds);
+        merge!(alert_bell);
+        merge!(alert_cooldown, |value: String| {
+            humantime::parse_duration(&value).map_err(|e| e.to_string())
+        });
+        merge!(notify);
+        merge!(alert_webhook, |value| Ok::<_, String>(Some(value)));
+        merge!(alert_ntfy, |value| Ok::<_, String>(Some(value)));
+        merge!(budget, |value: String| crate::alerts::parse_size(&value).map(Some));
+        merge!(log, path);
+        merge!(db, path);
+        merge!(export_dir, path);
+        merge!(theme, |value: String| ThemeName::from_str(&value, false));
+        merge!(no_color);
+        merge!(no_mouse);
+        merge!(log_scale);
+        let columns = settings.columns.unwrap_or_default();
+        if let Some(ids) = columns.processes {
+            self.process_columns = Columns::from_ids(&ids).map_err(|e| format!("columns.processes: {}", e))?;
         }
+        if let Some(ids) = columns.connections {
+            self.connection_columns =
+                Columns::from_ids(&ids).map_err(|e| format!("columns.connections: {}", e))?;
+        }
+        let widths = |widths: BTreeMap<String, Width>| {
+            widths.into_iter().map(|(id, w)| (id, w.into_string())).collect::<BTreeMap<_, _>>()
+        };
+        if let Some(process_widths) = columns.process_widths.map(widths) {
+            self.process_columns
+                .set_widths(&process_widths)
+                .map_err(|e| format!("columns.process-widths: {}", e))?;
+        }
+        if let Some(connection_widths) = columns.connection_widths.map(widths) {
+            self.connection_columns
+                .set_widths(&connection_widths)
+                .map_err(|e| format!("columns.connection-widths: {}", e))?;
+        }
+        let thresholds = settings.thresholds.unwrap_or_default();
+        let size = |value: String| crate::alerts::parse_size(&value).map(Some);
+        if let Some(value) = thresholds.medium.filter(|_| !from_cli("rate_medium")) {
+            self.rate_medium = size(value).map_err(|e| format!("thresholds.medium: {}", e))?;
+        }
+        if let Some(value) = thresholds.high.filter(|_| !from_cli("rate_high")) {
+            self.rate_high = size(value).map_err(|e| format!("thresholds.high: {}", e))?;
+        }
+        if let Some(value) = thresholds.relative.filter(|_| !from_cli("relative_colors")) {
+            self.relative_colors = value;
+        }
+        let colors = settings.colors.unwrap_or_default();
+        // Overrides are still checked so a bad color is caught either way
+        let mut palette = Theme::preset(self.theme)
+            .with_colors(&colors)
+            .map_err(|e| format!("colors: {}", e))?;
+        palette.rate_medium_above = self.rate_medium.unwrap_or(palette.rate_medium_above);
+        palette.rate_high_above = self.rate_high.unwrap_or(palette.rate_high_above);
+        palette.rate_relative = self.relative_colors;
+        if palette.rate_medium_above >= palette.rate_high_above {
+            return Err("the medium rate threshold must be below the high one".to_string());
+        }
+        self.palette = if self.no_color || no_color_env() {
+            Theme::preset(ThemeName::Monochrome)
+        } else {
+            palette
+        };
+        if let Some(keys) = settings.keys {
+            let keys = keys.into_iter().map(|(action, list)| (action, list.into_vec())).collect();
+            self.keymap = Keymap::with_overrides(&keys).map_err(|e| format!("keys: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+/// The NO_COLOR convention (https://no-color.org): set and non-empty.
+fn no_color_env() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+/// Shortest refresh interval accepted; below this nettop can't keep up and
+/// rates turn to noise.
+const MIN_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Parse `--interval` as fractional seconds or a humantime duration.
+fn parse_interval(s: &str) -> Result<std::time::Duration, String> {
+    let interval = match s.parse::<f64>() {
+        Ok(secs) if secs.is_finite() && secs >= 0.0 => std::time::Duration::from_secs_f64(secs),
+        Ok(_) => return Err(format!("invalid interval {:?}", s)),
+        Err(_) => humantime::parse_duration(s).map_err(|e| e.to_string())?,
+    };
+    if interval < MIN_INTERVAL {
+        return Err(format!("interval must be at least {}", humantime::format_duration(MIN_INTERVAL)));
+    }
+    Ok(interval)
+}
+
+/// Shortest and longest `--history` spans accepted.
+const HISTORY_RANGE: std::ops::RangeInclusive<std::time::Duration> =
+    std::time::Duration::from_secs(60)..=std::time::Duration::from_secs(24 * 60 * 60);
+
+/// Parse `--history` as a humantime duration within `HISTORY_RANGE`.
+fn parse_history(s: &str) -> Result<std::time::Duration, String> {
+    let history = humantime::parse_duration(s).map_err(|e| e.to_string())?;
+    if !HISTORY_RANGE.contains(&history) {
+        return Err(format!(
+            "history must be between {} and {}",
+            humantime::format_duration(*HISTORY_RANGE.start()),
+            humantime::format_duration(*HISTORY_RANGE.end())
+        ));
+    }
+    Ok(history)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::model::SortField;
+
+    fn sort_by(value: &str) -> Result<SortField, String> {
+        Config::try_parse_from(["nm", "--sort-by", value])
+            .map(|c| c.sort_by)
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_sort_by_accepts_names_and_aliases() {
+        assert_eq!(sort_by("rate-in"), Ok(SortField::RateIn));
+        assert_eq!(sort_by("rate_in"), Ok(SortField::RateIn));
+        assert_eq!(sort_by("ratein"), Ok(SortField::RateIn));
+        assert_eq!(sort_by("pps_out"), Ok(SortField::PpsOut));
+        assert_eq!(sort_by("conn"), Ok(SortField::Connections));
+        assert_eq!(sort_by("down"), Ok(SortField::BytesIn));
+        assert_eq!(Config::try_parse_from(["nm"]).unwrap().sort_by, SortField::RateIn);
+    }
+
+    fn merged(args: &[&str], file: &str) -> Result<Config, String> {
+        let matches = Config::command().try_get_matches_from(args).unwrap();
+        let mut config = Config::from_arg_matches(&matches).unwrap();
+        config.merge(Settings::parse(file)?, &matches)?;
+        Ok(config)
+    }
+
+    #[test]
+    fn test_command_line_overrides_config_file() {
+        let file = "sort-by = \"pid\"\ninterval = \"500ms\"\nshow-peak = true\nalert-up = [\"curl=1MB\"]\n";
+        let config = merged(&["nm", "--sort-by", "name"], file).unwrap();
+        assert_eq!(config.sort_by, SortField::Name);
+        assert_eq!(config.interval, std::time::Duration::from_millis(500));
+        assert!(config.show_peak);
+        assert_eq!(config.alert_up, vec![(Some("curl".to_string()), 1_048_576.0)]);
+
+        let error = merged(&["nm"], "sort-by = \"speed\"\n").unwrap_err();
+        assert!(error.starts_with("sort-by: invalid variant: speed"), "{}", error);
+    }
+
+    #[test]
+    fn test_rate_thresholds() {
+        let file = "[thresholds]\nmedium = \"500KB\"\nhigh = \"10MB\"\n";
+        let config = merged(&["nm", "--rate-high", "20MB"], file).unwrap();
+        assert_eq!(config.palette.rate_medium_above, 512_000.0);
+        assert_eq!(config.palette.rate_high_above, 20.0 * 1_048_576.0);
+        assert!(!config.palette.rate_relative);
+
+        let config = merged(&["nm", "--relative-colors"], "").unwrap();
+        assert!(config.palette.rate_relative);
+
+        let error = merged(&["nm"], "[thresholds]\nhigh = \"fast\"\n").unwrap_err();
+        assert_eq!(error, "thresholds.high: invalid size \"fast\"");
+        let error = merged(&["nm", "--rate-medium", "2MB"], "").unwrap_err();
+        assert_eq!(error, "the medium rate threshold must be below the high one");
+    }
+
+    #[test]
+    fn test_no_color_overrides_theme() {
+        use ratatui::style::Color;
+        let file = "theme = \"gruvbox\"\n[colors]\nborder = \"red\"\n";
+        let config = merged(&["nm", "--no-color"], file).unwrap();
+        assert_eq!(config.palette.border, Color::Reset);
+        assert_eq!(config.palette.rate_high, Color::Reset);
+
+        let error = merged(&["nm", "--no-color"], "[colors]\nborder = \"blurple\"\n").unwrap_err();
+        assert_eq!(error, "colors: border: unknown color \"blurple\"");
+    }
+
+    #[test]
+    fn test_keys_section_rebinds_actions() {
+        let config = merged(&["nm"], "[keys]\ndrill-down = \"d\"\nprocess-detail = [\"D\"]\n").unwrap();
+        assert_eq!(config.keymap.hint(crate::keymap::Action::DrillDown).as_deref(), Some("d"));
+
+        let error = merged(&["nm"], "[keys]\ndrill-down = \"d\"\n").unwrap_err();
+        assert_eq!(error, "keys: key d is bound to both drill-down and process-detail");
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        use std::time::Duration;
+        assert_eq!(parse_interval("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_interval("0.5"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_interval("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_interval("1m"), Ok(Duration::from_secs(60)));
+        assert_eq!(parse_interval("0"), Err("interval must be at least 100ms".to_string()));
+        assert_eq!(parse_interval("50ms"), Err("interval must be at least 100ms".to_string()));
+        assert!(parse_interval("-1").is_err());
+        assert!(parse_interval("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_history() {
+        use std::time::Duration;
+        assert_eq!(parse_history("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_history("24h"), Ok(Duration::from_secs(86_400)));
+        assert_eq!(parse_history("30s"), Err("history must be between 1m and 1day".to_string()));
+        assert!(parse_history("2days").is_err());
+        assert!(parse_history("30").is_err());
+    }
+
+    #[test]
+    fn test_sort_by_rejects_unknown_values() {
+        let error = sort_by("speed").unwrap_err();
+        assert!(error.contains("invalid value 'speed' for '--sort-by <SORT_BY>'"), "{}", error);
+        assert!(
+            error.contains("[possible values: name, pid, conn, down, up, rate-in, rate-out, pps-in, pps-out]"),
+            "{}",
+            error
+        );
     }
 }
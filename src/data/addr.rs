@@ -0,0 +1,227 @@
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use super::model::Process;
+
+/// Parse an address as reported by nettop, dropping any IPv6 zone suffix
+/// ("fe80::1%en0").
+pub fn parse_ip(addr: &str) -> Option<IpAddr> {
+    let addr = addr.split('%').next().unwrap_or(addr);
+    addr.parse().ok()
+}
+
+/// Coarse reachability class of an address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddrClass {
+    Unspecified,
+    Loopback,
+    /// RFC 1918 IPv4 or IPv6 unique-local (fc00::/7)
+    Private,
+    /// 169.254.0.0/16 or fe80::/10
+    LinkLocal,
+    /// Carrier-grade NAT shared space, 100.64.0.0/10
+    Cgnat,
+    Multicast,
+    Broadcast,
+    Public,
+}
+
+impl AddrClass {
+    /// Whether traffic to this class stays on the local network.
+    pub fn is_lan(self) -> bool {
+        !matches!(self, AddrClass::Public)
+    }
+}
+
+pub fn classify(ip: &IpAddr) -> AddrClass {
+    match ip {
+        IpAddr::V4(v4) => classify_v4(v4),
+        IpAddr::V6(v6) => classify_v6(v6),
+    }
+}
+
+/// Classify an address string as reported by nettop; None for wildcards
+/// and anything unparseable.
+pub fn classify_addr(addr: &str) -> Option<AddrClass> {
+    parse_ip(addr).map(|ip| classify(&ip))
+}
+
+fn classify_v4(ip: &Ipv4Addr) -> AddrClass {
+    let o = ip.octets();
+    if ip.is_unspecified() {
+        AddrClass::Unspecified
+    } else if ip.is_loopback() {
+        AddrClass::Loopback
+    } else if ip.is_private() {
+        AddrClass::Private
+    } else if ip.is_link_local() {
+        AddrClass::LinkLocal
+    } else if o[0] == 100 && (o[1] & 0xc0) == 64 {
+        AddrClass::Cgnat
+    } else if ip.is_multicast() {
+        AddrClass::Multicast
+    } else if ip.is_broadcast() {
+        AddrClass::Broadcast
+    } else {
+        AddrClass::Public
+    }
+}
+
+fn classify_v6(ip: &Ipv6Addr) -> AddrClass {
+    if let Some(v4) = ip.to_ipv4_mapped() {
+        return classify_v4(&v4);
+    }
+    let first = ip.segments()[0];
+    if ip.is_unspecified() {
+        AddrClass::Unspecified
+    } else if ip.is_loopback() {
+        AddrClass::Loopback
+    } else if (first & 0xfe00) == 0xfc00 {
+        AddrClass::Private
+    } else if (first & 0xffc0) == 0xfe80 {
+        AddrClass::LinkLocal
+    } else if ip.is_multicast() {
+        AddrClass::Multicast
+    } else {
+        AddrClass::Public
+    }
+}
+
+fn is_loopback_or_link_local(ip: &IpAddr) -> bool {
+    matches!(classify(ip), AddrClass::Loopback | AddrClass::LinkLocal)
+}
+
+/// Addresses this machine is using, taken from the local side of every
+/// connection.
+fn own_addresses(processes: &[Process]) -> HashSet<IpAddr> {
+    processes
+        .iter()
+        .flat_map(|p| p.connections.iter())
+        .filter_map(|c| parse_ip(&c.local_addr))
+        .filter(|ip| !ip.is_unspecified())
+        .collect()
+}
+
+/// Remove connections whose remote end is loopback, link-local, or one of
+/// this machine's own addresses, subtracting their traffic from the owning
+/// process. Processes left with only local connections are dropped.
+pub fn exclude_local(processes: &mut Vec<Process>) {
+    let own = own_addresses(processes);
+    let is_local = |addr: &str| {
+        parse_ip(addr).is_some_and(|ip| is_loopback_or_link_local(&ip) || own.contains(&ip))
+    };
+
+    processes.retain_mut(|p| {
+        let had_connections = !p.connections.is_empty();
+        p.connections.retain(|c| {
+            if !is_local(&c.remote_addr) {
+                return true;
+            }
+            p.bytes_in = p.bytes_in.saturating_sub(c.bytes_in);
+            p.bytes_out = p.bytes_out.saturating_sub(c.bytes_out);
+            p.rate_in = (p.rate_in - c.rate_in).max(0.0);
+            p.rate_out = (p.rate_out - c.rate_out).max(0.0);
+            false
+        });
+        !(had_connections && p.connections.is_empty())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::model::Connection;
+
+    fn conn(local: &str, remote: &str, bytes_in: u64) -> Connection {
+        Connection {
+            local_addr: local.to_string(),
+            remote_addr: remote.to_string(),
+            bytes_in,
+            ..Connection::for_test(443)
+        }
+    }
+
+    fn process(name: &str, connections: Vec<Connection>) -> Process {
+        Process {
+            bytes_in: connections.iter().map(|c| c.bytes_in).sum(),
+            connections,
+            ..Process::for_test(name, 1)
+        }
+    }
+
+    #[test]
+    fn test_parse_ip_strips_zone() {
+        assert_eq!(parse_ip("fe80::1%en0"), "fe80::1".parse().ok());
+        assert_eq!(parse_ip("*"), None);
+    }
+
+    fn class(addr: &str) -> AddrClass {
+        classify_addr(addr).unwrap()
+    }
+
+    #[test]
+    fn test_classify_ipv4_boundaries() {
+        assert_eq!(class("10.0.0.0"), AddrClass::Private);
+        assert_eq!(class("10.255.255.255"), AddrClass::Private);
+        assert_eq!(class("11.0.0.0"), AddrClass::Public);
+        assert_eq!(class("172.15.255.255"), AddrClass::Public);
+        assert_eq!(class("172.16.0.0"), AddrClass::Private);
+        assert_eq!(class("172.31.255.255"), AddrClass::Private);
+        assert_eq!(class("172.32.0.0"), AddrClass::Public);
+        assert_eq!(class("192.168.0.1"), AddrClass::Private);
+        assert_eq!(class("192.169.0.1"), AddrClass::Public);
+        assert_eq!(class("100.63.255.255"), AddrClass::Public);
+        assert_eq!(class("100.64.0.0"), AddrClass::Cgnat);
+        assert_eq!(class("100.127.255.255"), AddrClass::Cgnat);
+        assert_eq!(class("100.128.0.0"), AddrClass::Public);
+        assert_eq!(class("169.254.1.1"), AddrClass::LinkLocal);
+        assert_eq!(class("127.0.0.1"), AddrClass::Loopback);
+        assert_eq!(class("224.0.0.251"), AddrClass::Multicast);
+        assert_eq!(class("239.255.255.250"), AddrClass::Multicast);
+        assert_eq!(class("255.255.255.255"), AddrClass::Broadcast);
+        assert_eq!(class("0.0.0.0"), AddrClass::Unspecified);
+        assert_eq!(class("17.57.146.59"), AddrClass::Public);
+    }
+
+    #[test]
+    fn test_classify_ipv6_boundaries() {
+        assert_eq!(class("::1"), AddrClass::Loopback);
+        assert_eq!(class("::"), AddrClass::Unspecified);
+        assert_eq!(class("fbff:ffff::1"), AddrClass::Public);
+        assert_eq!(class("fc00::1"), AddrClass::Private);
+        assert_eq!(class("fdff:ffff::1"), AddrClass::Private);
+        assert_eq!(class("fe00::1"), AddrClass::Public);
+        assert_eq!(class("fe80::1%en0"), AddrClass::LinkLocal);
+        assert_eq!(class("febf:ffff::1"), AddrClass::LinkLocal);
+        assert_eq!(class("fec0::1"), AddrClass::Public);
+        assert_eq!(class("ff02::fb"), AddrClass::Multicast);
+        assert_eq!(class("::ffff:192.168.1.1"), AddrClass::Private);
+        assert_eq!(class("2606:4700::1111"), AddrClass::Public);
+        assert_eq!(classify_addr("*"), None);
+    }
+
+    #[test]
+    fn test_exclude_local_subtracts_and_drops() {
+        let mut procs = vec![
+            process(
+                "curl",
+                vec![conn("192.168.0.2", "1.2.3.4", 100), conn("127.0.0.1", "127.0.0.1", 50)],
+            ),
+            process("devserver", vec![conn("192.168.0.2", "192.168.0.2", 10)]),
+            process("mdns", vec![conn("fe80::1%en0", "fe80::2%en0", 10)]),
+        ];
+        exclude_local(&mut procs);
+
+        assert_eq!(procs.len(), 1);
+        assert_eq!(procs[0].name, "curl");
+        assert_eq!(procs[0].connections.len(), 1);
+        assert_eq!(procs[0].bytes_in, 100);
+    }
+
+    #[test]
+    fn test_exclude_local_keeps_connectionless_processes() {
+        let mut procs = vec![process("kernel_task", Vec::new())];
+        exclude_local(&mut procs);
+        assert_eq!(procs.len(), 1);
+    }
+}
@@ -0,0 +1,188 @@
+use std::net::IpAddr;
+use std::path::Path;
+use std::time::SystemTime;
+
+use super::addr::parse_ip;
+use super::model::Process;
+
+/// A network and the label shown for every address inside it.
+#[derive(Debug, Clone)]
+struct Alias {
+    network: IpAddr,
+    prefix: u8,
+    label: String,
+}
+
+/// User-supplied labels for addresses without useful PTR records, read from
+/// a hosts-style file:
+///
+/// ```text
+/// # address or CIDR    label
+/// 192.168.1.10         NAS
+/// 10.8.0.0/24          wg-peers
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Aliases {
+    entries: Vec<Alias>,
+}
+
+impl Aliases {
+    /// Parse the alias file format. Malformed lines are skipped and
+    /// described in the returned warnings.
+    pub fn parse(text: &str) -> (Self, Vec<String>) {
+        let mut entries = Vec::new();
+        let mut warnings = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (target, label) = match line.split_once(char::is_whitespace) {
+                Some((target, label)) if !label.trim().is_empty() => (target, label.trim()),
+                _ => {
+                    warnings.push(format!("line {}: expected an address and a label", i + 1));
+                    continue;
+                }
+            };
+            match parse_network(target) {
+                Some((network, prefix)) => entries.push(Alias {
+                    network,
+                    prefix,
+                    label: label.to_string(),
+                }),
+                None => warnings.push(format!("line {}: invalid address {:?}", i + 1, target)),
+            }
+        }
+        (Self { entries }, warnings)
+    }
+
+    /// Label for an address; the most specific matching entry wins.
+    pub fn lookup(&self, addr: &str) -> Option<&str> {
+        let ip = parse_ip(addr)?.to_canonical();
+        self.entries
+            .iter()
+            .filter(|a| contains(a.network, a.prefix, ip))
+            .max_by_key(|a| a.prefix)
+            .map(|a| a.label.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// What `AliasFile::reload` did.
+#[derive(Debug, PartialEq)]
+pub enum Reload {
+    /// The file hasn't changed since it was last read
+    Unchanged,
+    /// The file was read, with a warning to show the user if any
+    Reloaded(Option<String>),
+}
+
+/// An alias file and the modification time it was last read at, so it can
+/// be re-read when edited.
+#[derive(Debug, Default)]
+pub struct AliasFile {
+    pub aliases: Aliases,
+    modified: Option<SystemTime>,
+}
+
+impl AliasFile {
+    /// Re-read the file if it changed since the last load (or always when
+    /// `force`).
+    pub fn reload(&mut self, path: &Path, force: bool) -> Reload {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        if !force && modified.is_some() && modified == self.modified {
+            return Reload::Unchanged;
+        }
+        self.modified = modified;
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => return Reload::Reloaded(Some(format!("aliases: {}: {}", path.display(), e))),
+        };
+        let (aliases, warnings) = Aliases::parse(&text);
+        self.aliases = aliases;
+        Reload::Reloaded(match warnings.len() {
+            0 => None,
+            1 => Some(format!("aliases: {}", warnings[0])),
+            n => Some(format!("aliases: {} (and {} more)", warnings[0], n - 1)),
+        })
+    }
+}
+
+/// Parse "10.8.0.0/24" or a bare address (a full-length prefix).
+fn parse_network(s: &str) -> Option<(IpAddr, u8)> {
+    let (addr, prefix) = match s.split_once('/') {
+        Some((addr, prefix)) => (addr, Some(prefix.parse::<u8>().ok()?)),
+        None => (s, None),
+    };
+    let ip = addr.parse::<IpAddr>().ok()?.to_canonical();
+    let max = if ip.is_ipv4() { 32 } else { 128 };
+    let prefix = prefix.unwrap_or(max);
+    (prefix <= max).then_some((ip, prefix))
+}
+
+fn contains(network: IpAddr, prefix: u8, ip: IpAddr) -> bool {
+    match (network, ip) {
+        (IpAddr::V4(net), IpAddr::V4(ip)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            u32::from(net) & mask == u32::from(ip) & mask
+        }
+        (IpAddr::V6(net), IpAddr::V6(ip)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            u128::from(net) & mask == u128::from(ip) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Label connections whose remote address has an alias, overriding any
+/// resolved hostname.
+pub fn apply_aliases(processes: &mut [Process], aliases: &Aliases) {
+    if aliases.is_empty() {
+        return;
+    }
+    for proc in processes.iter_mut() {
+        for conn in proc.connections.iter_mut() {
+            if let Some(label) = aliases.lookup(&conn.remote_addr) {
+                conn.hostname = Some(label.to_string());
+                conn.resolving = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_lookup_prefers_most_specific() {
+        let text = "\
+# home network
+192.168.1.10   NAS
+10.8.0.0/24    wg-peers
+10.8.0.1       Office VPN   # gateway
+fd00::/8       ula
+";
+        let (aliases, warnings) = Aliases::parse(text);
+        assert!(warnings.is_empty());
+        assert_eq!(aliases.lookup("192.168.1.10"), Some("NAS"));
+        assert_eq!(aliases.lookup("::ffff:192.168.1.10"), Some("NAS"));
+        assert_eq!(aliases.lookup("10.8.0.77"), Some("wg-peers"));
+        assert_eq!(aliases.lookup("10.8.0.1"), Some("Office VPN"));
+        assert_eq!(aliases.lookup("10.8.1.1"), None);
+        assert_eq!(aliases.lookup("fd12::1%en0"), Some("ula"));
+        assert_eq!(aliases.lookup("*"), None);
+    }
+
+    #[test]
+    fn test_parse_reports_bad_lines() {
+        let (aliases, warnings) = Aliases::parse("10.0.0.1\nnot-an-ip host\n10.0.0.0/40 big\n10.0.0.2 ok\n");
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].starts_with("line 1:"));
+        assert!(warnings[1].starts_with("line 2:"));
+        assert_eq!(aliases.lookup("10.0.0.2"), Some("ok"));
+    }
+}
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::Path;
+
+use maxminddb::geoip2;
+
+use super::model::Process;
+
+/// Autonomous-system lookups against a GeoLite2-ASN (or compatible) database,
+/// cached per IP like the DNS cache.
+pub struct AsnLookup {
+    reader: maxminddb::Reader<Vec<u8>>,
+    cache: HashMap<String, Option<String>>,
+}
+
+impl AsnLookup {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let reader = maxminddb::Reader::open_readfile(path)
+            .map_err(|e| format!("Failed to open ASN database {}: {}", path.display(), e))?;
+        Ok(AsnLookup {
+            reader,
+            cache: HashMap::new(),
+        })
+    }
+
+    /// Organization label for an IP, e.g. "AS13335 Cloudflare".
+    pub fn lookup(&mut self, ip_str: &str) -> Option<String> {
+        if let Some(cached) = self.cache.get(ip_str) {
+            return cached.clone();
+        }
+        let result = ip_str
+            .parse::<IpAddr>()
+            .ok()
+            .and_then(|ip| self.reader.lookup::<geoip2::Asn>(ip).ok())
+            .and_then(|asn| format_asn(asn.autonomous_system_number, asn.autonomous_system_organization));
+        self.cache.insert(ip_str.to_string(), result.clone());
+        result
+    }
+}
+
+fn format_asn(number: Option<u32>, org: Option<&str>) -> Option<String> {
+    match (number, org) {
+        (Some(n), Some(o)) => Some(format!("AS{} {}", n, o)),
+        (Some(n), None) => Some(format!("AS{}", n)),
+        (None, Some(o)) => Some(o.to_string()),
+        (None, None) => None,
+    }
+}
+
+/// Annotate every connection with its remote address's AS organization.
+pub fn annotate(processes: &mut [Process], lookup: &mut AsnLookup) {
+    for proc in processes.iter_mut() {
+        for conn in proc.connections.iter_mut() {
+            if conn.remote_addr.is_empty() || conn.remote_addr == "*" {
+                continue;
+            }
+            conn.asn = lookup.lookup(&conn.remote_addr);
+        }
+    }
+}
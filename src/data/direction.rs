@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+
+use super::listeners::Listener;
+use super::model::{Connection, Direction, Process, Protocol};
+
+/// Start of the macOS ephemeral port range used for outgoing connections.
+const EPHEMERAL_PORT_START: u16 = 49152;
+
+/// Guess who initiated a connection. In order of confidence:
+/// - a local port with a listening socket means the peer connected to us;
+/// - a listening or peerless socket has no direction;
+/// - an ephemeral local port talking to a non-ephemeral remote port is ours;
+/// - otherwise the side with the lower (service) port is the server.
+pub fn infer(conn: &Connection, listening: &HashSet<(Protocol, u16)>) -> Direction {
+    if !conn.protocol.has_ports() || conn.state == "LISTEN" {
+        return Direction::Unknown;
+    }
+    if conn.remote_addr == "*" || conn.remote_port == 0 {
+        return Direction::Unknown;
+    }
+    if listening.contains(&(conn.protocol.clone(), conn.local_port)) {
+        return Direction::Inbound;
+    }
+    let local_ephemeral = conn.local_port >= EPHEMERAL_PORT_START;
+    let remote_ephemeral = conn.remote_port >= EPHEMERAL_PORT_START;
+    match (local_ephemeral, remote_ephemeral) {
+        (true, false) => Direction::Outbound,
+        (false, true) => Direction::Inbound,
+        _ if conn.local_port > conn.remote_port => Direction::Outbound,
+        _ if conn.local_port < conn.remote_port => Direction::Inbound,
+        _ => Direction::Unknown,
+    }
+}
+
+/// Set `direction` on every connection using the current listening sockets.
+pub fn tag_directions(processes: &mut [Process], listeners: &[Listener]) {
+    let listening: HashSet<(Protocol, u16)> = listeners
+        .iter()
+        .map(|l| (l.protocol.clone(), l.port))
+        .collect();
+    for proc in processes.iter_mut() {
+        for conn in proc.connections.iter_mut() {
+            conn.direction = infer(conn, &listening);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conn(protocol: Protocol, local_port: u16, remote: &str, remote_port: u16) -> Connection {
+        Connection {
+            local_addr: "192.168.1.20".to_string(),
+            local_port,
+            remote_addr: remote.to_string(),
+            protocol,
+            ..Connection::for_test(remote_port)
+        }
+    }
+
+    #[test]
+    fn test_outbound_from_ephemeral_port() {
+        let none = HashSet::new();
+        let https = conn(Protocol::Tcp, 61859, "17.57.146.59", 443);
+        assert_eq!(infer(&https, &none), Direction::Outbound);
+        let dns = conn(Protocol::Udp, 53012, "1.1.1.1", 53);
+        assert_eq!(infer(&dns, &none), Direction::Outbound);
+    }
+
+    #[test]
+    fn test_inbound_to_listening_port() {
+        let listening = HashSet::from([(Protocol::Tcp, 22)]);
+        let ssh = conn(Protocol::Tcp, 22, "203.0.113.9", 51000);
+        assert_eq!(infer(&ssh, &listening), Direction::Inbound);
+        // Listening on TCP says nothing about the same UDP port
+        let udp = conn(Protocol::Udp, 22, "203.0.113.9", 22);
+        assert_eq!(infer(&udp, &listening), Direction::Unknown);
+    }
+
+    #[test]
+    fn test_inbound_by_port_heuristic_without_listeners() {
+        let none = HashSet::new();
+        let dev_server = conn(Protocol::Tcp, 3000, "192.168.1.30", 50123);
+        assert_eq!(infer(&dev_server, &none), Direction::Inbound);
+        let both_low = conn(Protocol::Tcp, 8080, "10.0.0.1", 5432);
+        assert_eq!(infer(&both_low, &none), Direction::Outbound);
+    }
+
+    #[test]
+    fn test_unknown_without_peer() {
+        let none = HashSet::new();
+        assert_eq!(infer(&conn(Protocol::Udp, 5353, "*", 0), &none), Direction::Unknown);
+        assert_eq!(infer(&conn(Protocol::Icmp, 0, "8.8.8.8", 0), &none), Direction::Unknown);
+        let mut listen = conn(Protocol::Tcp, 8080, "*", 0);
+        listen.state = "LISTEN".to_string();
+        assert_eq!(infer(&listen, &none), Direction::Unknown);
+    }
+}
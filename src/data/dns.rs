@@ -1,19 +1,258 @@
-use std::net::IpAddr;
-use tokio::sync::mpsc;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{mpsc, Semaphore};
 
-use super::model::DnsCache;
+use super::model::Resolution;
+
+/// How long a resolved hostname is trusted.
+const POSITIVE_TTL: Duration = Duration::from_secs(600);
+/// How long a "no PTR record" answer is remembered before asking again.
+const NEGATIVE_TTL: Duration = Duration::from_secs(60);
+pub const DEFAULT_CACHE_SIZE: usize = 4096;
+
+struct CacheEntry {
+    hostname: Option<String>,
+    resolved_at: Instant,
+    /// Access counter value when last read or written, for LRU eviction
+    last_used: u64,
+}
+
+/// Reverse DNS results keyed by IP. Positive and negative answers expire
+/// after their own TTLs, and past `max_entries` the least recently used
+/// entry is evicted. IPs with a lookup in flight are tracked separately.
+pub struct DnsCache {
+    entries: HashMap<String, CacheEntry>,
+    pending: HashSet<String>,
+    max_entries: usize,
+    clock: u64,
+}
+
+impl DnsCache {
+    pub fn new(max_entries: usize) -> Self {
+        DnsCache {
+            entries: HashMap::new(),
+            pending: HashSet::new(),
+            max_entries: max_entries.max(1),
+            clock: 0,
+        }
+    }
+
+    /// What is known about an IP: None if it was never asked for (or its
+    /// answer expired, or the lookup errored and will be retried).
+    pub fn get(&mut self, ip: &str) -> Option<Resolution> {
+        match self.get_at(ip, Instant::now()) {
+            Some(Some(hostname)) => Some(Resolution::Resolved(hostname)),
+            Some(None) => Some(Resolution::Failed),
+            None if self.pending.contains(ip) => Some(Resolution::Pending),
+            None => None,
+        }
+    }
+
+    /// Forget in-flight lookups, e.g. after restarting the resolver.
+    pub fn clear_pending(&mut self) {
+        self.pending.clear();
+    }
+
+    pub fn insert(&mut self, ip: String, hostname: Option<String>) {
+        self.insert_at(ip, hostname, Instant::now());
+    }
+
+    /// Load entries saved by `save`, skipping ones past their TTL. A missing
+    /// or unreadable file simply leaves the cache as it is.
+    pub fn load(&mut self, path: &Path) {
+        if let Ok(text) = std::fs::read_to_string(path) {
+            self.load_text(&text, Instant::now(), unix_now());
+        }
+    }
+
+    /// Write the cache to `path`, via a temporary file so a crash mid-write
+    /// never leaves a truncated cache behind.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, self.to_text(Instant::now(), unix_now()))?;
+        std::fs::rename(tmp, path)
+    }
+
+    /// One `ip<TAB>hostname<TAB>resolved-at-unix-seconds` line per entry;
+    /// negative answers have an empty hostname.
+    fn to_text(&self, now: Instant, now_unix: u64) -> String {
+        let mut text = String::new();
+        for (ip, entry) in &self.entries {
+            let age = now.duration_since(entry.resolved_at).as_secs();
+            let host = entry.hostname.as_deref().unwrap_or("");
+            text.push_str(&format!("{}\t{}\t{}\n", ip, host, now_unix.saturating_sub(age)));
+        }
+        text
+    }
+
+    fn load_text(&mut self, text: &str, now: Instant, now_unix: u64) {
+        for line in text.lines() {
+            let mut fields = line.split('\t');
+            let (Some(ip), Some(host), Some(at), None) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let Ok(at) = at.parse::<u64>() else {
+                continue;
+            };
+            if ip.is_empty() || at > now_unix {
+                continue;
+            }
+            let hostname = (!host.is_empty()).then(|| host.to_string());
+            let age = Duration::from_secs(now_unix - at);
+            if age >= ttl_for(&hostname) {
+                continue;
+            }
+            if let Some(resolved_at) = now.checked_sub(age) {
+                self.insert_at(ip.to_string(), hostname, resolved_at);
+            }
+        }
+    }
+
+    fn get_at(&mut self, ip: &str, now: Instant) -> Option<Option<String>> {
+        let entry = self.entries.get(ip)?;
+        if now.duration_since(entry.resolved_at) >= ttl_for(&entry.hostname) {
+            self.entries.remove(ip);
+            return None;
+        }
+        self.clock += 1;
+        let entry = self.entries.get_mut(ip)?;
+        entry.last_used = self.clock;
+        Some(entry.hostname.clone())
+    }
+
+    fn insert_at(&mut self, ip: String, hostname: Option<String>, now: Instant) {
+        self.clock += 1;
+        if !self.entries.contains_key(&ip) && self.entries.len() >= self.max_entries {
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.last_used)
+                .map(|(k, _)| k.clone())
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(
+            ip,
+            CacheEntry {
+                hostname,
+                resolved_at: now,
+                last_used: self.clock,
+            },
+        );
+    }
+}
+
+fn ttl_for(hostname: &Option<String>) -> Duration {
+    if hostname.is_some() {
+        POSITIVE_TTL
+    } else {
+        NEGATIVE_TTL
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Where the DNS cache is kept between runs: `$XDG_CACHE_HOME/nm` or
+/// `~/.cache/nm`.
+pub fn cache_file_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+    };
+    Some(base.join("nm").join("dns_cache.tsv"))
+}
+
+/// Outcome of a reverse lookup.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Lookup {
+    Found(String),
+    /// The resolver answered: no PTR record
+    NotFound,
+    /// Temporary resolver failure or timeout; worth asking again later
+    Failed,
+}
+
+/// A blocking reverse lookup of one IP string.
+pub type ResolveFn = Arc<dyn Fn(&str) -> Lookup + Send + Sync>;
+
+/// Limits applied to the resolver task.
+#[derive(Debug, Clone)]
+pub struct ResolverLimits {
+    /// Lookups allowed in flight at once
+    pub concurrency: usize,
+    /// Time allowed for a single attempt before it counts as failed
+    pub timeout: Duration,
+    /// Extra attempts after a transient failure
+    pub retries: u32,
+    /// Delay before the first retry, doubled for each one after
+    pub backoff: Duration,
+}
+
+impl Default for ResolverLimits {
+    fn default() -> Self {
+        ResolverLimits {
+            concurrency: 8,
+            timeout: Duration::from_millis(1500),
+            retries: 2,
+            backoff: Duration::from_millis(250),
+        }
+    }
+}
 
 /// Performs reverse DNS lookups asynchronously and returns results via channel.
-pub fn spawn_dns_resolver() -> (mpsc::Sender<String>, mpsc::Receiver<(String, Option<String>)>) {
+/// With `system_cache`, the macOS directory service cache is consulted
+/// before asking the network for a PTR record.
+pub fn spawn_dns_resolver(
+    system_cache: bool,
+) -> (mpsc::Sender<String>, mpsc::Receiver<(String, Lookup)>) {
+    let resolve: ResolveFn = if system_cache {
+        Arc::new(|ip: &str| match cached_forward_name(ip) {
+            Some(name) => Lookup::Found(name),
+            None => resolve_hostname(ip),
+        })
+    } else {
+        Arc::new(resolve_hostname)
+    };
+    spawn_resolver_with(resolve, ResolverLimits::default())
+}
+
+/// Like `spawn_dns_resolver` but with the lookup function and limits
+/// supplied by the caller. Lookups run on the blocking thread pool so a slow
+/// getnameinfo never ties up the async workers that drive the UI.
+pub fn spawn_resolver_with(
+    resolve: ResolveFn,
+    limits: ResolverLimits,
+) -> (mpsc::Sender<String>, mpsc::Receiver<(String, Lookup)>) {
     let (req_tx, mut req_rx) = mpsc::channel::<String>(256);
-    let (res_tx, res_rx) = mpsc::channel::<(String, Option<String>)>(256);
+    let (res_tx, res_rx) = mpsc::channel::<(String, Lookup)>(256);
+    let permits = Arc::new(Semaphore::new(limits.concurrency.max(1)));
 
     tokio::spawn(async move {
         while let Some(ip_str) = req_rx.recv().await {
             let res_tx = res_tx.clone();
+            let resolve = Arc::clone(&resolve);
+            let permits = Arc::clone(&permits);
+            let limits = limits.clone();
             tokio::spawn(async move {
-                let hostname = resolve_hostname(&ip_str);
-                let _ = res_tx.send((ip_str, hostname)).await;
+                let Ok(_permit) = permits.acquire_owned().await else {
+                    return;
+                };
+                let result = lookup_with_retry(&resolve, &ip_str, &limits).await;
+                let _ = res_tx.send((ip_str, result)).await;
             });
         }
     });
@@ -21,43 +260,388 @@ pub fn spawn_dns_resolver() -> (mpsc::Sender<String>, mpsc::Receiver<(String, Op
     (req_tx, res_rx)
 }
 
-fn resolve_hostname(ip_str: &str) -> Option<String> {
-    let ip: IpAddr = ip_str.parse().ok()?;
-    // dns_lookup::lookup_addr does reverse DNS
-    dns_lookup::lookup_addr(&ip).ok()
+/// Run one lookup with a per-attempt timeout, retrying failures with
+/// exponential backoff. A timed-out attempt keeps its blocking thread until
+/// getnameinfo returns, but its answer is discarded.
+async fn lookup_with_retry(resolve: &ResolveFn, ip: &str, limits: &ResolverLimits) -> Lookup {
+    let mut backoff = limits.backoff;
+    for attempt in 0..=limits.retries {
+        if attempt > 0 {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+        let resolve = Arc::clone(resolve);
+        let ip = ip.to_string();
+        let task = tokio::task::spawn_blocking(move || resolve(&ip));
+        match tokio::time::timeout(limits.timeout, task).await {
+            Ok(Ok(Lookup::Failed)) | Err(_) => continue,
+            Ok(Ok(result)) => return result,
+            Ok(Err(_)) => return Lookup::Failed,
+        }
+    }
+    Lookup::Failed
 }
 
-/// Update connection hostnames from the DNS cache and request lookups for unknown IPs.
-pub fn update_dns(
-    processes: &mut [super::model::Process],
-    cache: &DnsCache,
-    pending: &mut std::collections::HashSet<String>,
-    req_tx: &mpsc::Sender<String>,
-) {
+fn resolve_hostname(ip_str: &str) -> Lookup {
+    let Some(ip) = super::addr::parse_ip(ip_str) else {
+        return Lookup::NotFound;
+    };
+    let sock: SocketAddr = (ip, 0).into();
+    match dns_lookup::getnameinfo(&sock, libc::NI_NAMEREQD) {
+        Ok((host, _)) => Lookup::Found(host),
+        Err(e) if matches!(e.kind(), dns_lookup::LookupErrorKind::Again) => Lookup::Failed,
+        Err(_) => Lookup::NotFound,
+    }
+}
+
+/// Ask the system DNS cache which name recently resolved forward to this
+/// IP. That is usually the name an app actually connected to, where the
+/// PTR record tends to be a provider's generic load balancer name.
+fn cached_forward_name(ip: &str) -> Option<String> {
+    let output = std::process::Command::new("dscacheutil")
+        .args(["-q", "host", "-a", "ip_address", ip])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    parse_dscacheutil(&String::from_utf8_lossy(&output.stdout), ip)
+}
+
+/// Parse `dscacheutil -q host` output: blank-line separated records of
+/// `key: value` lines. Returns the `name` of the first record listing the
+/// IP among its addresses.
+/// ```text
+/// name: api.github.com
+/// ip_address: 140.82.112.6
+///
+/// name: github.com
+/// alias: www.github.com
+/// ip_address: 140.82.112.3
+/// ```
+fn parse_dscacheutil(output: &str, ip: &str) -> Option<String> {
+    let mut name: Option<&str> = None;
+    let mut matched = false;
+    for line in output.lines().chain(std::iter::once("")) {
+        let line = line.trim();
+        if line.is_empty() {
+            if matched {
+                if let Some(name) = name {
+                    return Some(name.to_string());
+                }
+            }
+            name = None;
+            matched = false;
+            continue;
+        }
+        match line.split_once(':') {
+            Some(("name", value)) => name = Some(value.trim()),
+            Some(("ip_address" | "ipv6_address", value)) if value.trim() == ip => matched = true,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether an address is worth a PTR lookup. Wildcards never are, and
+/// loopback, private, link-local, and multicast addresses rarely have
+/// reverse zones, so they are skipped unless `resolve_local` is set.
+fn should_resolve(ip: &str, resolve_local: bool) -> bool {
+    match super::addr::classify_addr(ip) {
+        Some(class) => resolve_local || !class.is_lan(),
+        None => false,
+    }
+}
+
+/// Fill connection hostnames from the DNS cache and flag those still being
+/// resolved. No lookups are requested.
+pub fn apply_hostnames(processes: &mut [super::model::Process], cache: &mut DnsCache) {
     for proc in processes.iter_mut() {
         for conn in proc.connections.iter_mut() {
-            let ip = &conn.remote_addr;
-            if ip.is_empty() {
-                continue;
-            }
-            if let Some(hostname) = cache.get(ip) {
-                conn.hostname.clone_from(hostname);
-            } else if !pending.contains(ip) {
-                pending.insert(ip.clone());
-                let _ = req_tx.try_send(ip.clone());
+            let resolution = cache.get(&conn.remote_addr);
+            conn.resolving = resolution == Some(Resolution::Pending);
+            match resolution {
+                Some(Resolution::Resolved(hostname)) => conn.hostname = Some(hostname),
+                Some(Resolution::Failed) => conn.hostname = None,
+                Some(Resolution::Pending) | None => {}
             }
         }
     }
 }
 
-/// Drain resolved DNS results into the cache.
+/// Queue lookups for up to `limit` of the given IPs that are neither cached
+/// nor already pending, in order. Returns how many were queued.
+pub fn request_lookups<'a>(
+    ips: impl IntoIterator<Item = &'a str>,
+    limit: usize,
+    cache: &mut DnsCache,
+    req_tx: &mpsc::Sender<String>,
+    resolve_local: bool,
+) -> usize {
+    let mut queued = 0;
+    for ip in ips {
+        if queued >= limit {
+            break;
+        }
+        if !should_resolve(ip, resolve_local) || cache.get(ip).is_some() {
+            continue;
+        }
+        // A full queue leaves the IP unmarked so a later call retries it
+        if req_tx.try_send(ip.to_string()).is_err() {
+            break;
+        }
+        cache.pending.insert(ip.to_string());
+        queued += 1;
+    }
+    queued
+}
+
+/// Drain resolved DNS results into the cache, returning how many arrived.
+/// Failed lookups are only unmarked as pending so a later tick asks again.
 pub fn drain_dns_results(
-    rx: &mut mpsc::Receiver<(String, Option<String>)>,
+    rx: &mut mpsc::Receiver<(String, Lookup)>,
     cache: &mut DnsCache,
-    pending: &mut std::collections::HashSet<String>,
-) {
-    while let Ok((ip, hostname)) = rx.try_recv() {
-        pending.remove(&ip);
-        cache.insert(ip, hostname);
+) -> usize {
+    let mut received = 0;
+    while let Ok((ip, result)) = rx.try_recv() {
+        received += 1;
+        cache.pending.remove(&ip);
+        match result {
+            Lookup::Found(host) => {
+                cache.insert(ip, Some(host));
+            }
+            Lookup::NotFound => {
+                cache.insert(ip, None);
+            }
+            Lookup::Failed => {}
+        }
+    }
+    received
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn fast_limits() -> ResolverLimits {
+        ResolverLimits {
+            concurrency: 8,
+            timeout: Duration::from_millis(100),
+            retries: 2,
+            backoff: Duration::from_millis(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolver_uses_injected_lookup() {
+        let resolve: ResolveFn = Arc::new(|ip: &str| {
+            if ip == "1.2.3.4" {
+                Lookup::Found("one.example".to_string())
+            } else {
+                Lookup::NotFound
+            }
+        });
+        let (tx, mut rx) = spawn_resolver_with(resolve, fast_limits());
+
+        tx.send("1.2.3.4".to_string()).await.unwrap();
+        assert_eq!(rx.recv().await, Some(("1.2.3.4".to_string(), Lookup::Found("one.example".to_string()))));
+
+        tx.send("5.6.7.8".to_string()).await.unwrap();
+        assert_eq!(rx.recv().await, Some(("5.6.7.8".to_string(), Lookup::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_transient_failure_is_retried() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&attempts);
+        let resolve: ResolveFn = Arc::new(move |_: &str| {
+            if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                Lookup::Failed
+            } else {
+                Lookup::Found("late.example".to_string())
+            }
+        });
+        let (tx, mut rx) = spawn_resolver_with(resolve, fast_limits());
+
+        tx.send("1.2.3.4".to_string()).await.unwrap();
+        let (_, result) = rx.recv().await.unwrap();
+        assert_eq!(result, Lookup::Found("late.example".to_string()));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_fails_and_clears_pending() {
+        let resolve: ResolveFn = Arc::new(|_: &str| {
+            std::thread::sleep(Duration::from_millis(300));
+            Lookup::Found("slow.example".to_string())
+        });
+        let limits = ResolverLimits {
+            retries: 0,
+            ..fast_limits()
+        };
+        let (tx, mut rx) = spawn_resolver_with(resolve, limits);
+
+        tx.send("1.2.3.4".to_string()).await.unwrap();
+        let result = rx.recv().await.unwrap();
+        assert_eq!(result.1, Lookup::Failed);
+
+        // Feed the failure back through drain: no cache entry, no longer pending
+        let (res_tx, mut res_rx) = mpsc::channel(1);
+        res_tx.send(result).await.unwrap();
+        let mut cache = DnsCache::new(DEFAULT_CACHE_SIZE);
+        cache.pending.insert("1.2.3.4".to_string());
+        assert_eq!(cache.get("1.2.3.4"), Some(Resolution::Pending));
+        drain_dns_results(&mut res_rx, &mut cache);
+        assert_eq!(cache.entries.len(), 0);
+        assert_eq!(cache.get("1.2.3.4"), None);
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_is_bounded() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_seen = Arc::new(AtomicUsize::new(0));
+        let (current, max) = (Arc::clone(&in_flight), Arc::clone(&max_seen));
+        let resolve: ResolveFn = Arc::new(move |_: &str| {
+            let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+            max.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            current.fetch_sub(1, Ordering::SeqCst);
+            Lookup::NotFound
+        });
+        let limits = ResolverLimits {
+            concurrency: 3,
+            ..fast_limits()
+        };
+        let (tx, mut rx) = spawn_resolver_with(resolve, limits);
+
+        for i in 0..12 {
+            tx.send(format!("10.0.0.{}", i)).await.unwrap();
+        }
+        for _ in 0..12 {
+            rx.recv().await.unwrap();
+        }
+        assert!(max_seen.load(Ordering::SeqCst) <= 3);
+    }
+
+    #[test]
+    fn test_parse_dscacheutil() {
+        let output = "\
+name: api.github.com
+ip_address: 140.82.112.6
+
+name: github.com
+alias: www.github.com
+ip_address: 140.82.112.3
+ip_address: 140.82.112.4
+
+name: dns.google
+ipv6_address: 2001:4860:4860::8888
+";
+        assert_eq!(parse_dscacheutil(output, "140.82.112.6"), Some("api.github.com".to_string()));
+        assert_eq!(parse_dscacheutil(output, "140.82.112.4"), Some("github.com".to_string()));
+        assert_eq!(parse_dscacheutil(output, "2001:4860:4860::8888"), Some("dns.google".to_string()));
+        assert_eq!(parse_dscacheutil(output, "1.1.1.1"), None);
+        assert_eq!(parse_dscacheutil("", "1.1.1.1"), None);
+    }
+
+    #[test]
+    fn test_should_resolve_skips_local_addresses() {
+        assert!(should_resolve("140.82.112.3", false));
+        assert!(should_resolve("2606:4700::1111", false));
+        for ip in ["192.168.1.1", "10.0.0.8", "127.0.0.1", "fe80::1%en0", "fd12::1", "224.0.0.251", "ff02::fb"] {
+            assert!(!should_resolve(ip, false), "{} should be skipped", ip);
+            assert!(should_resolve(ip, true), "{} should resolve with --resolve-local", ip);
+        }
+        assert!(!should_resolve("*", true));
+        assert!(!should_resolve("", true));
+    }
+
+    #[tokio::test]
+    async fn test_request_lookups_respects_limit_cache_and_pending() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let mut cache = DnsCache::new(DEFAULT_CACHE_SIZE);
+        cache.insert("1.1.1.1".to_string(), Some("one.one.one.one".to_string()));
+        cache.pending.insert("8.8.8.8".to_string());
+
+        let ips = ["1.1.1.1", "8.8.8.8", "192.168.1.1", "9.9.9.9", "140.82.112.3", "17.0.0.1"];
+        let queued = request_lookups(ips, 2, &mut cache, &tx, false);
+        assert_eq!(queued, 2);
+        assert_eq!(rx.try_recv().unwrap(), "9.9.9.9");
+        assert_eq!(rx.try_recv().unwrap(), "140.82.112.3");
+        assert!(rx.try_recv().is_err());
+        assert_eq!(cache.get("9.9.9.9"), Some(Resolution::Pending));
+        assert_eq!(cache.get("17.0.0.1"), None);
+    }
+
+    #[tokio::test]
+    async fn test_drain_resolves_pending_entries() {
+        let (tx, mut rx) = mpsc::channel(4);
+        let mut cache = DnsCache::new(DEFAULT_CACHE_SIZE);
+        cache.pending.extend(["1.1.1.1".to_string(), "9.9.9.9".to_string()]);
+        tx.send(("1.1.1.1".to_string(), Lookup::Found("one.one.one.one".to_string()))).await.unwrap();
+        tx.send(("9.9.9.9".to_string(), Lookup::NotFound)).await.unwrap();
+
+        assert_eq!(drain_dns_results(&mut rx, &mut cache), 2);
+        assert_eq!(cache.get("1.1.1.1"), Some(Resolution::Resolved("one.one.one.one".to_string())));
+        assert_eq!(cache.get("9.9.9.9"), Some(Resolution::Failed));
+        assert!(cache.pending.is_empty());
+    }
+
+    #[test]
+    fn test_cache_entries_expire_by_ttl() {
+        let start = Instant::now();
+        let mut cache = DnsCache::new(10);
+        cache.insert_at("1.1.1.1".to_string(), Some("one.one.one.one".to_string()), start);
+        cache.insert_at("10.9.9.9".to_string(), None, start);
+
+        let later = start + Duration::from_secs(61);
+        assert_eq!(cache.get_at("1.1.1.1", later), Some(Some("one.one.one.one".to_string())));
+        // Negative answers expire first so failed lookups get retried
+        assert_eq!(cache.get_at("10.9.9.9", later), None);
+
+        let much_later = start + POSITIVE_TTL;
+        assert_eq!(cache.get_at("1.1.1.1", much_later), None);
+        assert_eq!(cache.entries.len(), 0);
+    }
+
+    #[test]
+    fn test_cache_file_round_trip_skips_stale_and_corrupt() {
+        let now = Instant::now();
+        let now_unix = 1_700_000_000;
+        let mut cache = DnsCache::new(10);
+        cache.insert_at("1.1.1.1".to_string(), Some("one.one.one.one".to_string()), now);
+        cache.insert_at("9.9.9.9".to_string(), None, now);
+        let text = cache.to_text(now, now_unix);
+
+        let mut loaded = DnsCache::new(10);
+        loaded.load_text(&text, now, now_unix);
+        assert_eq!(loaded.get_at("1.1.1.1", now), Some(Some("one.one.one.one".to_string())));
+        assert_eq!(loaded.get_at("9.9.9.9", now), Some(None));
+
+        // Two minutes later the negative entry is stale; garbage is ignored
+        let text = format!("{}garbage\n\t\t\n8.8.8.8\tdns.google\tnot-a-time\n", text);
+        let mut later = DnsCache::new(10);
+        later.load_text(&text, now, now_unix + 120);
+        assert_eq!(later.entries.len(), 1);
+        assert!(later.get_at("1.1.1.1", now).is_some());
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let now = Instant::now();
+        let mut cache = DnsCache::new(2);
+        cache.insert_at("a".to_string(), Some("a.example".to_string()), now);
+        cache.insert_at("b".to_string(), Some("b.example".to_string()), now);
+        // Touch "a" so "b" becomes the eviction candidate
+        assert!(cache.get_at("a", now).is_some());
+        cache.insert_at("c".to_string(), Some("c.example".to_string()), now);
+
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.get_at("a", now).is_some());
+        assert!(cache.get_at("b", now).is_none());
+        assert!(cache.get_at("c", now).is_some());
+
+        // Refreshing an existing key never evicts
+        cache.insert_at("c".to_string(), None, now);
+        assert_eq!(cache.entries.len(), 2);
     }
 }
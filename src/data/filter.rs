@@ -0,0 +1,743 @@
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::data::listeners::Listener;
+use crate::data::model::{Connection, Process, RemoteHost};
+
+/// One compiled filter pattern.
+#[derive(Debug, Clone)]
+pub enum FilterTerm {
+    /// Case-insensitive substring, stored lowercased
+    Text(String),
+    /// Case-insensitive whole value, stored lowercased
+    Whole(String),
+    /// Case-insensitive regular expression
+    Pattern(Regex),
+}
+
+impl FilterTerm {
+    /// Whether a field matches this term.
+    pub fn matches(&self, field: &str) -> bool {
+        match self {
+            FilterTerm::Text(text) => field.to_lowercase().contains(text),
+            FilterTerm::Whole(text) => field.to_lowercase() == *text,
+            FilterTerm::Pattern(regex) => regex.is_match(field),
+        }
+    }
+
+    /// Whether a whole value matches: equal ignoring case, or for a regex,
+    /// matching anywhere as usual.
+    pub fn equals(&self, value: &str) -> bool {
+        match self {
+            FilterTerm::Text(text) | FilterTerm::Whole(text) => value.to_lowercase() == *text,
+            FilterTerm::Pattern(regex) => regex.is_match(value),
+        }
+    }
+
+    /// Byte ranges of `text` this term matches, for highlighting.
+    pub fn find(&self, text: &str) -> Vec<Range<usize>> {
+        match self {
+            FilterTerm::Text(needle) => find_ignoring_case(needle, text),
+            FilterTerm::Whole(value) => (text.to_lowercase() == *value).then_some(0..text.len()).into_iter().collect(),
+            FilterTerm::Pattern(regex) => regex
+                .find_iter(text)
+                .filter(|m| !m.is_empty())
+                .map(|m| m.range())
+                .collect(),
+        }
+    }
+
+    /// Whether this is the plain keyword `word`, for filters like "vpn"
+    /// that select on a flag rather than a field.
+    pub fn is_keyword(&self, word: &str) -> bool {
+        matches!(self, FilterTerm::Text(text) if text == word)
+    }
+}
+
+/// A field a term can be limited to with `field:value`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// Local or remote port, compared whole
+    Port,
+    /// Remote address or hostname
+    Host,
+    /// Protocol name, compared whole
+    Proto,
+    /// Process ID, compared whole
+    Pid,
+    /// Connection state, e.g. "established"
+    State,
+}
+
+impl Field {
+    const ALL: [Field; 5] = [Field::Port, Field::Host, Field::Proto, Field::Pid, Field::State];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Port => "port",
+            Field::Host => "host",
+            Field::Proto => "proto",
+            Field::Pid => "pid",
+            Field::State => "state",
+        }
+    }
+
+    /// Check a plain value where the field takes numbers.
+    fn check(self, value: &str) -> Result<(), String> {
+        let number = match self {
+            Field::Port => value.parse::<u16>().is_ok(),
+            Field::Pid => value.parse::<u32>().is_ok(),
+            Field::Host | Field::Proto | Field::State => true,
+        };
+        if number {
+            Ok(())
+        } else {
+            Err(format!("{}: expected a number, got {:?}", self.name(), value))
+        }
+    }
+}
+
+/// A term that must match (or, negated, must not match) a row: any of its
+/// text for a bare term, or one field.
+#[derive(Debug, Clone)]
+pub struct Clause {
+    pub negated: bool,
+    pub field: Option<Field>,
+    pub term: FilterTerm,
+}
+
+impl Clause {
+    fn eval(&self, row: &impl Filterable) -> bool {
+        let matched = match self.field {
+            Some(field) => row.matches_field(field, &self.term),
+            None => row.matches_text(&self.term),
+        };
+        matched != self.negated
+    }
+}
+
+/// A row the filter can test.
+pub trait Filterable {
+    /// Whether any of the row's searchable text matches a bare term.
+    fn matches_text(&self, term: &FilterTerm) -> bool;
+
+    /// Whether the row's value for `field` matches; rows without the field
+    /// never do.
+    fn matches_field(&self, field: Field, term: &FilterTerm) -> bool;
+}
+
+/// A process matches by name, path, PID, or owner, and by the fields of
+/// any of its connections.
+impl Filterable for Process {
+    fn matches_text(&self, term: &FilterTerm) -> bool {
+        term.matches(&self.name)
+            || self.display_name.as_deref().is_some_and(|n| term.matches(n))
+            || term.matches(self.path.as_deref().unwrap_or(""))
+            || term.matches(&self.pid.to_string())
+            || self.user.as_deref().is_some_and(|u| term.matches(u))
+    }
+
+    fn matches_field(&self, field: Field, term: &FilterTerm) -> bool {
+        match field {
+            Field::Pid => term.equals(&self.pid.to_string()),
+            _ => self.connections.iter().any(|c| connection_field(c, field, term)),
+        }
+    }
+}
+
+/// A connection row with its process and the remote label as shown.
+pub struct ConnectionRow<'a> {
+    pub process: &'a Process,
+    pub conn: &'a Connection,
+    pub services: bool,
+    pub hostnames: bool,
+}
+
+impl Filterable for ConnectionRow<'_> {
+    fn matches_text(&self, term: &FilterTerm) -> bool {
+        let conn = self.conn;
+        term.matches(&self.process.name)
+            || term.matches(&conn.remote_label(self.services, self.hostnames))
+            || term.matches(&conn.local_label())
+            || term.matches(&conn.protocol.to_string())
+            || term.matches(&conn.interface)
+            || (conn.via_vpn && term.is_keyword("vpn"))
+            || conn.asn.as_deref().is_some_and(|a| term.matches(a))
+    }
+
+    fn matches_field(&self, field: Field, term: &FilterTerm) -> bool {
+        match field {
+            Field::Pid => term.equals(&self.process.pid.to_string()),
+            _ => connection_field(self.conn, field, term),
+        }
+    }
+}
+
+fn connection_field(conn: &Connection, field: Field, term: &FilterTerm) -> bool {
+    match field {
+        Field::Port => term.equals(&conn.local_port.to_string()) || term.equals(&conn.remote_port.to_string()),
+        Field::Host => term.matches(&conn.remote_addr) || conn.hostname.as_deref().is_some_and(|h| term.matches(h)),
+        Field::Proto => term.equals(&conn.protocol.to_string()),
+        Field::State => term.matches(&conn.state),
+        Field::Pid => false,
+    }
+}
+
+impl Filterable for RemoteHost {
+    fn matches_text(&self, term: &FilterTerm) -> bool {
+        term.matches(&self.host) || self.processes.iter().any(|p| term.matches(p))
+    }
+
+    fn matches_field(&self, field: Field, term: &FilterTerm) -> bool {
+        field == Field::Host && term.matches(&self.host)
+    }
+}
+
+impl Filterable for Listener {
+    fn matches_text(&self, term: &FilterTerm) -> bool {
+        term.matches(&self.process)
+            || term.matches(&self.addr)
+            || term.matches(&self.port.to_string())
+            || term.matches(&self.pid.to_string())
+            || term.matches(&self.protocol.to_string())
+    }
+
+    fn matches_field(&self, field: Field, term: &FilterTerm) -> bool {
+        match field {
+            Field::Port => term.equals(&self.port.to_string()),
+            Field::Host => term.matches(&self.addr),
+            Field::Proto => term.equals(&self.protocol.to_string()),
+            Field::Pid => term.equals(&self.pid.to_string()),
+            Field::State => false,
+        }
+    }
+}
+
+/// An interface row, which matches the `vpn` keyword when it is a tunnel.
+pub struct InterfaceRow<'a> {
+    pub name: &'a str,
+    pub vpn: bool,
+}
+
+impl Filterable for InterfaceRow<'_> {
+    fn matches_text(&self, term: &FilterTerm) -> bool {
+        term.matches(self.name) || (self.vpn && term.is_keyword("vpn"))
+    }
+
+    fn matches_field(&self, _field: Field, _term: &FilterTerm) -> bool {
+        false
+    }
+}
+
+/// A parsed filter: a row is shown if it satisfies every clause of any one
+/// line, so `--filter a --filter b` is either and `a !b` is both.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    lines: Vec<Vec<Clause>>,
+}
+
+impl Filter {
+    /// Parse filter lines, skipping those with no terms.
+    pub fn parse(syntax: &FilterSyntax, lines: &[String]) -> Result<Self, String> {
+        let lines = lines
+            .iter()
+            .map(|line| syntax.parse(line))
+            .filter(|clauses| !clauses.as_ref().is_ok_and(|c| c.is_empty()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Filter { lines })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Whether a row passes.
+    pub fn eval(&self, row: &impl Filterable) -> bool {
+        self.is_empty() || self.lines.iter().any(|clauses| clauses.iter().all(|c| c.eval(row)))
+    }
+
+    /// Byte ranges of a cell's text to highlight, in order and merged: what
+    /// the bare terms match, plus terms for `field` when the cell shows it.
+    /// Negated terms never highlight.
+    pub fn highlights(&self, text: &str, field: Option<Field>) -> Vec<Range<usize>> {
+        let ranges = self
+            .lines
+            .iter()
+            .flatten()
+            .filter(|c| !c.negated && (c.field.is_none() || c.field == field))
+            .flat_map(|c| match (c.field, &c.term) {
+                // Fields compared whole light up the whole cell
+                (Some(Field::Port | Field::Proto | Field::Pid), FilterTerm::Text(_)) => {
+                    c.term.equals(text).then_some(0..text.len()).into_iter().collect()
+                }
+                _ => c.term.find(text),
+            })
+            .collect();
+        merge_ranges(ranges)
+    }
+}
+
+/// Sort ranges and join those that overlap or touch.
+pub fn merge_ranges(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// How typed filter text becomes terms: input is split at spaces outside
+/// double quotes, a leading `!` negates a term, `field:` limits it to one
+/// field, and a value starting with `prefix` is a regex (or every value is
+/// with `always_regex`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterSyntax {
+    pub prefix: String,
+    pub always_regex: bool,
+}
+
+impl FilterSyntax {
+    /// Compile one line of filter input into clauses that must all hold. A
+    /// bad pattern gives a one-line error for the filter prompt.
+    pub fn parse(&self, input: &str) -> Result<Vec<Clause>, String> {
+        let mut clauses = Vec::new();
+        for (negated, token) in tokens(input) {
+            let (field, value) = split_field(&token);
+            let Some(term) = self.term(value)? else {
+                match field {
+                    Some(field) => return Err(format!("{}: missing value", field.name())),
+                    None => continue,
+                }
+            };
+            if let (Some(field), FilterTerm::Text(value) | FilterTerm::Whole(value)) = (field, &term) {
+                field.check(value)?;
+            }
+            clauses.push(Clause { negated, field, term });
+        }
+        Ok(clauses)
+    }
+
+    /// Compile one term; `None` when there is nothing to match. A leading
+    /// `=` compares the value whole, whatever the regex settings.
+    fn term(&self, input: &str) -> Result<Option<FilterTerm>, String> {
+        if let Some(value) = input.strip_prefix('=') {
+            return Ok((!value.is_empty()).then(|| FilterTerm::Whole(value.to_lowercase())));
+        }
+        let pattern = match self.strip_prefix(input) {
+            Some(pattern) => pattern,
+            None if self.always_regex => input,
+            None if input.is_empty() => return Ok(None),
+            None => return Ok(Some(FilterTerm::Text(input.to_lowercase()))),
+        };
+        if pattern.is_empty() {
+            return Ok(None);
+        }
+        RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map(|regex| Some(FilterTerm::Pattern(regex)))
+            .map_err(|e| error_message(&e))
+    }
+
+    /// Input for a term on `field` that matches `value` whole, for filters
+    /// set from a selected row rather than typed.
+    pub fn exact(field: Field, value: &str) -> String {
+        if value.contains(char::is_whitespace) {
+            format!("{}:=\"{}\"", field.name(), value)
+        } else {
+            format!("{}:={}", field.name(), value)
+        }
+    }
+
+    fn strip_prefix<'a>(&self, input: &'a str) -> Option<&'a str> {
+        if self.prefix.is_empty() {
+            return None;
+        }
+        input.strip_prefix(self.prefix.as_str())
+    }
+}
+
+impl Default for FilterSyntax {
+    fn default() -> Self {
+        FilterSyntax { prefix: "/".to_string(), always_regex: false }
+    }
+}
+
+/// Where a lowercased needle occurs in `text`, as byte ranges of the
+/// original text. Characters are lowercased one at a time so each match
+/// maps back even where lowercasing changes a character's length.
+fn find_ignoring_case(needle: &str, text: &str) -> Vec<Range<usize>> {
+    let needle = needle.chars().collect::<Vec<_>>();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    let lowered = text
+        .char_indices()
+        .flat_map(|(i, c)| c.to_lowercase().map(move |l| (l, i..i + c.len_utf8())))
+        .collect::<Vec<_>>();
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i + needle.len() <= lowered.len() {
+        let window = &lowered[i..i + needle.len()];
+        if window.iter().map(|(l, _)| *l).eq(needle.iter().copied()) {
+            ranges.push(window[0].1.start..window[needle.len() - 1].1.end);
+            i += needle.len();
+        } else {
+            i += 1;
+        }
+    }
+    ranges
+}
+
+/// A token's field, if it starts with a known field name and a colon, and
+/// the rest as its value.
+fn split_field(token: &str) -> (Option<Field>, &str) {
+    if let Some((name, value)) = token.split_once(':') {
+        if let Some(field) = Field::ALL.into_iter().find(|f| f.name().eq_ignore_ascii_case(name)) {
+            return (Some(field), value);
+        }
+    }
+    (None, token)
+}
+
+/// Split input at whitespace outside double quotes, dropping the quotes. A
+/// token's leading unquoted `!` negates it.
+fn tokens(input: &str) -> Vec<(bool, String)> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            return tokens;
+        }
+        let negated = chars.next_if_eq(&'!').is_some();
+        let mut token = String::new();
+        let mut quoted = false;
+        while let Some(c) = chars.next_if(|c| quoted || !c.is_whitespace()) {
+            if c == '"' {
+                quoted = !quoted;
+            } else {
+                token.push(c);
+            }
+        }
+        tokens.push((negated, token));
+    }
+}
+
+/// The last line of a regex error, which names the problem; the lines
+/// before it repeat the pattern.
+fn error_message(error: &regex::Error) -> String {
+    let message = error.to_string();
+    let last = message.lines().last().unwrap_or_default();
+    format!("invalid regex: {}", last.strip_prefix("error: ").unwrap_or(last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::model::{Direction, Protocol};
+
+    /// A row of plain text, with no structured fields.
+    struct Text<'a>(&'a [&'a str]);
+
+    impl Filterable for Text<'_> {
+        fn matches_text(&self, term: &FilterTerm) -> bool {
+            self.0.iter().any(|f| term.matches(f))
+        }
+
+        fn matches_field(&self, _field: Field, _term: &FilterTerm) -> bool {
+            false
+        }
+    }
+
+    fn conn(remote_port: u16, protocol: Protocol, state: &str, hostname: Option<&str>) -> Connection {
+        Connection {
+            remote_addr: "140.82.112.3".to_string(),
+            protocol,
+            state: state.to_string(),
+            interface: "en0".to_string(),
+            direction: Direction::Outbound,
+            hostname: hostname.map(str::to_string),
+            ..Connection::for_test(remote_port)
+        }
+    }
+
+    fn process(name: &str, pid: u32, connections: Vec<Connection>) -> Process {
+        Process {
+            connections,
+            ..Process::for_test(name, pid)
+        }
+    }
+
+    /// The text of each range.
+    fn matched<'a>(text: &'a str, ranges: &[Range<usize>]) -> Vec<&'a str> {
+        ranges.iter().map(|r| &text[r.clone()]).collect()
+    }
+
+    fn filter(input: &str) -> Filter {
+        Filter::parse(&FilterSyntax::default(), &[input.to_string()]).unwrap()
+    }
+
+    fn term(syntax: &FilterSyntax, input: &str) -> FilterTerm {
+        syntax.term(input).unwrap().unwrap()
+    }
+
+    /// Whether a row with these fields passes the filter.
+    fn shows(filter: &[&str], fields: &[&str]) -> bool {
+        let lines = filter.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+        let filter = Filter::parse(&FilterSyntax::default(), &lines).unwrap();
+        filter.eval(&Text(fields))
+    }
+
+    #[test]
+    fn test_plain_text_is_a_substring() {
+        let syntax = FilterSyntax::default();
+        let t = term(&syntax, "Apple");
+        assert!(matches!(t, FilterTerm::Text(_)));
+        assert!(t.matches("com.apple.WebKit"));
+        assert!(!t.matches("firefox"));
+        // Regex syntax is literal without the prefix
+        assert!(term(&syntax, "a.b").matches("x a.b y"));
+        assert!(!term(&syntax, "a.b").matches("axb"));
+        assert!(syntax.term("").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_prefix_compiles_a_case_insensitive_regex() {
+        let syntax = FilterSyntax::default();
+        let t = term(&syntax, r"/^com\.apple\.");
+        assert!(t.matches("COM.Apple.WebKit"));
+        assert!(!t.matches("org.com.apple."));
+        let ports = term(&syntax, "/(443|8443)$");
+        assert!(ports.matches("1.2.3.4:8443"));
+        assert!(!ports.matches("1.2.3.4:4430"));
+        assert!(syntax.term("/").unwrap().is_none());
+
+        let error = syntax.parse("ok /(443").unwrap_err();
+        assert_eq!(error, "invalid regex: unclosed group");
+    }
+
+    #[test]
+    fn test_always_regex_and_custom_prefix() {
+        let always = FilterSyntax { always_regex: true, ..FilterSyntax::default() };
+        assert!(term(&always, "^fire").matches("Firefox"));
+        assert!(term(&always, "/^fire").matches("Firefox"));
+        assert!(!term(&always, "vpn").is_keyword("vpn"));
+
+        let tilde = FilterSyntax { prefix: "~".to_string(), always_regex: false };
+        assert!(term(&tilde, "~fox$").matches("firefox"));
+        assert!(matches!(term(&tilde, "/fox$"), FilterTerm::Text(_)));
+
+        let none = FilterSyntax { prefix: String::new(), always_regex: false };
+        assert!(term(&none, "vpn").is_keyword("vpn"));
+    }
+
+    #[test]
+    fn test_exact_terms_compare_whole() {
+        assert_eq!(FilterSyntax::exact(Field::Host, "1.2.3.4"), "host:=1.2.3.4");
+        assert_eq!(FilterSyntax::exact(Field::Host, "my nas"), "host:=\"my nas\"");
+        for syntax in [
+            FilterSyntax::default(),
+            FilterSyntax { always_regex: true, ..FilterSyntax::default() },
+            FilterSyntax { prefix: String::new(), ..FilterSyntax::default() },
+        ] {
+            let clauses = syntax.parse(&FilterSyntax::exact(Field::Host, "1.2.3.4")).unwrap();
+            assert_eq!(clauses.len(), 1);
+            assert_eq!(clauses[0].field, Some(Field::Host));
+            let term = &clauses[0].term;
+            assert!(term.matches("1.2.3.4"));
+            assert!(!term.matches("11.2.3.45"));
+            assert!(!term.matches("1.2.3.40"));
+            assert_eq!(term.find("1.2.3.4"), vec![0..7]);
+            assert!(term.find("1.2.3.40").is_empty());
+
+            let clauses = syntax.parse(&FilterSyntax::exact(Field::Host, "My NAS")).unwrap();
+            assert!(clauses[0].term.matches("my nas"));
+        }
+        assert_eq!(FilterSyntax::default().parse("host:=").unwrap_err(), "host: missing value");
+        assert_eq!(
+            FilterSyntax::default().parse("port:=https").unwrap_err(),
+            "port: expected a number, got \"https\""
+        );
+    }
+
+    #[test]
+    fn test_terms_are_anded_and_negatable() {
+        assert!(shows(&["chrome"], &["Google Chrome Helper"]));
+        assert!(!shows(&["chrome !helper"], &["Google Chrome Helper"]));
+        assert!(shows(&["chrome !helper"], &["Google Chrome"]));
+        assert!(shows(&["chrome 443"], &["Google Chrome", "1.2.3.4:443"]));
+        assert!(!shows(&["chrome 443"], &["Google Chrome", "1.2.3.4:80"]));
+        assert!(!shows(&["!mdns"], &["mDNSResponder"]));
+        assert!(shows(&["!mdns"], &["Safari"]));
+        // Negated regex
+        assert!(!shows(&[r"!/^com\.apple\."], &["com.apple.WebKit"]));
+        // Lines are alternatives
+        assert!(shows(&["safari", "chrome !helper"], &["Safari"]));
+        assert!(!shows(&["safari", "chrome !helper"], &["Chrome Helper"]));
+    }
+
+    #[test]
+    fn test_quotes_and_empty_terms() {
+        assert!(shows(&["\"chrome helper\""], &["Google Chrome Helper"]));
+        assert!(!shows(&["\"chrome helper\""], &["Chrome", "Helper"]));
+        assert!(!shows(&["!\"chrome helper\""], &["Google Chrome Helper"]));
+        // An unclosed quote runs to the end
+        assert!(shows(&["\"chrome h"], &["Google Chrome Helper"]));
+
+        let syntax = FilterSyntax::default();
+        for input in ["", "   ", "!", "! !", "\"\"", "!\"\""] {
+            assert!(syntax.parse(input).unwrap().is_empty(), "{:?}", input);
+            assert!(shows(&[input], &["anything"]));
+        }
+        assert!(Filter::parse(&syntax, &["!".to_string()]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_fields() {
+        let syntax = FilterSyntax::default();
+        let clauses = syntax.parse("PORT:443 !host:/^git proto:udp pid:12 state:est chrome").unwrap();
+        let fields = clauses.iter().map(|c| (c.negated, c.field)).collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            [
+                (false, Some(Field::Port)),
+                (true, Some(Field::Host)),
+                (false, Some(Field::Proto)),
+                (false, Some(Field::Pid)),
+                (false, Some(Field::State)),
+                (false, None),
+            ]
+        );
+        assert!(matches!(clauses[1].term, FilterTerm::Pattern(_)));
+        // Other colons are part of a bare term
+        let clauses = syntax.parse("1.2.3.4:443 fe80::1 :443").unwrap();
+        assert!(clauses.iter().all(|c| c.field.is_none()));
+
+        assert_eq!(syntax.parse("port:https").unwrap_err(), "port: expected a number, got \"https\"");
+        assert_eq!(syntax.parse("pid:-1").unwrap_err(), "pid: expected a number, got \"-1\"");
+        assert_eq!(syntax.parse("port:70000").unwrap_err(), "port: expected a number, got \"70000\"");
+        assert_eq!(syntax.parse("chrome host:").unwrap_err(), "host: missing value");
+        assert_eq!(syntax.parse("!state:").unwrap_err(), "state: missing value");
+        assert_eq!(syntax.parse("port:/(44").unwrap_err(), "invalid regex: unclosed group");
+        // A regex value needn't be a number
+        assert!(syntax.parse("port:/^44").is_ok());
+    }
+
+    #[test]
+    fn test_fields_match_connections() {
+        let github = conn(443, Protocol::Tcp, "Established", Some("lb-140-82-112-3-iad.github.com"));
+        let dns = conn(53, Protocol::Udp, "", None);
+        let chrome = process("Google Chrome", 4430, vec![github.clone(), dns.clone()]);
+        let row = |conn| ConnectionRow { process: &chrome, conn, services: false, hostnames: true };
+
+        assert!(filter("port:443").eval(&row(&github)));
+        assert!(!filter("port:443").eval(&row(&dns)));
+        // Whole values only: no substring of the PID or other ports
+        assert!(!filter("port:44").eval(&row(&github)));
+        assert!(!filter("pid:443").eval(&row(&github)));
+        assert!(filter("pid:4430").eval(&row(&dns)));
+        assert!(filter("port:50000").eval(&row(&dns)));
+        // ...while a bare term still matches anywhere, the PID included
+        assert!(filter("443").eval(&chrome));
+        assert!(!filter("port:443").eval(&process("Google Chrome", 4430, vec![dns.clone()])));
+
+        assert!(filter("host:github").eval(&row(&github)));
+        assert!(filter("host:140.82").eval(&row(&github)));
+        assert!(!filter("host:github").eval(&row(&dns)));
+        assert!(filter("host:/\\.com$").eval(&row(&github)));
+
+        assert!(filter("proto:udp").eval(&row(&dns)));
+        assert!(!filter("proto:udp").eval(&row(&github)));
+        assert!(!filter("proto:u").eval(&row(&dns)));
+        // "tcp" no longer has to mean a process name containing it
+        let tcpdump = process("tcpdump", 1, vec![]);
+        assert!(filter("tcp").eval(&tcpdump));
+        assert!(!filter("proto:tcp").eval(&tcpdump));
+
+        assert!(filter("state:established").eval(&row(&github)));
+        assert!(filter("state:est").eval(&row(&github)));
+        assert!(!filter("state:est").eval(&row(&dns)));
+
+        assert!(filter("chrome port:53 proto:udp").eval(&row(&dns)));
+        assert!(!filter("chrome port:53 !proto:udp").eval(&row(&dns)));
+    }
+
+    #[test]
+    fn test_fields_match_processes_through_connections() {
+        let chrome = process(
+            "Google Chrome",
+            4430,
+            vec![conn(443, Protocol::Tcp, "Established", Some("github.com"))],
+        );
+        assert!(filter("port:443").eval(&chrome));
+        assert!(filter("host:github proto:tcp").eval(&chrome));
+        assert!(filter("pid:4430").eval(&chrome));
+        assert!(!filter("pid:443").eval(&chrome));
+        assert!(!filter("port:53").eval(&chrome));
+        assert!(filter("!port:53").eval(&chrome));
+        assert!(!filter("port:443").eval(&process("idle", 2, vec![])));
+    }
+
+    #[test]
+    fn test_fields_match_listeners_and_interfaces() {
+        let listener = Listener {
+            process: "sshd".to_string(),
+            pid: 88,
+            protocol: Protocol::Tcp,
+            addr: "*".to_string(),
+            port: 22,
+            ipv4: true,
+            ipv6: true,
+        };
+        assert!(filter("port:22 proto:tcp pid:88").eval(&listener));
+        assert!(!filter("port:2").eval(&listener));
+        assert!(!filter("state:listen").eval(&listener));
+
+        let tunnel = InterfaceRow { name: "utun3", vpn: true };
+        assert!(filter("vpn").eval(&tunnel));
+        assert!(filter("utun").eval(&tunnel));
+        assert!(!filter("port:443").eval(&tunnel));
+    }
+
+    #[test]
+    fn test_find_maps_back_to_the_original_text() {
+        let syntax = FilterSyntax::default();
+        assert_eq!(term(&syntax, "apple").find("com.Apple.apple"), [4..9, 10..15]);
+        assert_eq!(term(&syntax, "aa").find("aaaa"), [0..2, 2..4]);
+        // "İ" lowercases to two chars, which mustn't shift later offsets
+        let text = "İstanbul-İzmir";
+        let ranges = term(&syntax, "zmir").find(text);
+        assert_eq!(&text[ranges[0].clone()], "zmir");
+        assert_eq!(matched("Zürich ÜBER", &term(&syntax, "ü").find("Zürich ÜBER")), ["ü", "Ü"]);
+
+        assert_eq!(matched("1.2.3.4:443", &term(&syntax, "/[0-9]+$").find("1.2.3.4:443")), ["443"]);
+        // Empty regex matches are skipped
+        assert!(term(&syntax, "/x*").find("abc").is_empty());
+    }
+
+    #[test]
+    fn test_highlights() {
+        let f = filter("chrome !helper host:git pid:42 proto:tcp");
+        assert_eq!(matched("Google Chrome", &f.highlights("Google Chrome", None)), ["Chrome"]);
+        // Negated terms don't highlight
+        assert!(f.highlights("helper", None).is_empty());
+        // Field terms only light up their own column
+        assert!(f.highlights("github.com", None).is_empty());
+        let text = "github.com:443";
+        assert_eq!(matched(text, &f.highlights(text, Some(Field::Host))), ["git"]);
+        assert_eq!(matched("42", &f.highlights("42", Some(Field::Pid))), ["42"]);
+        assert!(f.highlights("420", Some(Field::Pid)).is_empty());
+        assert_eq!(matched("TCP", &f.highlights("TCP", Some(Field::Proto))), ["TCP"]);
+
+        // Overlapping matches from several terms merge
+        let f = filter("chro rome");
+        assert_eq!(matched("chrome chr", &f.highlights("chrome chr", None)), ["chrome"]);
+        assert!(Filter::default().highlights("anything", None).is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::model::{Process, ProcessKey};
+
+/// Processes with a history at most; the least recently seen go first.
+const MAX_TRACKED: usize = 512;
+
+#[derive(Debug, Clone, Default)]
+struct Entry {
+    rates: VecDeque<(f64, f64)>,
+    /// Sample the process was last present in
+    last_seen: u64,
+}
+
+/// In and out rates of each process, one sample per refresh. Each
+/// history holds `len` samples; a process that stops showing up records
+/// zeros until a full window has passed, then is forgotten. At most
+/// `capacity` processes are kept, dropping the least recently seen.
+#[derive(Debug, Clone)]
+pub struct ProcessHistory {
+    entries: HashMap<ProcessKey, Entry>,
+    len: usize,
+    capacity: usize,
+    samples: u64,
+}
+
+impl ProcessHistory {
+    pub fn new(len: usize) -> Self {
+        Self::with_capacity(len, MAX_TRACKED)
+    }
+
+    pub fn with_capacity(len: usize, capacity: usize) -> Self {
+        ProcessHistory {
+            entries: HashMap::new(),
+            len: len.max(1),
+            capacity,
+            samples: 0,
+        }
+    }
+
+    /// Add a sample for every tracked process and start tracking new ones.
+    pub fn record(&mut self, processes: &[Process]) {
+        self.samples += 1;
+        let rates: HashMap<ProcessKey, (f64, f64)> = processes
+            .iter()
+            .map(|p| (p.key(), (p.rate_in, p.rate_out)))
+            .collect();
+        for (key, rate) in &rates {
+            let entry = self.entries.entry(key.clone()).or_default();
+            entry.last_seen = self.samples;
+            push_bounded(&mut entry.rates, *rate, self.len);
+        }
+        for (key, entry) in self.entries.iter_mut() {
+            if !rates.contains_key(key) {
+                push_bounded(&mut entry.rates, (0.0, 0.0), self.len);
+            }
+        }
+
+        let (samples, len) = (self.samples, self.len as u64);
+        self.entries.retain(|_, entry| samples - entry.last_seen < len);
+        if self.entries.len() > self.capacity {
+            let mut by_age: Vec<(u64, ProcessKey)> = self
+                .entries
+                .iter()
+                .map(|(key, entry)| (entry.last_seen, key.clone()))
+                .collect();
+            by_age.sort();
+            let excess = self.entries.len() - self.capacity;
+            for (_, key) in by_age.into_iter().take(excess) {
+                self.entries.remove(&key);
+            }
+        }
+    }
+
+    pub fn get(&self, key: &ProcessKey) -> Option<&VecDeque<(f64, f64)>> {
+        self.entries.get(key).map(|entry| &entry.rates)
+    }
+}
+
+fn push_bounded(history: &mut VecDeque<(f64, f64)>, value: (f64, f64), len: usize) {
+    if history.len() >= len {
+        history.pop_front();
+    }
+    history.push_back(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(name: &str, pid: u32, rate: f64) -> Process {
+        Process {
+            rate_in: rate,
+            ..Process::for_test(name, pid)
+        }
+    }
+
+    fn key(name: &str, pid: u32) -> ProcessKey {
+        (name.to_string(), pid)
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = ProcessHistory::new(3);
+        for rate in [1.0, 2.0, 3.0, 4.0] {
+            history.record(&[process("curl", 1, rate)]);
+        }
+        assert_eq!(history.get(&key("curl", 1)), Some(&VecDeque::from([(2.0, 0.0), (3.0, 0.0), (4.0, 0.0)])));
+        assert_eq!(history.get(&key("curl", 2)), None);
+    }
+
+    #[test]
+    fn test_gone_processes_fade_out() {
+        let mut history = ProcessHistory::new(3);
+        history.record(&[process("curl", 1, 5.0), process("ssh", 2, 1.0)]);
+        history.record(&[process("ssh", 2, 1.0)]);
+        history.record(&[process("ssh", 2, 1.0)]);
+        assert_eq!(history.get(&key("curl", 1)), Some(&VecDeque::from([(5.0, 0.0), (0.0, 0.0), (0.0, 0.0)])));
+        history.record(&[process("ssh", 2, 1.0)]);
+        assert_eq!(history.get(&key("curl", 1)), None);
+        assert_eq!(history.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_least_recently_seen_evicted() {
+        let mut history = ProcessHistory::with_capacity(10, 2);
+        history.record(&[process("a", 1, 1.0)]);
+        history.record(&[process("b", 2, 1.0)]);
+        history.record(&[process("b", 2, 1.0), process("c", 3, 1.0)]);
+        assert_eq!(history.entries.len(), 2);
+        assert!(history.get(&key("a", 1)).is_none());
+        assert!(history.get(&key("b", 2)).is_some());
+        assert!(history.get(&key("c", 3)).is_some());
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::process::Stdio;
+use tokio::process::Command;
+
+use super::model::Process;
+
+/// Interface name prefixes treated as tunnels.
+const VPN_PREFIXES: &[&str] = &["utun", "ipsec", "tap", "tun", "ppp", "wg"];
+
+/// Whether an interface name looks like a VPN tunnel. `extra` holds
+/// user-supplied patterns: a prefix with an optional trailing `*`, or an
+/// exact name. An empty interface is never a VPN.
+pub fn is_vpn_interface(name: &str, extra: &[String]) -> bool {
+    if name.is_empty() {
+        return false;
+    }
+    VPN_PREFIXES.iter().any(|p| name.starts_with(p))
+        || extra.iter().any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == pattern,
+        })
+}
+
+/// Flag connections whose interface is a tunnel.
+pub fn tag_vpn(processes: &mut [Process], extra: &[String]) {
+    for proc in processes.iter_mut() {
+        for conn in proc.connections.iter_mut() {
+            conn.via_vpn = is_vpn_interface(&conn.interface, extra);
+        }
+    }
+}
+
+/// Byte and packet counters for one network interface.
+#[derive(Debug, Clone, Default)]
+pub struct InterfaceStat {
+    pub name: String,
+    pub up: bool,
+    pub packets_in: u64,
+    pub packets_out: u64,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub rate_in: f64,
+    pub rate_out: f64,
+}
+
+/// Read per-interface counters from `netstat -ib`.
+pub async fn fetch_interface_stats() -> Result<Vec<InterfaceStat>, String> {
+    let output = Command::new("netstat")
+        .args(["-i", "-b", "-n"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run netstat: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("netstat exited with {}", output.status));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(parse_netstat_ib(&stdout))
+}
+
+/// Parse `netstat -ib` output, keeping only the `<Link#N>` row of each
+/// interface since it carries the hardware counters; the per-address rows
+/// that follow repeat them. The Address column is blank for some links
+/// (lo0, utun), so counters are read from the right:
+/// ```text
+/// Name  Mtu   Network     Address            Ipkts Ierrs    Ibytes Opkts Oerrs    Obytes Coll
+/// lo0   16384 <Link#1>                        4122     0    612345  4122     0    612345    0
+/// en0   1500  <Link#6>    a4:83:e7:00:00:01 91842     0 112003211 51210     0   8800122    0
+/// ```
+/// A trailing `*` on the name marks an interface that is down.
+fn parse_netstat_ib(output: &str) -> Vec<InterfaceStat> {
+    let mut stats: Vec<InterfaceStat> = Vec::new();
+    for line in output.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 || !fields[2].starts_with("<Link#") {
+            continue;
+        }
+        let n = fields.len();
+        let num = |i: usize| fields[i].parse::<u64>().unwrap_or(0);
+        let (name, up) = match fields[0].strip_suffix('*') {
+            Some(name) => (name, false),
+            None => (fields[0], true),
+        };
+        if stats.iter().any(|s| s.name == name) {
+            continue;
+        }
+        stats.push(InterfaceStat {
+            name: name.to_string(),
+            up,
+            packets_in: num(n - 7),
+            bytes_in: num(n - 5),
+            packets_out: num(n - 4),
+            bytes_out: num(n - 2),
+            ..Default::default()
+        });
+    }
+    stats
+}
+
+/// Compute interface rates against the previous tick's counters. A counter
+/// that went backwards (wrap or interface reset) yields a zero rate.
+pub fn compute_interface_rates(
+    current: &mut [InterfaceStat],
+    previous: &HashMap<String, (u64, u64)>,
+    interval_secs: f64,
+) {
+    for iface in current.iter_mut() {
+        if let Some(&(prev_in, prev_out)) = previous.get(&iface.name) {
+            iface.rate_in = iface.bytes_in.saturating_sub(prev_in) as f64 / interval_secs;
+            iface.rate_out = iface.bytes_out.saturating_sub(prev_out) as f64 / interval_secs;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_vpn_interface() {
+        assert!(is_vpn_interface("utun3", &[]));
+        assert!(is_vpn_interface("ipsec0", &[]));
+        assert!(is_vpn_interface("tun0", &[]));
+        assert!(!is_vpn_interface("en0", &[]));
+        assert!(!is_vpn_interface("lo0", &[]));
+        assert!(!is_vpn_interface("", &[]));
+    }
+
+    #[test]
+    fn test_is_vpn_interface_custom_patterns() {
+        let extra = vec!["zt*".to_string(), "bridge100".to_string()];
+        assert!(is_vpn_interface("ztabcdef", &extra));
+        assert!(is_vpn_interface("bridge100", &extra));
+        assert!(!is_vpn_interface("bridge1000", &extra));
+        assert!(!is_vpn_interface("", &["*".to_string()]));
+    }
+
+    #[test]
+    fn test_parse_netstat_ib() {
+        let output = "\
+Name       Mtu   Network       Address            Ipkts Ierrs     Ibytes    Opkts Oerrs     Obytes  Coll
+lo0        16384 <Link#1>                          4122     0     612345     4122     0     612345     0
+lo0        16384 127           127.0.0.1           4122     -     612345     4122     -     612345     -
+en0        1500  <Link#6>    a4:83:e7:00:00:01    91842     0  112003211    51210     0    8800122     0
+en0        1500  192.168.1     192.168.1.20       91000     -  111000000    51000     -    8700000     -
+en5*       1500  <Link#9>    ac:de:48:00:11:22        0     0          0        0     0          0     0
+";
+        let stats = parse_netstat_ib(output);
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats[0].name, "lo0");
+        assert_eq!(stats[0].bytes_in, 612345);
+        assert_eq!(stats[1].name, "en0");
+        assert_eq!(stats[1].packets_in, 91842);
+        assert_eq!(stats[1].bytes_in, 112003211);
+        assert_eq!(stats[1].packets_out, 51210);
+        assert_eq!(stats[1].bytes_out, 8800122);
+        assert!(stats[1].up);
+        assert_eq!(stats[2].name, "en5");
+        assert!(!stats[2].up);
+    }
+
+    #[test]
+    fn test_compute_interface_rates_handles_wrap() {
+        let mut current = vec![
+            InterfaceStat {
+                name: "en0".to_string(),
+                bytes_in: 3000,
+                bytes_out: 100,
+                ..Default::default()
+            },
+            InterfaceStat {
+                name: "utun3".to_string(),
+                bytes_in: 500,
+                ..Default::default()
+            },
+        ];
+        let mut previous = HashMap::new();
+        previous.insert("en0".to_string(), (1000, 500));
+        compute_interface_rates(&mut current, &previous, 2.0);
+        assert_eq!(current[0].rate_in, 1000.0);
+        assert_eq!(current[0].rate_out, 0.0);
+        // No previous sample for a newly appeared interface
+        assert_eq!(current[1].rate_in, 0.0);
+    }
+}
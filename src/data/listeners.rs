@@ -0,0 +1,153 @@
+use std::process::Stdio;
+use tokio::process::Command;
+
+use super::model::Protocol;
+
+/// A socket listening for connections (TCP LISTEN) or bound and unconnected (UDP).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Listener {
+    pub process: String,
+    pub pid: u32,
+    pub protocol: Protocol,
+    pub addr: String,
+    pub port: u16,
+    pub ipv4: bool,
+    pub ipv6: bool,
+}
+
+impl Listener {
+    /// "IPv4", "IPv6", or "4+6" when the same socket is bound on both.
+    pub fn family(&self) -> &'static str {
+        match (self.ipv4, self.ipv6) {
+            (true, true) => "4+6",
+            (false, true) => "IPv6",
+            _ => "IPv4",
+        }
+    }
+}
+
+/// List listening TCP sockets and bound UDP sockets via lsof field output.
+pub async fn fetch_listeners() -> Result<Vec<Listener>, String> {
+    let output = Command::new("lsof")
+        .args(["-nP", "-iTCP", "-sTCP:LISTEN", "-iUDP", "-FpcPtn"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run lsof: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(parse_lsof_output(&stdout))
+}
+
+/// Parse `lsof -F pcPtn` output. Each line is a one-letter field tag
+/// followed by its value; `p`/`c` describe a process, and the `f`-led
+/// groups that follow describe its files:
+/// ```text
+/// p1234
+/// cnode
+/// f23
+/// tIPv4
+/// PTCP
+/// n*:3000
+/// ```
+/// Connected UDP sockets (names containing `->`) are skipped, and rows for
+/// the same process/protocol/address/port on v4 and v6 are merged.
+fn parse_lsof_output(output: &str) -> Vec<Listener> {
+    let mut listeners: Vec<Listener> = Vec::new();
+    let mut pid = 0u32;
+    let mut command = String::new();
+    let mut family = "";
+    let mut protocol = Protocol::Tcp;
+
+    for line in output.lines() {
+        let Some(tag) = line.chars().next() else {
+            continue;
+        };
+        let value = &line[tag.len_utf8()..];
+        match tag {
+            'p' => pid = value.parse().unwrap_or(0),
+            'c' => command = value.to_string(),
+            'f' => {
+                family = "";
+                protocol = Protocol::Tcp;
+            }
+            't' => {
+                family = if value == "IPv6" { "IPv6" } else { "IPv4" };
+            }
+            'P' => {
+                protocol = match value {
+                    "TCP" => Protocol::Tcp,
+                    "UDP" => Protocol::Udp,
+                    other => Protocol::Other(other.to_lowercase()),
+                };
+            }
+            'n' => {
+                if value.contains("->") {
+                    continue;
+                }
+                let Some((addr, port)) = split_lsof_name(value) else {
+                    continue;
+                };
+                let ipv6 = family == "IPv6";
+                if let Some(existing) = listeners.iter_mut().find(|l| {
+                    l.pid == pid && l.protocol == protocol && l.addr == addr && l.port == port
+                }) {
+                    existing.ipv4 |= !ipv6;
+                    existing.ipv6 |= ipv6;
+                } else {
+                    listeners.push(Listener {
+                        process: command.clone(),
+                        pid,
+                        protocol: protocol.clone(),
+                        addr,
+                        port,
+                        ipv4: !ipv6,
+                        ipv6,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    listeners
+}
+
+/// Split an lsof socket name like "*:8080", "127.0.0.1:631", or "[::1]:631".
+fn split_lsof_name(name: &str) -> Option<(String, u16)> {
+    let (addr, port) = name.rsplit_once(':')?;
+    let port = port.parse().ok()?;
+    let addr = addr.trim_start_matches('[').trim_end_matches(']');
+    Some((addr.to_string(), port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_lsof_name() {
+        assert_eq!(split_lsof_name("*:8080"), Some(("*".to_string(), 8080)));
+        assert_eq!(split_lsof_name("[::1]:631"), Some(("::1".to_string(), 631)));
+        assert_eq!(split_lsof_name("*:*"), None);
+    }
+
+    #[test]
+    fn test_parse_lsof_output_merges_families() {
+        let output = "p417\ncmDNSResponder\nf5\ntIPv4\nPUDP\nn*:5353\nf6\ntIPv6\nPUDP\nn*:5353\n\
+                      p900\ncnode\nf21\ntIPv6\nPTCP\nn[::1]:3000\nf22\ntIPv4\nPUDP\nn192.168.0.2:5000->1.2.3.4:5000\n";
+        let listeners = parse_lsof_output(output);
+        assert_eq!(listeners.len(), 2);
+
+        assert_eq!(listeners[0].process, "mDNSResponder");
+        assert_eq!(listeners[0].pid, 417);
+        assert_eq!(listeners[0].protocol, Protocol::Udp);
+        assert_eq!(listeners[0].family(), "4+6");
+
+        assert_eq!(listeners[1].process, "node");
+        assert_eq!(listeners[1].addr, "::1");
+        assert_eq!(listeners[1].port, 3000);
+        assert_eq!(listeners[1].family(), "IPv6");
+    }
+}
@@ -1,4 +1,14 @@
+pub mod addr;
+pub mod aliases;
+pub mod asn;
+pub mod direction;
 pub mod dns;
+pub mod filter;
+pub mod history;
+pub mod interfaces;
+pub mod listeners;
 pub mod model;
+pub mod netstat;
 pub mod nettop;
 pub mod procinfo;
+pub mod services;
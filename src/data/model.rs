@@ -1,6 +1,14 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::time::{Duration, Instant, SystemTime};
 
-#[derive(Debug, Clone)]
+use serde::Serialize;
+
+use super::addr;
+use super::services;
+
+#[derive(Debug, Clone, Serialize)]
 pub struct Connection {
     pub local_addr: String,
     pub local_port: u16,
@@ -9,58 +17,293 @@ pub struct Connection {
     pub protocol: Protocol,
     pub state: String,
     pub interface: String,
+    pub via_vpn: bool,
+    pub direction: Direction,
     pub bytes_in: u64,
     pub bytes_out: u64,
+    pub rate_in: f64,
+    pub rate_out: f64,
     pub hostname: Option<String>,
+    /// A reverse lookup for the remote address is in flight
+    pub resolving: bool,
+    pub asn: Option<String>,
+    #[serde(skip)]
+    pub first_seen: Option<Instant>,
+    /// Socket receive/send queue sizes in bytes, from netstat when enabled
+    pub recv_q: Option<u32>,
+    pub send_q: Option<u32>,
+    /// Send queue has stayed large for several ticks (likely a stalled peer)
+    pub send_stalled: bool,
+    /// Byte counters have not moved for the configured number of snapshots
+    pub idle: bool,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// Reverse DNS state of an address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    Pending,
+    Resolved(String),
+    /// No PTR record; the raw address is shown
+    Failed,
+}
+
+/// Identity of a connection across snapshots: owning PID, protocol, and the
+/// local/remote address tuple.
+pub type ConnectionKey = (u32, Protocol, String, u16, String, u16);
+
+impl Connection {
+    /// Fixed display order within a process: remote end, then local end,
+    /// then protocol, so rows keep their place whatever order nettop
+    /// reports them in.
+    pub fn display_order(&self, other: &Connection) -> Ordering {
+        (&self.remote_addr, self.remote_port, &self.local_addr, self.local_port, &self.protocol).cmp(&(
+            &other.remote_addr,
+            other.remote_port,
+            &other.local_addr,
+            other.local_port,
+            &other.protocol,
+        ))
+    }
+
+    /// Resolved hostname if known, otherwise the raw remote address (also
+    /// while a lookup is pending).
+    pub fn remote_host(&self) -> &str {
+        match self.hostname.as_deref() {
+            Some(host) if !host.is_empty() => host,
+            _ => &self.remote_addr,
+        }
+    }
+
+    /// "IPv4" or "IPv6", from whichever end has a parseable address.
+    pub fn family(&self) -> Option<&'static str> {
+        let ip = addr::parse_ip(&self.remote_addr).or_else(|| addr::parse_ip(&self.local_addr))?;
+        Some(if ip.is_ipv4() { "IPv4" } else { "IPv6" })
+    }
+
+    /// Local "addr:port", or just the address when there is no port.
+    pub fn local_label(&self) -> String {
+        if self.local_port > 0 {
+            format!("{}:{}", self.local_addr, self.local_port)
+        } else {
+            self.local_addr.clone()
+        }
+    }
+
+    /// Remote host and port, with the port annotated by its service name
+    /// when `services` is set. `hostnames` picks the resolved name over the
+    /// raw address when one is known.
+    pub fn remote_label(&self, services: bool, hostnames: bool) -> String {
+        let host = if hostnames {
+            self.remote_host()
+        } else {
+            &self.remote_addr
+        };
+        if self.remote_port > 0 && services {
+            format!("{}:{}", host, services::annotate_port(self.remote_port, &self.protocol))
+        } else if self.remote_port > 0 {
+            format!("{}:{}", host, self.remote_port)
+        } else {
+            host.to_string()
+        }
+    }
+
+    pub fn key(&self, pid: u32) -> ConnectionKey {
+        (
+            pid,
+            self.protocol.clone(),
+            self.local_addr.clone(),
+            self.local_port,
+            self.remote_addr.clone(),
+            self.remote_port,
+        )
+    }
+}
+
+/// Who initiated a connection, as inferred by `direction::infer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Inbound,
+    Outbound,
+    Unknown,
+}
+
+impl Direction {
+    pub fn label(self) -> &'static str {
+        match self {
+            Direction::Inbound => "← in",
+            Direction::Outbound => "→ out",
+            Direction::Unknown => "",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Protocol {
     Tcp,
     Udp,
+    Icmp,
     Other(String),
 }
 
+impl Protocol {
+    /// Whether addresses for this protocol carry a port number.
+    pub fn has_ports(&self) -> bool {
+        matches!(self, Protocol::Tcp | Protocol::Udp)
+    }
+}
+
 impl std::fmt::Display for Protocol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Protocol::Tcp => write!(f, "TCP"),
             Protocol::Udp => write!(f, "UDP"),
+            Protocol::Icmp => write!(f, "ICMP"),
             Protocol::Other(s) => write!(f, "{}", s),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Process {
     pub name: String,
     pub pid: u32,
     pub path: Option<String>,
+    /// Login name of the owning user, when the process could be inspected
+    pub user: Option<String>,
+    /// Friendly name from the containing app bundle ("Safari (WebKit Networking)")
+    pub display_name: Option<String>,
+    /// When the process was started, if it could be inspected
+    #[serde(serialize_with = "serialize_unix_secs")]
+    pub started: Option<SystemTime>,
     pub connections: Vec<Connection>,
     pub bytes_in: u64,
     pub bytes_out: u64,
     pub rate_in: f64,
     pub rate_out: f64,
+    pub packets_in: u64,
+    pub packets_out: u64,
+    /// Packets per second since the previous snapshot
+    pub pps_in: f64,
+    pub pps_out: f64,
+    pub peak_rate_in: f64,
+    pub peak_rate_out: f64,
+    /// Connections that appeared this tick / since the session started
+    pub new_connections: usize,
+    pub total_new_connections: u64,
+}
+
+/// Serialize a time as whole seconds since the Unix epoch.
+fn serialize_unix_secs<S: serde::Serializer>(
+    time: &Option<SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let secs = time
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+    serializer.serialize_some(&secs)
+}
+
+/// Identity of a process across snapshots: name plus PID.
+pub type ProcessKey = (String, u32);
+
+/// Cumulative counters remembered between snapshots to compute rates.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Counters {
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub packets_in: u64,
+    pub packets_out: u64,
 }
 
 impl Process {
     pub fn connection_count(&self) -> usize {
         self.connections.len()
     }
+
+    pub fn key(&self) -> ProcessKey {
+        (self.name.clone(), self.pid)
+    }
+
+    pub fn counters(&self) -> Counters {
+        Counters {
+            bytes_in: self.bytes_in,
+            bytes_out: self.bytes_out,
+            packets_in: self.packets_in,
+            packets_out: self.packets_out,
+        }
+    }
+
+    /// Time since the process started, when known.
+    pub fn uptime(&self) -> Option<Duration> {
+        self.started.and_then(|s| SystemTime::now().duration_since(s).ok())
+    }
+
+    /// Average in/out bytes per second over the process's lifetime.
+    pub fn lifetime_rates(&self) -> Option<(f64, f64)> {
+        let secs = self.uptime()?.as_secs_f64();
+        (secs >= 1.0).then(|| (self.bytes_in as f64 / secs, self.bytes_out as f64 / secs))
+    }
+}
+
+/// The one process nm is scoped to (`--pid` / `--process`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Watch {
+    Pid(u32),
+    /// Case-insensitive process name; every PID with the name matches
+    Name(String),
+}
+
+impl Watch {
+    pub fn matches(&self, p: &Process) -> bool {
+        match self {
+            Watch::Pid(pid) => p.pid == *pid,
+            Watch::Name(name) => p.name.eq_ignore_ascii_case(name),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Watch::Pid(pid) => format!("PID {}", pid),
+            Watch::Name(name) => name.clone(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
 pub enum SortField {
     Name,
     Pid,
+    #[value(name = "conn", alias = "connections")]
     Connections,
+    #[value(name = "down", alias = "bytes-in", alias = "bytes_in")]
     BytesIn,
+    #[value(name = "up", alias = "bytes-out", alias = "bytes_out")]
     BytesOut,
+    #[value(alias = "rate_in", alias = "ratein")]
     RateIn,
+    #[value(alias = "rate_out", alias = "rateout")]
     RateOut,
+    #[value(alias = "pps_in", alias = "ppsin")]
+    PpsIn,
+    #[value(alias = "pps_out", alias = "ppsout")]
+    PpsOut,
 }
 
 impl SortField {
+    pub const ALL: [SortField; 9] = [
+        SortField::Name,
+        SortField::Pid,
+        SortField::Connections,
+        SortField::BytesIn,
+        SortField::BytesOut,
+        SortField::RateIn,
+        SortField::RateOut,
+        SortField::PpsIn,
+        SortField::PpsOut,
+    ];
+
     pub fn next(self) -> Self {
         match self {
             SortField::Name => SortField::Pid,
@@ -69,7 +312,47 @@ impl SortField {
             SortField::BytesIn => SortField::BytesOut,
             SortField::BytesOut => SortField::RateIn,
             SortField::RateIn => SortField::RateOut,
-            SortField::RateOut => SortField::Name,
+            SortField::RateOut => SortField::PpsIn,
+            SortField::PpsIn => SortField::PpsOut,
+            SortField::PpsOut => SortField::Name,
+        }
+    }
+
+    pub fn prev(self) -> Self {
+        match self {
+            SortField::Name => SortField::PpsOut,
+            SortField::Pid => SortField::Name,
+            SortField::Connections => SortField::Pid,
+            SortField::BytesIn => SortField::Connections,
+            SortField::BytesOut => SortField::BytesIn,
+            SortField::RateIn => SortField::BytesOut,
+            SortField::RateOut => SortField::RateIn,
+            SortField::PpsIn => SortField::RateOut,
+            SortField::PpsOut => SortField::PpsIn,
+        }
+    }
+
+    /// Names and PIDs read best A→Z / low→high; counts and rates
+    /// busiest-first.
+    pub fn default_direction(self) -> SortDirection {
+        match self {
+            SortField::Name | SortField::Pid => SortDirection::Asc,
+            _ => SortDirection::Desc,
+        }
+    }
+
+    /// Ascending comparison of two processes by this field.
+    pub fn compare(self, a: &Process, b: &Process) -> Ordering {
+        match self {
+            SortField::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortField::Pid => a.pid.cmp(&b.pid),
+            SortField::Connections => a.connection_count().cmp(&b.connection_count()),
+            SortField::BytesIn => a.bytes_in.cmp(&b.bytes_in),
+            SortField::BytesOut => a.bytes_out.cmp(&b.bytes_out),
+            SortField::RateIn => a.rate_in.total_cmp(&b.rate_in),
+            SortField::RateOut => a.rate_out.total_cmp(&b.rate_out),
+            SortField::PpsIn => a.pps_in.total_cmp(&b.pps_in),
+            SortField::PpsOut => a.pps_out.total_cmp(&b.pps_out),
         }
     }
 
@@ -82,26 +365,319 @@ impl SortField {
             SortField::BytesOut => "Up",
             SortField::RateIn => "Rate In",
             SortField::RateOut => "Rate Out",
+            SortField::PpsIn => "PPS In",
+            SortField::PpsOut => "PPS Out",
+        }
+    }
+}
+
+/// Sort processes by a field, breaking ties by name and then PID so equal
+/// rows (often all the idle ones) don't swap places between refreshes.
+/// Each process's connections are put in display order too.
+pub fn sort_processes(processes: &mut [Process], field: SortField, direction: SortDirection) {
+    processes.sort_by(|a, b| {
+        direction
+            .apply(field.compare(a, b))
+            .then_with(|| SortField::Name.compare(a, b))
+            .then(a.pid.cmp(&b.pid))
+    });
+    for process in processes {
+        process.connections.sort_by(Connection::display_order);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    pub fn flip(self) -> Self {
+        match self {
+            SortDirection::Asc => SortDirection::Desc,
+            SortDirection::Desc => SortDirection::Asc,
+        }
+    }
+
+    /// Orient an ascending comparison.
+    pub fn apply(self, ordering: Ordering) -> Ordering {
+        match self {
+            SortDirection::Asc => ordering,
+            SortDirection::Desc => ordering.reverse(),
+        }
+    }
+
+    /// Header marker: ▲ ascending, ▼ descending.
+    pub fn glyph(self) -> &'static str {
+        match self {
+            SortDirection::Asc => "▲",
+            SortDirection::Desc => "▼",
         }
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct NetworkSnapshot {
     pub processes: Vec<Process>,
     pub total_bytes_in: u64,
     pub total_bytes_out: u64,
     pub total_rate_in: f64,
     pub total_rate_out: f64,
+    pub total_pps_in: f64,
+    pub total_pps_out: f64,
     pub total_connections: usize,
 }
 
+/// Traffic to a single remote host aggregated across all processes.
+#[derive(Debug, Clone, Default)]
+pub struct RemoteHost {
+    pub host: String,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub rate_in: f64,
+    pub rate_out: f64,
+    pub connections: usize,
+    pub processes: Vec<String>,
+}
+
+/// Traffic split by transport protocol and by IP version. The amounts are
+/// weights for working out shares: current rates, or bytes moved when
+/// nothing is moving.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProtocolMix {
+    pub tcp: f64,
+    pub udp: f64,
+    pub other: f64,
+    pub ipv4: f64,
+    pub ipv6: f64,
+}
+
+impl ProtocolMix {
+    /// Whether there is anything to split.
+    pub fn is_empty(&self) -> bool {
+        self.tcp + self.udp + self.other == 0.0
+    }
+}
+
+/// Traffic to a single AS organization aggregated across all processes.
+#[derive(Debug, Clone, Default)]
+pub struct OrgStat {
+    pub org: String,
+    pub rate_in: f64,
+    pub rate_out: f64,
+    pub connections: usize,
+}
+
+/// Traffic to a single remote port/protocol aggregated across all processes.
+#[derive(Debug, Clone)]
+pub struct PortStat {
+    pub port: u16,
+    pub protocol: Protocol,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub rate_in: f64,
+    pub rate_out: f64,
+    pub connections: usize,
+}
+
+impl PortStat {
+    pub fn rate(&self) -> f64 {
+        self.rate_in + self.rate_out
+    }
+}
+
 impl NetworkSnapshot {
+    /// Aggregate connections by remote port and protocol, sorted by combined
+    /// rate. With `exclude_loopback`, loopback connections are left out so
+    /// ports used only for local chatter disappear.
+    pub fn port_stats(&self, exclude_loopback: bool) -> Vec<PortStat> {
+        let mut ports: HashMap<(u16, Protocol), PortStat> = HashMap::new();
+        for conn in self.processes.iter().flat_map(|p| p.connections.iter()) {
+            if conn.remote_port == 0 {
+                continue;
+            }
+            if exclude_loopback
+                && conn.remote_addr.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+            {
+                continue;
+            }
+            let entry = ports
+                .entry((conn.remote_port, conn.protocol.clone()))
+                .or_insert_with(|| PortStat {
+                    port: conn.remote_port,
+                    protocol: conn.protocol.clone(),
+                    bytes_in: 0,
+                    bytes_out: 0,
+                    rate_in: 0.0,
+                    rate_out: 0.0,
+                    connections: 0,
+                });
+            entry.bytes_in += conn.bytes_in;
+            entry.bytes_out += conn.bytes_out;
+            entry.rate_in += conn.rate_in;
+            entry.rate_out += conn.rate_out;
+            entry.connections += 1;
+        }
+        let mut ports: Vec<PortStat> = ports.into_values().collect();
+        ports.sort_by(|a, b| {
+            b.rate()
+                .partial_cmp(&a.rate())
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| (b.bytes_in + b.bytes_out).cmp(&(a.bytes_in + a.bytes_out)))
+        });
+        ports
+    }
+
+    /// Current (in, out) rates split into LAN and WAN by remote address class.
+    pub fn lan_wan_rates(&self) -> ((f64, f64), (f64, f64)) {
+        let mut lan = (0.0, 0.0);
+        let mut wan = (0.0, 0.0);
+        for conn in self.processes.iter().flat_map(|p| p.connections.iter()) {
+            let bucket = match addr::classify_addr(&conn.remote_addr) {
+                Some(class) if !class.is_lan() => &mut wan,
+                Some(_) => &mut lan,
+                None => continue,
+            };
+            bucket.0 += conn.rate_in;
+            bucket.1 += conn.rate_out;
+        }
+        (lan, wan)
+    }
+
+    /// Current total rate (in + out) over tunnel interfaces vs everything else.
+    pub fn vpn_split(&self) -> (f64, f64) {
+        let mut vpn = 0.0;
+        let mut physical = 0.0;
+        for conn in self.processes.iter().flat_map(|p| p.connections.iter()) {
+            if conn.via_vpn {
+                vpn += conn.rate_in + conn.rate_out;
+            } else {
+                physical += conn.rate_in + conn.rate_out;
+            }
+        }
+        (vpn, physical)
+    }
+
+    /// Connections per TCP state, most common first, with states spelled
+    /// the netstat way ("CLOSE_WAIT") whichever source filled them in.
+    /// Connections without a state are left out.
+    pub fn state_counts(&self) -> Vec<(String, usize)> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for conn in self.processes.iter().flat_map(|p| p.connections.iter()) {
+            if !conn.state.is_empty() {
+                *counts.entry(normalize_state(&conn.state)).or_default() += 1;
+            }
+        }
+        let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
+    }
+
+    /// Split traffic by protocol and IP version, by combined current rate,
+    /// or by bytes moved when every connection is quiet.
+    pub fn protocol_mix(&self) -> ProtocolMix {
+        let conns = || self.processes.iter().flat_map(|p| p.connections.iter());
+        let by_rate = conns().any(|c| c.rate_in + c.rate_out > 0.0);
+        let mut mix = ProtocolMix::default();
+        for conn in conns() {
+            let amount = if by_rate {
+                conn.rate_in + conn.rate_out
+            } else {
+                (conn.bytes_in + conn.bytes_out) as f64
+            };
+            match conn.protocol {
+                Protocol::Tcp => mix.tcp += amount,
+                Protocol::Udp => mix.udp += amount,
+                _ => mix.other += amount,
+            }
+            match conn.family() {
+                Some("IPv4") => mix.ipv4 += amount,
+                Some(_) => mix.ipv6 += amount,
+                None => {}
+            }
+        }
+        mix
+    }
+
+    /// Aggregate connections by AS organization, busiest first. Connections
+    /// without an ASN annotation are skipped.
+    pub fn org_stats(&self) -> Vec<OrgStat> {
+        let mut orgs: HashMap<&str, OrgStat> = HashMap::new();
+        for conn in self.processes.iter().flat_map(|p| p.connections.iter()) {
+            let Some(org) = conn.asn.as_deref() else {
+                continue;
+            };
+            let entry = orgs.entry(org).or_insert_with(|| OrgStat {
+                org: org.to_string(),
+                ..OrgStat::default()
+            });
+            entry.rate_in += conn.rate_in;
+            entry.rate_out += conn.rate_out;
+            entry.connections += 1;
+        }
+        let mut orgs: Vec<OrgStat> = orgs.into_values().collect();
+        orgs.sort_by(|a, b| {
+            (b.rate_in + b.rate_out)
+                .partial_cmp(&(a.rate_in + a.rate_out))
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.connections.cmp(&a.connections))
+        });
+        orgs
+    }
+
+    /// Aggregate connections by resolved hostname, falling back to the IP.
+    /// Wildcard and empty remote addresses are skipped.
+    pub fn remote_hosts(&self) -> Vec<RemoteHost> {
+        let mut hosts: HashMap<String, RemoteHost> = HashMap::new();
+        for p in &self.processes {
+            for conn in &p.connections {
+                if conn.remote_addr.is_empty() || conn.remote_addr == "*" {
+                    continue;
+                }
+                let host = conn.remote_host();
+                let entry = hosts.entry(host.to_string()).or_insert_with(|| RemoteHost {
+                    host: host.to_string(),
+                    ..RemoteHost::default()
+                });
+                entry.bytes_in += conn.bytes_in;
+                entry.bytes_out += conn.bytes_out;
+                entry.rate_in += conn.rate_in;
+                entry.rate_out += conn.rate_out;
+                entry.connections += 1;
+                if !entry.processes.contains(&p.name) {
+                    entry.processes.push(p.name.clone());
+                }
+            }
+        }
+        let mut hosts: Vec<RemoteHost> = hosts.into_values().collect();
+        for h in hosts.iter_mut() {
+            h.processes.sort();
+        }
+        hosts
+    }
+
+    /// The `n` busiest remote hosts by combined current rate, then by bytes
+    /// moved.
+    pub fn top_remote_hosts(&self, n: usize) -> Vec<RemoteHost> {
+        let mut hosts = self.remote_hosts();
+        hosts.sort_by(|a, b| {
+            (b.rate_in + b.rate_out)
+                .total_cmp(&(a.rate_in + a.rate_out))
+                .then_with(|| (b.bytes_in + b.bytes_out).cmp(&(a.bytes_in + a.bytes_out)))
+                .then_with(|| a.host.cmp(&b.host))
+        });
+        hosts.truncate(n);
+        hosts
+    }
+
     pub fn from_processes(processes: Vec<Process>) -> Self {
         let total_bytes_in: u64 = processes.iter().map(|p| p.bytes_in).sum();
         let total_bytes_out: u64 = processes.iter().map(|p| p.bytes_out).sum();
         let total_rate_in: f64 = processes.iter().map(|p| p.rate_in).sum();
         let total_rate_out: f64 = processes.iter().map(|p| p.rate_out).sum();
+        let total_pps_in: f64 = processes.iter().map(|p| p.pps_in).sum();
+        let total_pps_out: f64 = processes.iter().map(|p| p.pps_out).sum();
         let total_connections: usize = processes.iter().map(|p| p.connection_count()).sum();
         NetworkSnapshot {
             processes,
@@ -109,9 +685,442 @@ impl NetworkSnapshot {
             total_bytes_out,
             total_rate_in,
             total_rate_out,
+            total_pps_in,
+            total_pps_out,
             total_connections,
         }
     }
 }
 
-pub type DnsCache = HashMap<String, Option<String>>;
+/// A TCP state in netstat's spelling: "CloseWait" and "close_wait" both
+/// become "CLOSE_WAIT".
+fn normalize_state(state: &str) -> String {
+    let mixed_case = state.chars().any(|c| c.is_lowercase()) && !state.contains('_');
+    let mut normalized = String::with_capacity(state.len() + 2);
+    for (i, c) in state.trim().chars().enumerate() {
+        if mixed_case && i > 0 && c.is_uppercase() {
+            normalized.push('_');
+        }
+        normalized.push(c.to_ascii_uppercase());
+    }
+    normalized
+}
+
+/// Connections opened and closed between two consecutive snapshots.
+#[derive(Debug, Default, Clone)]
+pub struct Churn {
+    pub opened: usize,
+    pub closed: usize,
+    pub opened_by_process: HashMap<ProcessKey, usize>,
+}
+
+/// Collect the identity of every connection in a set of processes.
+pub fn connection_keys(processes: &[Process]) -> HashSet<ConnectionKey> {
+    processes
+        .iter()
+        .flat_map(|p| p.connections.iter().map(|c| c.key(p.pid)))
+        .collect()
+}
+
+/// Diff connection identities against the previous snapshot's.
+pub fn connection_churn(previous: &HashSet<ConnectionKey>, processes: &[Process]) -> Churn {
+    let current = connection_keys(processes);
+    let mut churn = Churn {
+        closed: previous.difference(&current).count(),
+        ..Churn::default()
+    };
+    for p in processes {
+        let opened = p
+            .connections
+            .iter()
+            .filter(|c| !previous.contains(&c.key(p.pid)))
+            .count();
+        if opened > 0 {
+            churn.opened += opened;
+            churn.opened_by_process.insert(p.key(), opened);
+        }
+    }
+    churn
+}
+
+/// Last byte counters seen for a connection and for how many consecutive
+/// snapshots they have stayed the same.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Activity {
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub idle_ticks: usize,
+}
+
+/// Mark connections idle once their counters have not changed for
+/// `idle_after` snapshots (0 never marks anything idle). A connection seen
+/// for the first time starts out active. Returns the activity map for the
+/// next call; connections no longer present are dropped from it.
+pub fn track_idle(
+    previous: &HashMap<ConnectionKey, Activity>,
+    processes: &mut [Process],
+    idle_after: usize,
+) -> HashMap<ConnectionKey, Activity> {
+    let mut activity = HashMap::new();
+    for p in processes.iter_mut() {
+        for conn in p.connections.iter_mut() {
+            let key = conn.key(p.pid);
+            let idle_ticks = match previous.get(&key) {
+                Some(prev) if prev.bytes_in == conn.bytes_in && prev.bytes_out == conn.bytes_out => {
+                    prev.idle_ticks + 1
+                }
+                _ => 0,
+            };
+            conn.idle = idle_after > 0 && idle_ticks >= idle_after;
+            activity.insert(
+                key,
+                Activity {
+                    bytes_in: conn.bytes_in,
+                    bytes_out: conn.bytes_out,
+                    idle_ticks,
+                },
+            );
+        }
+    }
+    activity
+}
+
+
+#[cfg(test)]
+impl Connection {
+    /// An idle TCP connection from 192.168.0.2:50000 to 1.2.3.4, for tests
+    /// to adjust with struct update syntax.
+    pub fn for_test(remote_port: u16) -> Connection {
+        Connection {
+            local_addr: "192.168.0.2".to_string(),
+            local_port: 50000,
+            remote_addr: "1.2.3.4".to_string(),
+            remote_port,
+            protocol: Protocol::Tcp,
+            state: String::new(),
+            interface: String::new(),
+            via_vpn: false,
+            direction: Direction::Unknown,
+            bytes_in: 0,
+            bytes_out: 0,
+            rate_in: 0.0,
+            rate_out: 0.0,
+            hostname: None,
+            resolving: false,
+            asn: None,
+            first_seen: None,
+            recv_q: None,
+            send_q: None,
+            send_stalled: false,
+            idle: false,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Process {
+    /// A process with no connections or traffic, for tests to adjust with
+    /// struct update syntax.
+    pub fn for_test(name: &str, pid: u32) -> Process {
+        Process {
+            name: name.to_string(),
+            pid,
+            path: None,
+            user: None,
+            display_name: None,
+            started: None,
+            connections: Vec::new(),
+            bytes_in: 0,
+            bytes_out: 0,
+            rate_in: 0.0,
+            rate_out: 0.0,
+            packets_in: 0,
+            packets_out: 0,
+            pps_in: 0.0,
+            pps_out: 0.0,
+            peak_rate_in: 0.0,
+            peak_rate_out: 0.0,
+            new_connections: 0,
+            total_new_connections: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conn(remote_port: u16) -> Connection {
+        Connection::for_test(remote_port)
+    }
+
+    fn process(name: &str, pid: u32, connections: Vec<Connection>) -> Process {
+        Process {
+            connections,
+            ..Process::for_test(name, pid)
+        }
+    }
+
+    #[test]
+    fn test_churn_counts_opened_and_closed() {
+        let before = vec![process("curl", 10, vec![conn(443), conn(80)])];
+        let after = vec![
+            process("curl", 10, vec![conn(443), conn(8080)]),
+            process("ssh", 11, vec![conn(22)]),
+        ];
+
+        let churn = connection_churn(&connection_keys(&before), &after);
+        assert_eq!(churn.opened, 2);
+        assert_eq!(churn.closed, 1);
+        assert_eq!(churn.opened_by_process.get(&("curl".to_string(), 10)), Some(&1));
+        assert_eq!(churn.opened_by_process.get(&("ssh".to_string(), 11)), Some(&1));
+    }
+
+    #[test]
+    fn test_churn_same_tuple_different_pid_is_distinct() {
+        let before = vec![process("a", 1, vec![conn(443)])];
+        let after = vec![process("b", 2, vec![conn(443)])];
+
+        let churn = connection_churn(&connection_keys(&before), &after);
+        assert_eq!(churn.opened, 1);
+        assert_eq!(churn.closed, 1);
+    }
+
+    #[test]
+    fn test_port_stats_groups_and_skips_loopback() {
+        let mut local = conn(8080);
+        local.remote_addr = "127.0.0.1".to_string();
+        let mut busy = conn(443);
+        busy.rate_in = 1000.0;
+        let snapshot = NetworkSnapshot::from_processes(vec![
+            process("curl", 10, vec![conn(443), busy]),
+            process("node", 11, vec![local]),
+        ]);
+
+        let ports = snapshot.port_stats(false);
+        assert_eq!(ports.len(), 2);
+        assert_eq!(ports[0].port, 443);
+        assert_eq!(ports[0].connections, 2);
+
+        let ports = snapshot.port_stats(true);
+        assert_eq!(ports.len(), 1);
+        assert_eq!(ports[0].port, 443);
+    }
+
+    #[test]
+    fn test_top_remote_hosts() {
+        let mut resolved = conn(443);
+        resolved.hostname = Some("example.com".to_string());
+        resolved.rate_in = 500.0;
+        let mut pending = conn(443);
+        pending.remote_addr = "5.6.7.8".to_string();
+        pending.resolving = true;
+        pending.rate_out = 2000.0;
+        let mut quiet = conn(22);
+        quiet.remote_addr = "9.9.9.9".to_string();
+        quiet.bytes_in = 4096;
+        let mut same_host = resolved.clone();
+        same_host.rate_in = 700.0;
+        let snapshot = NetworkSnapshot::from_processes(vec![
+            process("curl", 10, vec![resolved, pending, quiet]),
+            process("ssh", 11, vec![same_host]),
+        ]);
+
+        let hosts = snapshot.top_remote_hosts(10);
+        let names: Vec<&str> = hosts.iter().map(|h| h.host.as_str()).collect();
+        assert_eq!(names, ["5.6.7.8", "example.com", "9.9.9.9"]);
+        assert_eq!(hosts[1].rate_in, 1200.0);
+        assert_eq!(hosts[1].processes, ["curl", "ssh"]);
+        assert_eq!(snapshot.top_remote_hosts(2).len(), 2);
+    }
+
+    #[test]
+    fn test_protocol_mix() {
+        let empty = NetworkSnapshot::from_processes(vec![process("curl", 10, vec![conn(443)])]);
+        assert!(empty.protocol_mix().is_empty());
+
+        let mut tcp = conn(443);
+        tcp.rate_in = 300.0;
+        tcp.bytes_in = 10;
+        let mut udp = conn(53);
+        udp.protocol = Protocol::Udp;
+        udp.remote_addr = "2001:db8::1".to_string();
+        udp.rate_out = 100.0;
+        let mut quiet = conn(22);
+        quiet.bytes_in = 5000;
+        let snapshot = NetworkSnapshot::from_processes(vec![process("curl", 10, vec![tcp.clone(), udp.clone(), quiet])]);
+        let mix = snapshot.protocol_mix();
+        assert_eq!((mix.tcp, mix.udp, mix.other), (300.0, 100.0, 0.0));
+        assert_eq!((mix.ipv4, mix.ipv6), (300.0, 100.0));
+
+        // With nothing moving, the bytes moved decide
+        tcp.rate_in = 0.0;
+        udp.rate_out = 0.0;
+        udp.bytes_out = 30;
+        let snapshot = NetworkSnapshot::from_processes(vec![process("curl", 10, vec![tcp, udp])]);
+        let mix = snapshot.protocol_mix();
+        assert_eq!((mix.tcp, mix.udp), (10.0, 30.0));
+        assert!(!mix.is_empty());
+    }
+
+    #[test]
+    fn test_state_counts() {
+        let state = |s: &str| {
+            let mut c = conn(443);
+            c.state = s.to_string();
+            c
+        };
+        let snapshot = NetworkSnapshot::from_processes(vec![
+            process("curl", 10, vec![state("Established"), state("ESTABLISHED"), state("SynSent")]),
+            process("node", 11, vec![state("CLOSE_WAIT"), state("CloseWait"), state("Established"), state("")]),
+        ]);
+        assert_eq!(
+            snapshot.state_counts(),
+            [
+                ("ESTABLISHED".to_string(), 3),
+                ("CLOSE_WAIT".to_string(), 2),
+                ("SYN_SENT".to_string(), 1),
+            ]
+        );
+        assert!(NetworkSnapshot::from_processes(vec![process("curl", 10, vec![conn(443)])])
+            .state_counts()
+            .is_empty());
+        assert_eq!(normalize_state("TimeWait"), "TIME_WAIT");
+        assert_eq!(normalize_state("close_wait"), "CLOSE_WAIT");
+        assert_eq!(normalize_state("LISTEN"), "LISTEN");
+    }
+
+    #[test]
+    fn test_churn_unchanged_snapshot() {
+        let procs = vec![process("curl", 10, vec![conn(443)])];
+        let churn = connection_churn(&connection_keys(&procs), &procs);
+        assert_eq!(churn.opened, 0);
+        assert_eq!(churn.closed, 0);
+        assert!(churn.opened_by_process.is_empty());
+    }
+
+    #[test]
+    fn test_idle_transitions() {
+        let mut processes = vec![process("ssh", 10, vec![conn(22)])];
+        let mut activity = HashMap::new();
+        let mut step = |processes: &mut Vec<Process>, bytes_in: u64| {
+            processes[0].connections[0].bytes_in = bytes_in;
+            activity = track_idle(&activity, processes, 3);
+            processes[0].connections[0].idle
+        };
+
+        // Active while counters move
+        assert!(!step(&mut processes, 100));
+        assert!(!step(&mut processes, 200));
+        // Unchanged for three snapshots: idle, and stays idle
+        assert!(!step(&mut processes, 200));
+        assert!(!step(&mut processes, 200));
+        assert!(step(&mut processes, 200));
+        assert!(step(&mut processes, 200));
+        // Traffic again: active immediately
+        assert!(!step(&mut processes, 300));
+        assert!(!step(&mut processes, 300));
+    }
+
+    #[test]
+    fn test_watch_matches() {
+        let dropbox = process("Dropbox", 4242, Vec::new());
+        assert!(Watch::Pid(4242).matches(&dropbox));
+        assert!(!Watch::Pid(1).matches(&dropbox));
+        assert!(Watch::Name("dropbox".to_string()).matches(&dropbox));
+        assert!(!Watch::Name("Drop".to_string()).matches(&dropbox));
+    }
+
+    #[test]
+    fn test_idle_disabled_and_pruned() {
+        let mut processes = vec![process("ssh", 10, vec![conn(22)])];
+        let mut activity = HashMap::new();
+        for _ in 0..5 {
+            activity = track_idle(&activity, &mut processes, 0);
+        }
+        assert!(!processes[0].connections[0].idle);
+        assert_eq!(activity.values().next().unwrap().idle_ticks, 4);
+
+        processes[0].connections.clear();
+        assert!(track_idle(&activity, &mut processes, 0).is_empty());
+    }
+
+    #[test]
+    fn test_sort_fields_honor_direction() {
+        let mut a = process("Safari", 300, vec![conn(443)]);
+        let mut b = process("curl", 20, vec![conn(443), conn(80), conn(22)]);
+        let mut c = process("bluetoothd", 1, vec![]);
+        (a.bytes_in, b.bytes_in, c.bytes_in) = (500, 100, 900);
+        (a.bytes_out, b.bytes_out, c.bytes_out) = (1, 3, 2);
+        (a.rate_in, b.rate_in, c.rate_in) = (10.0, 30.0, 20.0);
+        (a.rate_out, b.rate_out, c.rate_out) = (3.0, 1.0, 2.0);
+        (a.pps_in, b.pps_in, c.pps_in) = (2.0, 1.0, 3.0);
+        (a.pps_out, b.pps_out, c.pps_out) = (1.0, 2.0, 3.0);
+
+        let order = |field: SortField, direction: SortDirection| {
+            let mut processes = [a.clone(), b.clone(), c.clone()];
+            processes.sort_by(|x, y| direction.apply(field.compare(x, y)));
+            processes.iter().map(|p| p.pid).collect::<Vec<_>>()
+        };
+        let cases = [
+            (SortField::Name, [1, 20, 300]),
+            (SortField::Pid, [1, 20, 300]),
+            (SortField::Connections, [1, 300, 20]),
+            (SortField::BytesIn, [20, 300, 1]),
+            (SortField::BytesOut, [300, 1, 20]),
+            (SortField::RateIn, [300, 1, 20]),
+            (SortField::RateOut, [20, 1, 300]),
+            (SortField::PpsIn, [20, 300, 1]),
+            (SortField::PpsOut, [300, 20, 1]),
+        ];
+        for (field, ascending) in cases {
+            let mut descending = ascending;
+            descending.reverse();
+            assert_eq!(order(field, SortDirection::Asc), ascending, "{:?} asc", field);
+            assert_eq!(order(field, SortDirection::Desc), descending, "{:?} desc", field);
+        }
+        assert_eq!(SortField::Name.default_direction(), SortDirection::Asc);
+        assert_eq!(SortField::RateOut.default_direction(), SortDirection::Desc);
+    }
+
+    #[test]
+    fn test_sort_field_cycles_in_both_directions() {
+        for (i, field) in SortField::ALL.into_iter().enumerate() {
+            let following = SortField::ALL[(i + 1) % SortField::ALL.len()];
+            assert_eq!(field.next(), following);
+            assert_eq!(following.prev(), field);
+        }
+    }
+
+    #[test]
+    fn test_sort_order_ignores_input_order() {
+        let mut busy = process("curl", 40, vec![conn(443), conn(80)]);
+        busy.rate_in = 5.0;
+        let mut processes = vec![
+            busy,
+            process("Safari", 300, vec![conn(443), conn(8080), conn(80)]),
+            process("safari", 12, vec![]),
+            process("mDNSResponder", 7, vec![conn(53)]),
+        ];
+        let rows = |processes: &[Process]| {
+            processes
+                .iter()
+                .flat_map(|p| p.connections.iter().map(move |c| (p.pid, c.remote_port)).chain([(p.pid, 0)]))
+                .collect::<Vec<_>>()
+        };
+
+        sort_processes(&mut processes, SortField::RateIn, SortDirection::Desc);
+        let expected = rows(&processes);
+        assert_eq!(expected[..3], [(40, 80), (40, 443), (40, 0)]);
+        assert_eq!(processes.iter().map(|p| p.pid).collect::<Vec<_>>(), [40, 7, 12, 300]);
+
+        let mut shuffled = processes.clone();
+        shuffled.reverse();
+        for process in &mut shuffled {
+            process.connections.reverse();
+        }
+        shuffled.swap(0, 2);
+        sort_processes(&mut shuffled, SortField::RateIn, SortDirection::Desc);
+        assert_eq!(rows(&shuffled), expected);
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::process::Stdio;
+use tokio::process::Command;
+
+use super::addr;
+use super::model::{Process, Protocol};
+
+/// Socket details from `netstat -anv` that nettop does not report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SocketInfo {
+    pub protocol: Protocol,
+    pub local_addr: String,
+    pub local_port: u16,
+    pub remote_addr: String,
+    pub remote_port: u16,
+    pub state: String,
+    pub recv_q: u32,
+    pub send_q: u32,
+}
+
+/// Protocol plus local and remote address/port, with addresses normalized so
+/// nettop and netstat spellings of the same socket compare equal.
+type SocketKey = (Protocol, String, u16, String, u16);
+
+fn socket_key(protocol: &Protocol, local: &str, lport: u16, remote: &str, rport: u16) -> SocketKey {
+    let normalize = |a: &str| addr::parse_ip(a).map(|ip| ip.to_string()).unwrap_or_else(|| a.to_string());
+    (protocol.clone(), normalize(local), lport, normalize(remote), rport)
+}
+
+/// Run `netstat -anv` once and parse its TCP and UDP sockets.
+pub async fn fetch_sockets() -> Result<Vec<SocketInfo>, String> {
+    let output = Command::new("netstat")
+        .args(["-a", "-n", "-v"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run netstat: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(parse_netstat_anv(&stdout))
+}
+
+/// Parse socket rows from `netstat -anv`:
+/// ```text
+/// Proto Recv-Q Send-Q  Local Address          Foreign Address        (state)      rhiwat ...
+/// tcp4       0     35  192.168.1.20.52344     17.57.146.52.5223      ESTABLISHED  131072 ...
+/// udp4       0      0  *.5353                 *.*                                 786896 ...
+/// ```
+/// Addresses end in `.port`. UDP rows have no state column.
+fn parse_netstat_anv(output: &str) -> Vec<SocketInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 5 {
+                return None;
+            }
+            let protocol = if fields[0].starts_with("tcp") {
+                Protocol::Tcp
+            } else if fields[0].starts_with("udp") {
+                Protocol::Udp
+            } else {
+                return None;
+            };
+            let recv_q = fields[1].parse().ok()?;
+            let send_q = fields[2].parse().ok()?;
+            let (local_addr, local_port) = split_netstat_addr(fields[3]);
+            let (remote_addr, remote_port) = split_netstat_addr(fields[4]);
+            let state = match (&protocol, fields.get(5)) {
+                (Protocol::Tcp, Some(s)) if s.chars().all(|c| c.is_ascii_uppercase() || c == '_') => {
+                    s.to_string()
+                }
+                _ => String::new(),
+            };
+            Some(SocketInfo {
+                protocol,
+                local_addr,
+                local_port,
+                remote_addr,
+                remote_port,
+                state,
+                recv_q,
+                send_q,
+            })
+        })
+        .collect()
+}
+
+/// Split "192.168.1.20.52344", "fe80::1%lo0.5000", or "*.*" at the last dot.
+fn split_netstat_addr(s: &str) -> (String, u16) {
+    match s.rsplit_once('.') {
+        Some((addr, port)) => (addr.to_string(), port.parse().unwrap_or(0)),
+        None => (s.to_string(), 0),
+    }
+}
+
+/// Fill socket state and queue sizes on connections matching a netstat row.
+/// Connections without a match, and netstat rows without a connection, are
+/// left alone.
+pub fn enrich(processes: &mut [Process], sockets: &[SocketInfo]) {
+    let by_key: HashMap<SocketKey, &SocketInfo> = sockets
+        .iter()
+        .map(|s| {
+            let key = socket_key(&s.protocol, &s.local_addr, s.local_port, &s.remote_addr, s.remote_port);
+            (key, s)
+        })
+        .collect();
+
+    for proc in processes.iter_mut() {
+        for conn in proc.connections.iter_mut() {
+            let key = socket_key(
+                &conn.protocol,
+                &conn.local_addr,
+                conn.local_port,
+                &conn.remote_addr,
+                conn.remote_port,
+            );
+            if let Some(socket) = by_key.get(&key) {
+                if !socket.state.is_empty() {
+                    conn.state = socket.state.clone();
+                }
+                conn.recv_q = Some(socket.recv_q);
+                conn.send_q = Some(socket.send_q);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::model::Connection;
+
+    const SAMPLE: &str = "\
+Active Internet connections (including servers)
+Proto Recv-Q Send-Q  Local Address          Foreign Address        (state)      rhiwat  shiwat    pid   epid
+tcp4       0  65536  192.168.1.20.52344     17.57.146.52.5223      ESTABLISHED  131072  131768    412      0
+tcp6       0      0  fe80::1%lo0.5000       *.*                    LISTEN       131072  131072    900      0
+udp4      12      0  *.5353                 *.*                                 786896    9216    417      0
+";
+
+    #[test]
+    fn test_parse_netstat_anv() {
+        let sockets = parse_netstat_anv(SAMPLE);
+        assert_eq!(sockets.len(), 3);
+        assert_eq!(sockets[0].protocol, Protocol::Tcp);
+        assert_eq!(sockets[0].local_addr, "192.168.1.20");
+        assert_eq!(sockets[0].local_port, 52344);
+        assert_eq!(sockets[0].remote_port, 5223);
+        assert_eq!(sockets[0].state, "ESTABLISHED");
+        assert_eq!(sockets[0].send_q, 65536);
+        assert_eq!(sockets[1].local_addr, "fe80::1%lo0");
+        assert_eq!(sockets[1].state, "LISTEN");
+        assert_eq!(sockets[2].protocol, Protocol::Udp);
+        assert_eq!(sockets[2].state, "");
+        assert_eq!(sockets[2].recv_q, 12);
+    }
+
+    #[test]
+    fn test_enrich_tolerates_unmatched_rows() {
+        let conn = |port: u16| Connection {
+            local_addr: "192.168.1.20".to_string(),
+            local_port: port,
+            remote_addr: "17.57.146.52".to_string(),
+            ..Connection::for_test(5223)
+        };
+        let mut processes = vec![Process {
+            connections: vec![conn(52344), conn(60000)],
+            ..Process::for_test("apsd", 412)
+        }];
+        enrich(&mut processes, &parse_netstat_anv(SAMPLE));
+
+        let conns = &processes[0].connections;
+        assert_eq!(conns[0].state, "ESTABLISHED");
+        assert_eq!(conns[0].send_q, Some(65536));
+        assert_eq!(conns[1].state, "");
+        assert_eq!(conns[1].send_q, None);
+    }
+}
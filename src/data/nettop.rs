@@ -2,13 +2,16 @@ use std::collections::HashMap;
 use std::process::Stdio;
 use tokio::process::Command;
 
-use super::model::{Connection, Process, Protocol};
+use super::model::{Connection, ConnectionKey, Counters, Direction, Process, ProcessKey, Protocol};
+
+/// Columns requested from nettop via `-J`.
+const NETTOP_COLUMNS: &str = "interface,bytes_in,bytes_out,packets_in,packets_out";
 
 /// Fetch a snapshot from nettop (without -P to get per-connection detail).
 /// Uses `-x -J` for machine-readable CSV with selected columns.
 pub async fn fetch_nettop_snapshot() -> Result<Vec<Process>, String> {
     let output = Command::new("nettop")
-        .args(["-L", "1", "-x", "-J", "bytes_in,bytes_out"])
+        .args(["-L", "1", "-x", "-J", NETTOP_COLUMNS])
         .stdout(Stdio::piped())
         .stderr(Stdio::null())
         .output()
@@ -23,23 +26,24 @@ pub async fn fetch_nettop_snapshot() -> Result<Vec<Process>, String> {
 ///
 /// Format (without -P):
 /// ```text
-/// ,bytes_in,bytes_out,
-/// process_name.pid,bytes_in,bytes_out,
-/// tcp4 192.168.0.1:12345<->1.2.3.4:443,bytes_in,bytes_out,
-/// udp4 *:5353<->*:*,bytes_in,bytes_out,
-/// next_process.pid,bytes_in,bytes_out,
+/// ,interface,bytes_in,bytes_out,
+/// process_name.pid,,bytes_in,bytes_out,
+/// tcp4 192.168.0.1:12345<->1.2.3.4:443,en0,bytes_in,bytes_out,
+/// udp4 *:5353<->*:*,en0,bytes_in,bytes_out,
+/// next_process.pid,,bytes_in,bytes_out,
 /// ```
 ///
 /// Process lines have name.pid format. Connection lines start with a
-/// protocol prefix (tcp4, tcp6, udp4, udp6).
+/// protocol prefix (tcp4, udp6, icmp4, ...). Column positions are taken
+/// from the header line, so any subset/order of `-J` columns parses.
 fn parse_nettop_output(output: &str) -> Result<Vec<Process>, String> {
     let mut processes: Vec<Process> = Vec::new();
 
     let lines: Vec<&str> = output.lines().collect();
 
     // Find header line
-    let start = match lines.iter().position(|l| l.contains("bytes_in")) {
-        Some(i) => i + 1,
+    let (start, columns) = match lines.iter().position(|l| l.contains("bytes_in")) {
+        Some(i) => (i + 1, Columns::from_header(lines[i])),
         None => return Ok(Vec::new()),
     };
 
@@ -59,7 +63,7 @@ fn parse_nettop_output(output: &str) -> Result<Vec<Process>, String> {
         if is_connection_line(first_field) {
             // This is a connection line belonging to the current process
             if let Some(ref mut proc) = current_process {
-                if let Some(conn) = parse_connection_line(line) {
+                if let Some(conn) = parse_connection_line(line, &columns) {
                     proc.connections.push(conn);
                 }
             }
@@ -70,7 +74,7 @@ fn parse_nettop_output(output: &str) -> Result<Vec<Process>, String> {
                     processes.push(proc);
                 }
             }
-            current_process = parse_process_line(line);
+            current_process = parse_process_line(line, &columns);
         }
     }
 
@@ -84,16 +88,56 @@ fn parse_nettop_output(output: &str) -> Result<Vec<Process>, String> {
     Ok(processes)
 }
 
-/// Check if a first CSV field is a connection line (starts with protocol prefix).
+/// Positions of the `-J` columns within each CSV line.
+struct Columns {
+    interface: Option<usize>,
+    bytes_in: Option<usize>,
+    bytes_out: Option<usize>,
+    packets_in: Option<usize>,
+    packets_out: Option<usize>,
+}
+
+impl Columns {
+    fn from_header(header: &str) -> Self {
+        let names: Vec<&str> = header.split(',').map(str::trim).collect();
+        let find = |name: &str| names.iter().position(|n| *n == name);
+        Columns {
+            interface: find("interface"),
+            bytes_in: find("bytes_in"),
+            bytes_out: find("bytes_out"),
+            packets_in: find("packets_in"),
+            packets_out: find("packets_out"),
+        }
+    }
+}
+
+/// Trimmed CSV field at a column position, empty if the column is absent.
+fn field<'a>(parts: &[&'a str], col: Option<usize>) -> &'a str {
+    col.and_then(|i| parts.get(i)).map(|v| v.trim()).unwrap_or("")
+}
+
+fn field_u64(parts: &[&str], col: Option<usize>) -> u64 {
+    field(parts, col).parse::<u64>().unwrap_or(0)
+}
+
+/// Protocol prefixes nettop puts in front of connection descriptions.
+const CONNECTION_PREFIXES: &[&str] = &["tcp4", "tcp6", "udp4", "udp6", "icmp4", "icmp6", "raw4", "raw6"];
+
+/// Check if a first CSV field is a connection line: a known protocol prefix,
+/// or any lowercase token followed by a `local<->remote` pair so prefixes
+/// nettop adds later aren't mistaken for process lines.
 fn is_connection_line(first_field: &str) -> bool {
-    first_field.starts_with("tcp4 ")
-        || first_field.starts_with("tcp6 ")
-        || first_field.starts_with("udp4 ")
-        || first_field.starts_with("udp6 ")
+    let Some((prefix, rest)) = first_field.split_once(' ') else {
+        return false;
+    };
+    CONNECTION_PREFIXES.contains(&prefix)
+        || (rest.contains("<->")
+            && !prefix.is_empty()
+            && prefix.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()))
 }
 
-/// Parse a process summary line: "ProcessName.PID,bytes_in,bytes_out,"
-fn parse_process_line(line: &str) -> Option<Process> {
+/// Parse a process summary line: "ProcessName.PID,,bytes_in,bytes_out,"
+fn parse_process_line(line: &str, columns: &Columns) -> Option<Process> {
     let parts: Vec<&str> = line.split(',').collect();
     if parts.is_empty() {
         return None;
@@ -105,47 +149,69 @@ fn parse_process_line(line: &str) -> Option<Process> {
         return None;
     }
 
-    let bytes_in = parts.get(1).and_then(|v| v.trim().parse::<u64>().ok()).unwrap_or(0);
-    let bytes_out = parts.get(2).and_then(|v| v.trim().parse::<u64>().ok()).unwrap_or(0);
+    let bytes_in = field_u64(&parts, columns.bytes_in);
+    let bytes_out = field_u64(&parts, columns.bytes_out);
+    let packets_in = field_u64(&parts, columns.packets_in);
+    let packets_out = field_u64(&parts, columns.packets_out);
 
     Some(Process {
         name,
         pid,
         path: None,
+        user: None,
+        display_name: None,
+        started: None,
         connections: Vec::new(),
         bytes_in,
         bytes_out,
         rate_in: 0.0,
         rate_out: 0.0,
+        packets_in,
+        packets_out,
+        pps_in: 0.0,
+        pps_out: 0.0,
+        peak_rate_in: 0.0,
+        peak_rate_out: 0.0,
+        new_connections: 0,
+        total_new_connections: 0,
     })
 }
 
-/// Parse a connection line: "tcp4 192.168.0.1:12345<->1.2.3.4:443,bytes_in,bytes_out,"
-fn parse_connection_line(line: &str) -> Option<Connection> {
+/// Parse a connection line: "tcp4 192.168.0.1:12345<->1.2.3.4:443,en0,bytes_in,bytes_out,"
+fn parse_connection_line(line: &str, columns: &Columns) -> Option<Connection> {
     let parts: Vec<&str> = line.split(',').collect();
     if parts.is_empty() {
         return None;
     }
 
     let desc = parts[0].trim();
-    let bytes_in = parts.get(1).and_then(|v| v.trim().parse::<u64>().ok()).unwrap_or(0);
-    let bytes_out = parts.get(2).and_then(|v| v.trim().parse::<u64>().ok()).unwrap_or(0);
+    let interface = field(&parts, columns.interface).to_string();
+    let bytes_in = field_u64(&parts, columns.bytes_in);
+    let bytes_out = field_u64(&parts, columns.bytes_out);
 
     // desc = "tcp4 192.168.0.227:61859<->17.57.146.59:5223"
     // or    "udp6 *.5353<->*.*"
+    // or    "icmp4 192.168.0.227<->8.8.8.8" (no ports)
     let (proto_str, addr_part) = desc.split_once(' ')?;
 
     let protocol = match proto_str {
         "tcp4" | "tcp6" => Protocol::Tcp,
         "udp4" | "udp6" => Protocol::Udp,
+        "icmp4" | "icmp6" => Protocol::Icmp,
         _ => Protocol::Other(proto_str.to_string()),
     };
 
     // Split on <-> separator
     let (local_str, remote_str) = addr_part.split_once("<->")?;
 
-    let (local_addr, local_port) = parse_addr_port(local_str);
-    let (remote_addr, remote_port) = parse_addr_port(remote_str);
+    let (local_addr, local_port, remote_addr, remote_port) = if protocol.has_ports() {
+        let (local_addr, local_port) = parse_addr_port(local_str);
+        let (remote_addr, remote_port) = parse_addr_port(remote_str);
+        (local_addr, local_port, remote_addr, remote_port)
+    } else {
+        // A trailing ".227" here is part of the address, not a port
+        (local_str.trim().to_string(), 0, remote_str.trim().to_string(), 0)
+    };
 
     Some(Connection {
         local_addr,
@@ -154,10 +220,21 @@ fn parse_connection_line(line: &str) -> Option<Connection> {
         remote_port,
         protocol,
         state: String::new(),
-        interface: String::new(),
+        interface,
+        via_vpn: false,
+        direction: Direction::Unknown,
         bytes_in,
         bytes_out,
+        rate_in: 0.0,
+        rate_out: 0.0,
         hostname: None,
+        resolving: false,
+        asn: None,
+        first_seen: None,
+        recv_q: None,
+        send_q: None,
+        send_stalled: false,
+        idle: false,
     })
 }
 
@@ -208,16 +285,50 @@ fn parse_addr_port(s: &str) -> (String, u16) {
 /// Compute rates by comparing two snapshots taken `interval_secs` apart.
 pub fn compute_rates(
     current: &mut [Process],
-    previous: &HashMap<(String, u32), (u64, u64)>,
+    previous: &HashMap<ProcessKey, Counters>,
     interval_secs: f64,
 ) {
     for proc in current.iter_mut() {
-        let key = (proc.name.clone(), proc.pid);
-        if let Some(&(prev_in, prev_out)) = previous.get(&key) {
-            let delta_in = proc.bytes_in.saturating_sub(prev_in);
-            let delta_out = proc.bytes_out.saturating_sub(prev_out);
+        if let Some(prev) = previous.get(&proc.key()) {
+            let delta_in = proc.bytes_in.saturating_sub(prev.bytes_in);
+            let delta_out = proc.bytes_out.saturating_sub(prev.bytes_out);
             proc.rate_in = delta_in as f64 / interval_secs;
             proc.rate_out = delta_out as f64 / interval_secs;
+            proc.pps_in = proc.packets_in.saturating_sub(prev.packets_in) as f64 / interval_secs;
+            proc.pps_out = proc.packets_out.saturating_sub(prev.packets_out) as f64 / interval_secs;
+        }
+    }
+}
+
+/// Bytes (in + out) moved since `previous` by processes present in both
+/// samples. Only deltas count, so traffic from before the first sample
+/// never does, and a counter reset contributes zero.
+pub fn transferred(current: &[Process], previous: &HashMap<ProcessKey, Counters>) -> u64 {
+    current
+        .iter()
+        .filter_map(|proc| {
+            let prev = previous.get(&proc.key())?;
+            Some(
+                proc.bytes_in.saturating_sub(prev.bytes_in)
+                    + proc.bytes_out.saturating_sub(prev.bytes_out),
+            )
+        })
+        .sum()
+}
+
+/// Compute per-connection rates the same way `compute_rates` does for processes.
+pub fn compute_connection_rates(
+    current: &mut [Process],
+    previous: &HashMap<ConnectionKey, (u64, u64)>,
+    interval_secs: f64,
+) {
+    for proc in current.iter_mut() {
+        let pid = proc.pid;
+        for conn in proc.connections.iter_mut() {
+            if let Some(&(prev_in, prev_out)) = previous.get(&conn.key(pid)) {
+                conn.rate_in = conn.bytes_in.saturating_sub(prev_in) as f64 / interval_secs;
+                conn.rate_out = conn.bytes_out.saturating_sub(prev_out) as f64 / interval_secs;
+            }
         }
     }
 }
@@ -271,6 +382,9 @@ mod tests {
         assert!(is_connection_line("udp6 *.5353<->*.*"));
         assert!(!is_connection_line("firefox.1234"));
         assert!(!is_connection_line("Microsoft Teams.1263"));
+        assert!(is_connection_line("icmp4 192.168.0.227<->8.8.8.8"));
+        assert!(is_connection_line("icmp6 fe80::1%en0<->ff02::1"));
+        assert!(is_connection_line("raw6 *.*<->*.*"));
     }
 
     #[test]
@@ -297,4 +411,96 @@ tcp4 192.168.0.227:50501<->172.211.123.248:443,11296,3375,
         let mdns = processes.iter().find(|p| p.name == "mDNSResponder").unwrap();
         assert_eq!(mdns.connections.len(), 2);
     }
+
+    #[test]
+    fn test_parse_output_with_interface_column() {
+        let output = r#",interface,bytes_in,bytes_out,
+apsd.376,,7387,24329,
+tcp4 10.8.0.2:61859<->17.57.146.59:5223,utun3,7387,24329,
+"#;
+        let processes = parse_nettop_output(output).unwrap();
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0].bytes_in, 7387);
+        assert_eq!(processes[0].bytes_out, 24329);
+        assert_eq!(processes[0].connections[0].interface, "utun3");
+        assert_eq!(processes[0].connections[0].bytes_out, 24329);
+    }
+
+    #[test]
+    fn test_parse_output_with_icmp_rows() {
+        // nettop sample taken while running `ping 8.8.8.8` and `ping6 ff02::1%en0`
+        let output = r#",interface,bytes_in,bytes_out,
+ping.5120,,1344,1344,
+icmp4 192.168.0.227<->8.8.8.8,en0,1344,1344,
+ping6.5188,,560,560,
+icmp6 fe80::1c9b:e73b:41dd:4aa1%en0<->ff02::1%en0,en0,560,560,
+mDNSResponder.417,,542567,138705,
+udp6 *.5353<->*.*,en0,542567,138705,
+"#;
+        let processes = parse_nettop_output(output).unwrap();
+        assert_eq!(processes.len(), 3);
+
+        let ping = &processes[0].connections[0];
+        assert_eq!(processes[0].name, "ping");
+        assert_eq!(ping.protocol, Protocol::Icmp);
+        assert_eq!(ping.local_addr, "192.168.0.227");
+        assert_eq!(ping.local_port, 0);
+        assert_eq!(ping.remote_addr, "8.8.8.8");
+        assert_eq!(ping.remote_port, 0);
+        assert_eq!(ping.bytes_in, 1344);
+
+        let ping6 = &processes[1].connections[0];
+        assert_eq!(ping6.protocol, Protocol::Icmp);
+        assert_eq!(ping6.remote_addr, "ff02::1%en0");
+        assert_eq!(ping6.protocol.to_string(), "ICMP");
+
+        assert_eq!(processes[2].connections[0].protocol, Protocol::Udp);
+    }
+
+    #[test]
+    fn test_compute_rates_includes_packets() {
+        let output = r#",bytes_in,bytes_out,packets_in,packets_out,
+mDNSResponder.417,6000,2000,900,300,
+"#;
+        let mut processes = parse_nettop_output(output).unwrap();
+        assert_eq!(processes[0].packets_in, 900);
+
+        let mut previous = HashMap::new();
+        previous.insert(
+            processes[0].key(),
+            Counters {
+                bytes_in: 2000,
+                bytes_out: 2000,
+                packets_in: 100,
+                packets_out: 400,
+            },
+        );
+        compute_rates(&mut processes, &previous, 2.0);
+        assert_eq!(processes[0].rate_in, 2000.0);
+        assert_eq!(processes[0].pps_in, 400.0);
+        // Counter reset yields zero, never a negative rate
+        assert_eq!(processes[0].pps_out, 0.0);
+    }
+
+    #[test]
+    fn test_transferred_counts_only_deltas() {
+        let output = r#",bytes_in,bytes_out,
+Safari.100,6000,3000,
+curl.200,500,100,
+launchd.1,9000000,0,
+"#;
+        let processes = parse_nettop_output(output).unwrap();
+        let counters = |bytes_in, bytes_out| Counters {
+            bytes_in,
+            bytes_out,
+            packets_in: 0,
+            packets_out: 0,
+        };
+        let mut previous = HashMap::new();
+        previous.insert(("Safari".to_string(), 100), counters(5000, 2500));
+        // Restarted counters contribute nothing
+        previous.insert(("curl".to_string(), 200), counters(800, 900));
+        // launchd.1 is new this sample, so its lifetime total is ignored
+        assert_eq!(transferred(&processes, &previous), 1500);
+    }
 }
@@ -1,3 +1,9 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
 use super::model::Process;
 
 /// Enrich processes with full executable paths using libproc.
@@ -6,9 +12,341 @@ pub fn enrich_process_paths(processes: &mut [Process]) {
         if proc.pid == 0 {
             continue;
         }
-        match libproc::libproc::proc_pid::pidpath(proc.pid as i32) {
-            Ok(path) => proc.path = Some(path),
-            Err(_) => {}
+        if let Ok(path) = libproc::libproc::proc_pid::pidpath(proc.pid as i32) {
+            proc.path = Some(path);
+        }
+    }
+}
+
+/// Give processes that live inside an .app bundle a friendly name built from
+/// the bundle's display name. `bundle_names` caches bundle path → name so
+/// each Info.plist is read once.
+pub fn enrich_display_names(
+    processes: &mut [Process],
+    bundle_names: &mut HashMap<String, Option<String>>,
+) {
+    for proc in processes.iter_mut() {
+        let Some(path) = proc.path.as_deref() else {
+            continue;
+        };
+        let Some(bundle) = app_bundle(path) else {
+            continue;
+        };
+        let app_name = bundle_names
+            .entry(bundle.to_string())
+            .or_insert_with(|| read_bundle_name(bundle))
+            .clone();
+        proc.display_name = app_name.map(|app| friendly_name(&app, &proc.name));
+    }
+}
+
+/// Outermost "/…/Name.app" prefix of an executable path, so helpers nested
+/// in frameworks or plug-ins inside an app are attributed to the app.
+fn app_bundle(path: &str) -> Option<&str> {
+    let end = path.find(".app/")? + ".app".len();
+    Some(&path[..end])
+}
+
+/// CFBundleDisplayName or CFBundleName from the bundle's Info.plist, falling
+/// back to the bundle's file name. Binary plists are converted with plutil.
+fn read_bundle_name(bundle: &str) -> Option<String> {
+    let plist = Path::new(bundle).join("Contents").join("Info.plist");
+    let xml = match std::fs::read(&plist) {
+        Ok(bytes) if !bytes.starts_with(b"bplist") => String::from_utf8_lossy(&bytes).into_owned(),
+        Ok(_) => Command::new("plutil")
+            .args(["-convert", "xml1", "-o", "-"])
+            .arg(&plist)
+            .output()
+            .ok()
+            .map(|out| String::from_utf8_lossy(&out.stdout).into_owned())
+            .unwrap_or_default(),
+        Err(_) => String::new(),
+    };
+    plist_string(&xml, "CFBundleDisplayName")
+        .or_else(|| plist_string(&xml, "CFBundleName"))
+        .or_else(|| {
+            Path::new(bundle)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+        })
+}
+
+/// Value of a top-level `<key>…</key><string>…</string>` pair in an XML plist.
+fn plist_string(xml: &str, key: &str) -> Option<String> {
+    let after_key = &xml[xml.find(&format!("<key>{}</key>", key))?..];
+    let start = after_key.find("<string>")? + "<string>".len();
+    let end = start + after_key[start..].find("</string>")?;
+    let value = after_key[start..end]
+        .trim()
+        .replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">");
+    (!value.is_empty()).then_some(value)
+}
+
+/// "Safari" for the app's main process, "Safari (WebKit Networking)" for a
+/// helper it runs.
+fn friendly_name(app_name: &str, process_name: &str) -> String {
+    let helper = helper_label(process_name);
+    let helper = helper.strip_prefix(app_name).map(str::trim).unwrap_or(&helper);
+    if helper.is_empty() || helper.eq_ignore_ascii_case(app_name) {
+        app_name.to_string()
+    } else {
+        format!("{} ({})", app_name, helper)
+    }
+}
+
+/// Readable form of a process name: reverse-DNS names lose their vendor
+/// prefix ("com.apple.WebKit.Networking" → "WebKit Networking").
+fn helper_label(name: &str) -> String {
+    let parts: Vec<&str> = name.split('.').collect();
+    let reverse_dns = parts.len() >= 3
+        && matches!(parts[0], "com" | "org" | "net" | "io" | "dev" | "app")
+        && parts.iter().all(|p| !p.is_empty() && !p.contains(' '));
+    if reverse_dns {
+        parts[2..].join(" ")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Owner and start time of a process.
+struct BsdInfo {
+    uid: u32,
+    started: Option<SystemTime>,
+}
+
+/// Fill in the owning user and start time of each process. Processes we
+/// can't inspect (SIP-protected, already exited) are left without them.
+pub fn enrich_process_info(processes: &mut [Process]) {
+    for proc in processes.iter_mut() {
+        if let Some(info) = bsd_info(proc.pid) {
+            proc.user = Some(username(info.uid));
+            proc.started = info.started;
         }
     }
 }
+
+#[cfg(target_os = "macos")]
+fn bsd_info(pid: u32) -> Option<BsdInfo> {
+    use libproc::libproc::bsd_info::BSDInfo;
+    use libproc::libproc::proc_pid::pidinfo;
+
+    let info = pidinfo::<BSDInfo>(pid as i32, 0).ok()?;
+    // A zero start time means the kernel didn't tell us, not 1970
+    let started = (info.pbi_start_tvsec > 0).then(|| {
+        SystemTime::UNIX_EPOCH
+            + std::time::Duration::new(info.pbi_start_tvsec, info.pbi_start_tvusec as u32 * 1000)
+    });
+    Some(BsdInfo {
+        uid: info.pbi_uid,
+        started,
+    })
+}
+
+#[cfg(not(target_os = "macos"))]
+fn bsd_info(pid: u32) -> Option<BsdInfo> {
+    use std::os::unix::fs::MetadataExt;
+
+    let meta = std::fs::metadata(format!("/proc/{}", pid)).ok()?;
+    Some(BsdInfo {
+        uid: meta.uid(),
+        started: None,
+    })
+}
+
+/// Command line and working directory of one process, fetched on demand
+/// for the detail view rather than every refresh.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessDetails {
+    pub pid: u32,
+    /// Full argv; empty when it couldn't be read (permissions, zombies)
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+}
+
+pub fn fetch_process_details(pid: u32) -> ProcessDetails {
+    ProcessDetails {
+        pid,
+        args: process_args(pid),
+        cwd: process_cwd(pid),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn process_args(pid: u32) -> Vec<String> {
+    procargs2(pid).map(|buf| parse_procargs2(&buf)).unwrap_or_default()
+}
+
+/// Raw KERN_PROCARGS2 buffer for a process.
+#[cfg(target_os = "macos")]
+fn procargs2(pid: u32) -> Option<Vec<u8>> {
+    let mut mib = [libc::CTL_KERN, libc::KERN_ARGMAX];
+    let mut argmax: libc::c_int = 0;
+    let mut size = std::mem::size_of::<libc::c_int>();
+    let ret = unsafe {
+        libc::sysctl(
+            mib.as_mut_ptr(),
+            2,
+            &mut argmax as *mut libc::c_int as *mut libc::c_void,
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if ret != 0 || argmax <= 0 {
+        return None;
+    }
+
+    let mut buf = vec![0u8; argmax as usize];
+    let mut mib = [libc::CTL_KERN, libc::KERN_PROCARGS2, pid as libc::c_int];
+    let mut size = buf.len();
+    let ret = unsafe {
+        libc::sysctl(
+            mib.as_mut_ptr(),
+            3,
+            buf.as_mut_ptr() as *mut libc::c_void,
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if ret != 0 {
+        return None;
+    }
+    buf.truncate(size);
+    Some(buf)
+}
+
+#[cfg(target_os = "macos")]
+fn process_cwd(pid: u32) -> Option<String> {
+    let mut info: libc::proc_vnodepathinfo = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::proc_vnodepathinfo>() as libc::c_int;
+    let ret = unsafe {
+        libc::proc_pidinfo(
+            pid as libc::c_int,
+            libc::PROC_PIDVNODEPATHINFO,
+            0,
+            &mut info as *mut libc::proc_vnodepathinfo as *mut libc::c_void,
+            size,
+        )
+    };
+    if ret != size {
+        return None;
+    }
+    // vip_path is a MAXPATHLEN buffer declared as nested arrays
+    let path = info.pvi_cdir.vip_path.as_ptr() as *const libc::c_char;
+    let path = unsafe { std::ffi::CStr::from_ptr(path) }.to_string_lossy().into_owned();
+    (!path.is_empty()).then_some(path)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn process_args(pid: u32) -> Vec<String> {
+    std::fs::read(format!("/proc/{}/cmdline", pid))
+        .map(|buf| {
+            buf.split(|&b| b == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn process_cwd(pid: u32) -> Option<String> {
+    std::fs::read_link(format!("/proc/{}/cwd", pid))
+        .ok()
+        .map(|p| p.display().to_string())
+}
+
+/// Parse a KERN_PROCARGS2 buffer: a native-endian argc, the exec path,
+/// NUL padding, then argc NUL-terminated arguments (followed by the
+/// environment, which is ignored).
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_procargs2(buf: &[u8]) -> Vec<String> {
+    let Some((argc, rest)) = buf.split_first_chunk::<4>() else {
+        return Vec::new();
+    };
+    let argc = i32::from_ne_bytes(*argc).max(0) as usize;
+    let Some(path_end) = rest.iter().position(|&b| b == 0) else {
+        return Vec::new();
+    };
+    let rest = &rest[path_end..];
+    let args_start = rest.iter().position(|&b| b != 0).unwrap_or(rest.len());
+    rest[args_start..]
+        .split(|&b| b == 0)
+        .take(argc)
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect()
+}
+
+/// Login name for a uid, or the uid itself when it has no passwd entry.
+/// Names are cached since the same handful of uids repeat every refresh.
+fn username(uid: u32) -> String {
+    static NAMES: OnceLock<Mutex<HashMap<u32, String>>> = OnceLock::new();
+    let mut names = NAMES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    names
+        .entry(uid)
+        .or_insert_with(|| lookup_username(uid).unwrap_or_else(|| uid.to_string()))
+        .clone()
+}
+
+fn lookup_username(uid: u32) -> Option<String> {
+    let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let mut result = std::ptr::null_mut();
+    let ret = unsafe { libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result) };
+    if ret != 0 || result.is_null() || pwd.pw_name.is_null() {
+        return None;
+    }
+    let name = unsafe { std::ffi::CStr::from_ptr(pwd.pw_name) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_bundle_uses_outermost_app() {
+        assert_eq!(
+            app_bundle("/Applications/Slack.app/Contents/Frameworks/Slack Helper.app/Contents/MacOS/Slack Helper"),
+            Some("/Applications/Slack.app")
+        );
+        assert_eq!(app_bundle("/usr/sbin/mDNSResponder"), None);
+    }
+
+    #[test]
+    fn test_plist_string() {
+        let xml = "<plist><dict>\n\t<key>CFBundleIdentifier</key>\n\t<string>com.apple.Safari</string>\n\t                   <key>CFBundleName</key>\n\t<string>Safari</string>\n</dict></plist>";
+        assert_eq!(plist_string(xml, "CFBundleName"), Some("Safari".to_string()));
+        assert_eq!(plist_string(xml, "CFBundleDisplayName"), None);
+    }
+
+    #[test]
+    fn test_friendly_name() {
+        assert_eq!(friendly_name("Safari", "Safari"), "Safari");
+        assert_eq!(friendly_name("Safari", "com.apple.WebKit.Networking"), "Safari (WebKit Networking)");
+        assert_eq!(friendly_name("Firefox", "firefox-bin"), "Firefox (firefox-bin)");
+        assert_eq!(friendly_name("Slack", "Slack Helper"), "Slack (Helper)");
+    }
+
+    #[test]
+    fn test_parse_procargs2() {
+        let mut buf = 3i32.to_ne_bytes().to_vec();
+        buf.extend_from_slice(b"/usr/local/bin/node\0\0\0\0node\0server.js\0--port=3000\0PATH=/usr/bin\0");
+        assert_eq!(parse_procargs2(&buf), vec!["node", "server.js", "--port=3000"]);
+
+        assert!(parse_procargs2(&[1, 0]).is_empty());
+        assert!(parse_procargs2(&0i32.to_ne_bytes()).is_empty());
+    }
+
+    #[test]
+    fn test_username_resolves_root_and_falls_back_to_uid() {
+        assert_eq!(username(0), "root");
+        assert_eq!(username(4_000_000_000), "4000000000");
+    }
+}
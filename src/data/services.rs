@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use super::model::Protocol;
+
+const SERVICES_FILE: &str = "/etc/services";
+
+/// Well-known ports as (port, tcp, udp, name). Only common services are
+/// listed; unknown ports fall back to the bare number.
+const WELL_KNOWN: &[(u16, bool, bool, &str)] = &[
+    (7, true, true, "echo"),
+    (20, true, false, "ftp-data"),
+    (21, true, false, "ftp"),
+    (22, true, false, "ssh"),
+    (23, true, false, "telnet"),
+    (25, true, false, "smtp"),
+    (53, true, true, "dns"),
+    (67, false, true, "dhcp"),
+    (68, false, true, "dhcp"),
+    (69, false, true, "tftp"),
+    (79, true, false, "finger"),
+    (80, true, false, "http"),
+    (88, true, true, "kerberos"),
+    (110, true, false, "pop3"),
+    (113, true, false, "ident"),
+    (119, true, false, "nntp"),
+    (123, false, true, "ntp"),
+    (137, false, true, "netbios-ns"),
+    (138, false, true, "netbios-dgm"),
+    (139, true, false, "netbios-ssn"),
+    (143, true, false, "imap"),
+    (161, false, true, "snmp"),
+    (162, false, true, "snmptrap"),
+    (179, true, false, "bgp"),
+    (194, true, false, "irc"),
+    (389, true, false, "ldap"),
+    (443, true, true, "https"),
+    (445, true, false, "smb"),
+    (465, true, false, "smtps"),
+    (500, false, true, "isakmp"),
+    (514, false, true, "syslog"),
+    (548, true, false, "afp"),
+    (554, true, true, "rtsp"),
+    (587, true, false, "submission"),
+    (631, true, true, "ipp"),
+    (636, true, false, "ldaps"),
+    (853, true, true, "dns-over-tls"),
+    (873, true, false, "rsync"),
+    (993, true, false, "imaps"),
+    (995, true, false, "pop3s"),
+    (1080, true, false, "socks"),
+    (1194, true, true, "openvpn"),
+    (1433, true, false, "mssql"),
+    (1701, false, true, "l2tp"),
+    (1723, true, false, "pptp"),
+    (1883, true, false, "mqtt"),
+    (1900, false, true, "ssdp"),
+    (2049, true, true, "nfs"),
+    (3000, true, false, "dev-http"),
+    (3283, true, true, "net-assistant"),
+    (3306, true, false, "mysql"),
+    (3389, true, true, "rdp"),
+    (3478, true, true, "stun"),
+    (3690, true, false, "svn"),
+    (4500, false, true, "ipsec-nat-t"),
+    (5000, true, false, "airplay"),
+    (5060, true, true, "sip"),
+    (5061, true, false, "sips"),
+    (5223, true, false, "apns"),
+    (5228, true, false, "gcm"),
+    (5222, true, false, "xmpp"),
+    (5349, true, true, "turns"),
+    (5353, false, true, "mdns"),
+    (5432, true, false, "postgres"),
+    (5900, true, false, "vnc"),
+    (5672, true, false, "amqp"),
+    (6379, true, false, "redis"),
+    (6443, true, false, "kube-api"),
+    (6881, true, true, "bittorrent"),
+    (7000, true, false, "airplay"),
+    (8080, true, false, "http-alt"),
+    (8443, true, false, "https-alt"),
+    (8883, true, false, "mqtts"),
+    (9000, true, false, "http-alt"),
+    (9090, true, false, "prometheus"),
+    (9100, true, false, "jetdirect"),
+    (9200, true, false, "elasticsearch"),
+    (9418, true, false, "git"),
+    (11211, true, true, "memcached"),
+    (16384, false, true, "facetime"),
+    (27017, true, false, "mongodb"),
+    (41641, false, true, "tailscale"),
+    (51820, false, true, "wireguard"),
+];
+
+/// Look up the service name for a port, honoring the transport protocol.
+/// The embedded table wins; /etc/services fills in anything it lacks.
+pub fn service_name(port: u16, protocol: &Protocol) -> Option<&'static str> {
+    let transport = match protocol {
+        Protocol::Tcp => "tcp",
+        Protocol::Udp => "udp",
+        Protocol::Icmp | Protocol::Other(_) => return None,
+    };
+    WELL_KNOWN
+        .iter()
+        .find(|(p, tcp, udp, _)| *p == port && if transport == "tcp" { *tcp } else { *udp })
+        .map(|(_, _, _, name)| *name)
+        .or_else(|| system_services().get(&(port, transport)).map(String::as_str))
+}
+
+/// Format a port with its service name, e.g. "443 (https)", or the bare
+/// number when unknown.
+pub fn annotate_port(port: u16, protocol: &Protocol) -> String {
+    match service_name(port, protocol) {
+        Some(name) => format!("{} ({})", port, name),
+        None => port.to_string(),
+    }
+}
+
+/// Services from /etc/services, loaded once. Missing file means empty map.
+fn system_services() -> &'static HashMap<(u16, &'static str), String> {
+    static SERVICES: OnceLock<HashMap<(u16, &'static str), String>> = OnceLock::new();
+    SERVICES.get_or_init(|| {
+        std::fs::read_to_string(SERVICES_FILE)
+            .map(|text| parse_services(&text))
+            .unwrap_or_default()
+    })
+}
+
+/// Parse /etc/services format: "name  port/proto  [aliases...]  # comment".
+fn parse_services(text: &str) -> HashMap<(u16, &'static str), String> {
+    let mut map = HashMap::new();
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        let mut fields = line.split_whitespace();
+        let (Some(name), Some(port_proto)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let Some((port, proto)) = port_proto.split_once('/') else {
+            continue;
+        };
+        let Ok(port) = port.parse::<u16>() else {
+            continue;
+        };
+        let proto = match proto {
+            "tcp" => "tcp",
+            "udp" => "udp",
+            _ => continue,
+        };
+        // Keep the first name listed for a port, as getservbyport does
+        map.entry((port, proto)).or_insert_with(|| name.to_string());
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_name_is_protocol_aware() {
+        assert_eq!(service_name(443, &Protocol::Tcp), Some("https"));
+        assert_eq!(service_name(5353, &Protocol::Udp), Some("mdns"));
+        assert_eq!(service_name(22, &Protocol::Udp), None);
+        assert_eq!(service_name(443, &Protocol::Other("icmp4".to_string())), None);
+        assert_eq!(service_name(443, &Protocol::Icmp), None);
+    }
+
+    #[test]
+    fn test_annotate_port_falls_back_to_number() {
+        assert_eq!(annotate_port(3478, &Protocol::Udp), "3478 (stun)");
+        assert_eq!(annotate_port(1, &Protocol::Other("raw".to_string())), "1");
+    }
+
+    #[test]
+    fn test_parse_services() {
+        let text = "# comment line\n\
+                    ssh      22/tcp   # Secure Shell\n\
+                    ssh      22/udp\n\
+                    domain   53/udp   nameserver\n\
+                    dup      53/udp\n\
+                    bogus    notaport/tcp\n\
+                    ddp      7/ddp\n";
+        let map = parse_services(text);
+        assert_eq!(map.get(&(22, "tcp")).map(String::as_str), Some("ssh"));
+        assert_eq!(map.get(&(22, "udp")).map(String::as_str), Some("ssh"));
+        assert_eq!(map.get(&(53, "udp")).map(String::as_str), Some("domain"));
+        assert_eq!(map.len(), 3);
+    }
+}
@@ -0,0 +1,409 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::data::model::{Connection, NetworkSnapshot, Process};
+use crate::units::Units;
+
+/// A snapshot as written to disk, with when it was taken and the filter
+/// that was active.
+#[derive(Serialize)]
+struct SnapshotExport<'a> {
+    captured_at: String,
+    filter: Option<&'a str>,
+    #[serde(flatten)]
+    snapshot: &'a NetworkSnapshot,
+}
+
+/// Version of the `--json` document layout; bumped on incompatible changes.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// The `--json` document: a snapshot plus when and how it was sampled.
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    schema_version: u32,
+    timestamp: String,
+    interval_secs: f64,
+    #[serde(flatten)]
+    snapshot: &'a NetworkSnapshot,
+}
+
+/// Render a snapshot for `--once --json`.
+pub fn snapshot_json(snapshot: &NetworkSnapshot, interval_secs: f64) -> String {
+    let output = JsonOutput {
+        schema_version: JSON_SCHEMA_VERSION,
+        timestamp: chrono::Local::now().to_rfc3339(),
+        interval_secs,
+        snapshot,
+    };
+    serde_json::to_string_pretty(&output).unwrap_or_default()
+}
+
+/// Compact one-line snapshot for `--stream`. With `fields`, only those keys
+/// are kept, both at the top level and in each process (`timestamp`, and a
+/// process's `name` and `pid`, are always kept).
+pub fn stream_line(snapshot: &NetworkSnapshot, timestamp: String, fields: &[String]) -> String {
+    let processes: Vec<serde_json::Value> = snapshot
+        .processes
+        .iter()
+        .map(|p| {
+            serde_json::json!({
+                "name": p.name,
+                "pid": p.pid,
+                "bytes_in": p.bytes_in,
+                "bytes_out": p.bytes_out,
+                "rate_in": p.rate_in,
+                "rate_out": p.rate_out,
+                "connections": p.connection_count(),
+            })
+        })
+        .collect();
+    let mut line = serde_json::json!({
+        "timestamp": timestamp,
+        "total_bytes_in": snapshot.total_bytes_in,
+        "total_bytes_out": snapshot.total_bytes_out,
+        "total_rate_in": snapshot.total_rate_in,
+        "total_rate_out": snapshot.total_rate_out,
+        "total_connections": snapshot.total_connections,
+        "processes": processes,
+    });
+    if !fields.is_empty() {
+        let keep = |key: &str, always: &[&str]| {
+            always.contains(&key) || fields.iter().any(|f| f == key)
+        };
+        if let Some(obj) = line.as_object_mut() {
+            obj.retain(|k, _| keep(k, &["timestamp"]));
+            if let Some(procs) = obj.get_mut("processes").and_then(|p| p.as_array_mut()) {
+                for p in procs.iter_mut().filter_map(|p| p.as_object_mut()) {
+                    p.retain(|k, _| keep(k, &["name", "pid"]));
+                }
+            }
+        }
+    }
+    line.to_string()
+}
+
+/// `{"error": "..."}` for scripts to detect a failed sample.
+pub fn error_json(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+/// Write `snapshot` as pretty-printed JSON to a timestamped file in `dir`
+/// and return its path.
+pub fn write_snapshot_json(
+    snapshot: &NetworkSnapshot,
+    filter: Option<&str>,
+    dir: &Path,
+) -> std::io::Result<PathBuf> {
+    let now = chrono::Local::now();
+    let export = SnapshotExport {
+        captured_at: now.to_rfc3339(),
+        filter,
+        snapshot,
+    };
+    let json = serde_json::to_string_pretty(&export)?;
+    let path = dir.join(format!("nm-snapshot-{}.json", now.format("%Y%m%d-%H%M%S")));
+    std::fs::write(&path, json)?;
+    Ok(path)
+}
+
+/// Quote a CSV field when it contains a delimiter, quote, or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_row(fields: &[String]) -> String {
+    let mut row = fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(",");
+    row.push('\n');
+    row
+}
+
+/// Processes table as CSV, in the given order. Byte and rate columns are
+/// raw numbers (bytes, bytes/sec).
+pub fn processes_csv(processes: &[&Process]) -> String {
+    let mut out = csv_row(
+        &[
+            "name", "pid", "user", "connections", "bytes_in", "bytes_out", "rate_in", "rate_out",
+            "packets_in", "packets_out", "pps_in", "pps_out", "peak_rate_in", "peak_rate_out",
+        ]
+        .map(String::from),
+    );
+    for p in processes {
+        out.push_str(&csv_row(&[
+            p.name.clone(),
+            p.pid.to_string(),
+            p.user.clone().unwrap_or_default(),
+            p.connection_count().to_string(),
+            p.bytes_in.to_string(),
+            p.bytes_out.to_string(),
+            p.rate_in.to_string(),
+            p.rate_out.to_string(),
+            p.packets_in.to_string(),
+            p.packets_out.to_string(),
+            p.pps_in.to_string(),
+            p.pps_out.to_string(),
+            p.peak_rate_in.to_string(),
+            p.peak_rate_out.to_string(),
+        ]));
+    }
+    out
+}
+
+/// Connections table as CSV, one row per (process name, pid, connection,
+/// closed) in the given order.
+pub fn connections_csv(rows: &[(&str, u32, &Connection, bool)]) -> String {
+    let mut out = csv_row(
+        &[
+            "process", "pid", "protocol", "local_addr", "local_port", "remote_addr", "remote_port",
+            "hostname", "interface", "direction", "state", "bytes_in", "bytes_out", "rate_in",
+            "rate_out", "asn",
+        ]
+        .map(String::from),
+    );
+    for (name, pid, conn, closed) in rows {
+        let state = if *closed { "closed" } else { conn.state.as_str() };
+        out.push_str(&csv_row(&[
+            name.to_string(),
+            pid.to_string(),
+            conn.protocol.to_string(),
+            conn.local_addr.clone(),
+            conn.local_port.to_string(),
+            conn.remote_addr.clone(),
+            conn.remote_port.to_string(),
+            conn.hostname.clone().unwrap_or_default(),
+            conn.interface.clone(),
+            format!("{:?}", conn.direction).to_lowercase(),
+            state.to_string(),
+            conn.bytes_in.to_string(),
+            conn.bytes_out.to_string(),
+            conn.rate_in.to_string(),
+            conn.rate_out.to_string(),
+            conn.asn.clone().unwrap_or_default(),
+        ]));
+    }
+    out
+}
+
+/// Write CSV text to `nm-<table>-<timestamp>.csv` in `dir`.
+pub fn write_csv(csv: &str, table: &str, dir: &Path) -> std::io::Result<PathBuf> {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let path = dir.join(format!("nm-{}-{}.csv", table, stamp));
+    std::fs::write(&path, csv)?;
+    Ok(path)
+}
+
+/// Plain-text table of the first `top` processes (all when 0), for output
+/// outside the TUI.
+pub fn processes_table(processes: &[&Process], top: usize, units: Units) -> String {
+    let shown = if top == 0 { processes.len() } else { top.min(processes.len()) };
+    let processes = &processes[..shown];
+    let name_width = processes
+        .iter()
+        .map(|p| p.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .clamp("PROCESS".len(), 40);
+
+    let mut out = format!(
+        "{:<name_width$}  {:>7}  {:>5}  {:>10}  {:>10}  {:>11}  {:>11}\n",
+        "PROCESS", "PID", "CONN", "DOWN", "UP", "RATE IN", "RATE OUT"
+    );
+    for p in processes {
+        let name: String = p.name.chars().take(name_width).collect();
+        out.push_str(&format!(
+            "{:<name_width$}  {:>7}  {:>5}  {:>10}  {:>10}  {:>11}  {:>11}\n",
+            name,
+            p.pid,
+            p.connection_count(),
+            units.bytes(p.bytes_in),
+            units.bytes(p.bytes_out),
+            units.rate(p.rate_in),
+            units.rate(p.rate_out),
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::model::Direction;
+
+    /// Minimal RFC 4180 reader for checking the writer's output.
+    fn parse_csv(text: &str) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+        let mut field = String::new();
+        let mut chars = text.chars().peekable();
+        let mut quoted = false;
+        while let Some(c) = chars.next() {
+            match (c, quoted) {
+                ('"', true) if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                ('"', _) => quoted = !quoted,
+                (',', false) => row.push(std::mem::take(&mut field)),
+                ('\n', false) => {
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                }
+                (c, _) => field.push(c),
+            }
+        }
+        rows
+    }
+
+    fn process(name: &str, connections: Vec<Connection>) -> Process {
+        Process {
+            user: Some("alice".to_string()),
+            connections,
+            bytes_in: 1_500_000,
+            bytes_out: 300,
+            rate_in: 1234.5,
+            packets_in: 10,
+            packets_out: 5,
+            pps_in: 2.5,
+            pps_out: 1.0,
+            peak_rate_in: 4096.0,
+            peak_rate_out: 12.0,
+            ..Process::for_test(name, 42)
+        }
+    }
+
+    fn conn() -> Connection {
+        Connection {
+            local_addr: "192.168.1.2".to_string(),
+            remote_addr: "17.57.146.59".to_string(),
+            state: "Established".to_string(),
+            interface: "en0".to_string(),
+            direction: Direction::Outbound,
+            bytes_in: 900,
+            bytes_out: 100,
+            rate_in: 50.0,
+            rate_out: 0.5,
+            hostname: Some("apple.com".to_string()),
+            asn: Some("Apple, Inc.".to_string()),
+            ..Connection::for_test(443)
+        }
+    }
+
+    #[test]
+    fn test_snapshot_json_schema() {
+        let snapshot = NetworkSnapshot::from_processes(vec![process("curl", vec![conn()])]);
+        let json: serde_json::Value = serde_json::from_str(&snapshot_json(&snapshot, 2.0)).unwrap();
+
+        assert_eq!(json["schema_version"], JSON_SCHEMA_VERSION);
+        assert_eq!(json["interval_secs"], 2.0);
+        assert!(json["timestamp"].is_string());
+        assert_eq!(json["total_bytes_in"], 1_500_000);
+        assert_eq!(json["total_connections"], 1);
+        let proc = &json["processes"][0];
+        assert_eq!(proc["name"], "curl");
+        assert_eq!(proc["user"], "alice");
+        assert_eq!(proc["started"], serde_json::Value::Null);
+        let c = &proc["connections"][0];
+        assert_eq!(c["protocol"], "tcp");
+        assert_eq!(c["direction"], "outbound");
+        assert_eq!(c["hostname"], "apple.com");
+        assert!(c.get("first_seen").is_none());
+
+        let err: serde_json::Value = serde_json::from_str(&error_json("nettop failed")).unwrap();
+        assert_eq!(err["error"], "nettop failed");
+    }
+
+    #[test]
+    fn test_stream_line_is_compact_and_filterable() {
+        let snapshot = NetworkSnapshot::from_processes(vec![process("curl", vec![conn()])]);
+        let line = stream_line(&snapshot, "t".to_string(), &[]);
+        assert!(!line.contains('\n'));
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["total_rate_in"], 1234.5);
+        assert_eq!(json["processes"][0]["rate_in"], 1234.5);
+        assert_eq!(json["processes"][0]["connections"], 1);
+
+        let fields = ["total_rate_in".to_string(), "processes".to_string(), "rate_out".to_string()];
+        let json: serde_json::Value =
+            serde_json::from_str(&stream_line(&snapshot, "t".to_string(), &fields)).unwrap();
+        let top: Vec<&String> = json.as_object().unwrap().keys().collect();
+        assert_eq!(top.len(), 3);
+        assert!(json.get("total_bytes_in").is_none());
+        let proc = json["processes"][0].as_object().unwrap();
+        assert_eq!(proc.len(), 3);
+        assert!(proc.contains_key("name") && proc.contains_key("pid") && proc.contains_key("rate_out"));
+    }
+
+    #[test]
+    fn test_processes_table_limits_and_aligns() {
+        let a = process("Google Chrome Helper", Vec::new());
+        let b = process("curl", Vec::new());
+        let c = process("ssh", Vec::new());
+        let table = processes_table(&[&a, &b, &c], 2, Units::Bytes);
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("PROCESS "));
+        assert!(lines[1].starts_with("Google Chrome Helper  "));
+        assert!(lines[2].starts_with("curl "));
+        assert!(lines[1].contains("1.4 MB"));
+        assert!(lines.iter().all(|l| l.chars().count() == lines[0].chars().count()));
+        assert_eq!(processes_table(&[&a, &b, &c], 0, Units::Bytes).lines().count(), 4);
+    }
+
+    #[test]
+    fn test_processes_csv_round_trip() {
+        let tricky = process("Helper, \"Beta\"", Vec::new());
+        let plain = process("curl", Vec::new());
+        let rows = parse_csv(&processes_csv(&[&tricky, &plain]));
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0][0], "name");
+        assert_eq!(rows[0].len(), rows[1].len());
+        assert_eq!(rows[1][0], "Helper, \"Beta\"");
+        assert_eq!(rows[2][0], "curl");
+        assert_eq!(rows[1][4].parse::<u64>().unwrap(), 1_500_000);
+        assert_eq!(rows[1][6].parse::<f64>().unwrap(), 1234.5);
+    }
+
+    #[test]
+    fn test_connections_csv_round_trip() {
+        let c = conn();
+        let rows = parse_csv(&connections_csv(&[("Safari", 7, &c, false), ("Safari", 7, &c, true)]));
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].len(), rows[1].len());
+        assert_eq!(rows[1][5], "17.57.146.59");
+        assert_eq!(rows[1][7], "apple.com");
+        assert_eq!(rows[1][9], "outbound");
+        assert_eq!(rows[1][10], "Established");
+        assert_eq!(rows[2][10], "closed");
+        assert_eq!(rows[1][15], "Apple, Inc.");
+        assert_eq!(rows[1][13].parse::<f64>().unwrap(), 50.0);
+    }
+
+    #[test]
+    fn test_snapshot_json_includes_totals_and_filter() {
+        let snapshot = NetworkSnapshot {
+            total_bytes_in: 2048,
+            total_rate_in: 512.0,
+            total_connections: 3,
+            ..NetworkSnapshot::default()
+        };
+        let dir = std::env::temp_dir().join(format!("nm-export-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = write_snapshot_json(&snapshot, Some("safari"), &dir).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(path.file_name().unwrap().to_string_lossy().starts_with("nm-snapshot-"));
+        assert_eq!(json["filter"], "safari");
+        assert_eq!(json["total_bytes_in"], 2048);
+        assert_eq!(json["total_rate_in"], 512.0);
+        assert!(json["processes"].as_array().unwrap().is_empty());
+    }
+}
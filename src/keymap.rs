@@ -0,0 +1,476 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Everything a key can be bound to outside of text entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    NextTab,
+    PrevTab,
+    NavDown,
+    NavUp,
+    PageDown,
+    PageUp,
+    NavFirst,
+    NavLast,
+    DrillDown,
+    Back,
+    ProcessDetail,
+    DetailPane,
+    CycleSort,
+    PrevSort,
+    FlipSort,
+    SortMenu,
+    Filter,
+    ClearFilter,
+    Search,
+    SearchNext,
+    SearchPrev,
+    ToggleServices,
+    ToggleHostnames,
+    ReloadAliases,
+    Copy,
+    CopyAlt,
+    ExportJson,
+    ExportCsv,
+    AlertLog,
+    ResetBudget,
+    ToggleLocal,
+    ToggleInbound,
+    ToggleIdle,
+    Pause,
+    Reset,
+    Help,
+    Columns,
+    ShrinkColumn,
+    GrowColumn,
+    CycleUnits,
+    ZoomIn,
+    ZoomOut,
+    GraphProcess,
+    ToggleLogScale,
+}
+
+impl Action {
+    pub const ALL: [Action; 45] = [
+        Action::Quit,
+        Action::NextTab,
+        Action::PrevTab,
+        Action::NavDown,
+        Action::NavUp,
+        Action::PageDown,
+        Action::PageUp,
+        Action::NavFirst,
+        Action::NavLast,
+        Action::DrillDown,
+        Action::Back,
+        Action::ProcessDetail,
+        Action::DetailPane,
+        Action::CycleSort,
+        Action::PrevSort,
+        Action::FlipSort,
+        Action::SortMenu,
+        Action::Filter,
+        Action::ClearFilter,
+        Action::Search,
+        Action::SearchNext,
+        Action::SearchPrev,
+        Action::ToggleServices,
+        Action::ToggleHostnames,
+        Action::ReloadAliases,
+        Action::Copy,
+        Action::CopyAlt,
+        Action::ExportJson,
+        Action::ExportCsv,
+        Action::AlertLog,
+        Action::ResetBudget,
+        Action::ToggleLocal,
+        Action::ToggleInbound,
+        Action::ToggleIdle,
+        Action::Pause,
+        Action::Reset,
+        Action::Help,
+        Action::Columns,
+        Action::ShrinkColumn,
+        Action::GrowColumn,
+        Action::CycleUnits,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::GraphProcess,
+        Action::ToggleLogScale,
+    ];
+
+    /// Name used in the `[keys]` config section.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::NextTab => "next-tab",
+            Action::PrevTab => "prev-tab",
+            Action::NavDown => "nav-down",
+            Action::NavUp => "nav-up",
+            Action::PageDown => "page-down",
+            Action::PageUp => "page-up",
+            Action::NavFirst => "nav-first",
+            Action::NavLast => "nav-last",
+            Action::DrillDown => "drill-down",
+            Action::Back => "back",
+            Action::ProcessDetail => "process-detail",
+            Action::DetailPane => "detail-pane",
+            Action::CycleSort => "cycle-sort",
+            Action::PrevSort => "prev-sort",
+            Action::FlipSort => "flip-sort",
+            Action::SortMenu => "sort-menu",
+            Action::Filter => "filter",
+            Action::ClearFilter => "clear-filter",
+            Action::Search => "search",
+            Action::SearchNext => "search-next",
+            Action::SearchPrev => "search-prev",
+            Action::ToggleServices => "toggle-services",
+            Action::ToggleHostnames => "toggle-hostnames",
+            Action::ReloadAliases => "reload-aliases",
+            Action::Copy => "copy",
+            Action::CopyAlt => "copy-alt",
+            Action::ExportJson => "export-json",
+            Action::ExportCsv => "export-csv",
+            Action::AlertLog => "alert-log",
+            Action::ResetBudget => "reset-budget",
+            Action::ToggleLocal => "toggle-local",
+            Action::ToggleInbound => "toggle-inbound",
+            Action::ToggleIdle => "toggle-idle",
+            Action::Pause => "pause",
+            Action::Reset => "reset",
+            Action::Help => "help",
+            Action::Columns => "columns",
+            Action::ShrinkColumn => "shrink-column",
+            Action::GrowColumn => "grow-column",
+            Action::CycleUnits => "cycle-units",
+            Action::ZoomIn => "zoom-in",
+            Action::ZoomOut => "zoom-out",
+            Action::GraphProcess => "graph-process",
+            Action::ToggleLogScale => "toggle-log-scale",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|a| a.name() == name)
+    }
+
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["q"],
+            Action::NextTab => &["Tab"],
+            Action::PrevTab => &["Shift-Tab"],
+            Action::NavDown => &["j", "Down"],
+            Action::NavUp => &["k", "Up"],
+            Action::PageDown => &["PageDown", "Ctrl-f"],
+            Action::PageUp => &["PageUp", "Ctrl-b"],
+            Action::NavFirst => &["g", "Home"],
+            Action::NavLast => &["G", "End"],
+            Action::DrillDown => &["Enter"],
+            Action::Back => &["Backspace"],
+            Action::ProcessDetail => &["d"],
+            Action::DetailPane => &["w"],
+            Action::CycleSort => &["s"],
+            Action::PrevSort => &["Ctrl-s"],
+            Action::FlipSort => &["S"],
+            Action::SortMenu => &["o"],
+            Action::Filter => &["/"],
+            Action::ClearFilter => &["Esc"],
+            Action::Search => &["f"],
+            Action::SearchNext => &["n"],
+            Action::SearchPrev => &["N"],
+            Action::ToggleServices => &["v"],
+            Action::ToggleHostnames => &["H"],
+            Action::ReloadAliases => &["R"],
+            Action::Copy => &["y"],
+            Action::CopyAlt => &["Y"],
+            Action::ExportJson => &["e"],
+            Action::ExportCsv => &["E"],
+            Action::AlertLog => &["a"],
+            Action::ResetBudget => &["B"],
+            Action::ToggleLocal => &["l"],
+            Action::ToggleInbound => &["i"],
+            Action::ToggleIdle => &["z"],
+            Action::Pause => &["p"],
+            Action::Reset => &["r"],
+            Action::Help => &["?"],
+            Action::Columns => &["c"],
+            Action::ShrinkColumn => &["<"],
+            Action::GrowColumn => &[">"],
+            Action::CycleUnits => &["b"],
+            Action::ZoomIn => &["+", "="],
+            Action::ZoomOut => &["-"],
+            Action::GraphProcess => &["t"],
+            Action::ToggleLogScale => &["m"],
+        }
+    }
+}
+
+/// A key with the modifiers that matter for binding. Shift is folded into
+/// the character itself (`H`, `?`), so it is only kept for non-characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+const NAMED_KEYS: [(&str, KeyCode); 15] = [
+    ("Tab", KeyCode::Tab),
+    ("Shift-Tab", KeyCode::BackTab),
+    ("Enter", KeyCode::Enter),
+    ("Esc", KeyCode::Esc),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Space", KeyCode::Char(' ')),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+];
+
+/// Strip a case-insensitive modifier prefix such as `Ctrl-`, as long as a
+/// key follows it.
+fn strip_modifier<'a>(text: &'a str, prefixes: &[&str]) -> Option<&'a str> {
+    prefixes.iter().find_map(|prefix| {
+        let head = text.get(..prefix.len())?;
+        let rest = &text[prefix.len()..];
+        (head.eq_ignore_ascii_case(prefix) && !rest.is_empty()).then_some(rest)
+    })
+}
+
+impl Key {
+    pub fn from_event(event: &KeyEvent) -> Self {
+        let mut modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let code = match event.code {
+            // Terminals report Ctrl-letter in either case; bind it lowercase
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            code => code,
+        };
+        if code == KeyCode::BackTab {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Key { code, modifiers }
+    }
+
+    /// Parse `q`, `Enter`, `Shift-Tab`, `Ctrl-c`, `Alt-x`, `F5`, ...
+    /// Named keys are case-insensitive; single characters are not.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        loop {
+            if let Some(stripped) = strip_modifier(rest, &["ctrl-", "c-"]) {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = stripped;
+            } else if let Some(stripped) = strip_modifier(rest, &["alt-", "m-"]) {
+                modifiers |= KeyModifiers::ALT;
+                rest = stripped;
+            } else {
+                break;
+            }
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest {
+                "↑" => KeyCode::Up,
+                "↓" => KeyCode::Down,
+                "←" => KeyCode::Left,
+                "→" => KeyCode::Right,
+                _ if rest.eq_ignore_ascii_case("backtab") => KeyCode::BackTab,
+                _ => NAMED_KEYS
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(rest))
+                    .map(|(_, code)| *code)
+                    .or_else(|| {
+                        let n = rest.strip_prefix(['F', 'f'])?.parse::<u8>().ok()?;
+                        (1..=12).contains(&n).then_some(KeyCode::F(n))
+                    })
+                    .ok_or_else(|| format!("unknown key {:?}", text))?,
+            },
+        };
+        Ok(Key { code, modifiers })
+    }
+
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::F(n) => format!("F{}", n),
+            code => NAMED_KEYS
+                .iter()
+                .find(|(_, named)| *named == code)
+                .map_or_else(|| format!("{:?}", code), |(name, _)| name.to_string()),
+        };
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push_str("Ctrl-");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("Alt-");
+        }
+        label + &key
+    }
+}
+
+/// Key bindings: the defaults, with any `[keys]` overrides from the config
+/// file applied.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    keys: HashMap<Action, Vec<Key>>,
+    actions: HashMap<Key, Action>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let keys = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let keys = action
+                    .default_keys()
+                    .iter()
+                    .map(|k| Key::parse(k).expect("default key binding"))
+                    .collect();
+                (action, keys)
+            })
+            .collect();
+        Self::build(keys).expect("default bindings conflict")
+    }
+}
+
+impl Keymap {
+    /// Rebind actions by name. Each override replaces all of that action's
+    /// default keys (an empty list unbinds it). A key left bound to two
+    /// actions is an error.
+    pub fn with_overrides(overrides: &BTreeMap<String, Vec<String>>) -> Result<Self, String> {
+        let mut keys = Keymap::default().keys;
+        for (name, bound) in overrides {
+            let action = Action::from_name(name).ok_or_else(|| format!("unknown action {:?}", name))?;
+            let parsed = bound
+                .iter()
+                .map(|k| Key::parse(k).map_err(|e| format!("{}: {}", name, e)))
+                .collect::<Result<Vec<_>, _>>()?;
+            keys.insert(action, parsed);
+        }
+        Self::build(keys)
+    }
+
+    fn build(keys: HashMap<Action, Vec<Key>>) -> Result<Self, String> {
+        let mut actions = HashMap::new();
+        // Walk in a fixed order so the conflict reported is deterministic
+        for action in Action::ALL {
+            for key in &keys[&action] {
+                if let Some(other) = actions.insert(*key, action) {
+                    if other != action {
+                        return Err(format!(
+                            "key {} is bound to both {} and {}",
+                            key.label(),
+                            other.name(),
+                            action.name()
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(Keymap { keys, actions })
+    }
+
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        self.actions.get(&Key::from_event(event)).copied()
+    }
+
+    /// All keys for an action, e.g. `j / ↓`; empty when unbound.
+    pub fn label(&self, action: Action) -> String {
+        self.keys[&action]
+            .iter()
+            .map(Key::label)
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    /// The first key for an action, for compact hints.
+    pub fn hint(&self, action: Action) -> Option<String> {
+        self.keys[&action].first().map(Key::label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_key_names() {
+        assert_eq!(Key::parse("q").unwrap().label(), "q");
+        assert_eq!(Key::parse("H").unwrap().label(), "H");
+        assert_eq!(Key::parse("enter").unwrap().label(), "Enter");
+        assert_eq!(Key::parse("shift-tab").unwrap().label(), "Shift-Tab");
+        assert_eq!(Key::parse("BackTab").unwrap().label(), "Shift-Tab");
+        assert_eq!(Key::parse("Ctrl-D").unwrap().label(), "Ctrl-d");
+        assert_eq!(Key::parse("alt-x").unwrap().label(), "Alt-x");
+        assert_eq!(Key::parse("Down").unwrap().label(), "↓");
+        assert_eq!(Key::parse("PageDown").unwrap().label(), "PageDown");
+        assert_eq!(Key::parse("f5").unwrap().label(), "F5");
+        assert_eq!(Key::parse("-").unwrap().label(), "-");
+        assert!(Key::parse("Hyper").is_err());
+        assert!(Key::parse("F13").is_err());
+    }
+
+    #[test]
+    fn test_events_match_bindings() {
+        let keymap = Keymap::default();
+        // Uppercase letters and `?` arrive with Shift held
+        assert_eq!(keymap.action(&event(KeyCode::Char('H'), KeyModifiers::SHIFT)), Some(Action::ToggleHostnames));
+        assert_eq!(keymap.action(&event(KeyCode::Char('?'), KeyModifiers::SHIFT)), Some(Action::Help));
+        assert_eq!(keymap.action(&event(KeyCode::BackTab, KeyModifiers::SHIFT)), Some(Action::PrevTab));
+        assert_eq!(keymap.action(&event(KeyCode::Down, KeyModifiers::NONE)), Some(Action::NavDown));
+        assert_eq!(keymap.action(&event(KeyCode::Char('q'), KeyModifiers::CONTROL)), None);
+        assert_eq!(keymap.label(Action::NavDown), "j / ↓");
+    }
+
+    #[test]
+    fn test_overrides_replace_defaults_and_report_conflicts() {
+        let overrides = |pairs: &[(&str, &[&str])]| {
+            pairs
+                .iter()
+                .map(|(a, keys)| (a.to_string(), keys.iter().map(|k| k.to_string()).collect()))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let keymap = Keymap::with_overrides(&overrides(&[
+            ("drill-down", &["d"]),
+            ("process-detail", &["D"]),
+            ("next-tab", &["L", "Tab"]),
+        ]))
+        .unwrap();
+        assert_eq!(keymap.action(&event(KeyCode::Char('d'), KeyModifiers::NONE)), Some(Action::DrillDown));
+        assert_eq!(keymap.action(&event(KeyCode::Enter, KeyModifiers::NONE)), None);
+        assert_eq!(keymap.hint(Action::DrillDown).as_deref(), Some("d"));
+        assert_eq!(keymap.label(Action::NextTab), "L / Tab");
+
+        let error = Keymap::with_overrides(&overrides(&[("next-tab", &["l"])])).unwrap_err();
+        assert_eq!(error, "key l is bound to both next-tab and toggle-local");
+
+        let error = Keymap::with_overrides(&overrides(&[("drill", &["d"])])).unwrap_err();
+        assert_eq!(error, "unknown action \"drill\"");
+
+        let unbound = Keymap::with_overrides(&overrides(&[("pause", &[])])).unwrap();
+        assert_eq!(unbound.hint(Action::Pause), None);
+    }
+}
@@ -0,0 +1,21 @@
+use std::sync::{Arc, Mutex};
+
+/// The latest error from a background writer or sender, kept for the UI
+/// to report. Clones share one slot, so the background side records
+/// outcomes and the UI side reads them.
+#[derive(Debug, Clone, Default)]
+pub struct LastError(Arc<Mutex<Option<String>>>);
+
+impl LastError {
+    /// Record the outcome of an attempt: an error replaces the previous
+    /// one and a success (`None`) clears it.
+    pub fn set(&self, error: Option<String>) {
+        if let Ok(mut slot) = self.0.lock() {
+            *slot = error;
+        }
+    }
+
+    pub fn get(&self) -> Option<String> {
+        self.0.lock().ok().and_then(|e| e.clone())
+    }
+}
@@ -0,0 +1,154 @@
+use std::fs::OpenOptions;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::JoinHandle;
+
+use serde::Serialize;
+
+use crate::data::model::Process;
+use crate::last_error::LastError;
+
+/// Per-process counters in one log line.
+#[derive(Serialize)]
+struct ProcessEntry<'a> {
+    name: &'a str,
+    pid: u32,
+    bytes_in: u64,
+    bytes_out: u64,
+    rate_in: f64,
+    rate_out: f64,
+}
+
+/// One line of the `--log` file.
+#[derive(Serialize)]
+struct LogEntry<'a> {
+    timestamp: String,
+    total_bytes_in: u64,
+    total_bytes_out: u64,
+    total_rate_in: f64,
+    total_rate_out: f64,
+    processes: Vec<ProcessEntry<'a>>,
+}
+
+/// Render one refresh as a JSON line (without the trailing newline).
+pub fn log_line(processes: &[Process], timestamp: String) -> String {
+    let entry = LogEntry {
+        timestamp,
+        total_bytes_in: processes.iter().map(|p| p.bytes_in).sum(),
+        total_bytes_out: processes.iter().map(|p| p.bytes_out).sum(),
+        total_rate_in: processes.iter().map(|p| p.rate_in).sum(),
+        total_rate_out: processes.iter().map(|p| p.rate_out).sum(),
+        processes: processes
+            .iter()
+            .map(|p| ProcessEntry {
+                name: &p.name,
+                pid: p.pid,
+                bytes_in: p.bytes_in,
+                bytes_out: p.bytes_out,
+                rate_in: p.rate_in,
+                rate_out: p.rate_out,
+            })
+            .collect(),
+    };
+    serde_json::to_string(&entry).unwrap_or_default()
+}
+
+/// Appends JSON lines to a file from a background thread so slow or failing
+/// disks never stall the UI. The most recent write error is kept for the
+/// UI to report. Dropping it waits for queued lines to be written.
+pub struct LogWriter {
+    tx: Option<Sender<String>>,
+    thread: Option<JoinHandle<()>>,
+    error: LastError,
+}
+
+impl LogWriter {
+    pub fn spawn(path: PathBuf) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let error = LastError::default();
+        let thread_error = error.clone();
+        let thread = std::thread::spawn(move || write_loop(path, rx, thread_error));
+        LogWriter {
+            tx: Some(tx),
+            thread: Some(thread),
+            error,
+        }
+    }
+
+    pub fn log(&self, processes: &[Process]) {
+        let line = log_line(processes, chrono::Local::now().to_rfc3339());
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(line);
+        }
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.error.get()
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        // Closing the channel ends the loop once the backlog is written
+        self.tx.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn write_loop(path: PathBuf, rx: Receiver<String>, error: LastError) {
+    let mut writer = None;
+    for line in rx {
+        // (Re)open lazily so a permission fix or freed disk space recovers
+        if writer.is_none() {
+            match OpenOptions::new().create(true).append(true).open(&path) {
+                Ok(file) => writer = Some(BufWriter::new(file)),
+                Err(e) => {
+                    error.set(Some(format!("log {}: {}", path.display(), e)));
+                    continue;
+                }
+            }
+        }
+        let Some(w) = writer.as_mut() else {
+            continue;
+        };
+        match writeln!(w, "{}", line).and_then(|_| w.flush()) {
+            Ok(()) => error.set(None),
+            Err(e) => {
+                error.set(Some(format!("log {}: {}", path.display(), e)));
+                writer = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(name: &str, bytes_in: u64, rate_in: f64) -> Process {
+        Process {
+            bytes_in,
+            bytes_out: 10,
+            rate_in,
+            rate_out: 1.0,
+            ..Process::for_test(name, 7)
+        }
+    }
+
+    #[test]
+    fn test_log_line_has_totals_and_processes() {
+        let procs = vec![process("curl", 100, 50.0), process("ssh", 200, 25.0)];
+        let line = log_line(&procs, "2024-05-18T14:23:01+00:00".to_string());
+        assert!(!line.contains('\n'));
+
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["timestamp"], "2024-05-18T14:23:01+00:00");
+        assert_eq!(json["total_bytes_in"], 300);
+        assert_eq!(json["total_rate_in"], 75.0);
+        assert_eq!(json["processes"][1]["name"], "ssh");
+        assert_eq!(json["processes"][1]["bytes_out"], 10);
+    }
+}
@@ -1,195 +1,930 @@
+mod alerts;
 mod app;
+mod clipboard;
 mod config;
 mod data;
+mod export;
+mod keymap;
+mod last_error;
+mod logfile;
+mod metrics;
+mod notify;
+mod output;
+mod recorder;
+mod screen;
+mod settings;
+mod shutdown;
+mod ticker;
 mod ui;
+mod units;
 
-use std::io;
-use std::time::Duration;
+use std::io::{self, Write};
+use std::io::IsTerminal;
+use std::time::{Duration, Instant};
 
-use clap::Parser;
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::event::{self, Event, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use crossterm::execute;
+use crossterm::style::Print;
 use ratatui::backend::CrosstermBackend;
 use ratatui::style::{Modifier, Style};
-use ratatui::text::Span;
+use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph, Tabs};
 use ratatui::Terminal;
 
 use app::{ActiveTab, App};
 use config::Config;
+use data::asn::AsnLookup;
+use data::dns;
+use data::model::SortField;
+use keymap::Action;
+use shutdown::Shutdown;
+use ui::footer::{self, Hint};
 use ui::theme;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let config = Config::parse();
-    let sort_field = config.parse_sort_field();
-
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    screen::install_panic_hook();
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("nm: config {}", e);
+            std::process::exit(1);
+        }
+    };
+    theme::init(config.palette.clone());
+    if config.print_default_config {
+        print!("{}", settings::DEFAULT_CONFIG);
+        return Ok(());
+    }
+    if let Some(config::Command::Report { db, since }) = &config.command {
+        match recorder::report(db, *since, config.units) {
+            Ok(table) => {
+                let _ = io::stdout().write_all(table.as_bytes());
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("nm report: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 
-    let mut app = App::new(sort_field, config.interval);
+    let mut app = build_app(&config)?;
 
-    // Initial data fetch
+    if let Some(addr) = config.metrics_listen {
+        let shared = metrics::SharedSnapshot::default();
+        metrics::serve(addr, shared.clone(), config.metrics_by_name)
+            .await
+            .map_err(|e| format!("metrics listener on {}: {}", addr, e))?;
+        app.metrics_snapshot = Some(shared);
+    }
+
+    // Wall-clock deadline, so time spent paused counts toward it
+    let deadline = config.duration.map(|d| Instant::now() + d);
+
+    if config.once || config.json {
+        return run_once(&mut app, &config).await;
+    }
+    let shutdown = Shutdown::listen()?;
+    if config.stream {
+        return run_stream(&mut app, &config, deadline, &shutdown).await;
+    }
+    if !io::stdout().is_terminal() {
+        return run_headless(&mut app, &config, deadline, &shutdown).await;
+    }
+    run_tui(&mut app, &config, deadline, &shutdown).await
+}
+
+/// Resolves at `deadline`, or never without one.
+async fn until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Create the app from the merged settings and attach the resources they
+/// ask for (lookups, writers, notifiers, sinks).
+fn build_app(config: &Config) -> Result<App, Box<dyn std::error::Error>> {
+    let mut app = App::new(config);
+    if let Some(path) = &config.asn_db {
+        app.asn_lookup = Some(AsnLookup::open(path)?);
+    }
+    app.log_writer = config.log.clone().map(logfile::LogWriter::spawn);
+    let targets = notify::Targets {
+        webhook: config.alert_webhook.clone(),
+        ntfy: config.alert_ntfy.clone(),
+    };
+    if config.notify {
+        app.desktop_notifier = Some(notify::DesktopNotifier::new());
+    }
+    if !targets.is_empty() {
+        app.notifier = Some(notify::Notifier::spawn(targets, config.alert_cooldown));
+    }
+    if let Some(path) = &config.db {
+        app.recorder = Some(recorder::Recorder::spawn(path, config.interval.as_secs_f64())?);
+    }
+    if let Some(target) = &config.influx_udp {
+        app.sinks.push(Box::new(output::influx::InfluxUdp::connect(target)?));
+    }
+    if let Some(target) = &config.statsd_udp {
+        app.sinks.push(Box::new(output::statsd::StatsdUdp::connect(target)?));
+    }
+    if !config.no_cache_file && !config.no_dns {
+        app.dns_cache_file = dns::cache_file_path();
+        if let Some(path) = &app.dns_cache_file {
+            app.dns_cache.load(path);
+        }
+    }
+    if config.dns_system_cache {
+        app.enable_system_dns_cache();
+    }
+    if let Some(path) = settings::state_file_path() {
+        app.load_column_widths(path);
+    }
+    Ok(app)
+}
+
+/// `--once`: take two samples an interval apart so rates are meaningful,
+/// print the top processes as plain text, and exit.
+async fn run_once(app: &mut App, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
     app.update_data().await;
+    tokio::time::sleep(config.interval).await;
+    app.update_data().await;
+    // Take whatever reverse lookups finished meanwhile; never wait for more
+    app.poll_dns();
 
-    let tick_rate = Duration::from_secs(config.interval);
+    if let Some(error) = &app.fetch_error {
+        if config.json {
+            println!("{}", export::error_json(error));
+        } else {
+            eprintln!("nm: {}", error);
+        }
+        std::process::exit(1);
+    }
+
+    let output = if config.json {
+        let mut json = export::snapshot_json(&app.snapshot, config.interval.as_secs_f64());
+        json.push('\n');
+        json
+    } else {
+        export::processes_table(&app.filtered_processes(), config.top, app.units)
+    };
+    let mut stdout = io::stdout().lock();
+    match stdout.write_all(output.as_bytes()).and_then(|_| stdout.flush()) {
+        // A closed pipe (`nm --once | head`) is not an error
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e.into()),
+        _ => Ok(()),
+    }
+}
 
+/// `--stream`: print one compact JSON line per refresh until interrupted,
+/// the reader goes away, or `--duration` elapses.
+async fn run_stream(
+    app: &mut App,
+    config: &Config,
+    deadline: Option<Instant>,
+    shutdown: &Shutdown,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut ticks = tokio::time::interval(config.interval);
+    // The first sample only sets the rate baseline
+    ticks.tick().await;
+    app.update_data().await;
+    let result = loop {
+        tokio::select! {
+            _ = ticks.tick() => {}
+            _ = until(deadline) => break Ok(()),
+            _ = shutdown.wait() => break Ok(()),
+        }
+        app.update_data().await;
+        app.poll_dns();
+        let line = match &app.fetch_error {
+            Some(error) => export::error_json(error),
+            None => export::stream_line(
+                &app.snapshot,
+                chrono::Local::now().to_rfc3339(),
+                &config.stream_fields,
+            ),
+        };
+        let mut stdout = io::stdout().lock();
+        match writeln!(stdout, "{}", line).and_then(|_| stdout.flush()) {
+            Ok(()) => {}
+            // Downstream consumer exited (`nm --stream | head`)
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => break Ok(()),
+            Err(e) => break Err(e.into()),
+        }
+    };
+    app.save_dns_cache();
+    result
+}
+
+/// Without a terminal (CI, cron, output redirected): keep refreshing so
+/// `--log`, `--db`, metrics, and sinks are fed, until `--duration` elapses
+/// or the process is interrupted.
+async fn run_headless(
+    app: &mut App,
+    config: &Config,
+    deadline: Option<Instant>,
+    shutdown: &Shutdown,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut ticks = tokio::time::interval(config.interval);
     loop {
-        // Draw
-        terminal.draw(|f| draw_ui(f, &app))?;
+        tokio::select! {
+            _ = ticks.tick() => {}
+            _ = until(deadline) => break,
+            _ = shutdown.wait() => break,
+        }
+        app.update_data().await;
+        app.poll_dns();
+    }
+    app.save_dns_cache();
+    Ok(())
+}
+
+async fn run_tui(
+    app: &mut App,
+    config: &Config,
+    deadline: Option<Instant>,
+    shutdown: &Shutdown,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Setup terminal; it is restored when the guard goes, however that happens
+    let _screen = screen::ScreenGuard::enter(!config.no_mouse)?;
+    let backend = CrosstermBackend::new(io::stdout());
+    let mut terminal = Terminal::new(backend)?;
+
+    // Initial data fetch
+    fetch(&mut terminal, app).await?;
 
-        // Handle events with timeout
-        if event::poll(tick_rate)? {
-            if let Event::Key(key) = event::read()? {
-                if app.filtering {
+    let mut ticker = ticker::Ticker::new(config.interval, Instant::now());
+    let mut redraw = true;
+
+    loop {
+        // --duration elapsed: leave through the same cleanup as `q`
+        if deadline.is_some_and(|d| Instant::now() >= d) {
+            break;
+        }
+
+        if redraw {
+            draw(&mut terminal, app)?;
+            if std::mem::take(&mut app.bell_pending) {
+                execute!(terminal.backend_mut(), Print("\x07"))?;
+            }
+        }
+        redraw = true;
+
+        // Handle events until the next refresh is due
+        let ready = wait_for_event(ticker.timeout(Instant::now(), deadline), shutdown)?;
+        if shutdown.requested() {
+            // SIGTERM, SIGHUP, or SIGINT: leave through the same cleanup as `q`
+            app.should_quit = true;
+        } else if ready {
+            let event = event::read()?;
+            // Mouse capture reports every pointer movement; none of them
+            // change anything on screen
+            if let Event::Mouse(mouse) = &event {
+                if matches!(mouse.kind, MouseEventKind::Moved | MouseEventKind::Drag(_)) {
+                    redraw = false;
+                }
+            }
+            if let Event::Paste(text) = &event {
+                if app.search.typing {
+                    app.search.input.insert_str(text);
+                    app.update_search();
+                } else if app.filtering() {
+                    app.tab_filter_mut().input.insert_str(text);
+                }
+            }
+            if let Event::Mouse(mouse) = event {
+                handle_mouse(app, mouse);
+                app.request_visible_dns();
+            }
+            if let Event::Key(key) = event {
+                app.dismiss_status();
+                if app.search.typing {
                     match key.code {
-                        KeyCode::Enter => app.apply_filter(),
-                        KeyCode::Esc => app.cancel_filter(),
-                        KeyCode::Backspace => { app.filter_input.pop(); }
-                        KeyCode::Char(c) => app.filter_input.push(c),
-                        _ => {}
+                        KeyCode::Enter => app.finish_search(),
+                        KeyCode::Esc => app.cancel_search(),
+                        _ => {
+                            if app.search.input.handle_key(&key) {
+                                app.update_search();
+                            }
+                        }
                     }
-                } else if app.show_help {
+                } else if app.filtering() {
                     match key.code {
-                        KeyCode::Char('?') | KeyCode::Esc | KeyCode::Char('q') => {
-                            app.show_help = false;
+                        KeyCode::Enter => app.apply_filter(),
+                        KeyCode::Esc => app.cancel_filter(),
+                        _ => {
+                            app.tab_filter_mut().input.handle_key(&key);
                         }
-                        _ => {}
                     }
+                } else if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    // Always available, whatever the keymap says
+                    app.should_quit = true;
                 } else {
-                    match key.code {
-                        KeyCode::Char('q') => app.should_quit = true,
-                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            app.should_quit = true;
+                    let action = app.keymap.action(&key);
+                    // Popups close on their own key, Esc, or the quit key
+                    let closes = |own: Action| {
+                        key.code == KeyCode::Esc || action == Some(own) || action == Some(Action::Quit)
+                    };
+                    if app.show_help {
+                        if closes(Action::Help) {
+                            app.show_help = false;
                         }
-                        KeyCode::Tab => app.active_tab = app.active_tab.next(),
-                        KeyCode::BackTab => app.active_tab = app.active_tab.prev(),
-                        KeyCode::Char('j') | KeyCode::Down => app.nav_down(),
-                        KeyCode::Char('k') | KeyCode::Up => app.nav_up(),
-                        KeyCode::Char('s') => app.cycle_sort(),
-                        KeyCode::Char('/') => app.enter_filter(),
-                        KeyCode::Esc => app.cancel_filter(),
-                        KeyCode::Char('p') => app.paused = !app.paused,
-                        KeyCode::Char('?') => app.show_help = true,
-                        KeyCode::Enter => app.drill_down(),
-                        _ => {}
+                    } else if app.show_alert_log {
+                        if closes(Action::AlertLog) {
+                            app.show_alert_log = false;
+                        }
+                    } else if app.show_columns {
+                        if closes(Action::Columns) {
+                            app.show_columns = false;
+                        } else if action == Some(Action::NavDown) {
+                            app.column_nav(true);
+                        } else if action == Some(Action::NavUp) {
+                            app.column_nav(false);
+                        } else if matches!(key.code, KeyCode::Char(' ') | KeyCode::Enter) {
+                            app.toggle_column();
+                        }
+                    } else if app.show_sort_menu {
+                        if closes(Action::SortMenu) {
+                            app.show_sort_menu = false;
+                        } else if action == Some(Action::NavDown) {
+                            app.sort_menu_nav(true);
+                        } else if action == Some(Action::NavUp) {
+                            app.sort_menu_nav(false);
+                        } else if key.code == KeyCode::Enter {
+                            app.choose_sort(SortField::ALL[app.sort_menu_index]);
+                        } else if let KeyCode::Char(c @ '1'..='9') = key.code {
+                            let n = c as usize - '1' as usize;
+                            if let Some(field) = SortField::ALL.get(n) {
+                                app.choose_sort(*field);
+                            }
+                        }
+                    } else if app.show_process_detail {
+                        if closes(Action::ProcessDetail) {
+                            app.show_process_detail = false;
+                        } else if action == Some(Action::Reset) {
+                            app.reset_baselines();
+                        }
+                    } else if app.show_connection_detail {
+                        if closes(Action::DrillDown) || key.code == KeyCode::Enter {
+                            app.show_connection_detail = false;
+                        }
+                    } else if let Some(digit) = count_digit(&key).filter(|_| action.is_none()) {
+                        app.push_count_digit(digit);
+                    } else if key.code == KeyCode::Esc && app.pending_count.is_some() {
+                        app.pending_count = None;
+                    } else if let Some(action) = action {
+                        handle_action(app, action);
                     }
                 }
+                app.request_visible_dns();
             }
-        } else {
-            // Tick — refresh data
-            app.update_data().await;
         }
+        // Tick — refresh data, however busy the input has been
+        if !app.should_quit && ticker.due(Instant::now()) {
+            fetch(&mut terminal, app).await?;
+            redraw = true;
+        }
+        app.poll_dns();
 
         if app.should_quit {
             break;
         }
     }
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
-
+    app.save_dns_cache();
+    app.save_column_widths();
     Ok(())
 }
 
-fn draw_ui(f: &mut ratatui::Frame, app: &App) {
-    let (header_area, main_area, sparkline_area, footer_area) =
-        ui::layout::main_layout(f.area());
+/// How long input polling may block before checking for a shutdown signal.
+const SIGNAL_CHECK: Duration = Duration::from_millis(50);
+
+/// Wait up to `timeout` for terminal input, in slices short enough that a
+/// shutdown signal is acted on promptly. False if none arrived.
+fn wait_for_event(timeout: Duration, shutdown: &Shutdown) -> io::Result<bool> {
+    let end = Instant::now() + timeout;
+    loop {
+        let left = end.saturating_duration_since(Instant::now());
+        if event::poll(left.min(SIGNAL_CHECK))? {
+            return Ok(true);
+        }
+        if left <= SIGNAL_CHECK || shutdown.requested() {
+            return Ok(false);
+        }
+    }
+}
+
+/// Mouse input, ignored while a popup or the filter or search prompt is up.
+fn handle_mouse(app: &mut App, mouse: MouseEvent) {
+    let popup = app.show_help
+        || app.show_alert_log
+        || app.show_columns
+        || app.show_sort_menu
+        || app.show_process_detail
+        || app.show_connection_detail;
+    if app.filtering() || app.search.typing || popup {
+        return;
+    }
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => app.click(mouse.column, mouse.row),
+        MouseEventKind::ScrollDown => app.scroll(mouse.column, mouse.row, true),
+        MouseEventKind::ScrollUp => app.scroll(mouse.column, mouse.row, false),
+        _ => {}
+    }
+}
+
+/// Draw a frame, returning where the tabs and table went for mouse clicks.
+fn draw_ui(f: &mut ratatui::Frame, app: &App) -> ui::hitbox::Hitboxes {
+    // Below the minimum the layout constraints fight each other; draw
+    // nothing but a note until the terminal grows again
+    if ui::layout::too_small(f.area(), app.show_detail_pane) {
+        ui::layout::render_too_small(f, f.area(), app.show_detail_pane);
+        return ui::hitbox::Hitboxes::default();
+    }
+
+    if theme::current().rate_relative {
+        let busiest = app
+            .snapshot
+            .processes
+            .iter()
+            .map(|p| p.rate_in.max(p.rate_out))
+            .fold(0.0, f64::max);
+        theme::set_rate_scale(busiest);
+    }
+
+    let (header_area, main_area, pane_area, sparkline_area, footer_area) =
+        ui::layout::main_layout(f.area(), app.show_detail_pane);
 
     // Header: tabs + stats
-    draw_header(f, header_area, app);
+    let tabs = draw_header(f, header_area, app);
 
     // Main content based on active tab
-    match app.active_tab {
-        ActiveTab::Processes => ui::processes::render(f, main_area, app),
-        ActiveTab::Connections => ui::connections::render(f, main_area, app),
-        ActiveTab::Overview => ui::overview::render(f, main_area, app),
+    let table = match app.active_tab {
+        ActiveTab::Processes => Some(ui::processes::render(f, main_area, app)),
+        ActiveTab::Connections => Some(ui::connections::render(f, main_area, app)),
+        ActiveTab::Remotes => Some(ui::remotes::render(f, main_area, app)),
+        ActiveTab::Listeners => Some(ui::listeners::render(f, main_area, app)),
+        ActiveTab::Interfaces => Some(ui::interfaces::render(f, main_area, app)),
+        ActiveTab::Overview => {
+            ui::overview::render(f, main_area, app);
+            None
+        }
+        ActiveTab::Graph => {
+            ui::graph::render(f, main_area, app);
+            None
+        }
+    };
+
+    if app.show_detail_pane {
+        ui::detail_pane::render(f, pane_area, app);
     }
 
-    // Sparkline
-    ui::overview::render_footer_sparkline(f, sparkline_area, app);
+    // Sparkline, unless the terminal is too narrow or short for it
+    if !sparkline_area.is_empty() {
+        ui::overview::render_footer_sparkline(f, sparkline_area, app);
+    }
 
     // Footer
     draw_footer(f, footer_area, app);
 
+    // Process detail popup
+    if app.show_process_detail {
+        ui::process_detail::render(f, app);
+    }
+
+    if app.show_connection_detail {
+        ui::connection_detail::render(f, app);
+    }
+
+    if app.show_alert_log {
+        ui::alerts::render(f, app);
+    }
+
+    if app.show_columns {
+        ui::columns::render(f, app);
+    }
+
+    if app.show_sort_menu {
+        ui::sort::render(f, app);
+    }
+
     // Help overlay
     if app.show_help {
-        ui::help::render(f);
+        ui::help::render(f, &app.keymap);
     }
+
+    ui::hitbox::Hitboxes { tabs, table }
 }
 
-fn draw_header(f: &mut ratatui::Frame, area: ratatui::layout::Rect, app: &App) {
-    use ratatui::layout::{Constraint, Direction, Layout};
+/// Draw the tab bar and stats, returning each tab title's area.
+fn draw_header(
+    f: &mut ratatui::Frame,
+    area: ratatui::layout::Rect,
+    app: &App,
+) -> Vec<(ratatui::layout::Rect, ActiveTab)> {
+    use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
 
+    // Stats summary, sized to fit (borders included); narrow terminals get
+    // the short form and leave the tabs more room
+    let compact = ui::layout::compact(area);
+    let stats = header_stats(app, compact);
+    let stats_width = (stats.width() as u16 + 2).max(if compact { 0 } else { 50 });
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Min(40), Constraint::Length(40)])
+        .constraints([Constraint::Min(40), Constraint::Length(stats_width)])
         .split(area);
 
     // Tabs
-    let tab_titles = vec![
-        Span::raw(" Processes "),
-        Span::raw(" Connections "),
-        Span::raw(" Overview "),
+    let tab_titles = [
+        (ActiveTab::Processes, " Processes "),
+        (ActiveTab::Connections, " Connections "),
+        (ActiveTab::Remotes, " Remotes "),
+        (ActiveTab::Listeners, " Listeners "),
+        (ActiveTab::Interfaces, " Interfaces "),
+        (ActiveTab::Overview, " Overview "),
+        (ActiveTab::Graph, " Graph "),
     ];
-    let selected = match app.active_tab {
-        ActiveTab::Processes => 0,
-        ActiveTab::Connections => 1,
-        ActiveTab::Overview => 2,
-    };
-    let tabs = Tabs::new(tab_titles)
+    let selected = tab_titles.iter().position(|(tab, _)| *tab == app.active_tab).unwrap_or(0);
+    let tabs = Tabs::new(tab_titles.map(|(_, title)| Span::raw(title)))
         .block(Block::default().borders(Borders::ALL).border_style(
-            Style::default().fg(theme::BORDER_COLOR),
+            Style::default().fg(theme::current().border),
         ))
         .select(selected)
         .highlight_style(
             Style::default()
-                .fg(theme::ACTIVE_TAB_FG)
+                .fg(theme::current().active_tab)
                 .add_modifier(Modifier::BOLD),
         )
-        .style(Style::default().fg(theme::INACTIVE_TAB_FG));
+        .style(Style::default().fg(theme::current().inactive_tab));
 
     f.render_widget(tabs, chunks[0]);
 
-    // Stats summary
-    let stats = format!(
-        "▼ {} ▲ {} │ {} conn",
-        ui::processes::format_rate(app.snapshot.total_rate_in),
-        ui::processes::format_rate(app.snapshot.total_rate_out),
-        app.snapshot.total_connections,
-    );
-    let paused = if app.paused { " [PAUSED]" } else { "" };
-    let stats_widget = Paragraph::new(format!("{}{}", stats, paused))
+    let stats_widget = Paragraph::new(stats)
         .block(Block::default().borders(Borders::ALL).border_style(
-            Style::default().fg(theme::BORDER_COLOR),
+            Style::default().fg(theme::current().border),
         ))
         .style(theme::header_style());
     f.render_widget(stats_widget, chunks[1]);
+
+    // Each title sits between one-cell paddings, and titles are separated by
+    // a one-cell divider
+    let bar = chunks[0].inner(Margin::new(1, 1));
+    let mut x = bar.x;
+    let mut hitboxes = Vec::new();
+    for (tab, title) in tab_titles {
+        let width = (title.len() as u16 + 2).min(bar.right().saturating_sub(x));
+        hitboxes.push((Rect::new(x, bar.y, width, 1), tab));
+        x = x.saturating_add(width + 1);
+    }
+    hitboxes
 }
 
-fn draw_footer(f: &mut ratatui::Frame, area: ratatui::layout::Rect, app: &App) {
-    let text = if app.filtering {
-        format!("Filter: {}█", app.filter_input)
-    } else if let Some(ref filter) = app.filter_text {
-        format!(
-            "Tab: switch │ j/k: nav │ s: sort ({}) │ /: filter [{}] │ ?: help │ q: quit",
-            app.sort_field.label(),
-            filter
-        )
+/// Header stats: alert banner, totals, VPN share, data budget, pause flag.
+/// The compact form keeps the banners, rates, connection count, budget
+/// share, and pause flag, and shortens the freshness note.
+fn header_stats(app: &App, compact: bool) -> Line<'static> {
+    // The established share, once there are states to count
+    let states = app.snapshot.state_counts();
+    let established = if states.is_empty() {
+        String::new()
+    } else {
+        let count = states.iter().find(|(state, _)| state == "ESTABLISHED").map_or(0, |(_, n)| *n);
+        format!(" ({} est)", count)
+    };
+    let stats = if compact {
+        format!(" │ {} conn", app.snapshot.total_connections)
     } else {
         format!(
-            "Tab: switch │ j/k: nav │ s: sort ({}) │ /: filter │ Enter: drill │ p: pause │ ?: help │ q: quit",
-            app.sort_field.label()
+            " │ {} conn{} +{}/-{}",
+            app.snapshot.total_connections,
+            established,
+            app.churn.opened,
+            app.churn.closed,
         )
     };
+    let (vpn_rate, physical_rate) = app.snapshot.vpn_split();
+    let vpn = if vpn_rate > 0.0 && !compact {
+        format!(" │ VPN {:.0}%", 100.0 * vpn_rate / (vpn_rate + physical_rate))
+    } else {
+        String::new()
+    };
+    let paused = if app.paused { " [PAUSED]" } else { "" };
+    let mut spans = Vec::new();
+    if let Some(watch) = &app.watch {
+        if app.watch_exited {
+            spans.push(Span::styled(
+                format!("{}: process exited, waiting │ ", watch.label()),
+                Style::default().fg(theme::current().warning).add_modifier(Modifier::BOLD),
+            ));
+        } else {
+            spans.push(Span::styled(
+                format!("◉ {} │ ", watch.label()),
+                Style::default().fg(theme::current().active_tab),
+            ));
+        }
+    }
+    let active = app.alerts.active();
+    if let Some(first) = active.first() {
+        let banner = if active.len() == 1 {
+            format!("⚠ {} {} │ ", first.process, first.direction.label())
+        } else {
+            format!("⚠ {} alerts │ ", active.len())
+        };
+        spans.push(Span::styled(
+            banner,
+            Style::default().fg(theme::current().alert).add_modifier(Modifier::BOLD),
+        ));
+    }
+    spans.push(Span::styled(
+        format!("▼ {}", app.units.rate(app.snapshot.total_rate_in)),
+        Style::default().fg(theme::rate_color(app.snapshot.total_rate_in)),
+    ));
+    spans.push(Span::styled(
+        format!(" ▲ {}", app.units.rate(app.snapshot.total_rate_out)),
+        Style::default().fg(theme::rate_color(app.snapshot.total_rate_out)),
+    ));
+    spans.push(Span::raw(format!("{}{}", stats, vpn)));
+    if let Some(budget) = app.budget {
+        let used = app.budget_used as f64 / budget.max(1) as f64;
+        let color = if used >= 1.0 {
+            theme::current().alert
+        } else if used >= app::BUDGET_WARN_RATIO {
+            theme::current().warning
+        } else {
+            theme::current().header
+        };
+        let text = if compact {
+            format!(" │ used {:.0}%", used * 100.0)
+        } else {
+            format!(
+                " │ used {} / {} ({:.0}%)",
+                app.units.bytes(app.budget_used),
+                app.units.bytes(budget),
+                used * 100.0
+            )
+        };
+        spans.push(Span::styled(text, Style::default().fg(color)));
+    }
+    spans.push(Span::raw(paused));
+    spans.extend(freshness(app, compact));
+    Line::from(spans)
+}
+
+/// Spinner frames, one step per fetch.
+const SPINNER: [char; 8] = ['⣾', '⣽', '⣻', '⢿', '⡿', '⣟', '⣯', '⣷'];
+
+/// " │ updated 3s ago (took 0.8s)", or just " │ 3s" when compact, turning
+/// yellow and then red as the data ages, with a spinner while a fetch is
+/// under way.
+fn freshness(app: &App, compact: bool) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    if let Some((at, took)) = app.last_fetch {
+        let color = match app.freshness() {
+            app::Freshness::Fresh => theme::current().header,
+            app::Freshness::Stale => theme::current().warning,
+            app::Freshness::VeryStale => theme::current().alert,
+        };
+        let age = ui::processes::format_duration(at.elapsed());
+        let text = if compact {
+            format!(" │ {}", age)
+        } else {
+            format!(" │ updated {} ago (took {:.1}s)", age, took.as_secs_f64())
+        };
+        spans.push(Span::styled(text, Style::default().fg(color)));
+    }
+    if app.fetching {
+        spans.push(Span::styled(
+            format!(" {}", SPINNER[app.fetch_count % SPINNER.len()]),
+            Style::default().fg(theme::current().active_tab),
+        ));
+    }
+    spans
+}
+
+fn draw(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App) -> io::Result<()> {
+    terminal.draw(|f| {
+        app.viewport_rows = ui::layout::table_rows(f.area(), app.show_detail_pane);
+        app.hitboxes = draw_ui(f, app);
+    })?;
+    Ok(())
+}
+
+/// Refresh the data, first drawing a frame with the header's spinner since
+/// nothing is drawn while nettop runs.
+async fn fetch(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App) -> io::Result<()> {
+    app.fetching = true;
+    draw(terminal, app)?;
+    app.update_data().await;
+    app.fetching = false;
+    Ok(())
+}
+
+fn handle_action(app: &mut App, action: Action) {
+    // A typed count applies to motion keys; anything else drops it
+    let count = app.pending_count.take();
+    match action {
+        Action::Quit => app.should_quit = true,
+        Action::NextTab => app.active_tab = app.active_tab.next(),
+        Action::PrevTab => app.active_tab = app.active_tab.prev(),
+        Action::NavDown => app.nav_down(count.unwrap_or(1)),
+        Action::NavUp => app.nav_up(count.unwrap_or(1)),
+        Action::PageDown => app.page_down(count.unwrap_or(1)),
+        Action::PageUp => app.page_up(count.unwrap_or(1)),
+        // With a count both go to that row, as `15G` does in vim
+        Action::NavFirst | Action::NavLast if count.is_some() => app.nav_to_row(count.unwrap_or(1)),
+        Action::NavFirst => app.nav_first(),
+        Action::NavLast => app.nav_last(),
+        Action::DrillDown if app.active_tab == ActiveTab::Connections => app.toggle_connection_detail(),
+        Action::DrillDown => app.drill_down(),
+        Action::Back => app.drill_up(),
+        Action::ProcessDetail => app.toggle_process_detail(),
+        Action::DetailPane => app.show_detail_pane = !app.show_detail_pane,
+        Action::CycleSort => app.cycle_sort(),
+        Action::PrevSort => app.prev_sort(),
+        Action::FlipSort => app.flip_sort(),
+        Action::SortMenu => app.toggle_sort_menu(),
+        Action::Filter => app.enter_filter(),
+        Action::Search => app.enter_search(),
+        Action::SearchNext => app.search_next(true, count.unwrap_or(1)),
+        Action::SearchPrev => app.search_next(false, count.unwrap_or(1)),
+        // A search is cleared before the filter under it
+        Action::ClearFilter if app.searching() => app.cancel_search(),
+        Action::ClearFilter if app.in_drill_down() => app.drill_up(),
+        Action::ClearFilter => app.cancel_filter(),
+        Action::ToggleServices => app.toggle_services(),
+        Action::ToggleHostnames => app.toggle_hostnames(),
+        Action::ReloadAliases => app.refresh_aliases(),
+        Action::Copy => app.copy_selection(false),
+        Action::CopyAlt => app.copy_selection(true),
+        Action::ExportJson => app.export_snapshot(),
+        Action::ExportCsv => app.export_csv(),
+        Action::AlertLog => app.show_alert_log = true,
+        Action::ResetBudget => app.reset_budget(),
+        Action::ToggleLocal => app.toggle_exclude_local(),
+        Action::ToggleInbound => app.toggle_inbound_only(),
+        Action::ToggleIdle => app.toggle_hide_idle(),
+        Action::Pause => app.paused = !app.paused,
+        Action::Reset => app.reset_baselines(),
+        Action::Help => app.show_help = true,
+        Action::Columns => app.toggle_columns(),
+        Action::ShrinkColumn => app.resize_column(false),
+        Action::GrowColumn => app.resize_column(true),
+        Action::CycleUnits => app.cycle_units(),
+        Action::ZoomIn => app.zoom_graph(true),
+        Action::ZoomOut => app.zoom_graph(false),
+        Action::GraphProcess => app.graph_process = !app.graph_process,
+        Action::ToggleLogScale => app.log_scale = !app.log_scale,
+    }
+}
+
+/// The digit of an unmodified digit key, for count prefixes.
+fn count_digit(key: &event::KeyEvent) -> Option<u32> {
+    match key.code {
+        KeyCode::Char(c) if key.modifiers.difference(KeyModifiers::SHIFT).is_empty() => c.to_digit(10),
+        _ => None,
+    }
+}
+
+/// Footer key hints, using whatever keys are bound; unbound actions are
+/// left out.
+fn footer_hints(app: &App) -> Vec<Hint> {
+    let keys = &app.keymap;
+    let pair = |a: Action, b: Action| match (keys.hint(a), keys.hint(b)) {
+        (Some(a), Some(b)) => Some(format!("{}/{}", a, b)),
+        (a, b) => a.or(b),
+    };
+    let sort = format!("sort ({} {})", app.sort_field.label(), app.sort_direction.glyph());
+    let filter = match app.filter_label() {
+        Some(filter) => format!("filter [{}]", filter),
+        None => "filter".to_string(),
+    };
+    let mut hints = vec![
+        (keys.hint(Action::NextTab), Hint::new("", "switch", 4)),
+        (pair(Action::NavDown, Action::NavUp), Hint::new("", "nav", 2)),
+        (keys.hint(Action::CycleSort), Hint::new("", sort, 3).short("sort")),
+        (keys.hint(Action::Filter), Hint::new("", filter, 7).short("filter")),
+    ];
+    if app.in_drill_down() {
+        hints.push((keys.hint(Action::Back), Hint::new("", "back", 6)));
+    } else if app.filter_label().is_none() {
+        // What Enter does depends on the tab
+        let drill = match app.active_tab {
+            ActiveTab::Connections => Some("detail"),
+            ActiveTab::Overview => Some("open"),
+            ActiveTab::Graph => None,
+            _ => Some("drill"),
+        };
+        if let Some(drill) = drill {
+            hints.push((keys.hint(Action::DrillDown), Hint::new("", drill, 5)));
+        }
+        hints.push((keys.hint(Action::Pause), Hint::new("", "pause", 1)));
+    }
+    if app.active_tab == ActiveTab::Graph {
+        hints.push((pair(Action::ZoomIn, Action::ZoomOut), Hint::new("", "zoom", 5)));
+        hints.push((keys.hint(Action::GraphProcess), Hint::new("", "process", 2)));
+    }
+    hints.push((keys.hint(Action::Help), Hint::new("", "help", footer::ALWAYS)));
+    hints.push((keys.hint(Action::Quit), Hint::new("", "quit", footer::ALWAYS)));
+    hints
+        .into_iter()
+        .filter_map(|(key, hint)| key.map(|key| Hint { key, ..hint }))
+        .collect()
+}
+
+fn draw_footer(f: &mut ratatui::Frame, area: ratatui::layout::Rect, app: &App) {
+    let prompt = app.filtering() || app.search.typing;
+    let mut text = String::new();
+    if app.exclude_local && !prompt {
+        text.push_str(" │ [no local]");
+    }
+    if app.inbound_only && !prompt {
+        text.push_str(" │ [inbound]");
+    }
+    if app.hide_idle && !prompt {
+        text.push_str(" │ [no idle]");
+    }
+    if !prompt {
+        if !app.dns_enabled {
+            text.push_str(" │ [no dns]");
+        } else if !app.show_hostnames {
+            text.push_str(" │ [numeric]");
+        }
+    }
 
-    let footer = Paragraph::new(text).style(theme::footer_style());
+    let mut spans = Vec::new();
+    if let Some(count) = app.pending_count.filter(|_| !prompt) {
+        spans.push(Span::styled(
+            format!("{} │ ", count),
+            Style::default().fg(theme::current().active_tab),
+        ));
+    }
+    // A status message stands in for the key hints while it's up
+    let status = app.status_message().filter(|_| !prompt);
+    if let Some(message) = status {
+        let color = match message.severity {
+            app::Severity::Info => theme::current().active_tab,
+            app::Severity::Warning => theme::current().warning,
+            app::Severity::Error => theme::current().alert,
+        };
+        spans.push(Span::styled(message.text.clone(), Style::default().fg(color)));
+    }
+    if app.search.typing {
+        spans.push(Span::raw("Search: "));
+        spans.extend(app.search.input.spans());
+    } else if app.filtering() {
+        spans.push(Span::raw("Filter: "));
+        spans.extend(app.tab_filter().input.spans());
+    } else if let Some((current, total)) = app.search_status() {
+        spans.push(Span::styled(search_label(current, total), theme::match_style()));
+        spans.push(Span::raw(" │ "));
+    }
+    let mut tail = vec![Span::raw(text)];
+    let error = if app.search.typing { app.search_error() } else { app.filter_error() };
+    if let Some(error) = error.filter(|_| prompt) {
+        tail.push(Span::styled(
+            format!(" │ ⚠ {}", error),
+            Style::default().fg(theme::current().warning),
+        ));
+    }
+    let log_error = app.log_writer.as_ref().and_then(|w| w.error());
+    let db_error = app.recorder.as_ref().and_then(|r| r.error());
+    let notify_error = app.notifier.as_ref().and_then(|n| n.error());
+    let warnings = [
+        app.alias_warning.clone(),
+        log_error,
+        app.sink_warning.clone(),
+        db_error,
+        notify_error,
+    ];
+    for warning in warnings.into_iter().flatten() {
+        if !prompt {
+            tail.push(Span::styled(
+                format!(" │ ⚠ {}", warning),
+                Style::default().fg(theme::current().warning),
+            ));
+        }
+    }
+
+    // The key hints get whatever room the rest leaves
+    if !prompt && status.is_none() {
+        let used = Line::from(spans.clone()).width() + Line::from(tail.clone()).width();
+        let room = (area.width as usize).saturating_sub(used);
+        spans.push(Span::raw(footer::fit(&footer_hints(app), room)));
+    }
+    spans.extend(tail);
+
+    let footer = Paragraph::new(Line::from(spans)).style(theme::footer_style());
     f.render_widget(footer, area);
 }
+
+/// Footer indicator for a search: "match 3/17", "17 matches", "no matches".
+fn search_label(current: Option<usize>, total: usize) -> String {
+    match (current, total) {
+        (_, 0) => "no matches".to_string(),
+        (Some(n), total) => format!("match {}/{}", n, total),
+        (None, 1) => "1 match".to_string(),
+        (None, total) => format!("{} matches", total),
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+
+use crate::data::model::NetworkSnapshot;
+
+/// Latest snapshot, published by the refresh loop and read by scrapes.
+pub type SharedSnapshot = Arc<RwLock<NetworkSnapshot>>;
+
+#[derive(Clone)]
+struct MetricsState {
+    snapshot: SharedSnapshot,
+    by_name: bool,
+}
+
+/// Bind `addr` and serve `/metrics` in the background. Binding happens
+/// before returning so a bad address is reported at startup.
+pub async fn serve(addr: SocketAddr, snapshot: SharedSnapshot, by_name: bool) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let app = Router::new()
+        .route("/metrics", get(metrics_handler))
+        .with_state(MetricsState { snapshot, by_name });
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app).await;
+    });
+    Ok(())
+}
+
+async fn metrics_handler(State(state): State<MetricsState>) -> impl IntoResponse {
+    let body = match state.snapshot.read() {
+        Ok(snapshot) => render(&snapshot, state.by_name),
+        Err(_) => String::new(),
+    };
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+/// Escape a Prometheus label value.
+fn label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Per-process series: (rate in, rate out, bytes in, bytes out, connections).
+type Series = (f64, f64, u64, u64, usize);
+
+/// Render a snapshot in the Prometheus text exposition format. With
+/// `by_name`, processes sharing a name are summed and the `pid` label is
+/// dropped to keep label cardinality down.
+pub fn render(snapshot: &NetworkSnapshot, by_name: bool) -> String {
+    let mut series: BTreeMap<String, Series> = BTreeMap::new();
+    for p in &snapshot.processes {
+        let labels = if by_name {
+            format!("process=\"{}\"", label(&p.name))
+        } else {
+            format!("process=\"{}\",pid=\"{}\"", label(&p.name), p.pid)
+        };
+        let entry = series.entry(labels).or_default();
+        entry.0 += p.rate_in;
+        entry.1 += p.rate_out;
+        entry.2 += p.bytes_in;
+        entry.3 += p.bytes_out;
+        entry.4 += p.connection_count();
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# HELP nm_process_rate_bytes Per-process transfer rate in bytes per second.");
+    let _ = writeln!(out, "# TYPE nm_process_rate_bytes gauge");
+    for (labels, s) in &series {
+        let _ = writeln!(out, "nm_process_rate_bytes{{direction=\"in\",{}}} {}", labels, s.0);
+        let _ = writeln!(out, "nm_process_rate_bytes{{direction=\"out\",{}}} {}", labels, s.1);
+    }
+    let _ = writeln!(out, "# HELP nm_process_bytes Bytes transferred per process as reported by nettop.");
+    let _ = writeln!(out, "# TYPE nm_process_bytes gauge");
+    for (labels, s) in &series {
+        let _ = writeln!(out, "nm_process_bytes{{direction=\"in\",{}}} {}", labels, s.2);
+        let _ = writeln!(out, "nm_process_bytes{{direction=\"out\",{}}} {}", labels, s.3);
+    }
+    let _ = writeln!(out, "# HELP nm_process_connections Open connections per process.");
+    let _ = writeln!(out, "# TYPE nm_process_connections gauge");
+    for (labels, s) in &series {
+        let _ = writeln!(out, "nm_process_connections{{{}}} {}", labels, s.4);
+    }
+    let _ = writeln!(out, "# HELP nm_total_rate_bytes Total transfer rate in bytes per second.");
+    let _ = writeln!(out, "# TYPE nm_total_rate_bytes gauge");
+    let _ = writeln!(out, "nm_total_rate_bytes{{direction=\"in\"}} {}", snapshot.total_rate_in);
+    let _ = writeln!(out, "nm_total_rate_bytes{{direction=\"out\"}} {}", snapshot.total_rate_out);
+    let _ = writeln!(out, "# HELP nm_total_bytes Total bytes transferred by all processes.");
+    let _ = writeln!(out, "# TYPE nm_total_bytes gauge");
+    let _ = writeln!(out, "nm_total_bytes{{direction=\"in\"}} {}", snapshot.total_bytes_in);
+    let _ = writeln!(out, "nm_total_bytes{{direction=\"out\"}} {}", snapshot.total_bytes_out);
+    let _ = writeln!(out, "# HELP nm_connections_total Open connections across all processes.");
+    let _ = writeln!(out, "# TYPE nm_connections_total gauge");
+    let _ = writeln!(out, "nm_connections_total {}", snapshot.total_connections);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::model::Process;
+
+    fn process(name: &str, pid: u32, rate_in: f64) -> Process {
+        Process {
+            bytes_in: 1000,
+            bytes_out: 10,
+            rate_in,
+            ..Process::for_test(name, pid)
+        }
+    }
+
+    #[test]
+    fn test_render_per_pid_and_by_name() {
+        let snapshot = NetworkSnapshot::from_processes(vec![
+            process("firefox", 1234, 100.0),
+            process("firefox", 1300, 50.0),
+            process("we\"ird", 7, 1.0),
+        ]);
+
+        let text = render(&snapshot, false);
+        assert!(text.contains("nm_process_rate_bytes{direction=\"in\",process=\"firefox\",pid=\"1234\"} 100\n"));
+        assert!(text.contains("process=\"we\\\"ird\""));
+        assert!(text.contains("nm_total_rate_bytes{direction=\"in\"} 151\n"));
+        assert!(text.contains("nm_connections_total 0\n"));
+
+        let text = render(&snapshot, true);
+        assert!(text.contains("nm_process_rate_bytes{direction=\"in\",process=\"firefox\"} 150\n"));
+        assert!(text.contains("nm_process_bytes{direction=\"in\",process=\"firefox\"} 2000\n"));
+        assert!(!text.contains("pid="));
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::alerts::{AlertDirection, AlertEvent};
+use crate::last_error::LastError;
+use crate::units::Units;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// JSON body POSTed to `--alert-webhook` when an alert fires:
+///
+/// ```json
+/// {"timestamp":"2024-05-01T12:00:00+02:00","process":"Dropbox","pid":812,
+///  "direction":"upload","rate":6291456.0,"threshold":5242880.0}
+/// ```
+///
+/// `rate` (smoothed) and `threshold` are bytes/sec.
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    timestamp: String,
+    process: &'a str,
+    pid: u32,
+    direction: &'static str,
+    rate: f64,
+    threshold: f64,
+}
+
+impl<'a> WebhookPayload<'a> {
+    fn new(event: &'a AlertEvent) -> Self {
+        WebhookPayload {
+            timestamp: event.at.to_rfc3339(),
+            process: &event.process,
+            pid: event.pid,
+            direction: event.direction.label(),
+            rate: event.rate,
+            threshold: event.threshold,
+        }
+    }
+}
+
+/// Where fired alerts are delivered.
+#[derive(Debug, Clone, Default)]
+pub struct Targets {
+    pub webhook: Option<String>,
+    /// ntfy topic ("nm-alerts", sent to ntfy.sh) or full topic URL
+    pub ntfy: Option<String>,
+}
+
+impl Targets {
+    pub fn is_empty(&self) -> bool {
+        self.webhook.is_none() && self.ntfy.is_none()
+    }
+}
+
+/// Suppresses repeat notifications for the same process and direction
+/// within the cooldown, so a sustained burst that flaps sends one request.
+struct Cooldown {
+    period: Duration,
+    last_sent: HashMap<(String, AlertDirection), Instant>,
+}
+
+impl Cooldown {
+    fn allow(&mut self, event: &AlertEvent, now: Instant) -> bool {
+        let key = (event.process.clone(), event.direction);
+        match self.last_sent.get(&key) {
+            Some(&at) if now.duration_since(at) < self.period => false,
+            _ => {
+                self.last_sent.insert(key, now);
+                true
+            }
+        }
+    }
+}
+
+/// Delivers fired alerts from a background task. Delivery failures are
+/// kept for the status line and never block the UI.
+pub struct Notifier {
+    tx: UnboundedSender<AlertEvent>,
+    error: LastError,
+}
+
+impl Notifier {
+    pub fn spawn(targets: Targets, cooldown: Duration) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let error = LastError::default();
+        let cooldown = Cooldown {
+            period: cooldown,
+            last_sent: HashMap::new(),
+        };
+        tokio::spawn(send_loop(targets, cooldown, rx, error.clone()));
+        Notifier { tx, error }
+    }
+
+    pub fn notify(&self, event: &AlertEvent) {
+        let _ = self.tx.send(event.clone());
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.error.get()
+    }
+}
+
+async fn send_loop(
+    targets: Targets,
+    mut cooldown: Cooldown,
+    mut rx: UnboundedReceiver<AlertEvent>,
+    error: LastError,
+) {
+    let client = match reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            error.set(Some(format!("notify: {}", e)));
+            return;
+        }
+    };
+    while let Some(event) = rx.recv().await {
+        if !cooldown.allow(&event, Instant::now()) {
+            continue;
+        }
+        let mut result = Ok(());
+        if let Some(url) = &targets.webhook {
+            result = result.and(send_webhook(&client, url, &event).await);
+        }
+        if let Some(topic) = &targets.ntfy {
+            result = result.and(send_ntfy(&client, topic, &event).await);
+        }
+        error.set(result.err().map(|e| format!("notify: {}", e)));
+    }
+}
+
+async fn send_webhook(client: &reqwest::Client, url: &str, event: &AlertEvent) -> Result<(), String> {
+    client
+        .post(url)
+        .json(&WebhookPayload::new(event))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+async fn send_ntfy(client: &reqwest::Client, topic: &str, event: &AlertEvent) -> Result<(), String> {
+    let url = if topic.contains("://") {
+        topic.to_string()
+    } else {
+        format!("https://ntfy.sh/{}", topic)
+    };
+    client
+        .post(url)
+        .header("Title", format!("nm: {} {}", event.process, event.direction.label()))
+        .header("Tags", "warning")
+        .body(ntfy_message(event))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn ntfy_message(event: &AlertEvent) -> String {
+    format!(
+        "{} (pid {}) {} rate {} is over {}",
+        event.process,
+        event.pid,
+        event.direction.label(),
+        Units::default().rate(event.rate),
+        Units::default().rate(event.threshold)
+    )
+}
+
+/// Minimum time between desktop notifications for the same alert rule (or
+/// for new remote hosts).
+const DESKTOP_THROTTLE: Duration = Duration::from_secs(180);
+
+/// Native notifications via `terminal-notifier` when installed, otherwise
+/// `osascript`. Failures (no notifier, headless session) are ignored.
+pub struct DesktopNotifier {
+    terminal_notifier: bool,
+    last_sent: HashMap<String, Instant>,
+}
+
+impl DesktopNotifier {
+    pub fn new() -> Self {
+        let terminal_notifier = std::env::var_os("PATH").is_some_and(|path| {
+            std::env::split_paths(&path).any(|dir| dir.join("terminal-notifier").is_file())
+        });
+        DesktopNotifier {
+            terminal_notifier,
+            last_sent: HashMap::new(),
+        }
+    }
+
+    pub fn alert(&mut self, event: &AlertEvent) {
+        let title = format!("nm: {} {}", event.process, event.direction.label());
+        self.send(format!("rule {}", event.rule), &title, &ntfy_message(event));
+    }
+
+    pub fn new_host(&mut self, process: &str, host: &str) {
+        self.send(
+            "new host".to_string(),
+            "nm: new remote host",
+            &format!("{} connected to {}", process, host),
+        );
+    }
+
+    pub fn budget(&mut self, message: &str) {
+        self.send(message.to_string(), "nm: data budget", message);
+    }
+
+    fn send(&mut self, throttle_key: String, title: &str, message: &str) {
+        let now = Instant::now();
+        if let Some(&at) = self.last_sent.get(&throttle_key) {
+            if now.duration_since(at) < DESKTOP_THROTTLE {
+                return;
+            }
+        }
+        self.last_sent.insert(throttle_key, now);
+
+        let mut cmd = if self.terminal_notifier {
+            let mut cmd = std::process::Command::new("terminal-notifier");
+            cmd.args(["-title", title, "-message", message, "-group", "nm"]);
+            cmd
+        } else {
+            let mut cmd = std::process::Command::new("osascript");
+            cmd.arg("-e").arg(format!(
+                "display notification {} with title {}",
+                applescript_string(message),
+                applescript_string(title)
+            ));
+            cmd
+        };
+        cmd.stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null());
+        // Wait off the UI thread so the child is reaped
+        std::thread::spawn(move || {
+            let _ = cmd.status();
+        });
+    }
+}
+
+/// Quote text as an AppleScript string literal.
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn event(process: &str) -> AlertEvent {
+        AlertEvent {
+            at: Local.timestamp_opt(1_714_564_800, 0).unwrap(),
+            rule: 0,
+            process: process.to_string(),
+            pid: 812,
+            direction: AlertDirection::Up,
+            rate: 6_291_456.0,
+            threshold: 5_242_880.0,
+            fired: true,
+        }
+    }
+
+    #[test]
+    fn test_webhook_payload() {
+        let event = event("Dropbox");
+        let json: serde_json::Value =
+            serde_json::to_value(WebhookPayload::new(&event)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "timestamp": event.at.to_rfc3339(),
+                "process": "Dropbox",
+                "pid": 812,
+                "direction": "upload",
+                "rate": 6_291_456.0,
+                "threshold": 5_242_880.0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_applescript_string_escapes_quotes() {
+        assert_eq!(applescript_string("plain"), "\"plain\"");
+        assert_eq!(
+            applescript_string(r#"say "hi" \ bye"#),
+            r#""say \"hi\" \\ bye""#
+        );
+    }
+
+    #[test]
+    fn test_cooldown_per_process() {
+        let mut cooldown = Cooldown {
+            period: Duration::from_secs(300),
+            last_sent: HashMap::new(),
+        };
+        let start = Instant::now();
+        assert!(cooldown.allow(&event("Dropbox"), start));
+        assert!(!cooldown.allow(&event("Dropbox"), start + Duration::from_secs(60)));
+        assert!(cooldown.allow(&event("curl"), start + Duration::from_secs(60)));
+        assert!(cooldown.allow(&event("Dropbox"), start + Duration::from_secs(301)));
+    }
+}
@@ -0,0 +1,114 @@
+use std::net::UdpSocket;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{rates_by_name, send_lines, udp_socket, Sink};
+use crate::data::model::NetworkSnapshot;
+
+/// Emits InfluxDB line protocol over UDP: an `nm_total` point and one
+/// `nm_process` point per process name.
+pub struct InfluxUdp {
+    socket: UdpSocket,
+}
+
+impl InfluxUdp {
+    pub fn connect(target: &str) -> Result<Self, String> {
+        Ok(InfluxUdp {
+            socket: udp_socket(target)?,
+        })
+    }
+}
+
+impl Sink for InfluxUdp {
+    fn emit(&mut self, snapshot: &NetworkSnapshot) -> Result<(), String> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        send_lines(&self.socket, &lines(snapshot, now))
+    }
+}
+
+/// Escape a tag key or value: commas, equals signs, and spaces.
+fn escape_tag(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | '=' | ' ' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn lines(snapshot: &NetworkSnapshot, timestamp_ns: u128) -> Vec<String> {
+    let mut lines = vec![format!(
+        "nm_total rate_in={},rate_out={},bytes_in={}i,bytes_out={}i,connections={}i {}",
+        snapshot.total_rate_in,
+        snapshot.total_rate_out,
+        snapshot.total_bytes_in,
+        snapshot.total_bytes_out,
+        snapshot.total_connections,
+        timestamp_ns
+    )];
+    for (name, (rate_in, rate_out, bytes_in, bytes_out)) in rates_by_name(snapshot) {
+        // A tag can't be empty in line protocol
+        if name.is_empty() {
+            continue;
+        }
+        lines.push(format!(
+            "nm_process,process={} rate_in={},rate_out={},bytes_in={}i,bytes_out={}i {}",
+            escape_tag(name),
+            rate_in,
+            rate_out,
+            bytes_in,
+            bytes_out,
+            timestamp_ns
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::model::Process;
+
+    fn process(name: &str, rate_in: f64) -> Process {
+        Process {
+            bytes_in: 10,
+            bytes_out: 20,
+            rate_in,
+            rate_out: 0.5,
+            ..Process::for_test(name, 1)
+        }
+    }
+
+    #[test]
+    fn test_escape_tag() {
+        assert_eq!(escape_tag("Google Chrome Helper"), "Google\\ Chrome\\ Helper");
+        assert_eq!(escape_tag("a,b=c"), "a\\,b\\=c");
+        assert_eq!(escape_tag("back\\slash"), "back\\\\slash");
+        assert_eq!(escape_tag("curl"), "curl");
+    }
+
+    #[test]
+    fn test_lines_sum_processes_by_name() {
+        let snapshot = NetworkSnapshot::from_processes(vec![
+            process("Slack Helper", 100.0),
+            process("Slack Helper", 50.0),
+            process("curl", 1.0),
+        ]);
+        let lines = lines(&snapshot, 1_700_000_000_000_000_000);
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "nm_total rate_in=151,rate_out=1.5,bytes_in=30i,bytes_out=60i,connections=0i 1700000000000000000"
+        );
+        assert_eq!(
+            lines[1],
+            "nm_process,process=Slack\\ Helper rate_in=150,rate_out=1,bytes_in=20i,bytes_out=40i 1700000000000000000"
+        );
+        assert!(lines[2].starts_with("nm_process,process=curl "));
+    }
+}
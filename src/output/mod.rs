@@ -0,0 +1,67 @@
+//! Sinks that receive every refreshed snapshot, e.g. for a monitoring stack.
+
+pub mod influx;
+pub mod statsd;
+
+use std::collections::BTreeMap;
+use std::net::UdpSocket;
+
+use crate::data::model::NetworkSnapshot;
+
+/// Destination for per-refresh measurements. `emit` is called once per
+/// refresh; failures are reported but never interrupt collection.
+pub trait Sink {
+    fn emit(&mut self, snapshot: &NetworkSnapshot) -> Result<(), String>;
+}
+
+/// Per-name (rate in, rate out, bytes in, bytes out), summing processes that
+/// share a name so each emission has one point per tag value.
+fn rates_by_name(snapshot: &NetworkSnapshot) -> BTreeMap<&str, (f64, f64, u64, u64)> {
+    let mut by_name: BTreeMap<&str, (f64, f64, u64, u64)> = BTreeMap::new();
+    for p in &snapshot.processes {
+        let entry = by_name.entry(p.name.as_str()).or_default();
+        entry.0 += p.rate_in;
+        entry.1 += p.rate_out;
+        entry.2 += p.bytes_in;
+        entry.3 += p.bytes_out;
+    }
+    by_name
+}
+
+/// UDP socket connected to `target` ("host:port"), bound on the matching
+/// address family.
+fn udp_socket(target: &str) -> Result<UdpSocket, String> {
+    use std::net::ToSocketAddrs;
+
+    let addr = target
+        .to_socket_addrs()
+        .map_err(|e| format!("{}: {}", target, e))?
+        .next()
+        .ok_or_else(|| format!("{}: no address", target))?;
+    let bind = if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+    let socket = UdpSocket::bind(bind).map_err(|e| format!("{}: {}", target, e))?;
+    socket.connect(addr).map_err(|e| format!("{}: {}", target, e))?;
+    socket
+        .set_nonblocking(true)
+        .map_err(|e| format!("{}: {}", target, e))?;
+    Ok(socket)
+}
+
+/// Send datagrams built from `lines`, packing as many lines per datagram as
+/// fit in a conservative payload size.
+fn send_lines(socket: &UdpSocket, lines: &[String]) -> Result<(), String> {
+    const MAX_DATAGRAM: usize = 1400;
+    let mut datagram = String::new();
+    for line in lines {
+        if !datagram.is_empty() && datagram.len() + line.len() + 1 > MAX_DATAGRAM {
+            socket.send(datagram.as_bytes()).map_err(|e| e.to_string())?;
+            datagram.clear();
+        }
+        datagram.push_str(line);
+        datagram.push('\n');
+    }
+    if !datagram.is_empty() {
+        socket.send(datagram.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use std::net::UdpSocket;
+
+use super::{rates_by_name, send_lines, udp_socket, Sink};
+use crate::data::model::NetworkSnapshot;
+
+/// Emits statsd gauges over UDP: `nm.total.rate_in` and
+/// `nm.process.<name>.rate_in` style metric names.
+pub struct StatsdUdp {
+    socket: UdpSocket,
+}
+
+impl StatsdUdp {
+    pub fn connect(target: &str) -> Result<Self, String> {
+        Ok(StatsdUdp {
+            socket: udp_socket(target)?,
+        })
+    }
+}
+
+impl Sink for StatsdUdp {
+    fn emit(&mut self, snapshot: &NetworkSnapshot) -> Result<(), String> {
+        send_lines(&self.socket, &lines(snapshot))
+    }
+}
+
+/// Metric-name-safe form of a process name: anything other than ASCII
+/// letters, digits, `-`, and `_` becomes `_`.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+fn lines(snapshot: &NetworkSnapshot) -> Vec<String> {
+    let mut lines = vec![
+        format!("nm.total.rate_in:{}|g", snapshot.total_rate_in),
+        format!("nm.total.rate_out:{}|g", snapshot.total_rate_out),
+        format!("nm.total.connections:{}|g", snapshot.total_connections),
+    ];
+    for (name, (rate_in, rate_out, _, _)) in rates_by_name(snapshot) {
+        let name = sanitize(name);
+        lines.push(format!("nm.process.{}.rate_in:{}|g", name, rate_in));
+        lines.push(format!("nm.process.{}.rate_out:{}|g", name, rate_out));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_metric_names() {
+        assert_eq!(sanitize("Google Chrome Helper (GPU)"), "Google_Chrome_Helper__GPU_");
+        assert_eq!(sanitize("com.apple.WebKit"), "com_apple_WebKit");
+        assert_eq!(sanitize("ssh-agent"), "ssh-agent");
+    }
+
+    #[test]
+    fn test_lines_include_totals() {
+        let snapshot = NetworkSnapshot {
+            total_rate_in: 12.5,
+            total_connections: 3,
+            ..NetworkSnapshot::default()
+        };
+        let lines = lines(&snapshot);
+        assert_eq!(lines[0], "nm.total.rate_in:12.5|g");
+        assert_eq!(lines[2], "nm.total.connections:3|g");
+    }
+}
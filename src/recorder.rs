@@ -0,0 +1,312 @@
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection};
+
+use crate::data::model::NetworkSnapshot;
+use crate::last_error::LastError;
+use crate::units::Units;
+
+/// Schema version stored in `PRAGMA user_version`. Each entry in
+/// `MIGRATIONS` upgrades the database by one version.
+const SCHEMA_VERSION: i64 = 1;
+
+const MIGRATIONS: &[&str] = &["
+    CREATE TABLE snapshots (
+        id INTEGER PRIMARY KEY,
+        ts INTEGER NOT NULL,
+        interval_secs REAL NOT NULL,
+        total_bytes_in INTEGER NOT NULL,
+        total_bytes_out INTEGER NOT NULL,
+        total_rate_in REAL NOT NULL,
+        total_rate_out REAL NOT NULL,
+        total_connections INTEGER NOT NULL
+    );
+    CREATE INDEX snapshots_ts ON snapshots (ts);
+    CREATE TABLE processes (
+        snapshot_id INTEGER NOT NULL REFERENCES snapshots (id),
+        name TEXT NOT NULL,
+        pid INTEGER NOT NULL,
+        user TEXT,
+        bytes_in INTEGER NOT NULL,
+        bytes_out INTEGER NOT NULL,
+        rate_in REAL NOT NULL,
+        rate_out REAL NOT NULL,
+        connections INTEGER NOT NULL
+    );
+    CREATE INDEX processes_snapshot ON processes (snapshot_id);
+    CREATE TABLE connections (
+        snapshot_id INTEGER NOT NULL REFERENCES snapshots (id),
+        pid INTEGER NOT NULL,
+        protocol TEXT NOT NULL,
+        local_addr TEXT NOT NULL,
+        local_port INTEGER NOT NULL,
+        remote_addr TEXT NOT NULL,
+        remote_port INTEGER NOT NULL,
+        hostname TEXT,
+        state TEXT NOT NULL,
+        bytes_in INTEGER NOT NULL,
+        bytes_out INTEGER NOT NULL,
+        rate_in REAL NOT NULL,
+        rate_out REAL NOT NULL
+    );
+    CREATE INDEX connections_snapshot ON connections (snapshot_id);
+"];
+
+/// Open (creating if needed) a history database and bring its schema up to
+/// date.
+pub fn open(path: &Path) -> Result<Connection, String> {
+    let conn = Connection::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let version = schema_version(&conn).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "{}: schema version {} is newer than this nm supports ({})",
+            path.display(),
+            version,
+            SCHEMA_VERSION
+        ));
+    }
+    migrate(&conn).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(conn)
+}
+
+fn schema_version(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    let version = schema_version(conn)?;
+    for (i, sql) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(sql)?;
+        tx.pragma_update(None, "user_version", i as i64 + 1)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+/// Insert one refresh (snapshot, processes, and connections) in a single
+/// transaction.
+fn insert(
+    conn: &mut Connection,
+    snapshot: &NetworkSnapshot,
+    ts: i64,
+    interval_secs: f64,
+) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO snapshots (ts, interval_secs, total_bytes_in, total_bytes_out,
+             total_rate_in, total_rate_out, total_connections)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            ts,
+            interval_secs,
+            snapshot.total_bytes_in as i64,
+            snapshot.total_bytes_out as i64,
+            snapshot.total_rate_in,
+            snapshot.total_rate_out,
+            snapshot.total_connections as i64,
+        ],
+    )?;
+    let id = tx.last_insert_rowid();
+    {
+        let mut insert_process = tx.prepare_cached(
+            "INSERT INTO processes (snapshot_id, name, pid, user, bytes_in, bytes_out,
+                 rate_in, rate_out, connections)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+        let mut insert_connection = tx.prepare_cached(
+            "INSERT INTO connections (snapshot_id, pid, protocol, local_addr, local_port,
+                 remote_addr, remote_port, hostname, state, bytes_in, bytes_out, rate_in, rate_out)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        )?;
+        for p in &snapshot.processes {
+            insert_process.execute(params![
+                id,
+                p.name,
+                p.pid,
+                p.user,
+                p.bytes_in as i64,
+                p.bytes_out as i64,
+                p.rate_in,
+                p.rate_out,
+                p.connection_count() as i64,
+            ])?;
+            for c in &p.connections {
+                insert_connection.execute(params![
+                    id,
+                    p.pid,
+                    c.protocol.to_string(),
+                    c.local_addr,
+                    c.local_port,
+                    c.remote_addr,
+                    c.remote_port,
+                    c.hostname,
+                    c.state,
+                    c.bytes_in as i64,
+                    c.bytes_out as i64,
+                    c.rate_in,
+                    c.rate_out,
+                ])?;
+            }
+        }
+    }
+    tx.commit()
+}
+
+/// Writes every refresh into a SQLite database from a background thread so
+/// inserts never stall the UI. The most recent error is kept for the UI to
+/// report. Dropping it waits for queued snapshots to be inserted.
+pub struct Recorder {
+    tx: Option<Sender<(NetworkSnapshot, i64)>>,
+    thread: Option<JoinHandle<()>>,
+    error: LastError,
+}
+
+impl Recorder {
+    /// Open the database up front so a bad path or newer schema is reported
+    /// at startup, then hand the connection to the writer thread.
+    pub fn spawn(path: &Path, interval_secs: f64) -> Result<Self, String> {
+        let conn = open(path)?;
+        let (tx, rx) = mpsc::channel();
+        let error = LastError::default();
+        let thread_error = error.clone();
+        let thread = std::thread::spawn(move || write_loop(conn, interval_secs, rx, thread_error));
+        Ok(Recorder {
+            tx: Some(tx),
+            thread: Some(thread),
+            error,
+        })
+    }
+
+    pub fn record(&self, snapshot: &NetworkSnapshot) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send((snapshot.clone(), unix_now()));
+        }
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.error.get()
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        // Closing the channel ends the loop once the backlog is inserted
+        self.tx.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn write_loop(
+    mut conn: Connection,
+    interval_secs: f64,
+    rx: Receiver<(NetworkSnapshot, i64)>,
+    error: LastError,
+) {
+    for (snapshot, ts) in rx {
+        let result = insert(&mut conn, &snapshot, ts, interval_secs);
+        error.set(result.err().map(|e| format!("db: {}", e)));
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Per-process traffic since `since` ago, estimated from each sample's rate
+/// times its refresh interval, largest first.
+fn totals(conn: &Connection, since: Duration) -> rusqlite::Result<Vec<(String, u64, u64)>> {
+    let cutoff = unix_now() - since.as_secs() as i64;
+    let mut stmt = conn.prepare(
+        "SELECT p.name,
+                SUM(p.rate_in * s.interval_secs),
+                SUM(p.rate_out * s.interval_secs)
+         FROM processes p JOIN snapshots s ON s.id = p.snapshot_id
+         WHERE s.ts >= ?1
+         GROUP BY p.name
+         ORDER BY SUM((p.rate_in + p.rate_out) * s.interval_secs) DESC, p.name",
+    )?;
+    let rows = stmt.query_map([cutoff], |row| {
+        let bytes_in: f64 = row.get(1)?;
+        let bytes_out: f64 = row.get(2)?;
+        Ok((row.get::<_, String>(0)?, bytes_in as u64, bytes_out as u64))
+    })?;
+    rows.collect()
+}
+
+/// The `nm report` table: per-process totals recorded in the last `since`.
+pub fn report(path: &Path, since: Duration, units: Units) -> Result<String, String> {
+    if !path.exists() {
+        return Err(format!("{}: no such database", path.display()));
+    }
+    let conn = open(path)?;
+    let totals = totals(&conn, since).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let name_width = totals.iter().map(|t| t.0.chars().count()).max().unwrap_or(0).max(7);
+    let mut out = format!(
+        "{:<name_width$}  {:>10}  {:>10}  {:>10}\n",
+        "Process", "Down", "Up", "Total"
+    );
+    for (name, bytes_in, bytes_out) in totals {
+        out.push_str(&format!(
+            "{:<name_width$}  {:>10}  {:>10}  {:>10}\n",
+            name,
+            units.bytes(bytes_in),
+            units.bytes(bytes_out),
+            units.bytes(bytes_in + bytes_out)
+        ));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::model::Process;
+
+    fn process(name: &str, pid: u32, rate_in: f64, rate_out: f64) -> Process {
+        Process {
+            rate_in,
+            rate_out,
+            ..Process::for_test(name, pid)
+        }
+    }
+
+    #[test]
+    fn test_migrate_is_idempotent() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn).unwrap();
+        migrate(&conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_totals_sum_rates_over_window() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&conn).unwrap();
+        let now = unix_now();
+        let recent = NetworkSnapshot::from_processes(vec![
+            process("curl", 1, 100.0, 10.0),
+            process("Safari", 2, 5.0, 5.0),
+            process("Safari", 3, 5.0, 5.0),
+        ]);
+        insert(&mut conn, &recent, now - 60, 2.0).unwrap();
+        insert(&mut conn, &recent, now - 58, 0.5).unwrap();
+        let old = NetworkSnapshot::from_processes(vec![process("curl", 1, 1e6, 0.0)]);
+        insert(&mut conn, &old, now - 7200, 2.0).unwrap();
+
+        let totals = totals(&conn, Duration::from_secs(3600)).unwrap();
+        assert_eq!(
+            totals,
+            vec![("curl".to_string(), 250, 25), ("Safari".to_string(), 25, 25)]
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crossterm::cursor::Show;
+use crossterm::event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+
+/// Whether the terminal is set up for the TUI and needs restoring.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// The terminal in raw mode on the alternate screen, with bracketed paste
+/// and optionally mouse capture. Dropping it puts everything back, so an
+/// early return restores the terminal as `q` does.
+pub struct ScreenGuard;
+
+impl ScreenGuard {
+    pub fn enter(mouse: bool) -> io::Result<ScreenGuard> {
+        enable_raw_mode()?;
+        ACTIVE.store(true, Ordering::SeqCst);
+        // From here on a failure drops the guard and undoes what was done
+        let guard = ScreenGuard;
+        execute!(io::stdout(), EnterAlternateScreen, EnableBracketedPaste)?;
+        if mouse {
+            execute!(io::stdout(), EnableMouseCapture)?;
+        }
+        Ok(guard)
+    }
+}
+
+impl Drop for ScreenGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Leave raw mode and the alternate screen and show the cursor, if the
+/// TUI set them up. Shared by the guard and the panic hook; errors are
+/// ignored, as there is nothing more to be done about them.
+pub fn restore() {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let _ = disable_raw_mode();
+    let _ = execute!(
+        io::stdout(),
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        LeaveAlternateScreen,
+        Show
+    );
+}
+
+/// Restore the terminal before a panic's message is printed, so it lands on
+/// the normal screen and the shell is usable afterwards. Panics in runtime
+/// worker threads only end their task and leave the TUI running.
+pub fn install_panic_hook() {
+    let default = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if std::thread::current().name() == Some("main") {
+            restore();
+        }
+        default(info);
+    }));
+}
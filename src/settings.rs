@@ -0,0 +1,397 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Documented example printed by `nm --print-default-config`. Every key is
+/// optional; uncommented values are the built-in defaults.
+pub const DEFAULT_CONFIG: &str = r##"# nm configuration: ~/.config/nm/config.toml (or --config PATH)
+# Keys match the long command-line flags; flags given on the command line
+# take precedence over this file.
+
+# Refresh interval: seconds (2, 0.5) or a duration ("500ms", "1m")
+interval = 2
+# How far back the bandwidth histories go, from "1m" to "24h"
+history = "10m"
+# Initial sort: name, pid, conn, down, up, rate-in, rate-out, pps-in, pps-out
+sort-by = "rate-in"
+# Units: bytes (powers of 1024), bytes-si (powers of 1000), bits (rates
+# in kbps/Mbps/Gbps)
+units = "bytes"
+# Starting tab: processes, connections, remotes, listeners, interfaces, overview, graph
+# tab = "connections"
+
+# Filter input starting with regex-prefix is a case-insensitive regular
+# expression ("" for no prefix); regex-filter makes all input one
+regex-filter = false
+regex-prefix = "/"
+
+# Extra Processes columns
+show-peak = false
+show-pps = false
+show-user = false
+show-uptime = false
+# Show raw process names instead of app bundle names
+raw-names = false
+
+# Connections
+closed-retention = 3
+idle-after = 30
+no-local = false
+ports-skip-loopback = false
+netstat = false
+# vpn-iface = ["wg*", "corp0"]
+
+# Reverse DNS
+no-dns = false
+resolve-local = false
+dns-system-cache = false
+no-cache-file = false
+dns-cache-size = 4096
+# aliases = "~/.config/nm/aliases"
+# asn-db = "~/GeoLite2-ASN.mmdb"
+
+# Alerts and notifications
+# alert-up = ["5MB", "Dropbox=1MB"]
+# alert-down = ["20MB"]
+alert-bell = false
+alert-cooldown = "5m"
+notify = false
+# alert-webhook = "https://example.com/hook"
+# alert-ntfy = "my-nm-alerts"
+# budget = "2GB"
+
+# Output
+# log = "~/nm.jsonl"
+# db = "~/nm.db"
+# export-dir = "~/Downloads"
+
+# Color theme: default, light, solarized, gruvbox, monochrome
+theme = "default"
+# Draw without colors, as if NO_COLOR were set
+no-color = false
+# Leave the mouse to the terminal (for selecting text) instead of using it
+# to switch tabs, select rows, sort, and scroll
+no-mouse = false
+# Draw the bandwidth sparkline on a logarithmic scale, so small rates stay
+# visible next to a spike
+log-scale = false
+
+# Key bindings by action name. A binding replaces all of the action's
+# default keys; use a list for several keys, or [] to unbind. Keys are
+# characters ("d", "H", "?") or names ("Enter", "Esc", "Tab", "Shift-Tab",
+# "Up", "Down", "PageUp", "Space", "F5", "Ctrl-n", "Alt-x"). A key bound to
+# two actions is an error. Actions and their defaults:
+[keys]
+# quit = "q"
+# next-tab = "Tab"
+# prev-tab = "Shift-Tab"
+# nav-down = ["j", "Down"]
+# nav-up = ["k", "Up"]
+# page-down = ["PageDown", "Ctrl-f"]
+# page-up = ["PageUp", "Ctrl-b"]
+# nav-first = ["g", "Home"]
+# nav-last = ["G", "End"]
+# drill-down = "Enter"
+# back = "Backspace"
+# process-detail = "d"
+# detail-pane = "w"
+# cycle-sort = "s"
+# prev-sort = "Ctrl-s"
+# flip-sort = "S"
+# sort-menu = "o"
+# filter = "/"
+# clear-filter = "Esc"
+# search = "f"
+# search-next = "n"
+# search-prev = "N"
+# toggle-services = "v"
+# toggle-hostnames = "H"
+# reload-aliases = "R"
+# copy = "y"
+# copy-alt = "Y"
+# export-json = "e"
+# export-csv = "E"
+# alert-log = "a"
+# reset-budget = "B"
+# toggle-local = "l"
+# toggle-inbound = "i"
+# toggle-idle = "z"
+# pause = "p"
+# reset = "r"
+# help = "?"
+# columns = "c"
+# shrink-column = "<"
+# grow-column = ">"
+# cycle-units = "b"
+# zoom-in = ["+", "="]
+# zoom-out = "-"
+# graph-process = "t"
+# toggle-log-scale = "m"
+
+# Rates above `medium` are colored yellow and above `high` red (sizes per
+# second; by default about 100KB and 1MB). With `relative = true` the colors instead follow
+# each rate's share of the busiest process: above 10% medium, above 50% high.
+[thresholds]
+# medium = "500KB"
+# high = "10MB"
+relative = false
+
+# Columns to show, in order; unlisted columns start hidden and can be
+# turned on with `c`. Processes: process, pid, user, conn, down, up,
+# rate-in, rate-out, pps-in, pps-out, uptime, peak. Connections: process,
+# protocol, local, remote, iface, dir, state, age, down, up, queue (with
+# --netstat), org (with --asn-db).
+[columns]
+# processes = ["process", "rate-in", "rate-out", "conn"]
+# connections = ["process", "remote", "state", "age", "down", "up"]
+
+# Column widths by column id: cells (12), a share of the table ("30%"), or
+# a minimum that takes spare room ("min:16"). `<`/`>` adjust the sorted
+# column at runtime; those tweaks are saved to ~/.local/state/nm/state.toml
+# and take precedence over these.
+[columns.process-widths]
+# process = "min:24"
+[columns.connection-widths]
+# remote = "40%"
+# state = 8
+
+# Override individual theme colors with a name (red, lightblue, darkgray),
+# a 256-color index ("208"), or "#rrggbb". Slots: header, active-tab,
+# inactive-tab, selected-bg, border, footer, upload, download, closed, idle,
+# lan, multicast, vpn, stalled, inbound-public, warning, alert,
+# filter-match, rate-high, rate-medium, rate-low, rate-zero.
+[colors]
+# border = "#444444"
+# rate-high = "lightred"
+"##;
+
+/// Interval given as a number of seconds or a duration string.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Interval {
+    Secs(f64),
+    Text(String),
+}
+
+/// One key or a list of keys in the `[keys]` section.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    pub fn into_vec(self) -> Vec<String> {
+        match self {
+            KeyList::One(key) => vec![key],
+            KeyList::Many(keys) => keys,
+        }
+    }
+}
+
+/// Preferences from the config file. Keys are the long flag names; the
+/// command line overrides anything set here.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Settings {
+    pub interval: Option<Interval>,
+    pub history: Option<String>,
+    pub sort_by: Option<String>,
+    pub tab: Option<String>,
+    pub units: Option<String>,
+    pub regex_filter: Option<bool>,
+    pub regex_prefix: Option<String>,
+    pub show_peak: Option<bool>,
+    pub show_pps: Option<bool>,
+    pub show_user: Option<bool>,
+    pub show_uptime: Option<bool>,
+    pub raw_names: Option<bool>,
+    pub closed_retention: Option<usize>,
+    pub idle_after: Option<usize>,
+    pub no_local: Option<bool>,
+    pub ports_skip_loopback: Option<bool>,
+    pub netstat: Option<bool>,
+    #[serde(rename = "vpn-iface")]
+    pub vpn_ifaces: Option<Vec<String>>,
+    pub no_dns: Option<bool>,
+    pub resolve_local: Option<bool>,
+    pub dns_system_cache: Option<bool>,
+    pub no_cache_file: Option<bool>,
+    pub dns_cache_size: Option<usize>,
+    pub aliases: Option<PathBuf>,
+    pub asn_db: Option<PathBuf>,
+    pub alert_up: Option<Vec<String>>,
+    pub alert_down: Option<Vec<String>>,
+    pub alert_bell: Option<bool>,
+    pub alert_cooldown: Option<String>,
+    pub notify: Option<bool>,
+    pub alert_webhook: Option<String>,
+    pub alert_ntfy: Option<String>,
+    pub budget: Option<String>,
+    pub log: Option<PathBuf>,
+    pub db: Option<PathBuf>,
+    pub export_dir: Option<PathBuf>,
+    pub theme: Option<String>,
+    pub no_color: Option<bool>,
+    pub no_mouse: Option<bool>,
+    pub log_scale: Option<bool>,
+    pub keys: Option<BTreeMap<String, KeyList>>,
+    pub colors: Option<BTreeMap<String, String>>,
+    pub thresholds: Option<Thresholds>,
+    pub columns: Option<ColumnSettings>,
+}
+
+/// The `[columns]` section: columns to show, in order, per table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[serde(rename_all = "kebab-case")]
+pub struct ColumnSettings {
+    pub processes: Option<Vec<String>>,
+    pub connections: Option<Vec<String>>,
+    pub process_widths: Option<BTreeMap<String, Width>>,
+    pub connection_widths: Option<BTreeMap<String, Width>>,
+}
+
+/// A column width: a number of cells, or a string like "30%" or "min:16".
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Width {
+    Cells(u16),
+    Text(String),
+}
+
+impl Width {
+    pub fn into_string(self) -> String {
+        match self {
+            Width::Cells(n) => n.to_string(),
+            Width::Text(text) => text,
+        }
+    }
+}
+
+/// Column widths adjusted at runtime with `<`/`>`, kept between runs and
+/// applied over the config file's widths.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct State {
+    pub process_widths: BTreeMap<String, String>,
+    pub connection_widths: BTreeMap<String, String>,
+}
+
+impl State {
+    /// Read the state file; a missing or unreadable one is just empty.
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| toml::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = toml::to_string(self).map_err(|e| e.to_string())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(path, text).map_err(|e| e.to_string())
+    }
+}
+
+/// `$XDG_STATE_HOME/nm/state.toml`, falling back to `~/.local/state`.
+pub fn state_file_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".local").join("state"),
+    };
+    Some(base.join("nm").join("state.toml"))
+}
+
+/// The `[thresholds]` section: where rate colors change.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thresholds {
+    pub medium: Option<String>,
+    pub high: Option<String>,
+    pub relative: Option<bool>,
+}
+
+impl Settings {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// Read the config file. A missing file at the default location is
+    /// not an error; one named with `--config` is.
+    pub fn load(path: &Path, explicit: bool) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && !explicit => Ok(Self::default()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/nm/config.toml`, falling back to `~/.config`.
+pub fn config_file_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("nm").join("config.toml"))
+}
+
+/// Expand a leading `~/` to the home directory.
+pub fn expand_home(path: PathBuf) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_parses() {
+        let settings = Settings::parse(DEFAULT_CONFIG).unwrap();
+        assert!(matches!(settings.interval, Some(Interval::Secs(s)) if s == 2.0));
+        assert_eq!(settings.sort_by.as_deref(), Some("rate-in"));
+        assert_eq!(settings.dns_cache_size, Some(4096));
+        assert_eq!(settings.alert_cooldown.as_deref(), Some("5m"));
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_line() {
+        let error = Settings::parse("interval = 2\nshow-peak = yes\n").unwrap_err();
+        assert!(error.contains("line 2"), "{}", error);
+
+        let error = Settings::parse("colour = \"red\"\n").unwrap_err();
+        assert!(error.contains("unknown field `colour`"), "{}", error);
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let path = std::env::temp_dir()
+            .join(format!("nm-state-test-{}", std::process::id()))
+            .join("state.toml");
+        let mut state = State::default();
+        state.process_widths.insert("process".to_string(), "min:24".to_string());
+        state.connection_widths.insert("remote".to_string(), "40%".to_string());
+        state.save(&path).unwrap();
+
+        let loaded = State::load(&path);
+        assert_eq!(loaded.process_widths, state.process_widths);
+        assert_eq!(loaded.connection_widths, state.connection_widths);
+        assert!(State::load(&path.with_file_name("missing.toml")).process_widths.is_empty());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_expand_home() {
+        let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+        assert_eq!(expand_home(PathBuf::from("~/nm.db")), home.join("nm.db"));
+        assert_eq!(expand_home(PathBuf::from("/tmp/nm.db")), PathBuf::from("/tmp/nm.db"));
+    }
+}
@@ -0,0 +1,44 @@
+use std::io;
+
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::watch;
+
+/// Set once SIGTERM, SIGHUP, or SIGINT arrives, so the run loops can stop
+/// and leave through their usual cleanup: restoring the terminal, saving
+/// caches, and letting the writers finish.
+#[derive(Clone)]
+pub struct Shutdown {
+    rx: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+    /// Take over the signals. Must be called from within the runtime.
+    pub fn listen() -> io::Result<Shutdown> {
+        let mut term = signal(SignalKind::terminate())?;
+        let mut hup = signal(SignalKind::hangup())?;
+        let mut int = signal(SignalKind::interrupt())?;
+        let (tx, rx) = watch::channel(false);
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = term.recv() => {}
+                _ = hup.recv() => {}
+                _ = int.recv() => {}
+            }
+            let _ = tx.send(true);
+            // Keep the handlers installed so a second signal doesn't kill
+            // the process halfway through cleaning up
+            std::future::pending::<()>().await;
+        });
+        Ok(Shutdown { rx })
+    }
+
+    pub fn requested(&self) -> bool {
+        *self.rx.borrow()
+    }
+
+    /// Resolves once a signal has arrived.
+    pub async fn wait(&self) {
+        let mut rx = self.rx.clone();
+        let _ = rx.wait_for(|&requested| requested).await;
+    }
+}
@@ -0,0 +1,75 @@
+use std::time::{Duration, Instant};
+
+/// When the TUI's next refresh is due. The schedule is kept apart from
+/// input handling, so a stream of events (a pointer moving over the
+/// terminal, a held key) can't keep pushing the refresh back.
+pub struct Ticker {
+    interval: Duration,
+    next: Instant,
+}
+
+impl Ticker {
+    /// First tick one interval after `now`.
+    pub fn new(interval: Duration, now: Instant) -> Self {
+        Ticker {
+            interval,
+            next: now + interval,
+        }
+    }
+
+    /// How long to wait for input: until the next tick or the deadline,
+    /// whichever comes first.
+    pub fn timeout(&self, now: Instant, deadline: Option<Instant>) -> Duration {
+        let next = deadline.map_or(self.next, |d| d.min(self.next));
+        next.saturating_duration_since(now)
+    }
+
+    /// Whether a tick is due at `now`, scheduling the one after if so. A
+    /// slow refresh that overran the interval doesn't cause a burst of
+    /// catch-up ticks.
+    pub fn due(&mut self, now: Instant) -> bool {
+        if now < self.next {
+            return false;
+        }
+        self.next += self.interval;
+        if self.next <= now {
+            self.next = now + self.interval;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_flood_does_not_delay_ticks() {
+        let start = Instant::now();
+        let mut ticker = Ticker::new(Duration::from_secs(1), start);
+        // An event every 10ms for 3.5s, each ending the wait early
+        let mut ticks = Vec::new();
+        for i in 1..=350 {
+            let now = start + Duration::from_millis(10 * i);
+            if ticker.due(now) {
+                ticks.push(i * 10);
+            }
+        }
+        assert_eq!(ticks, [1000, 2000, 3000]);
+    }
+
+    #[test]
+    fn test_timeout_and_overrun() {
+        let start = Instant::now();
+        let mut ticker = Ticker::new(Duration::from_secs(2), start);
+        let at = |ms| start + Duration::from_millis(ms);
+        assert_eq!(ticker.timeout(at(500), None), Duration::from_millis(1500));
+        assert_eq!(ticker.timeout(at(500), Some(at(1000))), Duration::from_millis(500));
+        assert_eq!(ticker.timeout(at(2500), None), Duration::ZERO);
+
+        // A refresh that took 5s: one tick, then a full interval
+        assert!(ticker.due(at(7000)));
+        assert!(!ticker.due(at(7000)));
+        assert_eq!(ticker.timeout(at(7000), None), Duration::from_secs(2));
+    }
+}
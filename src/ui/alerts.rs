@@ -0,0 +1,49 @@
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::app::App;
+use crate::ui::layout::centered_rect;
+use crate::ui::theme;
+
+/// Popup listing when each alert fired and cleared, newest first.
+pub fn render(f: &mut Frame, app: &App) {
+    let area = centered_rect(70, 60, f.area());
+
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::current().header))
+        .title(" Alert Log ");
+
+    let mut lines = Vec::new();
+    if app.alerts.is_empty() {
+        lines.push(Line::from("No alert rules (use --alert-up / --alert-down)"));
+    } else if app.alerts.log.is_empty() {
+        lines.push(Line::from("No alerts yet"));
+    }
+    let visible = area.height.saturating_sub(2) as usize;
+    for event in app.alerts.log.iter().rev().take(visible) {
+        let (status, style) = if event.fired {
+            ("FIRED  ", Style::default().fg(theme::current().alert).add_modifier(Modifier::BOLD))
+        } else {
+            ("cleared", Style::default().fg(theme::current().idle))
+        };
+        lines.push(Line::from(vec![
+            Span::raw(format!("{}  ", event.at.format("%H:%M:%S"))),
+            Span::styled(status, style),
+            Span::raw(format!(
+                "  {} ({})  {} {} / {}",
+                event.process,
+                event.pid,
+                event.direction.label(),
+                app.units.rate(event.rate),
+                app.units.rate(event.threshold)
+            )),
+        ]));
+    }
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
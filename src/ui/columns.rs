@@ -0,0 +1,440 @@
+use ratatui::layout::Constraint;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::app::App;
+use crate::ui::layout::centered_rect;
+use crate::ui::theme;
+
+/// A table column: its config id, header label, default width, and
+/// whether it is shown by default.
+pub trait Column: Copy + PartialEq + 'static {
+    const ALL: &'static [Self];
+    fn id(self) -> &'static str;
+    fn label(self) -> &'static str;
+    fn width(self) -> Constraint;
+    fn default_visible(self) -> bool;
+
+    /// Table width below which the column gives way to the others (one of
+    /// the `layout` breakpoints), or 0 to always show it.
+    fn hide_below(self) -> u16 {
+        0
+    }
+}
+
+/// Narrowest a column is squeezed or resized to.
+const MIN_COLUMN_WIDTH: u16 = 3;
+
+/// Cells a table spends outside its columns: two borders and the "▸ "
+/// selection marker.
+const TABLE_CHROME_WIDTH: u16 = 4;
+
+/// Cells left for the columns of a table `width` cells wide.
+pub fn available(width: u16) -> u16 {
+    width.saturating_sub(TABLE_CHROME_WIDTH)
+}
+
+/// Parse a width setting: `12` (exactly 12 cells), `30%` (of the table),
+/// or `min:16` (at least 16, taking any spare room).
+pub fn parse_width(s: &str) -> Result<Constraint, String> {
+    let s = s.trim();
+    let invalid = || format!("invalid width {:?} (use 12, 30%, or min:16)", s);
+    let (constraint, value): (fn(u16) -> Constraint, &str) = if let Some(p) = s.strip_suffix('%') {
+        (Constraint::Percentage, p)
+    } else if let Some(n) = s.strip_prefix("min:") {
+        (Constraint::Min, n)
+    } else {
+        (Constraint::Length, s)
+    };
+    let value: u16 = value.trim().parse().map_err(|_| invalid())?;
+    match constraint(value) {
+        Constraint::Percentage(p) if !(1..=100).contains(&p) => Err(invalid()),
+        Constraint::Length(n) | Constraint::Min(n) if n < MIN_COLUMN_WIDTH => Err(invalid()),
+        c => Ok(c),
+    }
+}
+
+/// Inverse of `parse_width`, for saving adjusted widths.
+pub fn format_width(width: Constraint) -> String {
+    match width {
+        Constraint::Percentage(p) => format!("{}%", p),
+        Constraint::Min(n) => format!("min:{}", n),
+        Constraint::Length(n) => n.to_string(),
+        other => format!("{}", other),
+    }
+}
+
+/// Turn requested widths into ones that fit `available` cells (after the
+/// one-cell gaps between columns). When they don't fit, the widest columns
+/// give up space first, so no column is squeezed to nothing while others
+/// keep their full width.
+pub fn fit(widths: &[Constraint], available: u16) -> Vec<Constraint> {
+    let gaps = widths.len().saturating_sub(1) as u16;
+    let budget = available.saturating_sub(gaps);
+    let mut sizes: Vec<u16> = widths
+        .iter()
+        .map(|w| match *w {
+            Constraint::Length(n) | Constraint::Min(n) => n,
+            Constraint::Percentage(p) => (budget as u32 * p as u32 / 100) as u16,
+            _ => MIN_COLUMN_WIDTH,
+        })
+        .collect();
+    if sizes.iter().sum::<u16>() <= budget {
+        return widths.to_vec();
+    }
+    while sizes.iter().sum::<u16>() > budget {
+        let Some(widest) = sizes.iter_mut().filter(|s| **s > MIN_COLUMN_WIDTH).max() else {
+            break;
+        };
+        *widest -= 1;
+    }
+    sizes.into_iter().map(Constraint::Length).collect()
+}
+
+/// Column order, visibility, and width for one table.
+#[derive(Debug, Clone)]
+pub struct Columns<C> {
+    entries: Vec<(C, bool)>,
+    /// Widths set in the config file or adjusted with `<`/`>`
+    widths: Vec<(C, Constraint)>,
+    /// Columns adjusted with `<`/`>` or restored from an earlier session;
+    /// only their widths are saved, so edits to the config file still apply
+    resized: Vec<C>,
+    /// Visibility was set in the config file or with the columns popup
+    chosen: bool,
+}
+
+impl<C: Column> Default for Columns<C> {
+    fn default() -> Self {
+        Columns {
+            entries: C::ALL.iter().map(|c| (*c, c.default_visible())).collect(),
+            widths: Vec::new(),
+            resized: Vec::new(),
+            chosen: false,
+        }
+    }
+}
+
+impl<C: Column> Columns<C> {
+    /// Show exactly the columns named, in that order; the rest follow,
+    /// hidden, so they can still be toggled on.
+    pub fn from_ids(ids: &[String]) -> Result<Self, String> {
+        let mut entries: Vec<(C, bool)> = Vec::new();
+        for id in ids {
+            let column = C::ALL
+                .iter()
+                .find(|c| c.id() == id)
+                .copied()
+                .ok_or_else(|| {
+                    let known = C::ALL.iter().map(|c| c.id()).collect::<Vec<_>>().join(", ");
+                    format!("unknown column {:?} (expected one of {})", id, known)
+                })?;
+            if entries.iter().any(|(c, _)| *c == column) {
+                return Err(format!("column {:?} listed twice", id));
+            }
+            entries.push((column, true));
+        }
+        if entries.is_empty() {
+            return Err("at least one column must be shown".to_string());
+        }
+        for column in C::ALL {
+            if !entries.iter().any(|(c, _)| c == column) {
+                entries.push((*column, false));
+            }
+        }
+        Ok(Columns {
+            entries,
+            widths: Vec::new(),
+            resized: Vec::new(),
+            chosen: true,
+        })
+    }
+
+    /// Apply width settings keyed by column id.
+    pub fn set_widths<'a>(&mut self, widths: impl IntoIterator<Item = (&'a String, &'a String)>) -> Result<(), String> {
+        for (id, width) in widths {
+            let column = Self::column(id)?;
+            self.set_width(column, parse_width(width).map_err(|e| format!("{}: {}", id, e))?);
+        }
+        Ok(())
+    }
+
+    /// Apply widths saved by an earlier session, which are saved again
+    /// along with this session's adjustments.
+    pub fn restore_widths<'a>(
+        &mut self,
+        widths: impl IntoIterator<Item = (&'a String, &'a String)>,
+    ) -> Result<(), String> {
+        for (id, width) in widths {
+            self.set_widths([(id, width)])?;
+            self.mark_resized(Self::column(id)?);
+        }
+        Ok(())
+    }
+
+    fn column(id: &str) -> Result<C, String> {
+        C::ALL
+            .iter()
+            .find(|c| c.id() == id)
+            .copied()
+            .ok_or_else(|| format!("unknown column {:?}", id))
+    }
+
+    fn mark_resized(&mut self, column: C) {
+        if !self.resized.contains(&column) {
+            self.resized.push(column);
+        }
+    }
+
+    fn set_width(&mut self, column: C, width: Constraint) {
+        match self.widths.iter_mut().find(|(c, _)| *c == column) {
+            Some((_, w)) => *w = width,
+            None => self.widths.push((column, width)),
+        }
+    }
+
+    /// The configured width, if one was set.
+    pub fn width_override(&self, column: C) -> Option<Constraint> {
+        self.widths.iter().find(|(c, _)| *c == column).map(|(_, w)| *w)
+    }
+
+    pub fn width(&self, column: C) -> Constraint {
+        self.width_override(column).unwrap_or_else(|| column.width())
+    }
+
+    /// Grow or shrink a column by one cell (one percent for percentages).
+    pub fn resize(&mut self, column: C, grow: bool) {
+        let step = |n: u16, max: u16| {
+            if grow {
+                n.saturating_add(1).min(max)
+            } else {
+                n.saturating_sub(1).max(MIN_COLUMN_WIDTH.min(n))
+            }
+        };
+        let width = match self.width(column) {
+            Constraint::Length(n) => Constraint::Length(step(n, u16::MAX)),
+            Constraint::Min(n) => Constraint::Min(step(n, u16::MAX)),
+            Constraint::Percentage(p) => Constraint::Percentage(step(p, 100).max(1)),
+            other => other,
+        };
+        self.set_width(column, width);
+        self.mark_resized(column);
+    }
+
+    /// Widths of the resized columns by column id, for saving.
+    pub fn resized_widths(&self) -> impl Iterator<Item = (String, String)> + '_ {
+        self.resized.iter().map(|c| (c.id().to_string(), format_width(self.width(*c))))
+    }
+
+    pub fn show(&mut self, column: C) {
+        for (c, visible) in &mut self.entries {
+            if *c == column {
+                *visible = true;
+            }
+        }
+    }
+
+    pub fn visible(&self) -> impl Iterator<Item = C> + '_ {
+        self.entries.iter().filter(|(_, visible)| *visible).map(|(c, _)| *c)
+    }
+
+    /// The visible columns a table `width` cells wide shows. By default the
+    /// columns below their `hide_below` go; columns the user chose stay as
+    /// long as their widths fit, and otherwise give way in the same order
+    /// (widest breakpoint first) only until the rest fit.
+    pub fn visible_at(&self, width: u16) -> Vec<C> {
+        let mut shown: Vec<C> = self.visible().collect();
+        let mut hideable: Vec<C> = shown.iter().copied().filter(|c| width < c.hide_below()).collect();
+        hideable.sort_by_key(|c| std::cmp::Reverse(c.hide_below()));
+        let available = available(width);
+        for column in hideable {
+            if self.chosen && self.fits(&shown, available) {
+                break;
+            }
+            if shown.len() > 1 {
+                shown.retain(|c| *c != column);
+            }
+        }
+        shown
+    }
+
+    /// Whether the columns' requested widths and the gaps between them fit.
+    fn fits(&self, columns: &[C], available: u16) -> bool {
+        let gaps = columns.len().saturating_sub(1) as u32;
+        let needed: u32 = columns
+            .iter()
+            .map(|c| match self.width(*c) {
+                Constraint::Length(n) | Constraint::Min(n) => n as u32,
+                _ => MIN_COLUMN_WIDTH as u32,
+            })
+            .sum();
+        needed + gaps <= available as u32
+    }
+
+    pub fn entries(&self) -> &[(C, bool)] {
+        &self.entries
+    }
+
+    /// Flip one column, refusing to hide the last visible one.
+    pub fn toggle(&mut self, index: usize) {
+        let shown = self.visible().count();
+        if let Some((_, visible)) = self.entries.get_mut(index) {
+            if !*visible || shown > 1 {
+                *visible = !*visible;
+                self.chosen = true;
+            }
+        }
+    }
+}
+
+/// Popup listing the current table's columns with checkboxes.
+pub fn render(f: &mut Frame, app: &App) {
+    let area = centered_rect(40, 60, f.area());
+
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::current().header))
+        .title(" Columns (Space: toggle) ");
+
+    let lines = match app.column_choices() {
+        Some(choices) => choices
+            .into_iter()
+            .enumerate()
+            .map(|(i, (label, visible))| {
+                let text = format!("[{}] {}", if visible { "x" } else { " " }, label);
+                if i == app.column_index {
+                    Line::from(Span::styled(format!("▸ {}", text), theme::selected_style()))
+                } else {
+                    Line::from(format!("  {}", text))
+                }
+            })
+            .collect(),
+        None => vec![Line::from(Span::styled(
+            "Columns can be chosen on the Processes and Connections tabs",
+            Style::default().add_modifier(Modifier::DIM),
+        ))],
+    };
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::processes::ProcessColumn;
+
+    fn ids(columns: &Columns<ProcessColumn>) -> Vec<&'static str> {
+        columns.visible().map(|c| c.id()).collect()
+    }
+
+    #[test]
+    fn test_parse_width() {
+        assert_eq!(parse_width("12"), Ok(Constraint::Length(12)));
+        assert_eq!(parse_width("30%"), Ok(Constraint::Percentage(30)));
+        assert_eq!(parse_width("min:16"), Ok(Constraint::Min(16)));
+        assert!(parse_width("0%").is_err());
+        assert!(parse_width("120%").is_err());
+        assert!(parse_width("1").is_err());
+        assert!(parse_width("wide").is_err());
+        for width in ["12", "30%", "min:16"] {
+            assert_eq!(format_width(parse_width(width).unwrap()), width);
+        }
+    }
+
+    #[test]
+    fn test_fit_shrinks_widest_columns() {
+        let widths = [Constraint::Min(16), Constraint::Length(7), Constraint::Length(30)];
+        // 16 + 7 + 30 plus two gaps fits in 60
+        assert_eq!(fit(&widths, 60), widths.to_vec());
+        // In 40 there are 38 cells for 53 requested: the widest gives way first
+        assert_eq!(
+            fit(&widths, 40),
+            vec![Constraint::Length(16), Constraint::Length(7), Constraint::Length(15)]
+        );
+        // Nothing goes below the minimum, even when it can't all fit
+        assert_eq!(fit(&widths, 5), vec![Constraint::Length(3); 3]);
+    }
+
+    #[test]
+    fn test_resize_and_resized_widths() {
+        let mut columns = Columns::<ProcessColumn>::default();
+        let settings = [("process".to_string(), "min:20".to_string())];
+        columns.set_widths(settings.iter().map(|(k, v)| (k, v))).unwrap();
+        assert_eq!(columns.width(ProcessColumn::Name), Constraint::Min(20));
+        assert_eq!(columns.width(ProcessColumn::Pid), Constraint::Length(7));
+
+        columns.resize(ProcessColumn::Pid, true);
+        columns.resize(ProcessColumn::Name, false);
+        assert_eq!(columns.width(ProcessColumn::Pid), Constraint::Length(8));
+        let saved: Vec<_> = columns.resized_widths().collect();
+        assert_eq!(
+            saved,
+            [("pid".to_string(), "8".to_string()), ("process".to_string(), "min:19".to_string())]
+        );
+
+        // Config file widths aren't saved unless resized
+        let mut columns = Columns::<ProcessColumn>::default();
+        columns.set_widths(settings.iter().map(|(k, v)| (k, v))).unwrap();
+        let restored = [("pid".to_string(), "9".to_string())];
+        columns.restore_widths(restored.iter().map(|(k, v)| (k, v))).unwrap();
+        let saved: Vec<_> = columns.resized_widths().collect();
+        assert_eq!(saved, [("pid".to_string(), "9".to_string())]);
+
+        let widest = [("pid".to_string(), u16::MAX.to_string())];
+        columns.set_widths(widest.iter().map(|(k, v)| (k, v))).unwrap();
+        columns.resize(ProcessColumn::Pid, true);
+        assert_eq!(columns.width(ProcessColumn::Pid), Constraint::Length(u16::MAX));
+
+        let bad = [("pid".to_string(), "huge".to_string())];
+        assert!(columns.set_widths(bad.iter().map(|(k, v)| (k, v))).is_err());
+    }
+
+    #[test]
+    fn test_columns_from_ids_and_toggle() {
+        let names = ["process", "rate-in", "rate-out"].map(String::from);
+        let mut columns = Columns::<ProcessColumn>::from_ids(&names).unwrap();
+        assert_eq!(ids(&columns), ["process", "rate-in", "rate-out"]);
+        assert_eq!(columns.entries().len(), ProcessColumn::ALL.len());
+
+        columns.show(ProcessColumn::Peak);
+        assert_eq!(ids(&columns), ["process", "rate-in", "rate-out", "peak"]);
+
+        columns.toggle(0);
+        assert_eq!(ids(&columns), ["rate-in", "rate-out", "peak"]);
+        // The last visible column stays
+        columns.toggle(1);
+        columns.toggle(2);
+        let last = columns.entries().iter().position(|(c, _)| *c == ProcessColumn::Peak).unwrap();
+        columns.toggle(last);
+        assert_eq!(ids(&columns), ["peak"]);
+
+        let error = Columns::<ProcessColumn>::from_ids(&["pid".to_string(), "pid".to_string()]).unwrap_err();
+        assert_eq!(error, "column \"pid\" listed twice");
+        let error = Columns::<ProcessColumn>::from_ids(&["name".to_string()]).unwrap_err();
+        assert!(error.starts_with("unknown column \"name\" (expected one of process, pid,"), "{}", error);
+    }
+
+    #[test]
+    fn test_visible_at_breakpoints() {
+        let columns = Columns::<ProcessColumn>::default();
+        let at = |width| columns.visible_at(width).into_iter().map(|c| c.id()).collect::<Vec<_>>();
+        assert_eq!(at(120), ["process", "pid", "conn", "down", "up", "rate-in", "rate-out"]);
+        assert_eq!(at(100), ["process", "pid", "conn", "down", "up", "rate-in", "rate-out"]);
+        assert_eq!(at(99), ["process", "pid", "conn", "rate-in", "rate-out"]);
+        assert_eq!(at(59), ["process", "conn", "rate-in", "rate-out"]);
+
+        // Chosen columns are kept even below their breakpoints while they
+        // fit; a hidden column never comes back
+        let mut chosen = Columns::<ProcessColumn>::default();
+        let conn = chosen.entries().iter().position(|(c, _)| *c == ProcessColumn::Conn).unwrap();
+        chosen.toggle(conn);
+        let at = |width| chosen.visible_at(width).into_iter().map(|c| c.id()).collect::<Vec<_>>();
+        assert_eq!(at(99), ["process", "pid", "down", "up", "rate-in", "rate-out"]);
+        assert_eq!(at(80), ["process", "pid", "up", "rate-in", "rate-out"]);
+        assert_eq!(at(59), ["process", "rate-in", "rate-out"]);
+    }
+}
@@ -0,0 +1,145 @@
+use std::net::IpAddr;
+
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+
+use crate::app::App;
+use crate::data::addr;
+use crate::ui::layout::centered_rect;
+use crate::ui::processes::format_duration;
+use crate::ui::theme;
+
+pub fn render(f: &mut Frame, app: &App) {
+    let area = centered_rect(70, 70, f.area());
+
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::current().header))
+        .title(" Connection Detail ");
+
+    let Some(row) = app.selected_connection_row() else {
+        let empty = Paragraph::new("No connection selected").block(block);
+        f.render_widget(empty, area);
+        return;
+    };
+    let (p, conn) = (row.process, row.conn);
+
+    let label = |s: &'static str| Span::styled(format!("{:<12}", s), theme::header_style());
+    let or_unknown = |value: Option<String>| value.unwrap_or_else(|| "?".to_string());
+
+    let hostname = match &conn.hostname {
+        Some(name) => name.clone(),
+        None if conn.resolving => "resolving…".to_string(),
+        None => "—".to_string(),
+    };
+    let state = if row.closed {
+        "closed".to_string()
+    } else if conn.state.is_empty() {
+        "—".to_string()
+    } else {
+        conn.state.clone()
+    };
+    let interface = if conn.via_vpn {
+        format!("{} (VPN)", conn.interface)
+    } else {
+        conn.interface.clone()
+    };
+    let idle = match app.idle_time(p.pid, conn) {
+        Some(idle) if idle.is_zero() => "active".to_string(),
+        Some(idle) => format_duration(idle),
+        None => "?".to_string(),
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!(" {} → {} ", conn.local_label(), conn.remote_label(false, false)),
+            Style::default()
+                .fg(theme::current().active_tab)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![label("Local"), Span::raw(endpoint(&conn.local_addr, conn.local_port))]),
+        Line::from(vec![
+            label("Remote"),
+            Span::styled(
+                endpoint(&conn.remote_addr, conn.remote_port),
+                theme::addr_style(addr::classify_addr(&conn.remote_addr)),
+            ),
+        ]),
+        Line::from(vec![label("Hostname"), Span::raw(hostname)]),
+        Line::from(vec![
+            label("Protocol"),
+            Span::raw(match conn.family() {
+                Some(family) => format!("{} over {}", conn.protocol, family),
+                None => conn.protocol.to_string(),
+            }),
+        ]),
+        Line::from(vec![label("State"), Span::raw(state)]),
+        Line::from(vec![
+            label("Direction"),
+            Span::raw(match conn.direction.label() {
+                "" => "?",
+                direction => direction,
+            }),
+        ]),
+        Line::from(vec![label("Interface"), Span::raw(interface)]),
+    ];
+    if let Some(org) = &conn.asn {
+        lines.push(Line::from(vec![label("Org"), Span::raw(org.clone())]));
+    }
+    if let (Some(rx), Some(tx)) = (conn.recv_q, conn.send_q) {
+        lines.push(Line::from(vec![label("RxQ/TxQ"), Span::raw(format!("{} / {}", rx, tx))]));
+    }
+    lines.extend([
+        Line::from(""),
+        Line::from(vec![label("Process"), Span::raw(format!("{} ({})", p.name, p.pid))]),
+        Line::from(vec![label("Path"), Span::raw(or_unknown(p.path.clone()))]),
+        Line::from(""),
+        Line::from(vec![
+            label("Total"),
+            Span::styled(
+                format!("▼ {}", app.units.bytes(conn.bytes_in)),
+                Style::default().fg(theme::current().download),
+            ),
+            Span::raw("  "),
+            Span::styled(
+                format!("▲ {}", app.units.bytes(conn.bytes_out)),
+                Style::default().fg(theme::current().upload),
+            ),
+        ]),
+        Line::from(vec![
+            label("Rate"),
+            Span::styled(
+                format!("▼ {}", app.units.rate(conn.rate_in)),
+                Style::default().fg(theme::rate_color(conn.rate_in)),
+            ),
+            Span::raw("  "),
+            Span::styled(
+                format!("▲ {}", app.units.rate(conn.rate_out)),
+                Style::default().fg(theme::rate_color(conn.rate_out)),
+            ),
+        ]),
+        Line::from(vec![
+            label("First seen"),
+            Span::raw(or_unknown(conn.first_seen.map(|t| format!("{} ago", format_duration(t.elapsed()))))),
+        ]),
+        Line::from(vec![label("Idle"), Span::raw(idle)]),
+    ]);
+
+    // Wrapping keeps long hostnames and paths whole
+    let detail = Paragraph::new(lines).block(block).wrap(Wrap { trim: false });
+    f.render_widget(detail, area);
+}
+
+/// Address and port, bracketing IPv6 addresses so the port stands apart.
+fn endpoint(address: &str, port: u16) -> String {
+    match (addr::parse_ip(address), port) {
+        (_, 0) => address.to_string(),
+        (Some(IpAddr::V6(_)), port) => format!("[{}]:{}", address, port),
+        (_, port) => format!("{}:{}", address, port),
+    }
+}
@@ -1,89 +1,269 @@
 use ratatui::layout::{Constraint, Rect};
-use ratatui::style::Style;
-use ratatui::text::Span;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Cell, Row, Table, TableState};
 use ratatui::Frame;
 
-use crate::app::App;
+use crate::app::{ActiveTab, App};
+use crate::data::addr;
+use crate::data::filter::Field;
+use crate::data::model::{Connection, Direction};
+use crate::ui::columns::{self, Column};
+use crate::ui::hitbox::TableHitbox;
+use crate::ui::layout;
 use crate::ui::theme;
-use crate::ui::processes::{format_bytes, format_rate};
+use crate::ui::processes::format_duration;
 
-pub fn render(f: &mut Frame, area: Rect, app: &App) {
-    let header_cells = ["Process", "Protocol", "Local", "Remote", "State", "Down", "Up"]
+/// Columns of the Connections table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionColumn {
+    Process,
+    Protocol,
+    Local,
+    Remote,
+    Iface,
+    Dir,
+    State,
+    Age,
+    Down,
+    Up,
+    Queue,
+    Org,
+}
+
+impl Column for ConnectionColumn {
+    const ALL: &'static [Self] = &[
+        ConnectionColumn::Process,
+        ConnectionColumn::Protocol,
+        ConnectionColumn::Local,
+        ConnectionColumn::Remote,
+        ConnectionColumn::Iface,
+        ConnectionColumn::Dir,
+        ConnectionColumn::State,
+        ConnectionColumn::Age,
+        ConnectionColumn::Down,
+        ConnectionColumn::Up,
+        ConnectionColumn::Queue,
+        ConnectionColumn::Org,
+    ];
+
+    fn id(self) -> &'static str {
+        match self {
+            ConnectionColumn::Process => "process",
+            ConnectionColumn::Protocol => "protocol",
+            ConnectionColumn::Local => "local",
+            ConnectionColumn::Remote => "remote",
+            ConnectionColumn::Iface => "iface",
+            ConnectionColumn::Dir => "dir",
+            ConnectionColumn::State => "state",
+            ConnectionColumn::Age => "age",
+            ConnectionColumn::Down => "down",
+            ConnectionColumn::Up => "up",
+            ConnectionColumn::Queue => "queue",
+            ConnectionColumn::Org => "org",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ConnectionColumn::Process => "Process",
+            ConnectionColumn::Protocol => "Protocol",
+            ConnectionColumn::Local => "Local",
+            ConnectionColumn::Remote => "Remote",
+            ConnectionColumn::Iface => "Iface",
+            ConnectionColumn::Dir => "Dir",
+            ConnectionColumn::State => "State",
+            ConnectionColumn::Age => "Age",
+            ConnectionColumn::Down => "Down",
+            ConnectionColumn::Up => "Up",
+            ConnectionColumn::Queue => "RxQ/TxQ",
+            ConnectionColumn::Org => "Org",
+        }
+    }
+
+    fn width(self) -> Constraint {
+        match self {
+            ConnectionColumn::Process => Constraint::Min(14),
+            ConnectionColumn::Protocol | ConnectionColumn::Dir => Constraint::Length(5),
+            ConnectionColumn::Local => Constraint::Length(22),
+            ConnectionColumn::Remote => Constraint::Min(28),
+            ConnectionColumn::Iface => Constraint::Length(11),
+            ConnectionColumn::State | ConnectionColumn::Up => Constraint::Length(12),
+            ConnectionColumn::Age => Constraint::Length(7),
+            ConnectionColumn::Down => Constraint::Length(10),
+            ConnectionColumn::Queue => Constraint::Length(13),
+            ConnectionColumn::Org => Constraint::Min(20),
+        }
+    }
+
+    fn default_visible(self) -> bool {
+        true
+    }
+
+    fn hide_below(self) -> u16 {
+        match self {
+            ConnectionColumn::Down | ConnectionColumn::Up => layout::HIDE_TOTALS_BELOW,
+            ConnectionColumn::State => layout::COLLAPSE_BELOW,
+            _ => 0,
+        }
+    }
+}
+
+/// Visible columns that have data, as many as a table `width` cells wide
+/// shows: queue sizes need `--netstat` and organizations need `--asn-db`.
+fn active_columns(app: &App, width: u16) -> Vec<ConnectionColumn> {
+    app.connection_columns
+        .visible_at(width)
+        .into_iter()
+        .filter(|column| match column {
+            ConnectionColumn::Queue => app.netstat_enrich,
+            ConnectionColumn::Org => app.asn_lookup.is_some(),
+            _ => true,
+        })
+        .collect()
+}
+
+pub fn render(f: &mut Frame, area: Rect, app: &App) -> TableHitbox {
+    let columns = active_columns(app, area.width);
+    let header_cells = columns
         .iter()
-        .map(|h| Cell::from(Span::styled(*h, theme::header_style())))
+        .map(|c| Cell::from(Span::styled(c.label(), theme::header_style())))
         .collect::<Vec<_>>();
 
     let header = Row::new(header_cells).height(1);
 
-    let mut rows: Vec<Row> = Vec::new();
-
-    let processes = app.filtered_processes();
-    for p in &processes {
-        for conn in &p.connections {
-            let remote_display = conn
-                .hostname
-                .as_deref()
-                .unwrap_or(&conn.remote_addr);
-
-            let remote_str = if conn.remote_port > 0 {
-                format!("{}:{}", remote_display, conn.remote_port)
-            } else {
-                remote_display.to_string()
-            };
-
-            let local_str = if conn.local_port > 0 {
-                format!("{}:{}", conn.local_addr, conn.local_port)
-            } else {
-                conn.local_addr.clone()
-            };
-
-            // Apply filter
-            if let Some(ref filter) = app.filter_text {
-                let filter_lower = filter.to_lowercase();
-                let matches = p.name.to_lowercase().contains(&filter_lower)
-                    || remote_str.to_lowercase().contains(&filter_lower)
-                    || local_str.to_lowercase().contains(&filter_lower)
-                    || conn.protocol.to_string().to_lowercase().contains(&filter_lower);
-                if !matches {
-                    continue;
-                }
-            }
-
-            rows.push(Row::new(vec![
-                Cell::from(p.name.clone()),
-                Cell::from(conn.protocol.to_string()),
-                Cell::from(local_str),
-                Cell::from(remote_str),
-                Cell::from(conn.state.clone()),
-                Cell::from(format_bytes(conn.bytes_in)),
-                Cell::from(format_rate(conn.bytes_out as f64)),
-            ]));
+    let connections = app.filtered_connections();
+    let (rows, window) = layout::window_rows(&connections, app.connection_offset, app.connection_index, area, |r| {
+        let row = connection_row(app, &columns, &r.process.name, r.conn, r.closed);
+        if r.closed {
+            row.style(theme::closed_style())
+        } else if r.conn.idle {
+            row.style(theme::idle_style())
+        } else {
+            row
         }
-    }
+    });
 
-    let widths = [
-        Constraint::Min(14),
-        Constraint::Length(5),
-        Constraint::Length(22),
-        Constraint::Min(28),
-        Constraint::Length(12),
-        Constraint::Length(10),
-        Constraint::Length(12),
-    ];
+    let widths = columns
+        .iter()
+        .map(|column| match app.connection_columns.width_override(*column) {
+            Some(width) => width,
+            // Raw addresses are shorter than most hostnames
+            None if *column == ConnectionColumn::Remote && !app.show_hostnames => Constraint::Min(22),
+            None => column.width(),
+        })
+        .collect::<Vec<_>>();
+    let widths = columns::fit(&widths, columns::available(area.width));
 
-    let table = Table::new(rows, widths)
+    // A drill-down shows where it came from
+    let title = match &app.drilled {
+        Some((name, pid)) => format!(" Connections ▸ {} ({}) ", name, pid),
+        None => " Connections ".to_string(),
+    };
+    let table = Table::new(rows, widths.clone())
         .header(header)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(theme::BORDER_COLOR))
-                .title(" Connections "),
+                .border_style(Style::default().fg(theme::current().border))
+                .title(title),
         )
-        .row_highlight_style(theme::selected_style())
+        .row_highlight_style(theme::row_highlight(app.on_search_match()))
         .highlight_symbol("▸ ");
 
     let mut state = TableState::default();
-    state.select(Some(app.connection_index));
+    state.select(Some(app.connection_index.saturating_sub(window.start)));
     f.render_stateful_widget(table, area, &mut state);
+    layout::table_scrollbar(f, area, connections.len(), window.start);
+
+    // Connections have no sort of their own
+    TableHitbox::new(area, &widths, &[], window.start)
+}
+
+/// Build a table row for a connection.
+fn connection_row<'a>(
+    app: &App,
+    columns: &[ConnectionColumn],
+    name: &str,
+    conn: &Connection,
+    closed: bool,
+) -> Row<'a> {
+    let highlighted = |text: &str, field| {
+        Cell::from(Line::from(theme::highlight(text, &app.highlights(ActiveTab::Connections, text, field), Style::default())))
+    };
+    let cells = columns.iter().map(|column| match column {
+        ConnectionColumn::Process => highlighted(name, None),
+        ConnectionColumn::Protocol => highlighted(&conn.protocol.to_string(), Some(Field::Proto)),
+        ConnectionColumn::Local => highlighted(&conn.local_label(), None),
+        ConnectionColumn::Remote => remote_cell(app, conn),
+        ConnectionColumn::Iface => iface_cell(app, conn),
+        ConnectionColumn::Dir => direction_cell(conn),
+        ConnectionColumn::State if closed => Cell::from("closed"),
+        ConnectionColumn::State => highlighted(&conn.state, Some(Field::State)),
+        ConnectionColumn::Age => Cell::from(
+            conn.first_seen
+                .map(|t| format_duration(t.elapsed()))
+                .unwrap_or_default(),
+        ),
+        ConnectionColumn::Down => Cell::from(app.units.bytes(conn.bytes_in)),
+        ConnectionColumn::Up => Cell::from(app.units.bytes(conn.bytes_out)),
+        ConnectionColumn::Queue => queue_cell(conn),
+        ConnectionColumn::Org => highlighted(conn.asn.as_deref().unwrap_or_default(), None),
+    });
+    Row::new(cells.collect::<Vec<_>>())
+}
+
+/// Receive/send queue sizes, highlighted when the send queue looks stalled.
+fn queue_cell<'a>(conn: &Connection) -> Cell<'a> {
+    let (Some(rx), Some(tx)) = (conn.recv_q, conn.send_q) else {
+        return Cell::from("");
+    };
+    let text = format!("{}/{}", rx, tx);
+    if conn.send_stalled {
+        Cell::from(Span::styled(
+            text,
+            Style::default().fg(theme::current().stalled).add_modifier(Modifier::BOLD),
+        ))
+    } else {
+        Cell::from(text)
+    }
+}
+
+/// Remote address tinted by class. An address whose hostname is still being
+/// looked up gets a dimmed ellipsis so the later switch to a name is expected.
+fn remote_cell<'a>(app: &App, conn: &Connection) -> Cell<'a> {
+    let label = conn.remote_label(app.show_services, app.show_hostnames);
+    let mut spans = theme::highlight(
+        &label,
+        &app.highlights(ActiveTab::Connections, &label, Some(Field::Host)),
+        theme::addr_style(addr::classify_addr(&conn.remote_addr)),
+    );
+    if app.show_hostnames && conn.resolving && conn.hostname.is_none() {
+        spans.push(Span::styled(" …", theme::footer_style()));
+    }
+    Cell::from(Line::from(spans))
+}
+
+/// Direction tag; inbound connections from public addresses stand out.
+fn direction_cell<'a>(conn: &Connection) -> Cell<'a> {
+    let label = conn.direction.label();
+    let public = addr::classify_addr(&conn.remote_addr).is_some_and(|c| !c.is_lan());
+    if conn.direction == Direction::Inbound && public {
+        Cell::from(Span::styled(
+            label,
+            Style::default().fg(theme::current().inbound_public).add_modifier(Modifier::BOLD),
+        ))
+    } else {
+        Cell::from(label)
+    }
+}
+
+/// Interface name, tagged and colored when it is a VPN tunnel.
+fn iface_cell<'a>(app: &App, conn: &Connection) -> Cell<'a> {
+    let (text, style) = if conn.via_vpn {
+        (format!("{} VPN", conn.interface), Style::default().fg(theme::current().vpn))
+    } else {
+        (conn.interface.clone(), Style::default())
+    };
+    Cell::from(Line::from(theme::highlight(&text, &app.highlights(ActiveTab::Connections, &text, None), style)))
 }
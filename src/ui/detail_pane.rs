@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use ratatui::Frame;
+
+use crate::app::{ActiveTab, App};
+use crate::data::model::Process;
+use crate::ui::processes::format_duration;
+use crate::ui::theme;
+
+/// Remote hosts listed for a process.
+const TOP_HOSTS: usize = 3;
+
+/// A few lines about the active tab's selected row, under the table.
+pub fn render(f: &mut Frame, area: Rect, app: &App) {
+    if area.height == 0 {
+        return;
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::current().border))
+        .title(" Details ");
+
+    let lines = match app.active_tab {
+        ActiveTab::Processes => app.selected_process().map(|p| process_lines(app, p)),
+        ActiveTab::Connections => app.selected_connection_row().map(|row| {
+            let conn = row.conn;
+            let state = if row.closed { "closed" } else { conn.state.as_str() };
+            vec![
+                Line::from(format!("{} → {}", conn.local_label(), conn.remote_label(false, false))),
+                fields(&[
+                    ("Host", conn.hostname.clone().unwrap_or_else(|| "—".to_string())),
+                    ("State", if state.is_empty() { "—".to_string() } else { state.to_string() }),
+                    ("Age", conn.first_seen.map(|t| format_duration(t.elapsed())).unwrap_or_else(|| "?".to_string())),
+                ]),
+                fields(&[
+                    ("Process", format!("{} ({})", row.process.name, row.process.pid)),
+                    ("Iface", conn.interface.clone()),
+                ]),
+            ]
+        }),
+        ActiveTab::Remotes => app.filtered_remote_hosts().get(app.remote_index).map(|h| {
+            vec![
+                Line::from(h.host.clone()),
+                fields(&[
+                    ("Connections", h.connections.to_string()),
+                    ("Total", format!("▼ {}  ▲ {}", app.units.bytes(h.bytes_in), app.units.bytes(h.bytes_out))),
+                ]),
+                fields(&[("Processes", h.processes.join(", "))]),
+            ]
+        }),
+        ActiveTab::Listeners => app.filtered_listeners().get(app.listener_index).map(|l| {
+            vec![
+                Line::from(format!("{} {}:{}", l.protocol, l.addr, l.port)),
+                fields(&[
+                    ("Process", format!("{} ({})", l.process, l.pid)),
+                    ("Family", l.family().to_string()),
+                ]),
+            ]
+        }),
+        ActiveTab::Interfaces => app.filtered_interfaces().get(app.interface_index).map(|i| {
+            vec![
+                Line::from(format!("{} ({})", i.name, if i.up { "up" } else { "down" })),
+                fields(&[
+                    ("Total", format!("▼ {}  ▲ {}", app.units.bytes(i.bytes_in), app.units.bytes(i.bytes_out))),
+                    ("Packets", format!("▼ {}  ▲ {}", i.packets_in, i.packets_out)),
+                ]),
+            ]
+        }),
+        ActiveTab::Overview | ActiveTab::Graph => None,
+    };
+    let lines = lines.unwrap_or_else(|| vec![Line::from("Nothing selected")]);
+
+    // Wrapped and then cut off at the pane's height
+    let pane = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+    f.render_widget(pane, area);
+}
+
+fn process_lines<'a>(app: &App, p: &Process) -> Vec<Line<'a>> {
+    let hosts = top_hosts(p)
+        .into_iter()
+        .map(|(host, rate)| format!("{} ({})", host, app.units.rate(rate)))
+        .collect::<Vec<_>>();
+    vec![
+        Line::from(format!("{} ({})", p.name, p.pid)),
+        fields(&[
+            ("Path", p.path.clone().unwrap_or_else(|| "?".to_string())),
+            ("User", p.user.clone().unwrap_or_else(|| "?".to_string())),
+        ]),
+        fields(&[(
+            "Top hosts",
+            if hosts.is_empty() { "—".to_string() } else { hosts.join(", ") },
+        )]),
+    ]
+}
+
+/// The process's busiest remote hosts by combined rate, then by bytes moved.
+fn top_hosts(p: &Process) -> Vec<(&str, f64)> {
+    let mut by_host: HashMap<&str, (f64, u64)> = HashMap::new();
+    for conn in &p.connections {
+        let entry = by_host.entry(conn.remote_host()).or_default();
+        entry.0 += conn.rate_in + conn.rate_out;
+        entry.1 += conn.bytes_in + conn.bytes_out;
+    }
+    let mut hosts = by_host.into_iter().collect::<Vec<_>>();
+    hosts.sort_by(|a, b| {
+        b.1 .0
+            .total_cmp(&a.1 .0)
+            .then(b.1 .1.cmp(&a.1 .1))
+            .then(a.0.cmp(b.0))
+    });
+    hosts.into_iter().take(TOP_HOSTS).map(|(host, (rate, _))| (host, rate)).collect()
+}
+
+/// Labelled values on one line: "Path /usr/bin/curl  User root".
+fn fields<'a>(pairs: &[(&'static str, String)]) -> Line<'a> {
+    let mut spans = Vec::new();
+    for (i, (label, value)) in pairs.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw("  "));
+        }
+        spans.push(Span::styled(format!("{} ", label), theme::header_style()));
+        spans.push(Span::raw(value.clone()));
+    }
+    Line::from(spans)
+}
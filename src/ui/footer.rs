@@ -0,0 +1,121 @@
+use std::cmp::Reverse;
+
+use ratatui::text::Span;
+
+/// Priority of hints kept however narrow the footer gets.
+pub const ALWAYS: u8 = u8::MAX;
+
+const SEPARATOR: &str = " │ ";
+
+/// A "key: label" footer hint. When the footer is too narrow, every hint
+/// first switches to its short label, then the lowest priorities go.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub key: String,
+    pub label: String,
+    pub short: String,
+    pub priority: u8,
+}
+
+impl Hint {
+    pub fn new(key: impl Into<String>, label: impl Into<String>, priority: u8) -> Self {
+        let label = label.into();
+        Hint {
+            key: key.into(),
+            short: label.clone(),
+            label,
+            priority,
+        }
+    }
+
+    /// Label used once the full labels don't fit.
+    pub fn short(mut self, short: impl Into<String>) -> Self {
+        self.short = short.into();
+        self
+    }
+}
+
+/// The hints as one line of at most `width` cells if it can be done:
+/// full labels, else short ones with the lowest priorities dropped (later
+/// hints before earlier ones of the same priority) until the rest fit.
+/// `ALWAYS` hints stay even if they overflow.
+pub fn fit(hints: &[Hint], width: usize) -> String {
+    let full = join(hints.iter().map(|h| (h.key.as_str(), h.label.as_str())));
+    if Span::raw(full.as_str()).width() <= width {
+        return full;
+    }
+    let mut shown: Vec<&Hint> = hints.iter().collect();
+    loop {
+        let line = join(shown.iter().map(|h| (h.key.as_str(), h.short.as_str())));
+        if Span::raw(line.as_str()).width() <= width {
+            return line;
+        }
+        let lowest = shown
+            .iter()
+            .enumerate()
+            .filter(|(_, h)| h.priority != ALWAYS)
+            .min_by_key(|&(i, h)| (h.priority, Reverse(i)))
+            .map(|(i, _)| i);
+        match lowest {
+            Some(i) => {
+                shown.remove(i);
+            }
+            None => return line,
+        }
+    }
+}
+
+fn join<'a>(hints: impl Iterator<Item = (&'a str, &'a str)>) -> String {
+    hints
+        .map(|(key, label)| format!("{}: {}", key, label))
+        .collect::<Vec<_>>()
+        .join(SEPARATOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hints() -> Vec<Hint> {
+        vec![
+            Hint::new("Tab", "switch", 4),
+            Hint::new("j/k", "nav", 2),
+            Hint::new("s", "sort (Rate In ▼)", 3).short("sort"),
+            Hint::new("/", "filter [curl]", 6).short("filter"),
+            Hint::new("Enter", "drill", 5),
+            Hint::new("p", "pause", 1),
+            Hint::new("?", "help", ALWAYS),
+            Hint::new("q", "quit", ALWAYS),
+        ]
+    }
+
+    #[test]
+    fn test_fit_at_several_widths() {
+        let full = "Tab: switch │ j/k: nav │ s: sort (Rate In ▼) │ /: filter [curl] │ Enter: drill │ p: pause │ ?: help │ q: quit";
+        assert_eq!(fit(&hints(), 200), full);
+        assert_eq!(fit(&hints(), full.chars().count()), full);
+
+        // Short labels come before dropping anything
+        let short = "Tab: switch │ j/k: nav │ s: sort │ /: filter │ Enter: drill │ p: pause │ ?: help │ q: quit";
+        assert_eq!(fit(&hints(), full.chars().count() - 1), short);
+        assert_eq!(fit(&hints(), short.chars().count()), short);
+
+        // Then the lowest priorities go, pause first
+        assert_eq!(
+            fit(&hints(), 80),
+            "Tab: switch │ j/k: nav │ s: sort │ /: filter │ Enter: drill │ ?: help │ q: quit"
+        );
+        assert_eq!(fit(&hints(), 60), "Tab: switch │ /: filter │ Enter: drill │ ?: help │ q: quit");
+        assert_eq!(fit(&hints(), 40), "/: filter │ ?: help │ q: quit");
+        assert_eq!(fit(&hints(), 20), "?: help │ q: quit");
+        // Help and quit stay even when nothing fits
+        assert_eq!(fit(&hints(), 5), "?: help │ q: quit");
+        assert_eq!(fit(&[], 0), "");
+    }
+
+    #[test]
+    fn test_equal_priorities_drop_later_first() {
+        let hints = [Hint::new("a", "first", 1), Hint::new("b", "second", 1), Hint::new("q", "quit", ALWAYS)];
+        assert_eq!(fit(&hints, 20), "a: first │ q: quit");
+    }
+}
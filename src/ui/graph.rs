@@ -0,0 +1,138 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::Style;
+use ratatui::symbols::Marker;
+use ratatui::text::Span;
+use ratatui::widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, LegendPosition};
+use ratatui::Frame;
+
+use crate::app::App;
+use crate::ui::processes::format_duration;
+use crate::ui::theme;
+
+/// Total download and upload rates over the chosen span, with the selected
+/// process's combined rate drawn over them when asked for.
+pub fn render(f: &mut Frame, area: Rect, app: &App) {
+    let window = app.graph_span();
+    let down = points(&app.bandwidth_history, app.interval, window, |(rate_in, _)| rate_in);
+    let up = points(&app.bandwidth_history, app.interval, window, |(_, rate_out)| rate_out);
+    let process = app
+        .selected_process()
+        .filter(|_| app.graph_process)
+        .map(|p| {
+            let history = app.process_history.get(&p.key());
+            let data = history
+                .map(|h| points(h, app.interval, window, |(rate_in, rate_out)| rate_in + rate_out))
+                .unwrap_or_default();
+            (format!("{} ({})", p.name, p.pid), data)
+        });
+
+    let mut datasets = vec![
+        Dataset::default()
+            .name(format!("▼ {}", app.units.rate(last(&down))))
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(theme::current().download))
+            .data(&down),
+        Dataset::default()
+            .name(format!("▲ {}", app.units.rate(last(&up))))
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(theme::current().upload))
+            .data(&up),
+    ];
+    if let Some((name, data)) = &process {
+        datasets.push(
+            Dataset::default()
+                .name(format!("{} {}", name, app.units.rate(last(data))))
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(theme::current().active_tab))
+                .data(data),
+        );
+    }
+
+    // Headroom above the highest point so it doesn't sit on the border
+    let peak = down
+        .iter()
+        .chain(&up)
+        .chain(process.iter().flat_map(|(_, data)| data))
+        .map(|&(_, rate)| rate)
+        .fold(0.0_f64, f64::max);
+    let top = if peak > 0.0 { peak * 1.1 } else { 1.0 };
+    let rate_label = |rate: f64| Span::styled(app.units.rate(rate), theme::footer_style());
+    let span = window.as_secs_f64();
+    let age_label = |secs: f64| {
+        let text = if secs == 0.0 {
+            "now".to_string()
+        } else {
+            format!("{} ago", format_duration(Duration::from_secs_f64(secs)))
+        };
+        Span::styled(text, theme::footer_style())
+    };
+
+    let chart = Chart::new(datasets)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme::current().border))
+                .title(format!(" Bandwidth — last {} ", format_duration(window))),
+        )
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(theme::current().border))
+                .bounds([-span, 0.0])
+                .labels([age_label(span), age_label(span / 2.0), age_label(0.0)]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(theme::current().border))
+                .bounds([0.0, top])
+                .labels([rate_label(0.0), rate_label(top / 2.0), rate_label(top)]),
+        )
+        .legend_position(Some(LegendPosition::TopLeft))
+        // The legend carries the current rates, so keep it on short terminals
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)));
+    f.render_widget(chart, area);
+}
+
+/// Chart points for the samples within `window`, at their age in seconds
+/// (negative, with the newest at 0).
+fn points(
+    history: &VecDeque<(f64, f64)>,
+    interval: Duration,
+    window: Duration,
+    value: impl Fn((f64, f64)) -> f64,
+) -> Vec<(f64, f64)> {
+    let step = interval.as_secs_f64();
+    let shown = ((window.as_secs_f64() / step).floor() as usize + 1).min(history.len());
+    history
+        .iter()
+        .skip(history.len() - shown)
+        .enumerate()
+        .map(|(i, &sample)| (-((shown - 1 - i) as f64) * step, value(sample)))
+        .collect()
+}
+
+fn last(points: &[(f64, f64)]) -> f64 {
+    points.last().map_or(0.0, |&(_, rate)| rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_points_are_placed_by_age() {
+        let history = VecDeque::from([(1.0, 10.0), (2.0, 20.0), (3.0, 30.0), (4.0, 40.0)]);
+        let second = Duration::from_secs(1);
+        let two = Duration::from_secs(2);
+        let down = points(&history, second, two, |(rate_in, _)| rate_in);
+        assert_eq!(down, [(-2.0, 2.0), (-1.0, 3.0), (0.0, 4.0)]);
+        let total = points(&history, two, Duration::from_secs(60), |(i, o)| i + o);
+        assert_eq!(total, [(-6.0, 11.0), (-4.0, 22.0), (-2.0, 33.0), (0.0, 44.0)]);
+        assert!(points(&VecDeque::new(), second, two, |(i, _)| i).is_empty());
+    }
+}
@@ -1,87 +1,88 @@
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use ratatui::Frame;
 
+use crate::keymap::{Action, Keymap};
+use crate::ui::layout::centered_rect;
 use crate::ui::theme;
 
-pub fn render(f: &mut Frame) {
+/// Help lines in display order; actions sharing a line have their keys
+/// joined.
+const ENTRIES: [(&[Action], &str); 36] = [
+    (&[Action::NextTab, Action::PrevTab], "Switch between tabs"),
+    (&[Action::NavDown, Action::NavUp], "Navigate rows"),
+    (&[Action::PageDown, Action::PageUp], "Move a page down / up"),
+    (&[Action::NavFirst, Action::NavLast], "Jump to the first / last row"),
+    (&[Action::DrillDown], "Drill into process/host/listener connections / connection detail"),
+    (&[Action::Back], "Back out of a process drill-down"),
+    (&[Action::ProcessDetail], "Process details"),
+    (&[Action::DetailPane], "Toggle the detail pane under the table"),
+    (&[Action::CycleSort, Action::PrevSort], "Cycle sort field forward / back"),
+    (&[Action::SortMenu], "Pick sort field (1-9 in the picker)"),
+    (&[Action::FlipSort], "Reverse sort direction"),
+    (&[Action::CycleUnits], "Cycle units: bytes, SI bytes, bits/s"),
+    (&[Action::ZoomIn, Action::ZoomOut], "Graph: shorter / longer time span"),
+    (&[Action::GraphProcess], "Graph: overlay the selected process"),
+    (&[Action::ToggleLogScale], "Toggle log scale for the bandwidth sparkline"),
+    (&[Action::Filter], "Filter processes/connections"),
+    (&[Action::ClearFilter], "Clear search, drill-down, or filter / close help"),
+    (&[Action::Search], "Search rows, keeping all visible"),
+    (&[Action::SearchNext, Action::SearchPrev], "Next / previous search match"),
+    (&[Action::ToggleServices], "Toggle port service names"),
+    (&[Action::ToggleHostnames], "Toggle hostnames / raw IPs"),
+    (&[Action::Columns], "Choose table columns"),
+    (&[Action::ShrinkColumn, Action::GrowColumn], "Narrow / widen the sorted column"),
+    (&[Action::ReloadAliases], "Reload alias file"),
+    (&[Action::Copy, Action::CopyAlt], "Copy address or PID / hostname or path"),
+    (&[Action::ExportJson], "Export view to JSON"),
+    (&[Action::ExportCsv], "Export table to CSV"),
+    (&[Action::AlertLog], "Alert log"),
+    (&[Action::ResetBudget], "Reset data budget counter"),
+    (&[Action::ToggleLocal], "Exclude loopback/local traffic"),
+    (&[Action::ToggleInbound], "Show only inbound connections"),
+    (&[Action::ToggleIdle], "Hide idle connections"),
+    (&[Action::Pause], "Pause/resume data collection"),
+    (&[Action::Reset], "Reset rate baselines and peaks"),
+    (&[Action::Help], "Toggle this help"),
+    (&[Action::Quit], "Quit"),
+];
+
+pub fn render(f: &mut Frame, keymap: &Keymap) {
     let area = centered_rect(60, 70, f.area());
 
     f.render_widget(Clear, area);
 
-    let help_text = vec![
+    let mut help_text = vec![
         Line::from(Span::styled(
             " Network Monitor — Help ",
             Style::default()
-                .fg(theme::HEADER_FG)
+                .fg(theme::current().header)
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
-        Line::from(vec![
-            Span::styled("Tab / Shift-Tab  ", Style::default().fg(theme::ACTIVE_TAB_FG)),
-            Span::raw("Switch between tabs"),
-        ]),
-        Line::from(vec![
-            Span::styled("j / k / ↑ / ↓    ", Style::default().fg(theme::ACTIVE_TAB_FG)),
-            Span::raw("Navigate rows"),
-        ]),
-        Line::from(vec![
-            Span::styled("Enter            ", Style::default().fg(theme::ACTIVE_TAB_FG)),
-            Span::raw("Drill into process connections"),
-        ]),
-        Line::from(vec![
-            Span::styled("s                ", Style::default().fg(theme::ACTIVE_TAB_FG)),
-            Span::raw("Cycle sort field"),
-        ]),
-        Line::from(vec![
-            Span::styled("/                ", Style::default().fg(theme::ACTIVE_TAB_FG)),
-            Span::raw("Filter processes/connections"),
-        ]),
-        Line::from(vec![
-            Span::styled("Esc              ", Style::default().fg(theme::ACTIVE_TAB_FG)),
-            Span::raw("Clear filter / close help"),
-        ]),
-        Line::from(vec![
-            Span::styled("p                ", Style::default().fg(theme::ACTIVE_TAB_FG)),
-            Span::raw("Pause/resume data collection"),
-        ]),
-        Line::from(vec![
-            Span::styled("?                ", Style::default().fg(theme::ACTIVE_TAB_FG)),
-            Span::raw("Toggle this help"),
-        ]),
-        Line::from(vec![
-            Span::styled("q                ", Style::default().fg(theme::ACTIVE_TAB_FG)),
-            Span::raw("Quit"),
-        ]),
     ];
+    for (actions, description) in ENTRIES {
+        let keys = actions
+            .iter()
+            .map(|a| keymap.label(*a))
+            .filter(|label| !label.is_empty())
+            .collect::<Vec<_>>()
+            .join(" / ");
+        if keys.is_empty() {
+            continue;
+        }
+        help_text.push(Line::from(vec![
+            Span::styled(format!("{:<16} ", keys), Style::default().fg(theme::current().active_tab)),
+            Span::raw(description),
+        ]));
+    }
 
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(theme::HEADER_FG))
+        .border_style(Style::default().fg(theme::current().header))
         .title(" Help ");
 
     let help = Paragraph::new(help_text).block(block);
     f.render_widget(help, area);
 }
-
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(r);
-
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
-}
@@ -0,0 +1,121 @@
+use ratatui::layout::{Constraint, Flex, Layout, Margin, Position, Rect};
+
+use crate::app::ActiveTab;
+use crate::data::model::SortField;
+
+/// Width of the "▸ " selection marker the tables reserve left of the
+/// first column.
+const MARKER_WIDTH: u16 = 2;
+
+/// Where the last frame drew the things a mouse click can hit.
+#[derive(Debug, Clone, Default)]
+pub struct Hitboxes {
+    pub tabs: Vec<(Rect, ActiveTab)>,
+    pub table: Option<TableHitbox>,
+}
+
+/// What a click landed on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Click {
+    Tab(ActiveTab),
+    /// Index of a table row among those the table lists
+    Row(usize),
+    /// A column header that sorts by this field
+    Header(SortField),
+}
+
+impl Hitboxes {
+    pub fn click(&self, column: u16, row: u16) -> Option<Click> {
+        let position = Position::new(column, row);
+        if let Some((_, tab)) = self.tabs.iter().find(|(area, _)| area.contains(position)) {
+            return Some(Click::Tab(*tab));
+        }
+        self.table.as_ref()?.click(position)
+    }
+
+    /// Whether a position is over the table, for wheel scrolling.
+    pub fn over_table(&self, column: u16, row: u16) -> bool {
+        self.table.as_ref().is_some_and(|t| t.area.contains(Position::new(column, row)))
+    }
+}
+
+/// A bordered table with a one-line header and one-line rows.
+#[derive(Debug, Clone)]
+pub struct TableHitbox {
+    area: Rect,
+    header: Rect,
+    rows: Rect,
+    /// Column x ranges with the sort field each column shows
+    columns: Vec<(u16, u16, Option<SortField>)>,
+    /// Index of the first row shown, after the table scrolled to keep the
+    /// selection visible
+    offset: usize,
+}
+
+impl TableHitbox {
+    /// Lay out columns the way `Table` does: the selection marker first,
+    /// then the widths left to right with one cell between them.
+    pub fn new(area: Rect, widths: &[Constraint], sort_fields: &[Option<SortField>], offset: usize) -> Self {
+        let inner = area.inner(Margin::new(1, 1));
+        let [header, rows] = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(inner);
+        let [_, columns_area] =
+            Layout::horizontal([Constraint::Length(MARKER_WIDTH), Constraint::Fill(0)]).areas(header);
+        let columns = Layout::horizontal(widths.iter().copied())
+            .flex(Flex::Start)
+            .spacing(1)
+            .split(columns_area)
+            .iter()
+            .zip(sort_fields.iter().chain(std::iter::repeat(&None)))
+            .map(|(rect, field)| (rect.x, rect.right(), *field))
+            .collect();
+        TableHitbox { area, header, rows, columns, offset }
+    }
+
+    fn click(&self, position: Position) -> Option<Click> {
+        if self.header.contains(position) {
+            return self
+                .columns
+                .iter()
+                .find(|(start, end, _)| (*start..*end).contains(&position.x))
+                .and_then(|(_, _, field)| field.map(Click::Header));
+        }
+        if self.rows.contains(position) {
+            return Some(Click::Row(self.offset + (position.y - self.rows.y) as usize));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_clicks() {
+        // Borders at x=0 and y=5; header on y=6, rows from y=7
+        let table = TableHitbox::new(
+            Rect::new(0, 5, 40, 10),
+            &[Constraint::Length(10), Constraint::Length(6), Constraint::Min(5)],
+            &[Some(SortField::Name), None, Some(SortField::RateIn)],
+            20,
+        );
+        let hitboxes = Hitboxes {
+            tabs: vec![(Rect::new(1, 1, 11, 1), ActiveTab::Processes), (Rect::new(13, 1, 13, 1), ActiveTab::Connections)],
+            table: Some(table),
+        };
+        assert_eq!(hitboxes.click(12, 1), None);
+        assert_eq!(hitboxes.click(13, 1), Some(Click::Tab(ActiveTab::Connections)));
+        // The name column starts after the border and the selection marker
+        assert_eq!(hitboxes.click(2, 6), None);
+        assert_eq!(hitboxes.click(3, 6), Some(Click::Header(SortField::Name)));
+        assert_eq!(hitboxes.click(13, 6), None);
+        assert_eq!(hitboxes.click(15, 6), None);
+        assert_eq!(hitboxes.click(21, 6), Some(Click::Header(SortField::RateIn)));
+        assert_eq!(hitboxes.click(10, 7), Some(Click::Row(20)));
+        assert_eq!(hitboxes.click(10, 13), Some(Click::Row(26)));
+        // The bottom border is not a row
+        assert_eq!(hitboxes.click(10, 14), None);
+        assert!(hitboxes.over_table(0, 14));
+        assert!(!hitboxes.over_table(0, 15));
+    }
+}
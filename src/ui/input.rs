@@ -0,0 +1,216 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Span;
+
+/// A one-line text field with a cursor, for the filter prompt.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputLine {
+    text: String,
+    /// Byte offset into `text`, always on a char boundary
+    cursor: usize,
+}
+
+impl InputLine {
+    /// A line holding `text` with the cursor at the end.
+    pub fn new(text: &str) -> Self {
+        InputLine { text: text.to_string(), cursor: text.len() }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    /// Insert pasted text; line breaks and other control characters become
+    /// spaces since the line can't hold them.
+    pub fn insert_str(&mut self, s: &str) {
+        let s: String = s.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+        self.text.insert_str(self.cursor, &s);
+        self.cursor += s.len();
+    }
+
+    pub fn left(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+
+    pub fn right(&mut self) {
+        if let Some(c) = self.text[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    /// Delete the character before the cursor.
+    pub fn backspace(&mut self) {
+        let end = self.cursor;
+        self.left();
+        self.text.replace_range(self.cursor..end, "");
+    }
+
+    /// Delete the character under the cursor.
+    pub fn delete(&mut self) {
+        if let Some(c) = self.text[self.cursor..].chars().next() {
+            self.text.replace_range(self.cursor..self.cursor + c.len_utf8(), "");
+        }
+    }
+
+    /// Delete back to the start of the word before the cursor, along with
+    /// any spaces between, as Ctrl-w does in a shell.
+    pub fn delete_word(&mut self) {
+        let before = &self.text[..self.cursor];
+        let trimmed = before.trim_end_matches(char::is_whitespace);
+        let start = trimmed.rfind(char::is_whitespace).map_or(0, |i| {
+            i + trimmed[i..].chars().next().map_or(0, char::len_utf8)
+        });
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    /// Apply an editing key; false for keys the line doesn't use.
+    pub fn handle_key(&mut self, key: &KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Left => self.left(),
+            KeyCode::Right => self.right(),
+            KeyCode::Home => self.home(),
+            KeyCode::End => self.end(),
+            KeyCode::Backspace if alt || ctrl => self.delete_word(),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Char('a') if ctrl => self.home(),
+            KeyCode::Char('e') if ctrl => self.end(),
+            KeyCode::Char('w') if ctrl => self.delete_word(),
+            KeyCode::Char('u') if ctrl => self.clear(),
+            KeyCode::Char(_) if ctrl || alt => return false,
+            KeyCode::Char(c) => self.insert(c),
+            _ => return false,
+        }
+        true
+    }
+
+    /// The text with the cursor drawn as a reversed cell, or as a block
+    /// past the end.
+    pub fn spans(&self) -> Vec<Span<'static>> {
+        let (before, rest) = self.text.split_at(self.cursor);
+        let mut chars = rest.chars();
+        let mut spans = vec![Span::raw(before.to_string())];
+        match chars.next() {
+            Some(c) => {
+                spans.push(Span::styled(c.to_string(), Style::default().add_modifier(Modifier::REVERSED)));
+                spans.push(Span::raw(chars.as_str().to_string()));
+            }
+            None => spans.push(Span::raw("█")),
+        }
+        spans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    /// Type keys into a line, returning the text with `|` at the cursor.
+    fn typed(start: &str, keys: &[KeyEvent]) -> String {
+        let mut line = InputLine::new(start);
+        for k in keys {
+            line.handle_key(k);
+        }
+        let mut text = line.text().to_string();
+        text.insert(line.cursor, '|');
+        text
+    }
+
+    #[test]
+    fn test_cursor_movement_and_editing() {
+        let none = KeyModifiers::NONE;
+        let ctrl = KeyModifiers::CONTROL;
+        assert_eq!(typed("", &[key(KeyCode::Char('a'), none), key(KeyCode::Char('b'), none)]), "ab|");
+        assert_eq!(typed("chrme", &[key(KeyCode::Left, none), key(KeyCode::Left, none), key(KeyCode::Char('o'), none)]), "chro|me");
+        assert_eq!(typed("abc", &[key(KeyCode::Home, none), key(KeyCode::Delete, none)]), "|bc");
+        assert_eq!(typed("abc", &[key(KeyCode::Char('a'), ctrl), key(KeyCode::Right, none)]), "a|bc");
+        assert_eq!(typed("abc", &[key(KeyCode::Home, none), key(KeyCode::Char('e'), ctrl)]), "abc|");
+        assert_eq!(typed("abc", &[key(KeyCode::Left, none), key(KeyCode::Backspace, none)]), "a|c");
+        assert_eq!(typed("abc", &[key(KeyCode::Char('u'), ctrl)]), "|");
+        // Edges are no-ops
+        assert_eq!(typed("ab", &[key(KeyCode::Right, none), key(KeyCode::Delete, none)]), "ab|");
+        assert_eq!(typed("ab", &[key(KeyCode::Home, none), key(KeyCode::Left, none), key(KeyCode::Backspace, none)]), "|ab");
+        // Unused keys are left to the caller
+        let mut line = InputLine::new("x");
+        assert!(!line.handle_key(&key(KeyCode::Char('x'), ctrl)));
+        assert!(!line.handle_key(&key(KeyCode::Tab, none)));
+        assert_eq!(line.text(), "x");
+    }
+
+    #[test]
+    fn test_word_delete() {
+        let ctrl_w = key(KeyCode::Char('w'), KeyModifiers::CONTROL);
+        let alt_backspace = key(KeyCode::Backspace, KeyModifiers::ALT);
+        assert_eq!(typed("chrome !helper", &[ctrl_w]), "chrome |");
+        assert_eq!(typed("chrome !helper  ", &[alt_backspace]), "chrome |");
+        assert_eq!(typed("chrome", &[ctrl_w, ctrl_w]), "|");
+        let left = key(KeyCode::Left, KeyModifiers::NONE);
+        assert_eq!(typed("port:443 host:x", &[left, left, left, left, left, left, ctrl_w]), "|host:x");
+    }
+
+    #[test]
+    fn test_multibyte_text() {
+        let mut line = InputLine::new("naïve");
+        line.left();
+        line.left();
+        line.left();
+        assert_eq!(line.cursor, 2);
+        line.backspace();
+        assert_eq!(line.text(), "naïve".replace('a', ""));
+        line.delete();
+        assert_eq!(line.text(), "nve");
+        line.insert('é');
+        assert_eq!(line.text(), "néve");
+        assert_eq!(line.cursor, 3);
+    }
+
+    #[test]
+    fn test_paste_inserts_at_cursor() {
+        let mut line = InputLine::new("chrome");
+        line.home();
+        line.insert_str("host:github\n");
+        assert_eq!(line.text(), "host:github chrome");
+        assert_eq!(line.cursor, 12);
+    }
+
+    #[test]
+    fn test_spans_mark_the_cursor() {
+        let text = |line: &InputLine| line.spans().iter().map(|s| s.content.to_string()).collect::<Vec<_>>();
+        let mut line = InputLine::new("abc");
+        assert_eq!(text(&line), ["abc", "█"]);
+        line.left();
+        assert_eq!(text(&line), ["ab", "c", ""]);
+        assert!(line.spans()[1].style.add_modifier.contains(Modifier::REVERSED));
+    }
+}
@@ -0,0 +1,141 @@
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::text::Span;
+use ratatui::widgets::{Block, Borders, Cell, Row, Sparkline, Table, TableState};
+use ratatui::Frame;
+
+use crate::app::App;
+use crate::data::interfaces;
+use crate::data::model::SortField;
+use crate::ui::hitbox::TableHitbox;
+use crate::ui::theme;
+
+/// Rows under the Interfaces table given to the selected interface's
+/// history.
+pub const SPARKLINE_HEIGHT: u16 = 5;
+
+pub fn render(f: &mut Frame, area: Rect, app: &App) -> TableHitbox {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(SPARKLINE_HEIGHT)])
+        .split(area);
+
+    render_sparkline(f, chunks[1], app);
+    render_table(f, chunks[0], app)
+}
+
+fn render_table(f: &mut Frame, area: Rect, app: &App) -> TableHitbox {
+    let headers = [
+        ("Interface", Some(SortField::Name)),
+        ("Status", None),
+        ("Pkts In", None),
+        ("Pkts Out", None),
+        ("Down", Some(SortField::BytesIn)),
+        ("Up", Some(SortField::BytesOut)),
+        ("Rate In", Some(SortField::RateIn)),
+        ("Rate Out", Some(SortField::RateOut)),
+    ];
+    let header_cells = headers
+        .iter()
+        .map(|(label, field)| {
+            let text = if field.is_some_and(|f| f == app.sort_field) {
+                format!("{} {}", label, app.sort_direction.glyph())
+            } else {
+                label.to_string()
+            };
+            Cell::from(Span::styled(text, theme::header_style()))
+        })
+        .collect::<Vec<_>>();
+
+    let header = Row::new(header_cells).height(1);
+
+    let rows: Vec<Row> = app
+        .filtered_interfaces()
+        .iter()
+        .map(|i| {
+            let name = if interfaces::is_vpn_interface(&i.name, &app.vpn_patterns) {
+                Span::styled(format!("{} VPN", i.name), Style::default().fg(theme::current().vpn))
+            } else {
+                Span::raw(i.name.clone())
+            };
+            let status = if i.up {
+                Span::raw("up")
+            } else {
+                Span::styled("down", theme::closed_style())
+            };
+            Row::new(vec![
+                Cell::from(name),
+                Cell::from(status),
+                Cell::from(i.packets_in.to_string()),
+                Cell::from(i.packets_out.to_string()),
+                Cell::from(app.units.bytes(i.bytes_in)),
+                Cell::from(app.units.bytes(i.bytes_out)),
+                Cell::from(Span::styled(
+                    app.units.rate(i.rate_in),
+                    Style::default().fg(theme::rate_color(i.rate_in)),
+                )),
+                Cell::from(Span::styled(
+                    app.units.rate(i.rate_out),
+                    Style::default().fg(theme::rate_color(i.rate_out)),
+                )),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Min(14),
+        Constraint::Length(6),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(12),
+        Constraint::Length(12),
+    ];
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme::current().border))
+                .title(" Interfaces "),
+        )
+        .row_highlight_style(theme::row_highlight(app.on_search_match()))
+        .highlight_symbol("▸ ");
+
+    let mut state = TableState::default().with_offset(app.interface_offset);
+    state.select(Some(app.interface_index));
+    f.render_stateful_widget(table, area, &mut state);
+
+    let fields = headers.map(|(_, field)| field);
+    TableHitbox::new(area, &widths, &fields, state.offset())
+}
+
+/// Rate history of the selected interface, most recent samples on the right.
+fn render_sparkline(f: &mut Frame, area: Rect, app: &App) {
+    let selected = app.filtered_interfaces().get(app.interface_index).copied();
+    let title = match selected {
+        Some(i) => format!(" {} ", i.name),
+        None => " No interface selected ".to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::current().border))
+        .title(title);
+
+    let inner_width = area.width.saturating_sub(2) as usize;
+    let data: Vec<u64> = selected
+        .and_then(|i| app.interface_history.get(&i.name))
+        .map(|history| {
+            let skip = history.len().saturating_sub(inner_width);
+            history.iter().skip(skip).map(|&v| v as u64).collect()
+        })
+        .unwrap_or_default();
+
+    let sparkline = Sparkline::default()
+        .block(block)
+        .data(&data)
+        .style(Style::default().fg(theme::current().download));
+    f.render_widget(sparkline, area);
+}
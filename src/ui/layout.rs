@@ -1,16 +1,323 @@
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use std::ops::Range;
 
-/// Split the terminal into: header (3), main content (variable), sparkline (5), footer (1)
-pub fn main_layout(area: Rect) -> (Rect, Rect, Rect, Rect) {
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap};
+use ratatui::Frame;
+
+use crate::ui::theme;
+
+/// Height of the detail pane under the table, borders included.
+const DETAIL_PANE_HEIGHT: u16 = 6;
+
+const HEADER_HEIGHT: u16 = 3;
+const MAIN_MIN_HEIGHT: u16 = 10;
+const SPARKLINE_HEIGHT: u16 = 5;
+const FOOTER_HEIGHT: u16 = 1;
+
+/// Narrowest terminal the responsive layout still works in.
+const MIN_WIDTH: u16 = 50;
+
+/// Terminal widths below which the UI sheds parts to make room: first the
+/// cumulative Down/Up columns, then the sparkline and the longer header
+/// stats, then the State and PID columns.
+pub const HIDE_TOTALS_BELOW: u16 = 100;
+pub const COMPACT_BELOW: u16 = 80;
+pub const COLLAPSE_BELOW: u16 = 60;
+
+/// Whether the terminal is narrow enough for the compact header and no
+/// sparkline.
+pub fn compact(area: Rect) -> bool {
+    area.width < COMPACT_BELOW
+}
+
+/// Split the terminal into: header (3), main content (variable), detail
+/// pane (6, or nothing when hidden), sparkline (5), footer (1). The
+/// sparkline goes on compact terminals, and on ones too short for it and
+/// the main area's minimum.
+pub fn main_layout(area: Rect, detail_pane: bool) -> (Rect, Rect, Rect, Rect, Rect) {
+    let (_, min_height) = min_size(detail_pane);
+    let sparkline = !compact(area) && area.height >= min_height + SPARKLINE_HEIGHT;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3),  // header with tabs + stats
-            Constraint::Min(10),   // main content area
-            Constraint::Length(5), // sparkline area
-            Constraint::Length(1), // footer keybindings
+            Constraint::Length(HEADER_HEIGHT),
+            Constraint::Min(MAIN_MIN_HEIGHT),
+            Constraint::Length(if detail_pane { DETAIL_PANE_HEIGHT } else { 0 }),
+            Constraint::Length(if sparkline { SPARKLINE_HEIGHT } else { 0 }),
+            Constraint::Length(FOOTER_HEIGHT), // keybindings
         ])
         .split(area);
 
-    (chunks[0], chunks[1], chunks[2], chunks[3])
+    (chunks[0], chunks[1], chunks[2], chunks[3], chunks[4])
+}
+
+/// Smallest (width, height) the main layout works in: the header, pane,
+/// and footer at full size, the main area at its minimum, and no sparkline.
+pub fn min_size(detail_pane: bool) -> (u16, u16) {
+    let pane = if detail_pane { DETAIL_PANE_HEIGHT } else { 0 };
+    (MIN_WIDTH, HEADER_HEIGHT + MAIN_MIN_HEIGHT + pane + FOOTER_HEIGHT)
+}
+
+pub fn too_small(area: Rect, detail_pane: bool) -> bool {
+    let (width, height) = min_size(detail_pane);
+    area.width < width || area.height < height
+}
+
+/// Stand-in for the whole UI while the terminal is below `min_size`: a
+/// centered note of the size needed, wrapped on very narrow terminals.
+pub fn render_too_small(f: &mut Frame, area: Rect, detail_pane: bool) {
+    if area.is_empty() {
+        return;
+    }
+    let (width, height) = min_size(detail_pane);
+    let lines = vec![
+        Line::styled(
+            format!("Terminal too small — need at least {}×{}", width, height),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Line::styled(
+            format!("currently {}×{}", area.width, area.height),
+            Style::default().fg(theme::current().footer),
+        ),
+    ];
+    let wrapped: usize = lines
+        .iter()
+        .map(|line| line.width().div_ceil(area.width as usize).max(1))
+        .sum();
+    let message_height = (wrapped as u16).min(area.height);
+    let message = Rect {
+        y: area.y + (area.height - message_height) / 2,
+        height: message_height,
+        ..area
+    };
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(paragraph, message);
+}
+
+/// Table rows visible in the main area: its height less the borders and
+/// the header row.
+pub fn table_rows(area: Rect, detail_pane: bool) -> usize {
+    let (_, main, _, _, _) = main_layout(area, detail_pane);
+    main.height.saturating_sub(3) as usize
+}
+
+/// First row to show so the selection is on screen: the current offset
+/// when the selection is already visible, otherwise scrolled just far
+/// enough. A list that shrank is pulled back so it fills the view.
+pub fn scroll_offset(offset: usize, selected: usize, rows: usize, height: usize) -> usize {
+    let height = height.max(1);
+    let selected = selected.min(rows.saturating_sub(1));
+    let offset = offset.min(rows.saturating_sub(height));
+    if selected < offset {
+        selected
+    } else if selected >= offset + height {
+        selected + 1 - height
+    } else {
+        offset
+    }
+}
+
+/// The rows of a bordered table with a one-line header that are on screen,
+/// starting from the table's saved offset. Tables only build these rows.
+pub fn visible_rows(offset: usize, selected: usize, rows: usize, area: Rect) -> Range<usize> {
+    let height = (area.height.saturating_sub(3) as usize).max(1);
+    let start = scroll_offset(offset, selected, rows, height);
+    start..(start + height).min(rows)
+}
+
+/// Table rows for the items on screen, built with `row`, and which items
+/// they are. Only these rows are built, so only they are matched against
+/// the filter and search for highlighting.
+pub fn window_rows<'a, T>(
+    items: &[T],
+    offset: usize,
+    selected: usize,
+    area: Rect,
+    row: impl FnMut(&T) -> Row<'a>,
+) -> (Vec<Row<'a>>, Range<usize>) {
+    let window = visible_rows(offset, selected, items.len(), area);
+    (items[window.clone()].iter().map(row).collect(), window)
+}
+
+/// Draw a scrollbar over the right border of a bordered table with a
+/// one-line header, when its rows don't all fit. `offset` is the first
+/// row shown.
+pub fn table_scrollbar(f: &mut Frame, area: Rect, rows: usize, offset: usize) {
+    let visible = area.height.saturating_sub(3) as usize;
+    if rows <= visible {
+        return;
+    }
+    let mut state = ScrollbarState::new(rows - visible + 1)
+        .viewport_content_length(visible)
+        .position(offset);
+    let track = Rect {
+        y: area.y + 2,
+        height: visible as u16,
+        ..area
+    };
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        // The track doubles as the border
+        .track_symbol(Some("│"))
+        .style(Style::default().fg(theme::current().border));
+    f.render_stateful_widget(scrollbar, track, &mut state);
+}
+
+/// Rect of the given percentage size centered within `r`, used for popups.
+pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    /// The right border column of a 10-row table drawn with `rows` rows
+    /// scrolled to `offset`.
+    fn border(rows: usize, offset: usize) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(20, 10)).unwrap();
+        terminal
+            .draw(|f| table_scrollbar(f, f.area(), rows, offset))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..10).map(|y| buffer[(19, y)].symbol().to_string()).collect()
+    }
+
+    #[test]
+    fn test_scroll_offset() {
+        // Moving within the view keeps the offset
+        assert_eq!(scroll_offset(10, 12, 100, 7), 10);
+        assert_eq!(scroll_offset(10, 16, 100, 7), 10);
+        // Past either edge scrolls just far enough
+        assert_eq!(scroll_offset(10, 17, 100, 7), 11);
+        assert_eq!(scroll_offset(10, 9, 100, 7), 9);
+        assert_eq!(scroll_offset(0, 99, 100, 7), 93);
+        // The list shrank below the offset: show its last screenful
+        assert_eq!(scroll_offset(50, 9, 10, 7), 3);
+        assert_eq!(scroll_offset(50, 50, 10, 7), 3);
+        assert_eq!(scroll_offset(50, 0, 3, 7), 0);
+        assert_eq!(scroll_offset(5, 0, 0, 7), 0);
+    }
+
+    #[test]
+    fn test_visible_rows() {
+        // Seven rows fit
+        let area = Rect::new(0, 0, 20, 10);
+        assert_eq!(visible_rows(0, 0, 100, area), 0..7);
+        assert_eq!(visible_rows(0, 7, 100, area), 1..8);
+        assert_eq!(visible_rows(20, 22, 100, area), 20..27);
+        assert_eq!(visible_rows(0, 0, 3, area), 0..3);
+        assert_eq!(visible_rows(0, 0, 0, area), 0..0);
+    }
+
+    #[test]
+    fn test_table_scrollbar() {
+        // Seven rows fit between the borders and the header
+        assert_eq!(border(7, 0), " ".repeat(10));
+        assert_eq!(border(14, 0), "  ████│││ ");
+        assert_eq!(border(14, 7), "  ││││███ ");
+    }
+
+    #[test]
+    fn test_detail_pane_takes_from_main_area() {
+        let area = Rect::new(0, 0, 80, 40);
+        let (_, main, pane, sparkline, _) = main_layout(area, false);
+        assert_eq!((main.height, pane.height), (31, 0));
+        let (_, with_pane, pane, _, _) = main_layout(area, true);
+        assert_eq!((with_pane.height, pane.height), (25, DETAIL_PANE_HEIGHT));
+        assert_eq!(pane.y, with_pane.y + with_pane.height);
+        assert_eq!(sparkline.y, main.y + main.height);
+        assert_eq!(table_rows(area, true), 22);
+    }
+
+    /// The screen after `render_too_small` on a `width`×`height` terminal.
+    fn too_small_screen(width: u16, height: u16, detail_pane: bool) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|f| render_too_small(f, f.area(), detail_pane))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+            .collect()
+    }
+
+    #[test]
+    fn test_min_size_counts_the_detail_pane() {
+        assert_eq!(min_size(false), (50, 14));
+        assert_eq!(min_size(true), (50, 20));
+        assert!(!too_small(Rect::new(0, 0, 50, 14), false));
+        assert!(too_small(Rect::new(0, 0, 50, 14), true));
+        assert!(too_small(Rect::new(0, 0, 49, 40), false));
+        assert!(too_small(Rect::new(0, 0, 40, 10), false));
+        assert!(too_small(Rect::default(), false));
+    }
+
+    #[test]
+    fn test_degenerate_sizes() {
+        // The layout itself never hands out more than the area has
+        for (width, height) in [(0, 0), (1, 1), (40, 10), (80, 3)] {
+            let area = Rect::new(0, 0, width, height);
+            let (header, main, pane, sparkline, footer) = main_layout(area, true);
+            for part in [header, main, pane, sparkline, footer] {
+                assert!(area.union(part) == area || part.is_empty());
+            }
+            assert!(table_rows(area, true) <= height as usize);
+        }
+
+        // A tmux pane gets the note, centered
+        let screen = too_small_screen(60, 10, false);
+        assert_eq!(screen[4].trim(), "Terminal too small — need at least 50×14");
+        assert_eq!(screen[5].trim(), "currently 60×10");
+        assert!(screen[..4].iter().all(|line| line.trim().is_empty()));
+        let screen = too_small_screen(60, 10, true);
+        assert!(screen.join("").contains("need at least 50×20"));
+
+        // Narrower than the note: it wraps; smaller still: it is cut off
+        let screen = too_small_screen(20, 10, false).join("");
+        assert!(screen.contains("Terminal too small"));
+        assert!(screen.contains("50×14"));
+        for (width, height) in [(0, 0), (1, 1), (5, 1), (20, 2), (0, 5)] {
+            too_small_screen(width, height, false);
+        }
+    }
+
+    #[test]
+    fn test_sparkline_goes_on_narrow_or_short_terminals() {
+        let sparkline = |width, height, pane| main_layout(Rect::new(0, 0, width, height), pane).3.height;
+        assert_eq!(sparkline(80, 19, false), 5);
+        assert_eq!(sparkline(79, 40, false), 0);
+        assert_eq!(sparkline(120, 18, false), 0);
+        assert_eq!(sparkline(120, 24, true), 0);
+        assert_eq!(sparkline(120, 25, true), 5);
+
+        // Without it the main area takes the rows
+        let (_, main, _, _, footer) = main_layout(Rect::new(0, 0, 60, 14), false);
+        assert_eq!((main.height, footer.y), (10, 13));
+        assert!(compact(Rect::new(0, 0, 79, 40)));
+        assert!(!compact(Rect::new(0, 0, 80, 40)));
+    }
 }
@@ -0,0 +1,92 @@
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::Style;
+use ratatui::text::Span;
+use ratatui::widgets::{Block, Borders, Cell, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::app::App;
+use crate::data::addr;
+use crate::data::model::SortField;
+use crate::data::services;
+use crate::ui::hitbox::TableHitbox;
+use crate::ui::theme;
+
+pub fn render(f: &mut Frame, area: Rect, app: &App) -> TableHitbox {
+    let sorted_by_port = !matches!(app.sort_field, SortField::Name | SortField::Pid);
+    // The port fallback is always ascending unless flipped
+    let marker = if sorted_by_port {
+        if app.sort_flipped() { "▼" } else { "▲" }
+    } else {
+        app.sort_direction.glyph()
+    };
+    let header_cells = [
+        ("Process", app.sort_field == SortField::Name),
+        ("PID", app.sort_field == SortField::Pid),
+        ("Proto", false),
+        ("Family", false),
+        ("Address", false),
+        ("Port", sorted_by_port),
+    ]
+    .iter()
+    .map(|(label, sorted)| {
+        let text = if *sorted {
+            format!("{} {}", label, marker)
+        } else {
+            label.to_string()
+        };
+        Cell::from(Span::styled(text, theme::header_style()))
+    })
+    .collect::<Vec<_>>();
+
+    let header = Row::new(header_cells).height(1);
+
+    let rows: Vec<Row> = app
+        .filtered_listeners()
+        .iter()
+        .map(|l| {
+            let port = match services::service_name(l.port, &l.protocol) {
+                Some(name) if app.show_services => format!("{} ({})", l.port, name),
+                _ => l.port.to_string(),
+            };
+            Row::new(vec![
+                Cell::from(l.process.clone()),
+                Cell::from(l.pid.to_string()),
+                Cell::from(l.protocol.to_string()),
+                Cell::from(l.family()),
+                Cell::from(Span::styled(
+                    l.addr.clone(),
+                    theme::addr_style(addr::classify_addr(&l.addr)),
+                )),
+                Cell::from(port),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Min(20),
+        Constraint::Length(8),
+        Constraint::Length(6),
+        Constraint::Length(7),
+        Constraint::Min(24),
+        Constraint::Length(20),
+    ];
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme::current().border))
+                .title(format!(" Listening Sockets ({}) ", app.listeners.len())),
+        )
+        .row_highlight_style(theme::row_highlight(app.on_search_match()))
+        .highlight_symbol("▸ ");
+
+    let mut state = TableState::default().with_offset(app.listener_offset);
+    state.select(Some(app.listener_index));
+    f.render_stateful_widget(table, area, &mut state);
+
+    // The port order is a fallback, not a field of its own
+    let fields = [Some(SortField::Name), Some(SortField::Pid)];
+    TableHitbox::new(area, &widths, &fields, state.offset())
+}
@@ -1,6 +1,19 @@
+pub mod alerts;
+pub mod columns;
+pub mod connection_detail;
 pub mod connections;
+pub mod detail_pane;
+pub mod footer;
+pub mod graph;
 pub mod help;
+pub mod hitbox;
+pub mod input;
+pub mod interfaces;
 pub mod layout;
+pub mod listeners;
 pub mod overview;
+pub mod process_detail;
 pub mod processes;
+pub mod remotes;
+pub mod sort;
 pub mod theme;
@@ -1,18 +1,25 @@
+use std::collections::VecDeque;
+
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::style::Style;
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Paragraph, Sparkline};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Sparkline};
 use ratatui::Frame;
 
-use crate::app::App;
-use crate::ui::processes::{format_bytes, format_rate};
+use crate::app::{App, OVERVIEW_TOP};
+use crate::data::services;
+use crate::ui::processes::{format_duration, format_pps};
 use crate::ui::theme;
+use crate::units::Units;
+
+/// Rows in the Top Remote Hosts panel, as many as Top Processes has.
+const TOP_ROWS: usize = OVERVIEW_TOP;
 
 pub fn render(f: &mut Frame, area: Rect, app: &App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(5), // Stats summary
+            Constraint::Length(11), // Stats summary
             Constraint::Min(8),   // Top processes
         ])
         .split(area);
@@ -20,33 +27,36 @@ pub fn render(f: &mut Frame, area: Rect, app: &App) {
     // Stats summary
     let stats_block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(theme::BORDER_COLOR))
-        .title(" Overview ");
+        .border_style(Style::default().fg(theme::current().border))
+        .title(match &app.watch {
+            Some(watch) => format!(" Overview — {} ", watch.label()),
+            None => " Overview ".to_string(),
+        });
 
     let stats_text = vec![
         Line::from(vec![
             Span::styled("Total Down: ", theme::header_style()),
             Span::styled(
-                format_bytes(app.snapshot.total_bytes_in),
-                Style::default().fg(theme::DOWNLOAD_COLOR),
+                app.units.bytes(app.snapshot.total_bytes_in),
+                Style::default().fg(theme::current().download),
             ),
             Span::raw("  "),
             Span::styled("Total Up: ", theme::header_style()),
             Span::styled(
-                format_bytes(app.snapshot.total_bytes_out),
-                Style::default().fg(theme::UPLOAD_COLOR),
+                app.units.bytes(app.snapshot.total_bytes_out),
+                Style::default().fg(theme::current().upload),
             ),
         ]),
         Line::from(vec![
             Span::styled("Rate In: ", theme::header_style()),
             Span::styled(
-                format_rate(app.snapshot.total_rate_in),
+                app.units.rate(app.snapshot.total_rate_in),
                 Style::default().fg(theme::rate_color(app.snapshot.total_rate_in)),
             ),
             Span::raw("  "),
             Span::styled("Rate Out: ", theme::header_style()),
             Span::styled(
-                format_rate(app.snapshot.total_rate_out),
+                app.units.rate(app.snapshot.total_rate_out),
                 Style::default().fg(theme::rate_color(app.snapshot.total_rate_out)),
             ),
             Span::raw("  "),
@@ -56,76 +66,476 @@ pub fn render(f: &mut Frame, area: Rect, app: &App) {
         Line::from(vec![
             Span::styled("Processes: ", theme::header_style()),
             Span::raw(app.snapshot.processes.len().to_string()),
+            Span::raw("  "),
+            Span::styled("Packets: ", theme::header_style()),
+            Span::raw(format!(
+                "▼ {}  ▲ {}",
+                format_pps(app.snapshot.total_pps_in),
+                format_pps(app.snapshot.total_pps_out)
+            )),
         ]),
+        lan_wan_line(app),
+        vpn_line(app),
+        protocol_line(app),
+        state_line(app),
+        peak_line(app),
+        churn_line(app),
     ];
 
     let stats = Paragraph::new(stats_text).block(stats_block);
     f.render_widget(stats, chunks[0]);
 
-    // Top processes by rate
-    let top_procs: Vec<Line> = app
-        .snapshot
-        .processes
+    // Top processes, selectable, with bars against the busiest of them
+    let top_procs = app.top_processes();
+    let busiest = top_procs.iter().map(|p| p.rate_in + p.rate_out).fold(0.0, f64::max);
+    let items: Vec<ListItem> = top_procs
         .iter()
-        .take(10)
         .map(|p| {
-            Line::from(vec![
+            let rate = p.rate_in + p.rate_out;
+            ListItem::new(Line::from(vec![
                 Span::styled(
-                    format!("{:<20}", p.name),
-                    Style::default().fg(theme::ACTIVE_TAB_FG),
+                    format!("{:<20.20} ", p.name),
+                    Style::default().fg(theme::current().active_tab),
                 ),
+                Span::styled(theme::rate_bar(rate, busiest), Style::default().fg(theme::rate_color(rate))),
+                Span::raw(" "),
                 Span::styled(
-                    format!("▼{} ", format_rate(p.rate_in)),
+                    format!("▼{} ", app.units.rate(p.rate_in)),
                     Style::default().fg(theme::rate_color(p.rate_in)),
                 ),
                 Span::styled(
-                    format!("▲{}", format_rate(p.rate_out)),
+                    format!("▲{}", app.units.rate(p.rate_out)),
                     Style::default().fg(theme::rate_color(p.rate_out)),
                 ),
-            ])
+            ]))
         })
         .collect();
 
+    // Processes and hosts side by side, ports and organizations below
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[0]);
+    let bottom_constraints: &[Constraint] = if app.asn_lookup.is_some() {
+        &[Constraint::Percentage(50), Constraint::Percentage(50)]
+    } else {
+        &[Constraint::Percentage(100)]
+    };
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(bottom_constraints)
+        .split(rows[1]);
+
     let top_block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(theme::BORDER_COLOR))
+        .border_style(Style::default().fg(theme::current().border))
         .title(" Top Processes ");
-    let top = Paragraph::new(top_procs).block(top_block);
-    f.render_widget(top, chunks[1]);
+    let list = List::new(items)
+        .block(top_block)
+        .highlight_style(theme::row_highlight(false))
+        .highlight_symbol("▸ ");
+    let mut state = ListState::default();
+    state.select((!top_procs.is_empty()).then_some(app.overview_index));
+    f.render_stateful_widget(list, top[0], &mut state);
+
+    render_hosts(f, top[1], app);
+    render_ports(f, bottom[0], app);
+    if app.asn_lookup.is_some() {
+        render_orgs(f, bottom[1], app);
+    }
 }
 
-/// Render the sparkline shown in the footer area, filling full width
-pub fn render_footer_sparkline(f: &mut Frame, area: Rect, app: &App) {
+/// Traffic grouped by remote host across all processes, busiest first.
+fn render_hosts(f: &mut Frame, area: Rect, app: &App) {
+    let lines: Vec<Line> = app
+        .snapshot
+        .top_remote_hosts(TOP_ROWS)
+        .iter()
+        .map(|h| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<28.28} ", h.host),
+                    Style::default().fg(theme::current().active_tab),
+                ),
+                Span::styled(
+                    format!("▼{} ", app.units.rate(h.rate_in)),
+                    Style::default().fg(theme::rate_color(h.rate_in)),
+                ),
+                Span::styled(
+                    format!("▲{} ", app.units.rate(h.rate_out)),
+                    Style::default().fg(theme::rate_color(h.rate_out)),
+                ),
+                Span::raw(format!("{} ", app.units.bytes(h.bytes_in + h.bytes_out))),
+                Span::styled(
+                    format!("{} proc", h.processes.len()),
+                    theme::footer_style(),
+                ),
+            ])
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::current().border))
+        .title(" Top Remote Hosts ");
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Traffic grouped by AS organization, busiest first.
+fn render_orgs(f: &mut Frame, area: Rect, app: &App) {
+    let lines: Vec<Line> = app
+        .org_stats
+        .iter()
+        .take(area.height.saturating_sub(2) as usize)
+        .map(|o| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<24.24} ", o.org),
+                    Style::default().fg(theme::current().active_tab),
+                ),
+                Span::styled(
+                    format!("▼{} ", app.units.rate(o.rate_in)),
+                    Style::default().fg(theme::rate_color(o.rate_in)),
+                ),
+                Span::styled(
+                    format!("▲{} ", app.units.rate(o.rate_out)),
+                    Style::default().fg(theme::rate_color(o.rate_out)),
+                ),
+                Span::raw(format!("{} conn", o.connections)),
+            ])
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::current().border))
+        .title(" Top Organizations ");
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Traffic grouped by remote port/protocol, busiest first.
+fn render_ports(f: &mut Frame, area: Rect, app: &App) {
+    let lines: Vec<Line> = app
+        .port_stats
+        .iter()
+        .take(area.height.saturating_sub(2) as usize)
+        .map(|ps| {
+            let service = services::service_name(ps.port, &ps.protocol).unwrap_or("");
+            Line::from(vec![
+                Span::styled(
+                    format!("{:>5}/{:<4}", ps.port, ps.protocol.to_string().to_lowercase()),
+                    Style::default().fg(theme::current().active_tab),
+                ),
+                Span::styled(format!("{:<12}", service), theme::footer_style()),
+                Span::styled(
+                    format!("▼{} ", app.units.rate(ps.rate_in)),
+                    Style::default().fg(theme::rate_color(ps.rate_in)),
+                ),
+                Span::styled(
+                    format!("▲{} ", app.units.rate(ps.rate_out)),
+                    Style::default().fg(theme::rate_color(ps.rate_out)),
+                ),
+                Span::raw(format!("{} conn", ps.connections)),
+            ])
+        })
+        .collect();
+
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(theme::BORDER_COLOR))
-        .title(" Bandwidth ");
+        .border_style(Style::default().fg(theme::current().border))
+        .title(" Top Ports ");
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn lan_wan_line(app: &App) -> Line<'static> {
+    let ((lan_in, lan_out), (wan_in, wan_out)) = app.snapshot.lan_wan_rates();
+    Line::from(vec![
+        Span::styled("LAN: ", theme::header_style()),
+        Span::raw(format!("▼{} ▲{}", app.units.rate(lan_in), app.units.rate(lan_out))),
+        Span::raw("  "),
+        Span::styled("WAN: ", theme::header_style()),
+        Span::raw(format!("▼{} ▲{}", app.units.rate(wan_in), app.units.rate(wan_out))),
+    ])
+}
+
+fn vpn_line(app: &App) -> Line<'static> {
+    let (vpn, physical) = app.snapshot.vpn_split();
+    let total = vpn + physical;
+    let share = if total > 0.0 { 100.0 * vpn / total } else { 0.0 };
+    Line::from(vec![
+        Span::styled("VPN: ", theme::header_style()),
+        Span::styled(app.units.rate(vpn), Style::default().fg(theme::current().vpn)),
+        Span::raw(format!(" ({:.0}%)  ", share)),
+        Span::styled("Physical: ", theme::header_style()),
+        Span::raw(app.units.rate(physical)),
+    ])
+}
+
+/// Cells of a share bar at 100%.
+const SHARE_BAR_WIDTH: f64 = 10.0;
+
+/// "TCP 82% ▇▇▇▇▇▇▇▇ UDP 18% ▇▇  IPv4 90% ▇▇▇▇▇▇▇▇▇ IPv6 10% ▇".
+fn protocol_line(app: &App) -> Line<'static> {
+    let mix = app.snapshot.protocol_mix();
+    let mut spans = vec![Span::styled("Protocols:", theme::header_style())];
+    if mix.is_empty() {
+        spans.push(Span::styled(" no traffic", theme::footer_style()));
+        return Line::from(spans);
+    }
+    let total = mix.tcp + mix.udp + mix.other;
+    let family_total = mix.ipv4 + mix.ipv6;
+    let shares = [
+        ("TCP", mix.tcp, total, theme::current().download),
+        ("UDP", mix.udp, total, theme::current().upload),
+        ("Other", mix.other, total, theme::current().footer),
+        ("IPv4", mix.ipv4, family_total, theme::current().download),
+        ("IPv6", mix.ipv6, family_total, theme::current().upload),
+    ];
+    for (i, (label, amount, total, color)) in shares.into_iter().enumerate() {
+        // Other only when there is some; the gap sets the families apart
+        if (label == "Other" && amount == 0.0) || total == 0.0 {
+            continue;
+        }
+        spans.push(Span::raw(if i == 3 { "  " } else { " " }));
+        let share = amount / total;
+        spans.push(Span::raw(format!("{} {:.0}% ", label, share * 100.0)));
+        spans.push(Span::styled(
+            "▇".repeat((share * SHARE_BAR_WIDTH).round() as usize),
+            Style::default().fg(color),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// States that mean connections are stuck, worth noticing at any count.
+const WARNING_STATES: [&str; 2] = ["SYN_SENT", "CLOSE_WAIT"];
+
+/// "231 ESTABLISHED · 14 LISTEN · 6 CLOSE_WAIT", stuck states highlighted.
+fn state_line(app: &App) -> Line<'static> {
+    let mut spans = vec![Span::styled("States: ", theme::header_style())];
+    let counts = app.snapshot.state_counts();
+    if counts.is_empty() {
+        spans.push(Span::styled("—", theme::footer_style()));
+    }
+    for (i, (state, count)) in counts.into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(" · ", theme::footer_style()));
+        }
+        let style = if WARNING_STATES.contains(&state.as_str()) {
+            Style::default().fg(theme::current().warning).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        spans.push(Span::styled(format!("{} {}", count, state), style));
+    }
+    Line::from(spans)
+}
+
+fn peak_line(app: &App) -> Line<'static> {
+    let mut spans = vec![
+        Span::styled("Peak Total: ", theme::header_style()),
+        Span::styled(
+            app.units.rate(app.peak_total_rate),
+            Style::default().fg(theme::rate_color(app.peak_total_rate)),
+        ),
+    ];
+    if let Some(at) = app.peak_total_at {
+        spans.push(Span::raw(format!(" ({} ago)", format_duration(at.elapsed()))));
+    }
+    Line::from(spans)
+}
 
+fn churn_line(app: &App) -> Line<'static> {
+    let activity: Vec<usize> = app.churn_history.iter().map(|(o, c)| o + c).collect();
+    Line::from(vec![
+        Span::styled("Churn: ", theme::header_style()),
+        Span::styled(
+            format!("+{}", app.churn.opened),
+            Style::default().fg(theme::current().download),
+        ),
+        Span::raw(" / "),
+        Span::styled(
+            format!("-{}", app.churn.closed),
+            Style::default().fg(theme::current().upload),
+        ),
+        Span::raw("  "),
+        Span::styled(mini_sparkline(&activity), Style::default().fg(theme::current().footer)),
+    ])
+}
+
+/// Render values as a one-line string of block characters scaled to the max.
+fn mini_sparkline(values: &[usize]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.iter().copied().max().unwrap_or(0);
+    if max == 0 {
+        return BARS[0].to_string().repeat(values.len());
+    }
+    values
+        .iter()
+        .map(|&v| BARS[(v * (BARS.len() - 1)) / max])
+        .collect()
+}
+
+/// Render the footer's download and upload sparklines, stacked and filling
+/// the full width.
+pub fn render_footer_sparkline(f: &mut Frame, area: Rect, app: &App) {
     // Inner width excluding borders
     let inner_width = area.width.saturating_sub(2) as usize;
 
-    // Pad with leading zeros so the sparkline always fills the full width
-    let history_len = app.bandwidth_history.len();
-    let mut data: Vec<u64> = if history_len < inner_width {
-        let mut padded = vec![0u64; inner_width - history_len];
-        padded.extend(app.bandwidth_history.iter().map(|&v| v as u64));
-        padded
+    // A process drill-down shows that process's own history
+    let empty = VecDeque::new();
+    let drilled = app.drilled.as_ref().filter(|_| app.in_drill_down());
+    let history = match drilled {
+        Some(key) => app.process_history.get(key).unwrap_or(&empty),
+        None => &app.bandwidth_history,
+    };
+    let (rates_in, rates_out): (Vec<f64>, Vec<f64>) = history.iter().copied().unzip();
+    let down = fill_width(&rates_in, inner_width);
+    let up = fill_width(&rates_out, inner_width);
+
+    // One scale for both, labeled so the bar heights can be read
+    let peak = down.iter().chain(&up).copied().max().unwrap_or(0);
+    let label = scale_label(app.units, &down, &up, app.log_scale);
+    let (down, up, peak) = if app.log_scale {
+        let log = |data: &[u64]| data.iter().map(|&v| log_scale(v as f64)).collect::<Vec<_>>();
+        (log(&down), log(&up), log_scale(peak as f64))
     } else {
-        // Take only the most recent points that fit
-        app.bandwidth_history
-            .iter()
-            .skip(history_len - inner_width)
-            .map(|&v| v as u64)
-            .collect()
+        (down, up, peak)
     };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::current().border))
+        .title(match (drilled, &app.watch) {
+            (Some((name, pid)), _) => format!(" Bandwidth — {} ({}) — {} ", name, pid, label),
+            (None, Some(watch)) => format!(" Bandwidth — {} — {} ", watch.label(), label),
+            (None, None) => format!(" Bandwidth — {} ", label),
+        });
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    // Download on top, taking the odd row
+    let halves = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(inner.height.div_ceil(2)), Constraint::Min(0)])
+        .split(inner);
+    for (data, color, half) in [
+        (&down, theme::current().download, halves[0]),
+        (&up, theme::current().upload, halves[1]),
+    ] {
+        let sparkline = Sparkline::default()
+            .data(data)
+            .max(peak.max(1))
+            .style(Style::default().fg(color));
+        f.render_widget(sparkline, half);
+    }
+}
+
+/// The newest and highest rates among the bars on screen, "now ▼ 1.3 MB/s
+/// ▲ 20.0 KB/s, peak 12.4 MB/s", or "idle" when they are all zero.
+fn scale_label(units: Units, down: &[u64], up: &[u64], log: bool) -> String {
+    let peak = down.iter().chain(up).copied().max().unwrap_or(0);
+    let log = if log { " (log)" } else { "" };
+    if peak == 0 {
+        return format!("idle{}", log);
+    }
+    let now = |data: &[u64]| units.rate(data.last().copied().unwrap_or(0) as f64);
+    format!("now ▼ {} ▲ {}, peak {}{}", now(down), now(up), units.rate(peak as f64), log)
+}
+
+/// The values as `width` sparkline bars with the newest at the right edge.
+/// A history longer than the width is split into buckets, each bar the
+/// highest value in its bucket; a shorter one is padded with leading zeros.
+pub fn fill_width(values: &[f64], width: usize) -> Vec<u64> {
+    if values.len() <= width {
+        let mut data = vec![0; width - values.len()];
+        data.extend(values.iter().map(|&v| v as u64));
+        return data;
+    }
+    // Bucket edges are counted back from the newest sample: the rightmost
+    // bar covers the length over the width rounded down, and leftover
+    // samples widen older bars instead
+    let len = values.len();
+    let edge = |i: usize| len - (width - i) * len / width;
+    (0..width)
+        .map(|i| {
+            values[edge(i)..edge(i + 1)]
+                .iter()
+                .fold(0.0_f64, |max, &v| max.max(v)) as u64
+        })
+        .collect()
+}
+
+/// Steps per unit of `ln(1 + rate)`, so bars keep their detail as integers.
+const LOG_RESOLUTION: f64 = 1000.0;
+
+/// A rate as a bar height on a logarithmic scale. Zero (and anything not
+/// positive) stays zero rather than going to minus infinity.
+pub fn log_scale(rate: f64) -> u64 {
+    if rate > 0.0 {
+        (rate.ln_1p() * LOG_RESOLUTION) as u64
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_width() {
+        assert_eq!(fill_width(&[1.0, 2.0], 4), [0, 0, 1, 2]);
+        assert_eq!(fill_width(&[1.0, 2.0, 3.0], 3), [1, 2, 3]);
+        assert_eq!(fill_width(&[1.0, 2.0], 0), Vec::<u64>::new());
+        assert_eq!(fill_width(&[], 2), [0, 0]);
+        // Fractions of a byte round down
+        assert_eq!(fill_width(&[0.9, 1500.7], 2), [0, 1500]);
+    }
 
-    // Ensure we don't exceed the available width
-    data.truncate(inner_width);
+    #[test]
+    fn test_log_scale() {
+        assert_eq!(log_scale(0.0), 0);
+        assert_eq!(log_scale(-5.0), 0);
+        assert_eq!(log_scale(f64::NAN), 0);
+        // A 20 KB/s hum is still more than half the height of a 50 MB/s spike
+        let hum = log_scale(20_000.0);
+        let spike = log_scale(50_000_000.0);
+        assert!(hum > 0 && hum * 2 > spike);
+        assert!(log_scale(1.0) < hum);
+        // Even the largest values stay finite and ordered
+        assert!(log_scale(1e12) < log_scale(f64::MAX));
+        assert!(log_scale(f64::MAX) < u64::MAX);
+    }
 
-    let sparkline = Sparkline::default()
-        .block(block)
-        .data(&data)
-        .style(Style::default().fg(theme::DOWNLOAD_COLOR));
+    #[test]
+    fn test_scale_label() {
+        let units = Units::Bytes;
+        assert_eq!(scale_label(units, &[0, 0], &[0, 0], false), "idle");
+        assert_eq!(scale_label(units, &[], &[], true), "idle (log)");
+        assert_eq!(
+            scale_label(units, &[13_000_000, 1_363_149], &[0, 20_480], false),
+            "now ▼ 1.3 MB/s ▲ 20.0 KB/s, peak 12.4 MB/s"
+        );
+        assert!(scale_label(units, &[0, 2048], &[1024, 0], true).ends_with("peak 2.0 KB/s (log)"));
+    }
 
-    f.render_widget(sparkline, area);
+    #[test]
+    fn test_fill_width_downsamples() {
+        // Even buckets: each bar is its bucket's highest value
+        assert_eq!(fill_width(&[1.0, 5.0, 2.0, 3.0, 9.0, 0.0], 3), [5, 3, 9]);
+        assert_eq!(fill_width(&[1.0, 2.0, 3.0], 1), [3]);
+        // Leftovers go to the oldest buckets: 10 into 3 is 4, 3, 3
+        let values: Vec<f64> = (1..=10).map(f64::from).collect();
+        assert_eq!(fill_width(&values, 3), [4, 7, 10]);
+        // 7 into 4 is 2, 2, 2, 1, and the newest sample is always in the last bar
+        let values = [8.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        assert_eq!(fill_width(&values, 4), [8, 3, 5, 6]);
+        assert_eq!(fill_width(&[0.0, 0.0, 0.0, 7.0], 2), [0, 7]);
+        // One sample too many merges only the oldest pair
+        assert_eq!(fill_width(&[1.0, 2.0, 3.0, 4.0], 3), [2, 3, 4]);
+    }
 }
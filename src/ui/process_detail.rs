@@ -0,0 +1,218 @@
+use chrono::{DateTime, Local};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Sparkline, Wrap};
+use ratatui::Frame;
+
+use crate::app::App;
+use crate::data::model::ProcessKey;
+use crate::ui::layout::centered_rect;
+use crate::ui::processes::{format_duration, format_pps};
+use crate::ui::theme;
+
+pub fn render(f: &mut Frame, app: &App) {
+    let area = centered_rect(70, 70, f.area());
+
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::current().header))
+        .title(" Process Detail ");
+
+    // The popup follows the process it was opened on, refreshed each tick
+    let Some(p) = app.detail_process() else {
+        let text = match app.detail_key() {
+            Some((name, pid)) => vec![
+                Line::from(Span::styled(
+                    " process exited ",
+                    Style::default()
+                        .fg(theme::current().warning)
+                        .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+                )),
+                Line::from(""),
+                Line::from(format!("{} ({}) is no longer running.", name, pid)),
+            ],
+            None => vec![Line::from("No process selected")],
+        };
+        f.render_widget(Paragraph::new(text).block(block), area);
+        return;
+    };
+
+    let label = |s: &'static str| Span::styled(format!("{:<12}", s), theme::header_style());
+
+    let lines = vec![
+        Line::from(Span::styled(
+            format!(" {} ", p.name),
+            Style::default()
+                .fg(theme::current().active_tab)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![label("PID"), Span::raw(p.pid.to_string())]),
+        Line::from(vec![
+            label("Path"),
+            Span::raw(p.path.clone().unwrap_or_else(|| "?".to_string())),
+        ]),
+        Line::from(vec![
+            label("User"),
+            Span::raw(p.user.clone().unwrap_or_else(|| "?".to_string())),
+        ]),
+        Line::from(vec![label("Command"), Span::raw(command_line(app, p.pid))]),
+        Line::from(vec![
+            label("Cwd"),
+            Span::raw(
+                app.process_details
+                    .as_ref()
+                    .filter(|d| d.pid == p.pid)
+                    .and_then(|d| d.cwd.clone())
+                    .unwrap_or_else(|| "?".to_string()),
+            ),
+        ]),
+        Line::from(vec![
+            label("Started"),
+            Span::raw(match (p.started, p.uptime()) {
+                (Some(started), Some(uptime)) => format!(
+                    "{} (up {})",
+                    DateTime::<Local>::from(started).format("%Y-%m-%d %H:%M:%S"),
+                    format_duration(uptime)
+                ),
+                _ => "?".to_string(),
+            }),
+        ]),
+        Line::from(vec![label("Connections"), Span::raw(p.connection_count().to_string())]),
+        Line::from(vec![
+            label("New conns"),
+            Span::raw(format!(
+                "{} this tick, {} this session",
+                p.new_connections, p.total_new_connections
+            )),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            label("Total"),
+            Span::styled(
+                format!("▼ {}", app.units.bytes(p.bytes_in)),
+                Style::default().fg(theme::current().download),
+            ),
+            Span::raw("  "),
+            Span::styled(
+                format!("▲ {}", app.units.bytes(p.bytes_out)),
+                Style::default().fg(theme::current().upload),
+            ),
+        ]),
+        Line::from(vec![
+            label("Rate"),
+            Span::styled(
+                format!("▼ {}", app.units.rate(p.rate_in)),
+                Style::default().fg(theme::rate_color(p.rate_in)),
+            ),
+            Span::raw("  "),
+            Span::styled(
+                format!("▲ {}", app.units.rate(p.rate_out)),
+                Style::default().fg(theme::rate_color(p.rate_out)),
+            ),
+        ]),
+        Line::from(vec![
+            label("Avg rate"),
+            Span::raw(
+                p.lifetime_rates()
+                    .map(|(rate_in, rate_out)| {
+                        format!(
+                            "▼ {}  ▲ {}  (since start)",
+                            app.units.rate(rate_in),
+                            app.units.rate(rate_out)
+                        )
+                    })
+                    .unwrap_or_else(|| "?".to_string()),
+            ),
+        ]),
+        Line::from(vec![
+            label("Packets"),
+            Span::raw(format!(
+                "▼ {} ({})  ▲ {} ({})",
+                p.packets_in,
+                format_pps(p.pps_in),
+                p.packets_out,
+                format_pps(p.pps_out)
+            )),
+        ]),
+        Line::from(vec![
+            label("Peak"),
+            Span::styled(
+                format!("▼ {}", app.units.rate(p.peak_rate_in)),
+                Style::default().fg(theme::rate_color(p.peak_rate_in)),
+            ),
+            Span::raw("  "),
+            Span::styled(
+                format!("▲ {}", app.units.rate(p.peak_rate_out)),
+                Style::default().fg(theme::rate_color(p.peak_rate_out)),
+            ),
+        ]),
+    ];
+
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(5)])
+        .split(inner);
+
+    let detail = Paragraph::new(lines).wrap(Wrap { trim: false });
+    f.render_widget(detail, chunks[0]);
+    render_history(f, chunks[1], app, &p.key());
+}
+
+/// The process's combined in + out rate over the last few minutes, newest
+/// on the right.
+fn render_history(f: &mut Frame, area: Rect, app: &App, key: &ProcessKey) {
+    let history = app.process_history.get(key);
+    let width = area.width as usize;
+    let data: Vec<u64> = history
+        .map(|history| {
+            let skip = history.len().saturating_sub(width);
+            history.iter().skip(skip).map(|&(rate_in, rate_out)| (rate_in + rate_out) as u64).collect()
+        })
+        .unwrap_or_default();
+    let peak = data.iter().copied().max().unwrap_or(0);
+    let title = if peak > 0 {
+        format!(" Rate history (max {}) ", app.units.rate(peak as f64))
+    } else {
+        " Rate history ".to_string()
+    };
+
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .borders(Borders::TOP)
+                .border_style(Style::default().fg(theme::current().border))
+                .title(title),
+        )
+        .data(&data)
+        .style(Style::default().fg(theme::current().download));
+    f.render_widget(sparkline, area);
+}
+
+/// Full argv of the process, shell-quoting arguments that contain spaces,
+/// or "?" when it couldn't be read.
+fn command_line(app: &App, pid: u32) -> String {
+    let Some(details) = app.process_details.as_ref().filter(|d| d.pid == pid) else {
+        return "?".to_string();
+    };
+    if details.args.is_empty() {
+        return "?".to_string();
+    }
+    details
+        .args
+        .iter()
+        .map(|arg| {
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                format!("'{}'", arg)
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
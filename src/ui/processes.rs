@@ -1,34 +1,150 @@
 use ratatui::layout::Constraint;
 use ratatui::style::Style;
-use ratatui::text::Span;
+use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Cell, Row, Table, TableState};
 use ratatui::Frame;
 use ratatui::layout::Rect;
 
-use crate::app::App;
-use crate::data::model::SortField;
+use crate::app::{ActiveTab, App};
+use crate::data::filter::Field;
+use crate::data::model::{Process, SortField};
+use crate::ui::columns::{self, Column};
+use crate::ui::hitbox::TableHitbox;
+use crate::ui::layout;
 use crate::ui::theme;
 
-pub fn render(f: &mut Frame, area: Rect, app: &App) {
-    let header_cells = [
-        ("Process", SortField::Name),
-        ("PID", SortField::Pid),
-        ("Conn", SortField::Connections),
-        ("Down", SortField::BytesIn),
-        ("Up", SortField::BytesOut),
-        ("Rate In", SortField::RateIn),
-        ("Rate Out", SortField::RateOut),
-    ]
-    .iter()
-    .map(|(label, field)| {
-        let text = if app.sort_field == *field {
-            format!("{} ▼", label)
-        } else {
-            label.to_string()
-        };
-        Cell::from(Span::styled(text, theme::header_style()))
-    })
-    .collect::<Vec<_>>();
+/// Columns of the Processes table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessColumn {
+    Name,
+    Pid,
+    User,
+    Conn,
+    Down,
+    Up,
+    RateIn,
+    RateOut,
+    PpsIn,
+    PpsOut,
+    Uptime,
+    Peak,
+}
+
+impl Column for ProcessColumn {
+    const ALL: &'static [Self] = &[
+        ProcessColumn::Name,
+        ProcessColumn::Pid,
+        ProcessColumn::User,
+        ProcessColumn::Conn,
+        ProcessColumn::Down,
+        ProcessColumn::Up,
+        ProcessColumn::RateIn,
+        ProcessColumn::RateOut,
+        ProcessColumn::PpsIn,
+        ProcessColumn::PpsOut,
+        ProcessColumn::Uptime,
+        ProcessColumn::Peak,
+    ];
+
+    fn id(self) -> &'static str {
+        match self {
+            ProcessColumn::Name => "process",
+            ProcessColumn::Pid => "pid",
+            ProcessColumn::User => "user",
+            ProcessColumn::Conn => "conn",
+            ProcessColumn::Down => "down",
+            ProcessColumn::Up => "up",
+            ProcessColumn::RateIn => "rate-in",
+            ProcessColumn::RateOut => "rate-out",
+            ProcessColumn::PpsIn => "pps-in",
+            ProcessColumn::PpsOut => "pps-out",
+            ProcessColumn::Uptime => "uptime",
+            ProcessColumn::Peak => "peak",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ProcessColumn::Name => "Process",
+            ProcessColumn::Pid => "PID",
+            ProcessColumn::User => "User",
+            ProcessColumn::Conn => "Conn",
+            ProcessColumn::Down => "Down",
+            ProcessColumn::Up => "Up",
+            ProcessColumn::RateIn => "Rate In",
+            ProcessColumn::RateOut => "Rate Out",
+            ProcessColumn::PpsIn => "PPS In",
+            ProcessColumn::PpsOut => "PPS Out",
+            ProcessColumn::Uptime => "Uptime",
+            ProcessColumn::Peak => "Peak",
+        }
+    }
+
+    fn width(self) -> Constraint {
+        match self {
+            ProcessColumn::Name => Constraint::Min(16),
+            ProcessColumn::Pid => Constraint::Length(7),
+            ProcessColumn::User => Constraint::Length(14),
+            ProcessColumn::Conn => Constraint::Length(5),
+            ProcessColumn::Down | ProcessColumn::Up => Constraint::Length(10),
+            ProcessColumn::RateIn | ProcessColumn::Peak => Constraint::Length(12),
+            ProcessColumn::RateOut => Constraint::Length(18),
+            ProcessColumn::PpsIn | ProcessColumn::PpsOut => Constraint::Length(11),
+            ProcessColumn::Uptime => Constraint::Length(8),
+        }
+    }
+
+    fn default_visible(self) -> bool {
+        !matches!(
+            self,
+            ProcessColumn::User
+                | ProcessColumn::PpsIn
+                | ProcessColumn::PpsOut
+                | ProcessColumn::Uptime
+                | ProcessColumn::Peak
+        )
+    }
+
+    fn hide_below(self) -> u16 {
+        match self {
+            ProcessColumn::Down | ProcessColumn::Up => layout::HIDE_TOTALS_BELOW,
+            ProcessColumn::Pid => layout::COLLAPSE_BELOW,
+            _ => 0,
+        }
+    }
+}
+
+impl ProcessColumn {
+    /// The sort field this column shows, for the header's sort marker.
+    pub fn sort_field(self) -> Option<SortField> {
+        match self {
+            ProcessColumn::Name => Some(SortField::Name),
+            ProcessColumn::Pid => Some(SortField::Pid),
+            ProcessColumn::Conn => Some(SortField::Connections),
+            ProcessColumn::Down => Some(SortField::BytesIn),
+            ProcessColumn::Up => Some(SortField::BytesOut),
+            ProcessColumn::RateIn => Some(SortField::RateIn),
+            ProcessColumn::RateOut => Some(SortField::RateOut),
+            ProcessColumn::PpsIn => Some(SortField::PpsIn),
+            ProcessColumn::PpsOut => Some(SortField::PpsOut),
+            ProcessColumn::User | ProcessColumn::Uptime | ProcessColumn::Peak => None,
+        }
+    }
+}
+
+pub fn render(f: &mut Frame, area: Rect, app: &App) -> TableHitbox {
+    let columns = app.process_columns.visible_at(area.width);
+    let header_cells = columns
+        .iter()
+        .map(|column| {
+            let text = if column.sort_field() == Some(app.sort_field) {
+                format!("{} {}", column.label(), app.sort_direction.glyph())
+            } else {
+                column.label().to_string()
+            };
+            Cell::from(Span::styled(text, theme::header_style()))
+        })
+        .collect::<Vec<_>>();
 
     let header = Row::new(header_cells).height(1);
 
@@ -39,76 +155,111 @@ pub fn render(f: &mut Frame, area: Rect, app: &App) {
         .map(|p| p.rate_in.max(p.rate_out))
         .fold(0.0_f64, f64::max);
 
-    let rows: Vec<Row> = app
-        .filtered_processes()
+    let processes = app.filtered_processes();
+    let (rows, window) = layout::window_rows(&processes, app.process_offset, app.process_index, area, |p| {
+        let cells = columns
+            .iter()
+            .map(|column| process_cell(app, p, *column, max_rate))
+            .collect::<Vec<_>>();
+        if app.alerts.is_alerting(&p.key()) {
+            Row::new(cells).style(Style::default().fg(theme::current().alert))
+        } else {
+            Row::new(cells)
+        }
+    });
+
+    let widths = columns
         .iter()
-        .map(|p| {
-            let rate_color = theme::rate_color(p.rate_in.max(p.rate_out));
-            let bar = theme::rate_bar(p.rate_in + p.rate_out, max_rate * 2.0);
-            Row::new(vec![
-                Cell::from(p.name.clone()),
-                Cell::from(p.pid.to_string()),
-                Cell::from(p.connection_count().to_string()),
-                Cell::from(format_bytes(p.bytes_in)),
-                Cell::from(format_bytes(p.bytes_out)),
-                Cell::from(Span::styled(
-                    format_rate(p.rate_in),
-                    Style::default().fg(theme::rate_color(p.rate_in)),
-                )),
-                Cell::from(Span::styled(
-                    format!("{} {}", format_rate(p.rate_out), bar),
-                    Style::default().fg(rate_color),
-                )),
-            ])
-        })
-        .collect();
-
-    let widths = [
-        Constraint::Min(16),
-        Constraint::Length(7),
-        Constraint::Length(5),
-        Constraint::Length(10),
-        Constraint::Length(10),
-        Constraint::Length(12),
-        Constraint::Length(18),
-    ];
+        .map(|c| app.process_columns.width(*c))
+        .collect::<Vec<_>>();
+    let widths = columns::fit(&widths, columns::available(area.width));
 
-    let table = Table::new(rows, widths)
+    let table = Table::new(rows, widths.clone())
         .header(header)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(theme::BORDER_COLOR))
+                .border_style(Style::default().fg(theme::current().border))
                 .title(" Processes "),
         )
-        .row_highlight_style(theme::selected_style())
+        .row_highlight_style(theme::row_highlight(app.on_search_match()))
         .highlight_symbol("▸ ");
 
     let mut state = TableState::default();
-    state.select(Some(app.process_index));
+    state.select(Some(app.process_index.saturating_sub(window.start)));
     f.render_stateful_widget(table, area, &mut state);
+    layout::table_scrollbar(f, area, processes.len(), window.start);
+
+    let fields = columns.iter().map(|c| c.sort_field()).collect::<Vec<_>>();
+    TableHitbox::new(area, &widths, &fields, window.start)
 }
 
-pub fn format_bytes(bytes: u64) -> String {
-    if bytes >= 1_073_741_824 {
-        format!("{:.1} GB", bytes as f64 / 1_073_741_824.0)
-    } else if bytes >= 1_048_576 {
-        format!("{:.1} MB", bytes as f64 / 1_048_576.0)
-    } else if bytes >= 1024 {
-        format!("{:.1} KB", bytes as f64 / 1024.0)
-    } else {
-        format!("{} B", bytes)
+fn process_cell<'a>(app: &App, p: &Process, column: ProcessColumn, max_rate: f64) -> Cell<'a> {
+    let highlighted = |text: &str, field| {
+        Cell::from(Line::from(theme::highlight(text, &app.highlights(ActiveTab::Processes, text, field), Style::default())))
+    };
+    match column {
+        ProcessColumn::Name => highlighted(
+            match &p.display_name {
+                Some(display) if !app.raw_names => display,
+                _ => &p.name,
+            },
+            None,
+        ),
+        ProcessColumn::Pid => highlighted(&p.pid.to_string(), Some(Field::Pid)),
+        ProcessColumn::User => highlighted(p.user.as_deref().unwrap_or_default(), None),
+        ProcessColumn::Conn => Cell::from(p.connection_count().to_string()),
+        ProcessColumn::Down => Cell::from(app.units.bytes(p.bytes_in)),
+        ProcessColumn::Up => Cell::from(app.units.bytes(p.bytes_out)),
+        ProcessColumn::RateIn => Cell::from(Span::styled(
+            app.units.rate(p.rate_in),
+            Style::default().fg(theme::rate_color(p.rate_in)),
+        )),
+        ProcessColumn::RateOut => {
+            let bar = theme::rate_bar(p.rate_in + p.rate_out, max_rate * 2.0);
+            Cell::from(Span::styled(
+                format!("{} {}", app.units.rate(p.rate_out), bar),
+                Style::default().fg(theme::rate_color(p.rate_in.max(p.rate_out))),
+            ))
+        }
+        ProcessColumn::PpsIn => Cell::from(format_pps(p.pps_in)),
+        ProcessColumn::PpsOut => Cell::from(format_pps(p.pps_out)),
+        ProcessColumn::Uptime => Cell::from(
+            p.uptime().map(format_duration).unwrap_or_else(|| "?".to_string()),
+        ),
+        ProcessColumn::Peak => {
+            let peak = p.peak_rate_in.max(p.peak_rate_out);
+            Cell::from(Span::styled(
+                app.units.rate(peak),
+                Style::default().fg(theme::rate_color(peak)),
+            ))
+        }
     }
 }
 
-pub fn format_rate(bytes_per_sec: f64) -> String {
-    if bytes_per_sec >= 1_048_576.0 {
-        format!("{:.1} MB/s", bytes_per_sec / 1_048_576.0)
-    } else if bytes_per_sec >= 1024.0 {
-        format!("{:.1} KB/s", bytes_per_sec / 1024.0)
-    } else if bytes_per_sec > 0.0 {
-        format!("{:.0} B/s", bytes_per_sec)
+/// Packet rate counterpart to `Units::rate`: "850 pps", "1.2k pps".
+pub fn format_pps(packets_per_sec: f64) -> String {
+    if packets_per_sec >= 1_000_000.0 {
+        format!("{:.1}M pps", packets_per_sec / 1_000_000.0)
+    } else if packets_per_sec >= 1000.0 {
+        format!("{:.1}k pps", packets_per_sec / 1000.0)
+    } else if packets_per_sec > 0.0 {
+        format!("{:.0} pps", packets_per_sec)
     } else {
         "—".to_string()
     }
 }
+
+/// Compact human duration: "45s", "2m", "3h12m", "2d4h".
+pub fn format_duration(d: std::time::Duration) -> String {
+    let secs = d.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else if secs < 86_400 {
+        format!("{}h{}m", secs / 3600, (secs % 3600) / 60)
+    } else {
+        format!("{}d{}h", secs / 86_400, (secs % 86_400) / 3600)
+    }
+}
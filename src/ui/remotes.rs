@@ -0,0 +1,105 @@
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::Style;
+use ratatui::text::Span;
+use ratatui::widgets::{Block, Borders, Cell, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::app::App;
+use crate::data::model::SortField;
+use crate::ui::columns;
+use crate::ui::hitbox::TableHitbox;
+use crate::ui::layout;
+use crate::ui::theme;
+
+pub fn render(f: &mut Frame, area: Rect, app: &App) -> TableHitbox {
+    let headers = [
+        ("Host", Some(SortField::Name)),
+        ("Conn", Some(SortField::Connections)),
+        ("Down", Some(SortField::BytesIn)),
+        ("Up", Some(SortField::BytesOut)),
+        ("Rate In", Some(SortField::RateIn)),
+        ("Rate Out", Some(SortField::RateOut)),
+        ("Processes", None),
+    ];
+    // Narrow terminals drop the cumulative Down and Up columns
+    let totals = area.width >= layout::HIDE_TOTALS_BELOW;
+    let shown = |i: &usize| totals || !matches!(i, 2 | 3);
+    let pick = |cells: Vec<Cell<'static>>| {
+        cells
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| shown(i))
+            .map(|(_, c)| c)
+            .collect::<Vec<_>>()
+    };
+    let headers: Vec<_> = headers.into_iter().enumerate().filter(|(i, _)| shown(i)).map(|(_, h)| h).collect();
+    let header_cells = headers
+        .iter()
+        .map(|(label, field)| {
+            let text = if field.is_some_and(|f| f == app.sort_field) {
+                format!("{} {}", label, app.sort_direction.glyph())
+            } else {
+                label.to_string()
+            };
+            Cell::from(Span::styled(text, theme::header_style()))
+        })
+        .collect::<Vec<_>>();
+
+    let header = Row::new(header_cells).height(1);
+
+    let rows: Vec<Row> = app
+        .filtered_remote_hosts()
+        .iter()
+        .map(|h| {
+            Row::new(pick(vec![
+                Cell::from(h.host.clone()),
+                Cell::from(h.connections.to_string()),
+                Cell::from(app.units.bytes(h.bytes_in)),
+                Cell::from(app.units.bytes(h.bytes_out)),
+                Cell::from(Span::styled(
+                    app.units.rate(h.rate_in),
+                    Style::default().fg(theme::rate_color(h.rate_in)),
+                )),
+                Cell::from(Span::styled(
+                    app.units.rate(h.rate_out),
+                    Style::default().fg(theme::rate_color(h.rate_out)),
+                )),
+                Cell::from(h.processes.join(", ")),
+            ]))
+        })
+        .collect();
+
+    let widths: Vec<Constraint> = [
+        Constraint::Min(28),
+        Constraint::Length(5),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Min(16),
+    ]
+    .into_iter()
+    .enumerate()
+    .filter(|(i, _)| shown(i))
+    .map(|(_, w)| w)
+    .collect();
+    let widths = columns::fit(&widths, columns::available(area.width));
+
+    let table = Table::new(rows, widths.clone())
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme::current().border))
+                .title(" Remote Hosts "),
+        )
+        .row_highlight_style(theme::row_highlight(app.on_search_match()))
+        .highlight_symbol("▸ ");
+
+    let mut state = TableState::default().with_offset(app.remote_offset);
+    state.select(Some(app.remote_index));
+    f.render_stateful_widget(table, area, &mut state);
+
+    let fields = headers.iter().map(|(_, field)| *field).collect::<Vec<_>>();
+    TableHitbox::new(area, &widths, &fields, state.offset())
+}
@@ -0,0 +1,40 @@
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::app::App;
+use crate::data::model::SortField;
+use crate::ui::layout::centered_rect;
+use crate::ui::theme;
+
+/// Sort picker: the fields numbered for direct selection, with the current
+/// one marked by its direction.
+pub fn render(f: &mut Frame, app: &App) {
+    let area = centered_rect(30, 50, f.area());
+
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::current().header))
+        .title(" Sort by ");
+
+    let lines = SortField::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let marker = if *field == app.sort_field { app.sort_direction.glyph() } else { "" };
+            let text = format!("{} {} {}", i + 1, field.label(), marker);
+            if i == app.sort_menu_index {
+                Line::from(Span::styled(format!("▸ {}", text), theme::selected_style()))
+            } else if *field == app.sort_field {
+                Line::from(Span::styled(format!("  {}", text), Style::default().add_modifier(Modifier::BOLD)))
+            } else {
+                Line::from(format!("  {}", text))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
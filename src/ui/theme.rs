@@ -1,41 +1,371 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
 use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Span;
+
+use crate::data::addr::AddrClass;
+
+/// Built-in palettes, selectable with `--theme`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ThemeName {
+    #[default]
+    Default,
+    Light,
+    Solarized,
+    Gruvbox,
+    Monochrome,
+}
+
+/// Colors for every part of the UI, plus the rate thresholds that pick a
+/// rate's color.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub header: Color,
+    pub active_tab: Color,
+    pub inactive_tab: Color,
+    pub selected_bg: Color,
+    pub border: Color,
+    pub footer: Color,
+    pub upload: Color,
+    pub download: Color,
+    pub closed: Color,
+    pub idle: Color,
+    pub lan: Color,
+    pub multicast: Color,
+    pub vpn: Color,
+    pub stalled: Color,
+    pub inbound_public: Color,
+    pub warning: Color,
+    pub alert: Color,
+    pub filter_match: Color,
+    pub rate_high: Color,
+    pub rate_medium: Color,
+    pub rate_low: Color,
+    pub rate_zero: Color,
+    /// Rates above this many bytes/sec use `rate_high`
+    pub rate_high_above: f64,
+    /// Rates above this many bytes/sec use `rate_medium`
+    pub rate_medium_above: f64,
+    /// Ignore the fixed thresholds and color rates by their share of the
+    /// busiest process's rate (see `set_rate_scale`)
+    pub rate_relative: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            header: Color::Cyan,
+            active_tab: Color::White,
+            inactive_tab: Color::Gray,
+            selected_bg: Color::DarkGray,
+            border: Color::DarkGray,
+            footer: Color::DarkGray,
+            upload: Color::Magenta,
+            download: Color::Blue,
+            closed: Color::DarkGray,
+            idle: Color::Gray,
+            lan: Color::DarkGray,
+            multicast: Color::LightMagenta,
+            vpn: Color::LightGreen,
+            stalled: Color::Yellow,
+            inbound_public: Color::LightRed,
+            warning: Color::Yellow,
+            alert: Color::Red,
+            filter_match: Color::Yellow,
+            rate_high: Color::Red,
+            rate_medium: Color::Yellow,
+            rate_low: Color::Green,
+            rate_zero: Color::DarkGray,
+            rate_high_above: 1_000_000.0,
+            rate_medium_above: 100_000.0,
+            rate_relative: false,
+        }
+    }
+}
+
+impl Theme {
+    pub fn preset(name: ThemeName) -> Self {
+        let base = Theme::default();
+        match name {
+            ThemeName::Default => base,
+            ThemeName::Light => Theme {
+                header: Color::Blue,
+                active_tab: Color::Black,
+                inactive_tab: Color::DarkGray,
+                selected_bg: Color::Gray,
+                border: Color::Gray,
+                footer: Color::DarkGray,
+                closed: Color::Gray,
+                idle: Color::DarkGray,
+                lan: Color::DarkGray,
+                multicast: Color::Magenta,
+                vpn: Color::Green,
+                stalled: Color::Rgb(0xa0, 0x60, 0x00),
+                inbound_public: Color::Red,
+                warning: Color::Rgb(0xa0, 0x60, 0x00),
+                filter_match: Color::Rgb(0xa0, 0x60, 0x00),
+                rate_medium: Color::Rgb(0xa0, 0x60, 0x00),
+                rate_zero: Color::Gray,
+                ..base
+            },
+            ThemeName::Solarized => {
+                let base01 = Color::Rgb(0x58, 0x6e, 0x75);
+                let yellow = Color::Rgb(0xb5, 0x89, 0x00);
+                let red = Color::Rgb(0xdc, 0x32, 0x2f);
+                Theme {
+                    header: Color::Rgb(0x2a, 0xa1, 0x98),
+                    active_tab: Color::Rgb(0x93, 0xa1, 0xa1),
+                    inactive_tab: Color::Rgb(0x83, 0x94, 0x96),
+                    selected_bg: Color::Rgb(0x07, 0x36, 0x42),
+                    border: base01,
+                    footer: base01,
+                    upload: Color::Rgb(0xd3, 0x36, 0x82),
+                    download: Color::Rgb(0x26, 0x8b, 0xd2),
+                    closed: base01,
+                    idle: base01,
+                    lan: base01,
+                    multicast: Color::Rgb(0x6c, 0x71, 0xc4),
+                    vpn: Color::Rgb(0x85, 0x99, 0x00),
+                    stalled: yellow,
+                    inbound_public: Color::Rgb(0xcb, 0x4b, 0x16),
+                    warning: yellow,
+                    alert: red,
+                    filter_match: yellow,
+                    rate_high: red,
+                    rate_medium: yellow,
+                    rate_low: Color::Rgb(0x85, 0x99, 0x00),
+                    rate_zero: base01,
+                    ..base
+                }
+            }
+            ThemeName::Gruvbox => {
+                let gray = Color::Rgb(0x92, 0x83, 0x74);
+                let yellow = Color::Rgb(0xfa, 0xbd, 0x2f);
+                let red = Color::Rgb(0xfb, 0x49, 0x34);
+                let green = Color::Rgb(0xb8, 0xbb, 0x26);
+                Theme {
+                    header: Color::Rgb(0x8e, 0xc0, 0x7c),
+                    active_tab: Color::Rgb(0xeb, 0xdb, 0xb2),
+                    inactive_tab: Color::Rgb(0xa8, 0x99, 0x84),
+                    selected_bg: Color::Rgb(0x50, 0x49, 0x45),
+                    border: gray,
+                    footer: gray,
+                    upload: Color::Rgb(0xd3, 0x86, 0x9b),
+                    download: Color::Rgb(0x83, 0xa5, 0x98),
+                    closed: gray,
+                    idle: gray,
+                    lan: gray,
+                    multicast: Color::Rgb(0xd3, 0x86, 0x9b),
+                    vpn: green,
+                    stalled: yellow,
+                    inbound_public: Color::Rgb(0xfe, 0x80, 0x19),
+                    warning: yellow,
+                    alert: red,
+                    filter_match: yellow,
+                    rate_high: red,
+                    rate_medium: yellow,
+                    rate_low: green,
+                    rate_zero: gray,
+                    ..base
+                }
+            }
+            // Terminal default colors throughout; selection and emphasis
+            // come from modifiers alone
+            ThemeName::Monochrome => Theme {
+                header: Color::Reset,
+                active_tab: Color::Reset,
+                inactive_tab: Color::Reset,
+                selected_bg: Color::Reset,
+                border: Color::Reset,
+                footer: Color::Reset,
+                upload: Color::Reset,
+                download: Color::Reset,
+                closed: Color::Reset,
+                idle: Color::Reset,
+                lan: Color::Reset,
+                multicast: Color::Reset,
+                vpn: Color::Reset,
+                stalled: Color::Reset,
+                inbound_public: Color::Reset,
+                warning: Color::Reset,
+                alert: Color::Reset,
+                filter_match: Color::Reset,
+                rate_high: Color::Reset,
+                rate_medium: Color::Reset,
+                rate_low: Color::Reset,
+                rate_zero: Color::Reset,
+                ..base
+            },
+        }
+    }
+
+    /// Apply `[colors]` overrides: slot names as in the config file
+    /// (`border`, `rate-high`, ...) mapped to a color name (`red`,
+    /// `lightblue`, `darkgray`), an index (`208`), or `#rrggbb`.
+    pub fn with_colors(mut self, colors: &BTreeMap<String, String>) -> Result<Self, String> {
+        for (slot, value) in colors {
+            let color = Color::from_str(value)
+                .map_err(|_| format!("{}: unknown color {:?}", slot, value))?;
+            *self.slot_mut(slot).ok_or_else(|| format!("unknown color slot {:?}", slot))? = color;
+        }
+        Ok(self)
+    }
+
+    fn slot_mut(&mut self, slot: &str) -> Option<&mut Color> {
+        Some(match slot {
+            "header" => &mut self.header,
+            "active-tab" => &mut self.active_tab,
+            "inactive-tab" => &mut self.inactive_tab,
+            "selected-bg" => &mut self.selected_bg,
+            "border" => &mut self.border,
+            "footer" => &mut self.footer,
+            "upload" => &mut self.upload,
+            "download" => &mut self.download,
+            "closed" => &mut self.closed,
+            "idle" => &mut self.idle,
+            "lan" => &mut self.lan,
+            "multicast" => &mut self.multicast,
+            "vpn" => &mut self.vpn,
+            "stalled" => &mut self.stalled,
+            "inbound-public" => &mut self.inbound_public,
+            "warning" => &mut self.warning,
+            "alert" => &mut self.alert,
+            "filter-match" => &mut self.filter_match,
+            "rate-high" => &mut self.rate_high,
+            "rate-medium" => &mut self.rate_medium,
+            "rate-low" => &mut self.rate_low,
+            "rate-zero" => &mut self.rate_zero,
+            _ => return None,
+        })
+    }
+}
+
+static ACTIVE: OnceLock<Theme> = OnceLock::new();
+
+/// Install the theme resolved at startup. Only the first call has any
+/// effect.
+pub fn init(theme: Theme) {
+    let _ = ACTIVE.set(theme);
+}
+
+/// The active theme (the default palette until `init` is called).
+pub fn current() -> &'static Theme {
+    ACTIVE.get_or_init(Theme::default)
+}
 
-pub const HEADER_FG: Color = Color::Cyan;
-pub const ACTIVE_TAB_FG: Color = Color::White;
-pub const ACTIVE_TAB_BG: Color = Color::DarkGray;
-pub const INACTIVE_TAB_FG: Color = Color::Gray;
-pub const SELECTED_BG: Color = Color::DarkGray;
-pub const BORDER_COLOR: Color = Color::DarkGray;
-pub const FOOTER_FG: Color = Color::DarkGray;
-pub const UPLOAD_COLOR: Color = Color::Magenta;
-pub const DOWNLOAD_COLOR: Color = Color::Blue;
+/// Fractions of the busiest rate that count as high and medium in
+/// relative mode.
+const RELATIVE_HIGH: f64 = 0.5;
+const RELATIVE_MEDIUM: f64 = 0.1;
+
+/// Busiest process rate this frame, as f64 bits, for relative colors.
+static RATE_SCALE: AtomicU64 = AtomicU64::new(0);
+
+/// Record the rate that relative colors are scaled to; called once per draw.
+pub fn set_rate_scale(max_rate: f64) {
+    RATE_SCALE.store(max_rate.to_bits(), Ordering::Relaxed);
+}
+
+/// The (medium, high) thresholds in effect for this frame.
+fn rate_thresholds(theme: &Theme) -> (f64, f64) {
+    if theme.rate_relative {
+        let max = f64::from_bits(RATE_SCALE.load(Ordering::Relaxed));
+        (max * RELATIVE_MEDIUM, max * RELATIVE_HIGH)
+    } else {
+        (theme.rate_medium_above, theme.rate_high_above)
+    }
+}
 
 pub fn rate_color(bytes_per_sec: f64) -> Color {
-    if bytes_per_sec > 1_000_000.0 {
-        Color::Red
-    } else if bytes_per_sec > 100_000.0 {
-        Color::Yellow
+    let theme = current();
+    let (medium, high) = rate_thresholds(theme);
+    if bytes_per_sec > high {
+        theme.rate_high
+    } else if bytes_per_sec > medium {
+        theme.rate_medium
     } else if bytes_per_sec > 0.0 {
-        Color::Green
+        theme.rate_low
     } else {
-        Color::DarkGray
+        theme.rate_zero
+    }
+}
+
+/// Style for a remote address by class: dim for LAN, plain for public,
+/// distinct for multicast/broadcast.
+pub fn addr_style(class: Option<AddrClass>) -> Style {
+    match class {
+        Some(AddrClass::Multicast | AddrClass::Broadcast) => Style::default().fg(current().multicast),
+        Some(AddrClass::Public) | None => Style::default(),
+        Some(_) => Style::default().fg(current().lan),
     }
 }
 
 pub fn header_style() -> Style {
-    Style::default().fg(HEADER_FG).add_modifier(Modifier::BOLD)
+    Style::default().fg(current().header).add_modifier(Modifier::BOLD)
 }
 
 pub fn selected_style() -> Style {
-    Style::default().bg(SELECTED_BG).add_modifier(Modifier::BOLD)
+    let style = Style::default().bg(current().selected_bg).add_modifier(Modifier::BOLD);
+    if current().selected_bg == Color::Reset {
+        style.add_modifier(Modifier::REVERSED)
+    } else {
+        style
+    }
+}
+
+/// The selected row when it is a search match, tinted like matched text.
+pub fn search_match_style() -> Style {
+    selected_style().fg(current().filter_match)
 }
 
-pub fn normal_style() -> Style {
+/// Selection style for the active table.
+pub fn row_highlight(on_search_match: bool) -> Style {
+    if on_search_match {
+        search_match_style()
+    } else {
+        selected_style()
+    }
+}
+
+pub fn closed_style() -> Style {
+    Style::default().fg(current().closed).add_modifier(Modifier::DIM)
+}
+
+pub fn idle_style() -> Style {
+    Style::default().fg(current().idle).add_modifier(Modifier::DIM)
+}
+
+/// Text matching the filter, underlined so it shows without color too.
+pub fn match_style() -> Style {
     Style::default()
+        .fg(current().filter_match)
+        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+}
+
+/// `text` in `base`, with the byte ranges the filter matched (from
+/// `Filter::highlights`) in the match style.
+pub fn highlight(text: &str, ranges: &[Range<usize>], base: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut at = 0;
+    for range in ranges {
+        if range.start > at {
+            spans.push(Span::styled(text[at..range.start].to_string(), base));
+        }
+        spans.push(Span::styled(text[range.clone()].to_string(), base.patch(match_style())));
+        at = range.end;
+    }
+    if at < text.len() || spans.is_empty() {
+        spans.push(Span::styled(text[at..].to_string(), base));
+    }
+    spans
 }
 
 pub fn footer_style() -> Style {
-    Style::default().fg(FOOTER_FG)
+    Style::default().fg(current().footer)
 }
 
 /// Returns a bar string representing the rate visually
@@ -62,3 +392,43 @@ pub fn rate_bar(rate: f64, max_rate: f64) -> String {
     }
     bar
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_overrides() {
+        let colors = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let theme = Theme::preset(ThemeName::Light)
+            .with_colors(&colors(&[("border", "lightblue"), ("rate-high", "#ff8800")]))
+            .unwrap();
+        assert_eq!(theme.border, Color::LightBlue);
+        assert_eq!(theme.rate_high, Color::Rgb(0xff, 0x88, 0x00));
+        assert_eq!(theme.header, Color::Blue);
+
+        let error = Theme::default().with_colors(&colors(&[("border", "blurple")])).unwrap_err();
+        assert_eq!(error, "border: unknown color \"blurple\"");
+        let error = Theme::default().with_colors(&colors(&[("borders", "red")])).unwrap_err();
+        assert_eq!(error, "unknown color slot \"borders\"");
+    }
+
+    #[test]
+    fn test_highlight_splits_spans() {
+        let base = Style::default().fg(Color::Blue);
+        let spans = highlight("Google Chrome", &[0..1, 7..13], base);
+        let text = spans.iter().map(|s| s.content.as_ref()).collect::<Vec<_>>();
+        assert_eq!(text, ["G", "oogle ", "Chrome"]);
+        assert_eq!(spans[1].style, base);
+        assert!(spans[2].style.add_modifier.contains(Modifier::UNDERLINED));
+
+        let plain = highlight("", &[], base);
+        assert_eq!(plain.len(), 1);
+        assert_eq!(plain[0].style, base);
+    }
+}
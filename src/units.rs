@@ -0,0 +1,135 @@
+/// How byte counts and rates are shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Units {
+    /// Bytes in powers of 1024, labeled KB/MB/GB
+    #[default]
+    Bytes,
+    /// Bytes in powers of 1000 (kB/MB/GB)
+    BytesSi,
+    /// Rates in bits per second (kbps/Mbps/Gbps); totals as SI bytes
+    Bits,
+}
+
+impl Units {
+    pub fn next(self) -> Self {
+        match self {
+            Units::Bytes => Units::BytesSi,
+            Units::BytesSi => Units::Bits,
+            Units::Bits => Units::Bytes,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Units::Bytes => "bytes (1024)",
+            Units::BytesSi => "bytes (SI)",
+            Units::Bits => "bits/s",
+        }
+    }
+
+    /// A byte count: "512 B", "1.5 KB", "12.3 MB".
+    pub fn bytes(self, bytes: u64) -> String {
+        match self {
+            Units::Bytes => {
+                if bytes >= 1_073_741_824 {
+                    format!("{:.1} GB", bytes as f64 / 1_073_741_824.0)
+                } else if bytes >= 1_048_576 {
+                    format!("{:.1} MB", bytes as f64 / 1_048_576.0)
+                } else if bytes >= 1024 {
+                    format!("{:.1} KB", bytes as f64 / 1024.0)
+                } else {
+                    format!("{} B", bytes)
+                }
+            }
+            Units::BytesSi | Units::Bits => {
+                if bytes >= 1_000_000_000 {
+                    format!("{:.1} GB", bytes as f64 / 1e9)
+                } else if bytes >= 1_000_000 {
+                    format!("{:.1} MB", bytes as f64 / 1e6)
+                } else if bytes >= 1000 {
+                    format!("{:.1} kB", bytes as f64 / 1e3)
+                } else {
+                    format!("{} B", bytes)
+                }
+            }
+        }
+    }
+
+    /// A rate given in bytes per second; "—" when idle.
+    pub fn rate(self, bytes_per_sec: f64) -> String {
+        if bytes_per_sec <= 0.0 {
+            return "—".to_string();
+        }
+        match self {
+            Units::Bytes => {
+                if bytes_per_sec >= 1_048_576.0 {
+                    format!("{:.1} MB/s", bytes_per_sec / 1_048_576.0)
+                } else if bytes_per_sec >= 1024.0 {
+                    format!("{:.1} KB/s", bytes_per_sec / 1024.0)
+                } else {
+                    format!("{:.0} B/s", bytes_per_sec)
+                }
+            }
+            Units::BytesSi => scaled(bytes_per_sec, ["B/s", "kB/s", "MB/s", "GB/s"]),
+            Units::Bits => scaled(bytes_per_sec * 8.0, ["bps", "kbps", "Mbps", "Gbps"]),
+        }
+    }
+}
+
+/// Powers-of-1000 formatting: whole numbers below 1000, one decimal above.
+fn scaled(value: f64, labels: [&str; 4]) -> String {
+    if value >= 1e9 {
+        format!("{:.1} {}", value / 1e9, labels[3])
+    } else if value >= 1e6 {
+        format!("{:.1} {}", value / 1e6, labels[2])
+    } else if value >= 1e3 {
+        format!("{:.1} {}", value / 1e3, labels[1])
+    } else {
+        format!("{:.0} {}", value, labels[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_bytes() {
+        let units = Units::Bytes;
+        assert_eq!(units.bytes(512), "512 B");
+        assert_eq!(units.bytes(1536), "1.5 KB");
+        assert_eq!(units.bytes(1_048_576), "1.0 MB");
+        assert_eq!(units.bytes(3 * 1_073_741_824), "3.0 GB");
+        assert_eq!(units.rate(0.0), "—");
+        assert_eq!(units.rate(999.4), "999 B/s");
+        assert_eq!(units.rate(1024.0), "1.0 KB/s");
+        assert_eq!(units.rate(5.0 * 1_048_576.0), "5.0 MB/s");
+        // No GB/s step, as before
+        assert_eq!(units.rate(2.0 * 1_073_741_824.0), "2048.0 MB/s");
+    }
+
+    #[test]
+    fn test_si_bytes() {
+        let units = Units::BytesSi;
+        assert_eq!(units.bytes(999), "999 B");
+        assert_eq!(units.bytes(1000), "1.0 kB");
+        assert_eq!(units.bytes(1_048_576), "1.0 MB");
+        assert_eq!(units.bytes(2_500_000_000), "2.5 GB");
+        assert_eq!(units.rate(1_000.0), "1.0 kB/s");
+        assert_eq!(units.rate(1_250_000.0), "1.2 MB/s");
+        assert_eq!(units.rate(1.5e9), "1.5 GB/s");
+        assert_eq!(units.rate(-1.0), "—");
+    }
+
+    #[test]
+    fn test_bits() {
+        let units = Units::Bits;
+        assert_eq!(units.rate(100.0), "800 bps");
+        assert_eq!(units.rate(125.0), "1.0 kbps");
+        assert_eq!(units.rate(125_000.0), "1.0 Mbps");
+        // A gigabit link saturated
+        assert_eq!(units.rate(125_000_000.0), "1.0 Gbps");
+        assert_eq!(units.bytes(1_500_000), "1.5 MB");
+        assert_eq!(units.next(), Units::Bytes);
+    }
+}